@@ -0,0 +1,85 @@
+//! Compression of JSON Lines files (e.g., benchmark results).
+use crate::{Error, ErrorKind, Result};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// gzip.
+    Gzip,
+
+    /// Zstandard.
+    Zstd,
+}
+impl Compression {
+    /// Returns the compression format indicated by the extension (`.gz` or `.zst`) of the given path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension().and_then(|x| x.to_str()) {
+            Some("gz") => Some(Self::Gzip),
+            Some("zst") => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Compresses the given data into a gzip member or a zstd frame.
+    ///
+    /// Since the decoders (see `decompress`) handle concatenated members (frames),
+    /// the compressed chunks can be appended to a file one by one.
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                track!(encoder.write_all(data).map_err(Error::from))?;
+                track!(encoder.finish().map_err(Error::from))
+            }
+            Self::Zstd => track!(zstd::stream::encode_all(data, 0).map_err(Error::from)),
+        }
+    }
+}
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown compression: {:?}", s),
+        }
+    }
+}
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Gzip => write!(f, "gzip"),
+            Self::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Wraps the given reader to decompress its content if it is compressed by gzip or zstd.
+///
+/// The compression format is detected from the magic number at the beginning of the content,
+/// so uncompressed content is read as is.
+pub fn decompress<'a, R>(reader: R) -> Result<Box<dyn 'a + Read>>
+where
+    R: 'a + Read,
+{
+    let mut reader = BufReader::new(reader);
+    let head = track!(reader.fill_buf().map_err(Error::from))?;
+    if head.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(flate2::read::MultiGzDecoder::new(reader)))
+    } else if head.starts_with(ZSTD_MAGIC) {
+        let decoder =
+            track!(zstd::stream::read::Decoder::with_buffer(reader).map_err(Error::from))?;
+        Ok(Box::new(decoder))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
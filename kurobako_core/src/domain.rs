@@ -2,6 +2,7 @@
 use crate::{Error, ErrorKind, Result};
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use structopt::StructOpt;
 
@@ -353,7 +354,7 @@ impl Constraint {
     /// `lua_script` is the Lua script code that represents the constraint.
     /// In this script, you can access the variables that are located before
     /// the constrainted variable as global variables.
-    /// This script must return a boolean value.
+    /// This script must return a boolean value or a number `g` (the constraint is regarded as `g <= 0`).
     pub fn new(lua_script: &str) -> Self {
         Self {
             lua_script: lua_script.to_owned(),
@@ -362,7 +363,16 @@ impl Constraint {
 
     /// Returns `Ok(true)` if this constraint is satisfied, otherwise `Ok(false)` or an error.
     pub fn is_satisfied(&self, vars: &[Variable], vals: &[f64]) -> Result<bool> {
-        use rlua::Lua;
+        let violation = track!(self.violation(vars, vals))?;
+        Ok(violation == 0.0)
+    }
+
+    /// Returns the amount by which this constraint is violated (`0.0` if it is satisfied).
+    ///
+    /// If the script returns a number `g`, the amount is `max(g, 0)`.
+    /// If the script returns a boolean value, the amount of a violation is `1.0`.
+    pub fn violation(&self, vars: &[Variable], vals: &[f64]) -> Result<f64> {
+        use rlua::{Lua, Value};
 
         let lua = Lua::new();
         lua.context(|lua_ctx| {
@@ -381,11 +391,32 @@ impl Constraint {
                 }
             }
 
-            lua_ctx.load(&self.lua_script).eval().map_err(Error::from)
+            let value = track!(lua_ctx.load(&self.lua_script).eval().map_err(Error::from))?;
+            match value {
+                Value::Boolean(true) => Ok(0.0),
+                Value::Boolean(false) => Ok(1.0),
+                Value::Integer(g) => Ok((g as f64).max(0.0)),
+                Value::Number(g) => Ok(g.max(0.0)),
+                _ => track_panic!(
+                    ErrorKind::InvalidInput,
+                    "Not a boolean or number: {:?}",
+                    value
+                ),
+            }
         })
     }
 }
 
+/// Cause of an `UnevaluableParams` error that tells the total amount of the violated constraints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstraintViolation(pub f64);
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "constraints are violated by {}", self.0)
+    }
+}
+impl std::error::Error for ConstraintViolation {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -408,6 +439,15 @@ mod tests {
         assert!(!track!(constraint.is_satisfied(&vars, &[0.2, 1.0, 0.0]))?);
         assert!(!track!(constraint.is_satisfied(&vars, &[0.2, 1.0, 2.0]))?);
 
+        let constraint = Constraint::new("a + b - 2");
+        assert!(track!(constraint.is_satisfied(&vars, &[0.2, 1.0]))?);
+        assert_eq!(track!(constraint.violation(&vars, &[0.2, 1.0]))?, 0.0);
+        assert_eq!(track!(constraint.violation(&vars, &[1.5, 1.0]))?, 0.5);
+        assert_eq!(
+            track!(Constraint::new("c == \"bar\"").violation(&vars, &[0.2, 1.0, 0.0]))?,
+            1.0
+        );
+
         Ok(())
     }
 }
@@ -1,4 +1,124 @@
 //! **E**xternal **P**rogram **I**nterface.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use structopt::StructOpt;
+
+pub use self::capture::OutputCapture;
+pub use self::process::kill_all_processes;
+
 pub mod channel;
 pub mod problem;
 pub mod solver;
+
+mod capture;
+mod process;
+
+/// Options to run an external program inside a Docker container.
+///
+/// The container is run with a generated name (`kurobako-PID-RANDOM`) and the `--init` option,
+/// and is killed by `docker kill` when the program is killed (e.g., on `kurobako run --trial-timeout`)
+/// or no longer used, because killing the `docker run` client doesn't stop the container.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct DockerOpt {
+    /// Docker image in which the external program is run.
+    ///
+    /// If omitted, the program is run directly on the host.
+    /// Note that the path of the program is resolved inside the container.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker_image: Option<String>,
+
+    /// Volume mounted into the container (`HOST_PATH:CONTAINER_PATH[:OPTIONS]`), e.g., a dataset directory.
+    #[structopt(long, number_of_values = 1)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub docker_volume: Vec<String>,
+}
+impl DockerOpt {
+    // Makes the command to run the program (and the name of the container if Docker is used).
+    fn command(&self, path: &Path, args: &[String]) -> (Command, Option<String>) {
+        if let Some(image) = &self.docker_image {
+            let name = format!(
+                "kurobako-{}-{:016x}",
+                std::process::id(),
+                rand::random::<u64>()
+            );
+            let mut command = Command::new("docker");
+            command.args(&["run", "--rm", "-i", "--init", "--name", &name]);
+            for volume in &self.docker_volume {
+                command.arg("-v").arg(volume);
+            }
+            command.arg(image).arg(path).args(args);
+            (command, Some(name))
+        } else {
+            let mut command = Command::new(path);
+            command.args(args);
+            (command, None)
+        }
+    }
+
+    fn update_hash(&self, hasher: &mut Sha256) {
+        if let Some(image) = &self.docker_image {
+            hasher.update(image.as_bytes());
+            for volume in &self.docker_volume {
+                hasher.update(volume.as_bytes());
+            }
+        }
+    }
+}
+
+/// Resource limits imposed on an external program.
+///
+/// The limits are applied by running the program via `sh` (i.e., `ulimit` and `nice`),
+/// so they are also applied inside a Docker container.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ResourceLimitOpt {
+    /// Limit of the CPU time (in seconds) of the program process.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time_limit: Option<u64>,
+
+    /// Limit of the virtual memory (in MiB) of the program process.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit: Option<u64>,
+
+    /// Niceness adjustment of the program process.
+    #[structopt(long, allow_hyphen_values = true)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+}
+impl ResourceLimitOpt {
+    fn wrap(&self, path: &Path, args: &[String]) -> (PathBuf, Vec<String>) {
+        if *self == Self::default() {
+            return (path.to_owned(), args.to_owned());
+        }
+
+        let mut script = String::new();
+        if let Some(limit) = self.cpu_time_limit {
+            script += &format!("ulimit -t {} && ", limit);
+        }
+        if let Some(limit) = self.memory_limit {
+            script += &format!("ulimit -v {} && ", limit * 1024);
+        }
+        if let Some(nice) = self.nice {
+            script += &format!("exec nice -n {} \"$0\" \"$@\"", nice);
+        } else {
+            script += "exec \"$0\" \"$@\"";
+        }
+
+        let mut wrapped_args = vec!["-c".to_owned(), script];
+        wrapped_args.push(path.to_string_lossy().into_owned());
+        wrapped_args.extend(args.iter().cloned());
+        (PathBuf::from("sh"), wrapped_args)
+    }
+
+    fn update_hash(&self, hasher: &mut Sha256) {
+        if *self != Self::default() {
+            hasher.update(format!("{:?}", self).as_bytes());
+        }
+    }
+}
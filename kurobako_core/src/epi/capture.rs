@@ -0,0 +1,96 @@
+//! Capturing of the standard error outputs of external programs.
+use std::io::{BufRead as _, BufReader, Read};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Buffer that captures the standard error outputs of the external programs created via a `FactoryRegistry`.
+///
+/// If the size of the captured output exceeds the limit, the oldest part is discarded.
+#[derive(Debug, Clone)]
+pub struct OutputCapture(Arc<Mutex<Buffer>>);
+impl OutputCapture {
+    /// Makes a new `OutputCapture` instance.
+    ///
+    /// `limit` is the maximum number of the bytes of the captured output to be kept.
+    pub fn new(limit: Option<usize>) -> Self {
+        Self(Arc::new(Mutex::new(Buffer {
+            text: String::new(),
+            limit,
+            truncated: false,
+        })))
+    }
+
+    /// Takes the captured output.
+    ///
+    /// `None` is returned if nothing has been captured.
+    pub fn take(&self) -> Option<String> {
+        let mut buf = self.0.lock().unwrap_or_else(|e| panic!("{}", e));
+        if buf.text.is_empty() {
+            return None;
+        }
+
+        let text = std::mem::take(&mut buf.text);
+        if std::mem::replace(&mut buf.truncated, false) {
+            Some(format!("[...truncated...]\n{}", text))
+        } else {
+            Some(text)
+        }
+    }
+
+    fn append(&self, line: &str) {
+        let mut buf = self.0.lock().unwrap_or_else(|e| panic!("{}", e));
+        buf.text.push_str(line);
+        buf.text.push('\n');
+        if let Some(limit) = buf.limit {
+            if buf.text.len() > limit {
+                let mut start = buf.text.len() - limit;
+                while !buf.text.is_char_boundary(start) {
+                    start += 1;
+                }
+                buf.text.drain(..start);
+                buf.truncated = true;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buffer {
+    text: String,
+    limit: Option<usize>,
+    truncated: bool,
+}
+
+/// Destination of the standard error output of an external program.
+///
+/// If no `OutputCapture` is attached, the output is forwarded to the standard error of this process.
+/// Because the processes of external programs are reused by successive studies,
+/// the capture is re-attached each time the factory is requested.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StderrSink(Arc<Mutex<Option<OutputCapture>>>);
+impl StderrSink {
+    pub fn attach(&self, capture: Option<&OutputCapture>) {
+        *self.0.lock().unwrap_or_else(|e| panic!("{}", e)) = capture.cloned();
+    }
+
+    pub fn spawn_reader<R>(&self, name: String, reader: R)
+    where
+        R: 'static + Read + Send,
+    {
+        let this = self.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                let capture = this.0.lock().unwrap_or_else(|e| panic!("{}", e)).clone();
+                if let Some(capture) = capture {
+                    capture.append(&format!("[{}] {}", name, line));
+                } else {
+                    eprintln!("{}", line);
+                }
+            }
+        });
+    }
+}
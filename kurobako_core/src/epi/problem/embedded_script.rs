@@ -2,7 +2,7 @@ use crate::epi::problem::{
     ExternalProgramEvaluator, ExternalProgramProblem, ExternalProgramProblemFactory,
     ExternalProgramProblemRecipe,
 };
-use crate::problem::{Evaluator, Problem, ProblemFactory, ProblemRecipe, ProblemSpec};
+use crate::problem::{AbortHandle, Evaluator, Problem, ProblemFactory, ProblemRecipe, ProblemSpec};
 use crate::registry::FactoryRegistry;
 use crate::rng::ArcRng;
 use crate::trial::{Params, Values};
@@ -60,7 +60,12 @@ impl ProblemRecipe for EmbeddedScriptProblemRecipe {
         };
 
         let args = self.args.clone();
-        let eppr = ExternalProgramProblemRecipe { path, args };
+        let eppr = ExternalProgramProblemRecipe {
+            path,
+            args,
+            docker: Default::default(),
+            limits: Default::default(),
+        };
         let inner = track!(eppr.create_factory(registry))?;
 
         Ok(EmbeddedScriptProblemFactory { inner })
@@ -108,4 +113,8 @@ impl Evaluator for EmbeddedScriptEvaluator {
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
         track!(self.inner.evaluate(next_step))
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.inner.abort_handle()
+    }
 }
@@ -1,6 +1,9 @@
+use crate::epi::capture::StderrSink;
 use crate::epi::channel::{MessageReceiver, MessageSender};
 use crate::epi::problem::ProblemMessage;
-use crate::problem::{Evaluator, Problem, ProblemFactory, ProblemRecipe, ProblemSpec};
+use crate::epi::process::ProgramProcess;
+use crate::epi::{DockerOpt, ResourceLimitOpt};
+use crate::problem::{AbortHandle, Evaluator, Problem, ProblemFactory, ProblemRecipe, ProblemSpec};
 use crate::registry::FactoryRegistry;
 use crate::rng::{ArcRng, Rng as _};
 use crate::trial::{Params, Values};
@@ -8,10 +11,11 @@ use crate::{Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::atomic::{self, AtomicU64};
-use std::sync::{Arc, Mutex};
+use std::process::{ChildStdin, ChildStdout};
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread_local;
 use structopt::StructOpt;
 
@@ -29,47 +33,68 @@ pub struct ExternalProgramProblemRecipe {
 
     /// The command line arguments that are passed to the program.
     pub args: Vec<String>,
+
+    /// Options to run the program inside a Docker container.
+    #[structopt(flatten)]
+    #[serde(flatten)]
+    pub docker: DockerOpt,
+    /// Resource limits imposed on the program.
+    #[structopt(flatten)]
+    #[serde(flatten)]
+    pub limits: ResourceLimitOpt,
 }
 impl ExternalProgramProblemRecipe {
     fn create_new_factory(
         &self,
         _registry: &FactoryRegistry,
     ) -> Result<ExternalProgramProblemFactory> {
-        let mut child = track!(Command::new(&self.path)
-            .args(&self.args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .map_err(Error::from))?;
-
-        let stdin = track_assert_some!(child.stdin.take(), ErrorKind::IoError);
-        let stdout = track_assert_some!(child.stdout.take(), ErrorKind::IoError);
-
-        let tx = MessageSender::new(stdin);
-        let mut rx = MessageReceiver::new(stdout);
-        let spec = match track!(rx.recv())? {
-            ProblemMessage::ProblemSpecCast { spec } => spec,
-            m => track_panic!(ErrorKind::InvalidInput, "Unexpected message: {:?}", m),
-        };
+        let stderr_sink = StderrSink::default();
+        let (child, channel, spec) = track!(self.spawn(&stderr_sink))?;
 
         Ok(ExternalProgramProblemFactory(Arc::new(
             ExternalProgramProblemFactoryInner {
                 spec,
-                child,
-                tx: Arc::new(Mutex::new(tx)),
-                rx: Arc::new(Mutex::new(rx)),
+                process: Arc::new(Process {
+                    recipe: self.clone(),
+                    stderr_sink,
+                    child: Mutex::new(child),
+                    channel: Mutex::new(channel),
+                    killed: AtomicBool::new(false),
+                    generation: AtomicU64::new(0),
+                    problems: Mutex::new(HashMap::new()),
+                }),
                 next_problem_id: AtomicU64::new(0),
                 next_evaluator_id: Arc::new(AtomicU64::new(0)),
             },
         )))
     }
 
+    fn spawn(&self, stderr_sink: &StderrSink) -> Result<(ProgramProcess, Channel, ProblemSpec)> {
+        let (child, stdin, stdout, stderr) = track!(ProgramProcess::spawn(
+            &self.path,
+            &self.args,
+            &self.docker,
+            &self.limits
+        ))?;
+        stderr_sink.spawn_reader(self.path.to_string_lossy().into_owned(), stderr);
+
+        let tx = MessageSender::new(stdin);
+        let mut rx = MessageReceiver::new(stdout);
+        let spec = match track!(rx.recv())? {
+            ProblemMessage::ProblemSpecCast { spec } => spec,
+            m => track_panic!(ErrorKind::InvalidInput, "Unexpected message: {:?}", m),
+        };
+        Ok((child, Channel { tx, rx }, spec))
+    }
+
     fn cache_key(&self) -> Vec<u8> {
         let mut hasher = Sha256::new();
         hasher.update(&*self.path.to_string_lossy());
         for arg in &self.args {
             hasher.update(arg.as_bytes());
         }
+        self.docker.update_hash(&mut hasher);
+        self.limits.update_hash(&mut hasher);
         hasher.finalize().to_vec()
     }
 }
@@ -83,10 +108,20 @@ impl ProblemRecipe for ExternalProgramProblemRecipe {
 
             if let Some((k, factory)) = f.as_ref() {
                 if k == &key {
+                    factory
+                        .0
+                        .process
+                        .stderr_sink
+                        .attach(registry.output_capture());
                     return Ok(factory.clone());
                 }
             }
             let factory = track!(self.create_new_factory(registry))?;
+            factory
+                .0
+                .process
+                .stderr_sink
+                .attach(registry.output_capture());
             *f = Some((key, factory.clone()));
             Ok(factory)
         })
@@ -111,9 +146,7 @@ impl ProblemFactory for ExternalProgramProblemFactory {
 #[derive(Debug)]
 struct ExternalProgramProblemFactoryInner {
     spec: ProblemSpec,
-    child: Child,
-    tx: Arc<Mutex<MessageSender<ProblemMessage, ChildStdin>>>,
-    rx: Arc<Mutex<MessageReceiver<ProblemMessage, ChildStdout>>>,
+    process: Arc<Process>,
     next_problem_id: AtomicU64,
     next_evaluator_id: Arc<AtomicU64>,
 }
@@ -126,25 +159,111 @@ impl ProblemFactory for ExternalProgramProblemFactoryInner {
 
     fn create_problem(&self, mut rng: ArcRng) -> Result<Self::Problem> {
         let problem_id = self.next_problem_id.fetch_add(1, atomic::Ordering::SeqCst);
-        let m = ProblemMessage::CreateProblemCast {
-            problem_id,
-            random_seed: rng.gen(),
-        };
-        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
-        track!(tx.send(&m))?;
+        let random_seed = rng.gen();
+        track!(self.process.create_problem(problem_id, random_seed))?;
 
         Ok(ExternalProgramProblem {
             problem_id,
-            tx: Arc::clone(&self.tx),
-            rx: Arc::clone(&self.rx),
+            process: Arc::clone(&self.process),
             next_evaluator_id: Arc::clone(&self.next_evaluator_id),
         })
     }
 }
-impl Drop for ExternalProgramProblemFactoryInner {
-    fn drop(&mut self) {
-        if self.child.kill().is_ok() {
-            let _ = self.child.wait(); // for preventing the child process becomes a zombie.
+
+// The process of an external program.
+//
+// If an evaluation is aborted, the process is killed and then respawned before the next request.
+// The live problems are re-created in the respawned process with the same seeds,
+// and the evaluators are re-created lazily (i.e., they restart the evaluations from the beginning).
+#[derive(Debug)]
+struct Process {
+    recipe: ExternalProgramProblemRecipe,
+    stderr_sink: StderrSink,
+
+    // The child process is locked separately from the channel,
+    // so that it can be killed while an evaluation is waiting for a reply.
+    child: Mutex<ProgramProcess>,
+    channel: Mutex<Channel>,
+    killed: AtomicBool,
+
+    // Incremented each time the process is respawned.
+    generation: AtomicU64,
+
+    // The seeds of the live problems.
+    problems: Mutex<HashMap<u64, u64>>,
+}
+impl Process {
+    // Locks the channel to the process (after respawning it if it has been killed).
+    fn lock(&self) -> Result<MutexGuard<'_, Channel>> {
+        let mut channel = track!(self.channel.lock().map_err(Error::from))?;
+        if self.killed.swap(false, atomic::Ordering::SeqCst) {
+            let result = self.respawn(&mut channel);
+            if result.is_err() {
+                self.killed.store(true, atomic::Ordering::SeqCst);
+            }
+            track!(result)?;
+        }
+        Ok(channel)
+    }
+
+    fn respawn(&self, channel: &mut Channel) -> Result<()> {
+        let (new_child, new_channel, _) = track!(self.recipe.spawn(&self.stderr_sink))?;
+        {
+            let mut child = track!(self.child.lock().map_err(Error::from))?;
+            child.wait();
+            *child = new_child;
+        }
+        *channel = new_channel;
+        self.generation.fetch_add(1, atomic::Ordering::SeqCst);
+
+        let problems = track!(self.problems.lock().map_err(Error::from))?;
+        for (&problem_id, &random_seed) in problems.iter() {
+            let m = ProblemMessage::CreateProblemCast {
+                problem_id,
+                random_seed,
+            };
+            track!(channel.tx.send(&m))?;
+        }
+        Ok(())
+    }
+
+    fn create_problem(&self, problem_id: u64, random_seed: u64) -> Result<()> {
+        let mut channel = track!(self.lock())?;
+        let m = ProblemMessage::CreateProblemCast {
+            problem_id,
+            random_seed,
+        };
+        track!(channel.tx.send(&m))?;
+        track!(self.problems.lock().map_err(Error::from))?.insert(problem_id, random_seed);
+        Ok(())
+    }
+
+    // Kills the process (the pending request on the channel fails due to the closed pipe).
+    fn kill(&self) {
+        if let Ok(mut child) = self.child.lock() {
+            child.kill();
+            self.killed.store(true, atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Channel {
+    tx: MessageSender<ProblemMessage, ChildStdin>,
+    rx: MessageReceiver<ProblemMessage, ChildStdout>,
+}
+impl Channel {
+    fn call(&mut self, m: &ProblemMessage) -> Result<ProblemMessage> {
+        track!(self.tx.send(m))?;
+        match track!(self.rx.recv())? {
+            ProblemMessage::ErrorReply { kind, message } => {
+                if let Some(message) = message {
+                    track_panic!(kind, "{}", message);
+                } else {
+                    track_panic!(kind);
+                }
+            }
+            m => Ok(m),
         }
     }
 }
@@ -153,8 +272,7 @@ impl Drop for ExternalProgramProblemFactoryInner {
 #[derive(Debug)]
 pub struct ExternalProgramProblem {
     problem_id: u64,
-    tx: Arc<Mutex<MessageSender<ProblemMessage, ChildStdin>>>,
-    rx: Arc<Mutex<MessageReceiver<ProblemMessage, ChildStdout>>>,
+    process: Arc<Process>,
     next_evaluator_id: Arc<AtomicU64>,
 }
 impl Problem for ExternalProgramProblem {
@@ -164,89 +282,196 @@ impl Problem for ExternalProgramProblem {
         let evaluator_id = self
             .next_evaluator_id
             .fetch_add(1, atomic::Ordering::SeqCst);
-        let m = ProblemMessage::CreateEvaluatorCall {
-            problem_id: self.problem_id,
+        let mut channel = track!(self.process.lock())?;
+        let generation = self.process.generation.load(atomic::Ordering::SeqCst);
+        track!(create_evaluator(
+            &mut channel,
+            self.problem_id,
             evaluator_id,
-            params,
-        };
-        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
-        track!(tx.send(&m))?;
-
-        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
-        match track!(rx.recv())? {
-            ProblemMessage::CreateEvaluatorReply => {}
-            ProblemMessage::ErrorReply { kind, message } => {
-                if let Some(message) = message {
-                    track_panic!(kind, "{}", message);
-                } else {
-                    track_panic!(kind);
-                }
-            }
-            m => {
-                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
-            }
-        }
+            &params
+        ))?;
 
         Ok(ExternalProgramEvaluator {
+            problem_id: self.problem_id,
             evaluator_id,
-            tx: Arc::clone(&self.tx),
-            rx: Arc::clone(&self.rx),
+            params,
+            generation,
+            process: Arc::clone(&self.process),
         })
     }
 }
 impl Drop for ExternalProgramProblem {
     fn drop(&mut self) {
         let problem_id = self.problem_id;
+        if let Ok(mut problems) = self.process.problems.lock() {
+            problems.remove(&problem_id);
+        }
+
         let m = ProblemMessage::DropProblemCast { problem_id };
-        if let Ok(mut tx) = self.tx.lock() {
-            let _ = tx.send(&m);
+        if let Ok(mut channel) = self.process.channel.lock() {
+            let _ = channel.tx.send(&m);
         }
     }
 }
 
+fn create_evaluator(
+    channel: &mut Channel,
+    problem_id: u64,
+    evaluator_id: u64,
+    params: &Params,
+) -> Result<()> {
+    let m = ProblemMessage::CreateEvaluatorCall {
+        problem_id,
+        evaluator_id,
+        params: params.clone(),
+    };
+    match track!(channel.call(&m))? {
+        ProblemMessage::CreateEvaluatorReply => Ok(()),
+        m => track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m),
+    }
+}
+
 /// Evaluator that is implemented by an external program.
 #[derive(Debug)]
 pub struct ExternalProgramEvaluator {
+    problem_id: u64,
     evaluator_id: u64,
-    tx: Arc<Mutex<MessageSender<ProblemMessage, ChildStdin>>>,
-    rx: Arc<Mutex<MessageReceiver<ProblemMessage, ChildStdout>>>,
+    params: Params,
+
+    // The generation of the process in which this evaluator was created.
+    generation: u64,
+
+    process: Arc<Process>,
 }
 impl Evaluator for ExternalProgramEvaluator {
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
-        let evaluator_id = self.evaluator_id;
+        let mut channel = track!(self.process.lock())?;
+
+        let generation = self.process.generation.load(atomic::Ordering::SeqCst);
+        if self.generation != generation {
+            track!(create_evaluator(
+                &mut channel,
+                self.problem_id,
+                self.evaluator_id,
+                &self.params
+            ))?;
+            self.generation = generation;
+        }
+
         let m = ProblemMessage::EvaluateCall {
-            evaluator_id,
+            evaluator_id: self.evaluator_id,
             next_step,
         };
-        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
-        track!(tx.send(&m))?;
-
-        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
-        match track!(rx.recv())? {
+        match track!(channel.call(&m))? {
             ProblemMessage::EvaluateReply {
                 current_step,
                 values,
             } => Ok((current_step, values)),
-            ProblemMessage::ErrorReply { kind, message } => {
-                if let Some(message) = message {
-                    track_panic!(kind, "{}", message);
-                } else {
-                    track_panic!(kind);
-                }
-            }
-            m => {
-                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
-            }
+            m => track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m),
         }
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        let process = Arc::clone(&self.process);
+        Some(AbortHandle::new(move || process.kill()))
+    }
 }
 impl Drop for ExternalProgramEvaluator {
     fn drop(&mut self) {
         let m = ProblemMessage::DropEvaluatorCast {
             evaluator_id: self.evaluator_id,
         };
-        if let Ok(mut tx) = self.tx.lock() {
-            let _ = tx.send(&m);
+        if let Ok(mut channel) = self.process.channel.lock() {
+            // The evaluators of the killed processes don't exist in the respawned ones.
+            if self.generation == self.process.generation.load(atomic::Ordering::SeqCst) {
+                let _ = channel.tx.send(&m);
+            }
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::domain;
+    use crate::epi::solver::ExternalProgramSolverRecipe;
+    use crate::problem::ProblemSpecBuilder;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    // A problem that hangs at the first evaluation (i.e., until the marker file `$1` is created)
+    // by running the command `$3`.
+    const SLEEP_PROBLEM: &str = r#"
+printf '%s\n' "$2"
+while read -r line; do
+    case "$line" in
+        *CREATE_EVALUATOR_CALL*)
+            echo '{"type":"CREATE_EVALUATOR_REPLY"}' ;;
+        *EVALUATE_CALL*)
+            if [ ! -e "$1" ]; then
+                touch "$1"
+                eval "$3"
+            fi
+            echo '{"type":"EVALUATE_REPLY","current_step":1,"values":[0.5]}' ;;
+    esac
+done
+"#;
+
+    #[test]
+    fn abort_kills_and_respawns_process() -> trackable::result::TopLevelResult {
+        track!(check_abort("sleep 60 < /dev/null > /dev/null 2>&1"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn abort_kills_descendant_processes() -> trackable::result::TopLevelResult {
+        // The `sleep` process inherits (and keeps open) the standard output of the problem process.
+        track!(check_abort("sleep 60"))?;
+        Ok(())
+    }
+
+    fn check_abort(hang: &str) -> Result<()> {
+        let dir = track!(tempfile::tempdir().map_err(Error::from))?;
+        let marker = dir.path().join("marker");
+        let spec = track!(ProblemSpecBuilder::new("sleep")
+            .param(domain::var("x").continuous(0.0, 1.0))
+            .value(domain::var("y"))
+            .finish())?;
+        let spec = ProblemMessage::ProblemSpecCast { spec };
+        let recipe = ExternalProgramProblemRecipe {
+            path: PathBuf::from("sh"),
+            args: vec![
+                "-c".to_owned(),
+                SLEEP_PROBLEM.to_owned(),
+                "sh".to_owned(),
+                marker.to_string_lossy().into_owned(),
+                track!(serde_json::to_string(&spec).map_err(Error::from))?,
+                hang.to_owned(),
+            ],
+            docker: DockerOpt::default(),
+            limits: ResourceLimitOpt::default(),
+        };
+        let registry =
+            FactoryRegistry::new::<ExternalProgramProblemRecipe, ExternalProgramSolverRecipe>();
+        let factory = track!(recipe.create_factory(&registry))?;
+        let problem = track!(factory.create_problem(ArcRng::new(0)))?;
+
+        let mut evaluator = track!(problem.create_evaluator(Params::new(vec![0.5])))?;
+        let abort_handle = track_assert_some!(evaluator.abort_handle(), ErrorKind::Bug);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(evaluator.evaluate(1).is_err());
+        });
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        abort_handle.abort();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)).ok(), Some(true));
+
+        let mut evaluator = track!(problem.create_evaluator(Params::new(vec![0.5])))?;
+        let (current_step, values) = track!(evaluator.evaluate(1))?;
+        assert_eq!(current_step, 1);
+        assert_eq!(&values[..], &[0.5]);
+        Ok(())
+    }
+}
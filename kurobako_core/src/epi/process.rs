@@ -0,0 +1,140 @@
+use crate::epi::{DockerOpt, ResourceLimitOpt};
+use crate::{Error, ErrorKind, Result};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+lazy_static! {
+    // Process IDs (i.e., process group IDs) of the running programs and the names of their Docker containers.
+    static ref RUNNING: Mutex<HashMap<u32, Option<String>>> = Mutex::new(HashMap::new());
+}
+
+/// Kills all the running external programs (and their descendant processes and Docker containers).
+///
+/// This is intended to be called just before exiting the process without dropping the solvers and problems
+/// (e.g., on a forced interruption), because the programs are run in their own process groups
+/// and don't receive the signals sent from the terminal.
+pub fn kill_all_processes() {
+    if let Ok(running) = RUNNING.lock() {
+        for (&pid, container) in running.iter() {
+            kill_process_group(pid);
+            if let Some(container) = container {
+                kill_container(container);
+            }
+        }
+    }
+}
+
+/// Process of an external program.
+///
+/// On Unix, the program is run as the leader of a new process group, and the whole group is killed,
+/// so that no descendant processes (e.g., data loader workers) keep the pipes to the program open.
+/// If the program is run inside a Docker container, the container is also killed.
+/// The process is killed when this instance is dropped.
+#[derive(Debug)]
+pub(crate) struct ProgramProcess {
+    child: Child,
+    container: Option<String>,
+    exited: bool,
+}
+impl ProgramProcess {
+    /// Spawns the program with the piped standard I/O.
+    pub fn spawn(
+        path: &Path,
+        args: &[String],
+        docker: &DockerOpt,
+        limits: &ResourceLimitOpt,
+    ) -> Result<(Self, ChildStdin, ChildStdout, ChildStderr)> {
+        let (path, args) = limits.wrap(path, args);
+        let (mut command, container) = docker.command(&path, &args);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        set_process_group(&mut command);
+        let mut child = track!(command.spawn().map_err(Error::from); path)?;
+        track!(RUNNING.lock().map_err(Error::from))?.insert(child.id(), container.clone());
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let this = Self {
+            child,
+            container,
+            exited: false,
+        };
+        let stdin = track_assert_some!(stdin, ErrorKind::IoError);
+        let stdout = track_assert_some!(stdout, ErrorKind::IoError);
+        let stderr = track_assert_some!(stderr, ErrorKind::IoError);
+        Ok((this, stdin, stdout, stderr))
+    }
+
+    /// Kills the process group of the program.
+    pub fn kill(&mut self) {
+        if !self.exited {
+            kill_process_group(self.child.id());
+            let _ = self.child.kill();
+            if let Some(container) = &self.container {
+                kill_container(container);
+            }
+        }
+    }
+
+    /// Waits for the program to exit (for preventing the process becomes a zombie).
+    pub fn wait(&mut self) {
+        if !self.exited {
+            let _ = self.child.wait();
+            self.exited = true;
+            if let Ok(mut running) = RUNNING.lock() {
+                running.remove(&self.child.id());
+            }
+        }
+    }
+}
+impl Drop for ProgramProcess {
+    fn drop(&mut self) {
+        self.kill();
+        self.wait();
+    }
+}
+
+fn kill_container(name: &str) {
+    let _ = Command::new("docker")
+        .args(&["kill", name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(unix)]
+fn set_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt as _;
+
+    // SAFETY: `setpgid` is async-signal-safe.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn set_process_group(_command: &mut Command) {}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // SAFETY: `kill` has no memory safety requirements.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
@@ -5,7 +5,7 @@ use crate::problem::ProblemSpec;
 use crate::registry::FactoryRegistry;
 use crate::rng::ArcRng;
 use crate::solver::{Solver, SolverFactory, SolverRecipe, SolverSpec};
-use crate::trial::{EvaluatedTrial, IdGen, NextTrial};
+use crate::trial::{EvaluatedTrial, IdGen, NextTrial, PriorTrial};
 use crate::{Error, Result};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
@@ -60,7 +60,12 @@ impl SolverRecipe for EmbeddedScriptSolverRecipe {
         };
 
         let args = self.args.clone();
-        let eppr = ExternalProgramSolverRecipe { path, args };
+        let eppr = ExternalProgramSolverRecipe {
+            path,
+            args,
+            docker: Default::default(),
+            limits: Default::default(),
+        };
         let inner = track!(eppr.create_factory(registry))?;
         Ok(EmbeddedScriptSolverFactory { inner })
     }
@@ -94,7 +99,23 @@ impl Solver for EmbeddedScriptSolver {
         track!(self.inner.ask(idg))
     }
 
+    fn ask_batch(&mut self, idg: &mut IdGen, batch_size: usize) -> Result<Vec<NextTrial>> {
+        track!(self.inner.ask_batch(idg, batch_size))
+    }
+
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
         track!(self.inner.tell(trial))
     }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        track!(self.inner.tell_priors(trials))
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(self.inner.save_state())
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        track!(self.inner.load_state(state))
+    }
 }
@@ -1,17 +1,20 @@
+use crate::epi::capture::StderrSink;
 use crate::epi::channel::{MessageReceiver, MessageSender};
+use crate::epi::process::ProgramProcess;
 use crate::epi::solver::SolverMessage;
+use crate::epi::{DockerOpt, ResourceLimitOpt};
 use crate::problem::ProblemSpec;
 use crate::registry::FactoryRegistry;
 use crate::rng::{ArcRng, Rng as _};
 use crate::solver::{Solver, SolverFactory, SolverRecipe, SolverSpec};
-use crate::trial::{EvaluatedTrial, IdGen, NextTrial};
+use crate::trial::{EvaluatedTrial, IdGen, NextTrial, PriorTrial};
 use crate::{Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::process::{ChildStdin, ChildStdout};
 use std::sync::atomic::{self, AtomicU64};
 use std::sync::{Arc, Mutex};
 use std::thread_local;
@@ -31,21 +34,29 @@ pub struct ExternalProgramSolverRecipe {
 
     /// The command line arguments that are passed to the program.
     pub args: Vec<String>,
+
+    /// Options to run the program inside a Docker container.
+    #[structopt(flatten)]
+    #[serde(flatten)]
+    pub docker: DockerOpt,
+    /// Resource limits imposed on the program.
+    #[structopt(flatten)]
+    #[serde(flatten)]
+    pub limits: ResourceLimitOpt,
 }
 impl ExternalProgramSolverRecipe {
     fn create_new_factory(
         &self,
         _registry: &FactoryRegistry,
     ) -> Result<ExternalProgramSolverFactory> {
-        let mut child = track!(Command::new(&self.path)
-            .args(&self.args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .map_err(Error::from))?;
-
-        let stdin = track_assert_some!(child.stdin.take(), ErrorKind::IoError);
-        let stdout = track_assert_some!(child.stdout.take(), ErrorKind::IoError);
+        let (child, stdin, stdout, stderr) = track!(ProgramProcess::spawn(
+            &self.path,
+            &self.args,
+            &self.docker,
+            &self.limits
+        ))?;
+        let stderr_sink = StderrSink::default();
+        stderr_sink.spawn_reader(self.path.to_string_lossy().into_owned(), stderr);
 
         let tx = MessageSender::new(stdin);
         let mut rx = MessageReceiver::new(stdout);
@@ -57,7 +68,8 @@ impl ExternalProgramSolverRecipe {
         Ok(ExternalProgramSolverFactory(Arc::new(
             ExternalProgramSolverFactoryInner {
                 spec,
-                child,
+                _child: child,
+                stderr_sink,
                 tx: Arc::new(Mutex::new(tx)),
                 rx: Arc::new(Mutex::new(rx)),
                 next_solver_id: AtomicU64::new(0),
@@ -71,6 +83,8 @@ impl ExternalProgramSolverRecipe {
         for arg in &self.args {
             hasher.update(arg.as_bytes());
         }
+        self.docker.update_hash(&mut hasher);
+        self.limits.update_hash(&mut hasher);
         hasher.finalize().to_vec()
     }
 }
@@ -84,7 +98,9 @@ impl SolverRecipe for ExternalProgramSolverRecipe {
             if !f.contains_key(&key) {
                 f.insert(key.clone(), track!(self.create_new_factory(registry))?);
             }
-            Ok(f[&key].clone())
+            let factory = f[&key].clone();
+            factory.0.stderr_sink.attach(registry.output_capture());
+            Ok(factory)
         })
     }
 }
@@ -107,7 +123,11 @@ impl SolverFactory for ExternalProgramSolverFactory {
 #[derive(Debug)]
 struct ExternalProgramSolverFactoryInner {
     spec: SolverSpec,
-    child: Child,
+
+    // Only kept to kill the process when the factory is dropped.
+    _child: ProgramProcess,
+
+    stderr_sink: StderrSink,
     tx: Arc<Mutex<MessageSender<SolverMessage, ChildStdin>>>,
     rx: Arc<Mutex<MessageReceiver<SolverMessage, ChildStdout>>>,
     next_solver_id: AtomicU64,
@@ -136,13 +156,6 @@ impl SolverFactory for ExternalProgramSolverFactoryInner {
         })
     }
 }
-impl Drop for ExternalProgramSolverFactoryInner {
-    fn drop(&mut self) {
-        if self.child.kill().is_ok() {
-            let _ = self.child.wait(); // for preventing the child process becomes a zombie.
-        }
-    }
-}
 
 /// Solver that is implemented by an external program.
 #[derive(Debug)]
@@ -189,6 +202,51 @@ impl Solver for ExternalProgramSolver {
         }
     }
 
+    // Note that `ASK_BATCH_CALL` is sent only if `batch_size` is greater than `1`,
+    // so the solvers that don't support batch suggestions keep working in the non-batch mode.
+    fn ask_batch(&mut self, idg: &mut IdGen, batch_size: usize) -> Result<Vec<NextTrial>> {
+        if batch_size == 1 {
+            return track!(self.ask(idg)).map(|trial| vec![trial]);
+        }
+
+        let m = SolverMessage::AskBatchCall {
+            solver_id: self.solver_id,
+            next_trial_id: idg.peek_id().get(),
+            batch_size,
+        };
+        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
+        track!(tx.send(&m))?;
+
+        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
+        match track!(rx.recv())? {
+            SolverMessage::AskBatchReply {
+                trials,
+                next_trial_id,
+            } => {
+                track_assert!(!trials.is_empty(), ErrorKind::InvalidInput);
+                track_assert!(
+                    idg.peek_id().get() <= next_trial_id,
+                    ErrorKind::InvalidInput; idg.peek_id().get(), next_trial_id
+                );
+                while idg.peek_id().get() < next_trial_id {
+                    idg.generate();
+                }
+
+                Ok(trials)
+            }
+            SolverMessage::ErrorReply { kind, message } => {
+                if let Some(message) = message {
+                    track_panic!(kind, "{}", message);
+                } else {
+                    track_panic!(kind);
+                }
+            }
+            m => {
+                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
+            }
+        }
+    }
+
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
         let m = SolverMessage::TellCall {
             solver_id: self.solver_id,
@@ -212,6 +270,77 @@ impl Solver for ExternalProgramSolver {
             }
         }
     }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        let m = SolverMessage::TellPriorsCall {
+            solver_id: self.solver_id,
+            trials,
+        };
+        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
+        track!(tx.send(&m))?;
+
+        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
+        match track!(rx.recv())? {
+            SolverMessage::TellPriorsReply => Ok(()),
+            SolverMessage::ErrorReply { kind, message } => {
+                if let Some(message) = message {
+                    track_panic!(kind, "{}", message);
+                } else {
+                    track_panic!(kind);
+                }
+            }
+            m => {
+                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
+            }
+        }
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        let m = SolverMessage::SaveStateCall {
+            solver_id: self.solver_id,
+        };
+        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
+        track!(tx.send(&m))?;
+
+        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
+        match track!(rx.recv())? {
+            SolverMessage::SaveStateReply { state } => Ok(state),
+            SolverMessage::ErrorReply { kind, message } => {
+                if let Some(message) = message {
+                    track_panic!(kind, "{}", message);
+                } else {
+                    track_panic!(kind);
+                }
+            }
+            m => {
+                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
+            }
+        }
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let m = SolverMessage::LoadStateCall {
+            solver_id: self.solver_id,
+            state,
+        };
+        let mut tx = track!(self.tx.lock().map_err(Error::from))?;
+        track!(tx.send(&m))?;
+
+        let mut rx = track!(self.rx.lock().map_err(Error::from))?;
+        match track!(rx.recv())? {
+            SolverMessage::LoadStateReply => Ok(()),
+            SolverMessage::ErrorReply { kind, message } => {
+                if let Some(message) = message {
+                    track_panic!(kind, "{}", message);
+                } else {
+                    track_panic!(kind);
+                }
+            }
+            m => {
+                track_panic!(ErrorKind::Other, "Unexpected message: {:?}", m);
+            }
+        }
+    }
 }
 impl Drop for ExternalProgramSolver {
     fn drop(&mut self) {
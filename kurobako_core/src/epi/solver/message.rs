@@ -1,6 +1,6 @@
 use crate::problem::ProblemSpec;
 use crate::solver::SolverSpec;
-use crate::trial::{EvaluatedTrial, NextTrial};
+use crate::trial::{EvaluatedTrial, NextTrial, PriorTrial};
 use crate::ErrorKind;
 use serde::{Deserialize, Serialize};
 
@@ -28,11 +28,36 @@ pub enum SolverMessage {
         trial: NextTrial,
         next_trial_id: u64,
     },
+    AskBatchCall {
+        solver_id: u64,
+        next_trial_id: u64,
+        batch_size: usize,
+    },
+    AskBatchReply {
+        trials: Vec<NextTrial>,
+        next_trial_id: u64,
+    },
     TellCall {
         solver_id: u64,
         trial: EvaluatedTrial,
     },
     TellReply,
+    TellPriorsCall {
+        solver_id: u64,
+        trials: Vec<PriorTrial>,
+    },
+    TellPriorsReply,
+    SaveStateCall {
+        solver_id: u64,
+    },
+    SaveStateReply {
+        state: serde_json::Value,
+    },
+    LoadStateCall {
+        solver_id: u64,
+        state: serde_json::Value,
+    },
+    LoadStateReply,
     ErrorReply {
         kind: ErrorKind,
         #[serde(default)]
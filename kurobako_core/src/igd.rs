@@ -0,0 +1,106 @@
+//! Helpers for computing inverted generational distances.
+
+/// Computes the inverted generational distance (IGD).
+///
+/// This is the average of the Euclidean distances from each point of the reference front
+/// to the nearest point of the given points.
+///
+/// If `pts` is empty, `f64::INFINITY` is returned because no reference point has a nearest point.
+///
+/// # Panics
+///
+/// This function panics if `front` is empty.
+pub fn compute(pts: &[Vec<f64>], front: &[Vec<f64>]) -> f64 {
+    average_min_distance(pts, front, |p, r| (p - r).powi(2))
+}
+
+/// Computes the IGD+ (Ishibuchi et al., 2015).
+///
+/// Unlike IGD, only the components in which a point is worse than a reference point are counted in the distance,
+/// so this is weakly Pareto compliant.
+///
+/// As with `compute`, `f64::INFINITY` is returned if `pts` is empty, and this function panics if `front` is empty.
+pub fn compute_plus(pts: &[Vec<f64>], front: &[Vec<f64>]) -> f64 {
+    average_min_distance(pts, front, |p, r| (p - r).max(0.0).powi(2))
+}
+
+fn average_min_distance<F>(pts: &[Vec<f64>], front: &[Vec<f64>], f: F) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+{
+    assert!(
+        !front.is_empty(),
+        "Reference front must have at least one point"
+    );
+    front
+        .iter()
+        .map(|r| {
+            pts.iter()
+                .map(|p| {
+                    p.iter()
+                        .zip(r.iter())
+                        .map(|(&p, &r)| f(p, r))
+                        .sum::<f64>()
+                        .sqrt()
+                })
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .sum::<f64>()
+        / front.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_float_close(v0: f64, v1: f64) -> bool {
+        let rtol: f64 = 1e-5;
+        let atol: f64 = 1e-8;
+        (v0 - v1).abs() <= atol + rtol * v1.abs()
+    }
+
+    fn front() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.0, 10.0],
+            vec![1.0, 6.0],
+            vec![2.0, 2.0],
+            vec![6.0, 1.0],
+            vec![10.0, 0.0],
+        ]
+    }
+
+    #[test]
+    fn test_identical_front() {
+        let front = front();
+        assert!(is_float_close(compute(&front, &front), 0.0));
+        assert!(is_float_close(compute_plus(&front, &front), 0.0));
+    }
+
+    #[test]
+    fn test_dominating_point() {
+        // A point dominating the reference point is penalized only by IGD.
+        let front = vec![vec![1.0, 1.0]];
+        let pts = vec![vec![0.5, 0.5]];
+        assert!(is_float_close(compute(&pts, &front), 0.5f64.sqrt()));
+        assert!(is_float_close(compute_plus(&pts, &front), 0.0));
+    }
+
+    #[test]
+    fn test_dominated_points() {
+        // The example of Ishibuchi et al. (2015): `a` dominates `b`, but IGD prefers `b`.
+        let front = front();
+        let a = vec![vec![2.0, 4.0], vec![3.0, 3.0], vec![4.0, 2.0]];
+        let b = vec![vec![2.0, 8.0], vec![4.0, 4.0], vec![8.0, 2.0]];
+        assert!(is_float_close(compute(&a, &front), 3.707092031609239));
+        assert!(is_float_close(compute(&b, &front), 2.59148346584763));
+        assert!(is_float_close(compute_plus(&a, &front), 1.482842712474619));
+        assert!(is_float_close(compute_plus(&b, &front), 2.260112615949154));
+    }
+
+    #[test]
+    fn test_empty_points() {
+        let front = front();
+        assert_eq!(compute(&[], &front), std::f64::INFINITY);
+        assert_eq!(compute_plus(&[], &front), std::f64::INFINITY);
+    }
+}
@@ -1,28 +1,219 @@
 //! JSON.
-use crate::{Error, Result};
+use crate::compression;
+use crate::{Error, ErrorKind, Result};
 use serde::Deserialize;
+use std::env;
 use std::io::Read;
+use trackable::error::ErrorKindExt;
 
 /// JSON representation of a recipe.
 pub type JsonRecipe = serde_json::Value;
 
 /// Parses the given JSON string.
+///
+/// The environment variables and the home directory in the strings of the JSON are expanded (see `expand_vars`).
 pub fn parse_json<T>(json: &str) -> Result<T>
 where
     T: for<'a> Deserialize<'a>,
 {
-    let v = track!(serde_json::from_str(json).map_err(Error::from))?;
+    let mut v = track!(serde_json::from_str(json).map_err(Error::from))?;
+    track!(expand_vars(&mut v))?;
+    let v = track!(serde_json::from_value(v).map_err(Error::from))?;
     Ok(v)
 }
 
 /// Loads entries from the given reader.
+///
+/// If the content is compressed by gzip or zstd, it is decompressed transparently.
 pub fn load<R, T>(reader: R) -> Result<Vec<T>>
 where
     R: Read,
     T: for<'a> Deserialize<'a>,
 {
+    let reader = track!(compression::decompress(reader))?;
     serde_json::Deserializer::from_reader(reader)
         .into_iter()
         .map(|json| track!(json.map_err(Error::from)))
         .collect()
 }
+
+/// Loads recipes from the given reader.
+///
+/// Unlike `load`, the templates in the recipes are expanded (see `expand_template`),
+/// and then the environment variables and the home directory in the recipes are expanded (see `expand_vars`).
+pub fn load_recipes<R, T>(reader: R) -> Result<Vec<T>>
+where
+    R: Read,
+    T: for<'a> Deserialize<'a>,
+{
+    let reader = track!(compression::decompress(reader))?;
+    let mut recipes = Vec::new();
+    for json in serde_json::Deserializer::from_reader(reader).into_iter() {
+        let json = track!(json.map_err(Error::from))?;
+        for mut json in track!(expand_template(json))? {
+            track!(expand_vars(&mut json))?;
+            recipes.push(track!(serde_json::from_value(json).map_err(Error::from))?);
+        }
+    }
+    Ok(recipes)
+}
+
+/// Expands a recipe template.
+///
+/// If the given JSON is an object that has the `"vars"` field (an object),
+/// the field is removed and the `{{NAME}}` placeholders in the strings of the JSON are replaced with the variables.
+/// A string that consists of only a placeholder is replaced with the JSON value of the variable as is
+/// (e.g., `"{{dim}}"` with `{"dim": 2}` becomes `2`).
+///
+/// If the value of a variable is an array, the JSON is expanded for each element of the array
+/// (the cartesian product is taken if there are multiple array variables).
+pub fn expand_template(mut json: serde_json::Value) -> Result<Vec<serde_json::Value>> {
+    let vars = if let Some(vars) = json.as_object_mut().and_then(|o| o.remove("vars")) {
+        vars
+    } else {
+        return Ok(vec![json]);
+    };
+    let vars = track_assert_some!(vars.as_object(), ErrorKind::InvalidInput; vars);
+
+    let mut combinations = vec![Vec::new()];
+    for (name, value) in vars {
+        let candidates = if let serde_json::Value::Array(vs) = value {
+            vs.clone()
+        } else {
+            vec![value.clone()]
+        };
+        combinations = combinations
+            .into_iter()
+            .flat_map(|c| {
+                candidates.iter().map(move |v| {
+                    let mut c = c.clone();
+                    c.push((name.as_str(), v.clone()));
+                    c
+                })
+            })
+            .collect();
+    }
+
+    Ok(combinations
+        .into_iter()
+        .map(|c| substitute(&json, &c))
+        .collect())
+}
+
+fn substitute(json: &serde_json::Value, vars: &[(&str, serde_json::Value)]) -> serde_json::Value {
+    match json {
+        serde_json::Value::String(s) => {
+            for (name, value) in vars {
+                if *s == format!("{{{{{}}}}}", name) {
+                    return value.clone();
+                }
+            }
+
+            let mut s = s.clone();
+            for (name, value) in vars {
+                let value = if let serde_json::Value::String(v) = value {
+                    v.clone()
+                } else {
+                    value.to_string()
+                };
+                s = s.replace(&format!("{{{{{}}}}}", name), &value);
+            }
+            serde_json::Value::String(s)
+        }
+        serde_json::Value::Array(vs) => {
+            serde_json::Value::Array(vs.iter().map(|v| substitute(v, vars)).collect())
+        }
+        serde_json::Value::Object(vs) => serde_json::Value::Object(
+            vs.iter()
+                .map(|(k, v)| (k.clone(), substitute(v, vars)))
+                .collect(),
+        ),
+        _ => json.clone(),
+    }
+}
+
+/// Expands `${VAR}` and the leading `~` in the strings contained in the given JSON value.
+///
+/// `${VAR}` is replaced with the value of the environment variable `VAR`,
+/// and `~` at the beginning of a string (followed by `/` or nothing) is replaced with the home directory.
+/// `$${` is an escape that is replaced with a literal `${`.
+/// An error is returned if a referred environment variable isn't defined.
+///
+/// Note that the expanded values (rather than the variables) are stored in the recipes of the study records.
+/// So the studies of the same benchmark run on machines with different environments have different IDs,
+/// and they are regarded as different studies by the commands that group studies (e.g., `kurobako stats`).
+pub fn expand_vars(json: &mut serde_json::Value) -> Result<()> {
+    match json {
+        serde_json::Value::String(s) => {
+            *s = track!(expand_str(s))?;
+        }
+        serde_json::Value::Array(vs) => {
+            for v in vs {
+                track!(expand_vars(v))?;
+            }
+        }
+        serde_json::Value::Object(vs) => {
+            for v in vs.values_mut() {
+                track!(expand_vars(v))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand_str(s: &str) -> Result<String> {
+    let mut expanded = String::new();
+    let mut rest = s;
+    if rest == "~" || rest.starts_with("~/") {
+        let home = track!(env_var("HOME"))?;
+        expanded.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("$${") {
+            expanded.push_str("${");
+            rest = &rest[3..];
+        } else if rest.starts_with("${") {
+            let end = track_assert_some!(rest.find('}'), ErrorKind::InvalidInput; s);
+            expanded.push_str(&track!(env_var(&rest[2..end]); s)?);
+            rest = &rest[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn env_var(name: &str) -> Result<String> {
+    track!(env::var(name).map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))); name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_str_works() -> trackable::result::TopLevelResult {
+        env::set_var("KUROBAKO_TEST_DATA_DIR", "/data");
+        assert_eq!(
+            track!(expand_str("${KUROBAKO_TEST_DATA_DIR}/foo"))?,
+            "/data/foo"
+        );
+        assert_eq!(
+            track!(expand_str(
+                "$${KUROBAKO_TEST_DATA_DIR} ${KUROBAKO_TEST_DATA_DIR}"
+            ))?,
+            "${KUROBAKO_TEST_DATA_DIR} /data"
+        );
+        assert_eq!(track!(expand_str("$1 $$ $"))?, "$1 $$ $");
+        assert!(expand_str("${KUROBAKO_TEST_UNDEFINED_VAR}").is_err());
+        assert!(expand_str("${KUROBAKO_TEST_DATA_DIR").is_err());
+        Ok(())
+    }
+}
@@ -6,15 +6,18 @@ extern crate trackable;
 
 pub use error::{Error, ErrorKind};
 
+pub mod compression;
 pub mod domain;
 pub mod epi;
 pub mod hypervolume;
+pub mod igd;
 pub mod json;
 pub mod num;
 pub mod problem;
 pub mod registry;
 pub mod rng;
 pub mod solver;
+pub mod special;
 pub mod trial;
 
 mod error;
@@ -8,6 +8,7 @@ use crate::{ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
+use std::sync::Arc;
 use structopt::StructOpt;
 
 /// `ProblemSpec` builder.
@@ -295,11 +296,61 @@ pub trait Evaluator: Send {
     /// Although it's desirable that the current step matches to `next_step`,
     /// it's allowed to exceed `next_step`.
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)>;
+
+    /// Returns a handle to forcibly abort the running evaluation from another thread.
+    ///
+    /// After the abort, the ongoing `evaluate` call should return an error as soon as possible.
+    /// The default implementation returns `None` (i.e., the evaluation can't be aborted).
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        None
+    }
 }
 impl<T: Evaluator + ?Sized> Evaluator for Box<T> {
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
         (**self).evaluate(next_step)
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        (**self).abort_handle()
+    }
+}
+
+/// Handle to forcibly abort an evaluation (e.g., when it exceeds the time limit).
+#[derive(Clone)]
+pub struct AbortHandle(Arc<dyn Fn() + Send + Sync>);
+impl AbortHandle {
+    /// Makes a new `AbortHandle` instance that calls `abort` when aborting.
+    pub fn new<F>(abort: F) -> Self
+    where
+        F: 'static + Fn() + Send + Sync,
+    {
+        Self(Arc::new(abort))
+    }
+
+    /// Makes a handle that aborts all the given handles.
+    ///
+    /// If no handles are given, this returns `None`.
+    pub fn all<I>(handles: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Option<Self>>,
+    {
+        let handles = handles.into_iter().flatten().collect::<Vec<_>>();
+        if handles.is_empty() {
+            None
+        } else {
+            Some(Self::new(move || handles.iter().for_each(Self::abort)))
+        }
+    }
+
+    /// Aborts the evaluation.
+    pub fn abort(&self) {
+        (self.0)()
+    }
+}
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AbortHandle {{ .. }}")
+    }
 }
 
 /// Boxed evaluator.
@@ -317,6 +368,10 @@ impl Evaluator for BoxEvaluator {
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
         self.0.evaluate(next_step)
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.0.abort_handle()
+    }
 }
 impl fmt::Debug for BoxEvaluator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1,5 +1,6 @@
 //! Registry of problem and solver factories.
 // FIXME: Rename this module and structs.
+use crate::epi::OutputCapture;
 use crate::json::JsonRecipe;
 use crate::problem::{BoxProblemFactory, ProblemRecipe};
 use crate::solver::{BoxSolverFactory, SolverRecipe};
@@ -14,6 +15,7 @@ type CreateSolverFactory = dyn Fn(&JsonRecipe, &FactoryRegistry) -> Result<BoxSo
 pub struct FactoryRegistry {
     create_problem_factory: Box<CreateProblemFactory>,
     create_solver_factory: Box<CreateSolverFactory>,
+    output_capture: Option<OutputCapture>,
 }
 impl FactoryRegistry {
     /// Makes a new `FactoryRegistry` instance.
@@ -35,9 +37,21 @@ impl FactoryRegistry {
         Self {
             create_problem_factory,
             create_solver_factory,
+            output_capture: None,
         }
     }
 
+    /// Makes the external programs created via this registry write their standard error outputs to the given capture.
+    pub fn with_output_capture(mut self, capture: OutputCapture) -> Self {
+        self.output_capture = Some(capture);
+        self
+    }
+
+    /// Returns the capture of the standard error outputs of external programs if specified.
+    pub fn output_capture(&self) -> Option<&OutputCapture> {
+        self.output_capture.as_ref()
+    }
+
     /// Creates a problem factory associated with the given recipe JSON.
     pub fn create_problem_factory_from_json(&self, json: &JsonRecipe) -> Result<BoxProblemFactory> {
         track!((self.create_problem_factory)(json, self); json)
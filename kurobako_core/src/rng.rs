@@ -51,3 +51,52 @@ impl RngCore for ArcRng {
             .try_fill_bytes(dest)
     }
 }
+
+/// Samples a value from the standard normal distribution by the Box-Muller transform.
+pub fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u0: f64 = 1.0 - rng.gen::<f64>();
+    let u1: f64 = rng.gen();
+    (-2.0 * u0.ln()).sqrt() * (2.0 * std::f64::consts::PI * u1).cos()
+}
+
+/// Samples a value from the gamma distribution `Gamma(shape, 1)` by Marsaglia and Tsang's method.
+pub fn gamma<R: Rng + ?Sized>(shape: f64, rng: &mut R) -> f64 {
+    if shape < 1.0 {
+        let u: f64 = rng.gen();
+        return gamma(shape + 1.0, rng) * u.powf(1.0 / shape);
+    }
+
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: f64 = rng.gen();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for &shape in &[0.5, 1.0, 3.0] {
+            let n = 100_000;
+            let mean = (0..n).map(|_| gamma(shape, &mut rng)).sum::<f64>() / n as f64;
+            assert!(
+                (mean - shape).abs() < 0.01 * shape.max(1.0),
+                "{} {}",
+                shape,
+                mean
+            );
+        }
+    }
+}
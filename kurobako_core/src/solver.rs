@@ -2,8 +2,8 @@
 use crate::problem::ProblemSpec;
 use crate::registry::FactoryRegistry;
 use crate::rng::ArcRng;
-use crate::trial::{EvaluatedTrial, IdGen, NextTrial};
-use crate::Result;
+use crate::trial::{EvaluatedTrial, IdGen, NextTrial, PriorTrial};
+use crate::{ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
@@ -156,8 +156,39 @@ pub trait Solver: Send {
     /// Asks the next trial to be evaluated.
     fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial>;
 
+    /// Asks the next `batch_size` trials to be evaluated together.
+    ///
+    /// The default implementation just calls `ask` method `batch_size` times.
+    fn ask_batch(&mut self, idg: &mut IdGen, batch_size: usize) -> Result<Vec<NextTrial>> {
+        (0..batch_size).map(|_| track!(self.ask(idg))).collect()
+    }
+
     /// Tells the evaluation result of a trial.
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()>;
+
+    /// Tells the trials evaluated before the study started.
+    ///
+    /// The runner calls this method once before the first `ask`
+    /// only if the solver has the `WarmStarting` capability.
+    /// The default implementation just ignores the given trials.
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        let _ = trials;
+        Ok(())
+    }
+
+    /// Returns the internal state of this solver.
+    ///
+    /// The runner calls this method periodically only if the solver has the `Checkpointable` capability,
+    /// and restores the state by calling `load_state` method when resuming the study.
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track_panic!(ErrorKind::Incapable, "This solver isn't checkpointable");
+    }
+
+    /// Restores the internal state of this solver from the one returned by `save_state` method.
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let _ = state;
+        track_panic!(ErrorKind::Incapable, "This solver isn't checkpointable");
+    }
 }
 
 /// Boxed solver.
@@ -176,9 +207,25 @@ impl Solver for BoxSolver {
         track!(self.0.ask(idg))
     }
 
+    fn ask_batch(&mut self, idg: &mut IdGen, batch_size: usize) -> Result<Vec<NextTrial>> {
+        track!(self.0.ask_batch(idg, batch_size))
+    }
+
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
         track!(self.0.tell(trial))
     }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        track!(self.0.tell_priors(trials))
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(self.0.save_state())
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        track!(self.0.load_state(state))
+    }
 }
 impl fmt::Debug for BoxSolver {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
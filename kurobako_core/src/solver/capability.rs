@@ -24,6 +24,8 @@ impl Capabilities {
             Capability::Conditional,
             Capability::MultiObjective,
             Capability::Concurrent,
+            Capability::WarmStarting,
+            Capability::Checkpointable,
         ]
         .iter()
         .copied()
@@ -87,4 +89,15 @@ pub enum Capability {
 
     MultiObjective,
     Concurrent,
+
+    /// Warm starting.
+    ///
+    /// A solver that has this capability receives the trials of prior studies before the first ask.
+    WarmStarting,
+
+    /// Checkpointing of the internal state.
+    ///
+    /// A solver that has this capability can save and load its internal state,
+    /// so that an interrupted study can be resumed from the middle.
+    Checkpointable,
 }
@@ -0,0 +1,171 @@
+//! Special functions used by the statistics of `kurobako` and its solvers.
+use std::f64::consts::{PI, SQRT_2};
+
+/// Error function.
+///
+/// The fractional error of [`erfc`](fn.erfc.html) is less than `1.2e-7`.
+pub fn erf(x: f64) -> f64 {
+    1.0 - erfc(x)
+}
+
+/// Complementary error function (fractional error < 1.2e-7; Numerical Recipes `erfcc`).
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+pub fn normal_cdf(z: f64) -> f64 {
+    erfc(-z / SQRT_2) / 2.0
+}
+
+/// Survival function of the standard normal distribution.
+pub fn normal_sf(z: f64) -> f64 {
+    erfc(z / SQRT_2) / 2.0
+}
+
+/// Survival function of the chi-squared distribution with `df` degrees of freedom.
+pub fn chi_square_sf(x: f64, df: f64) -> f64 {
+    if x <= 0.0 {
+        1.0
+    } else {
+        upper_incomplete_gamma(df / 2.0, x / 2.0)
+    }
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x)`.
+pub fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const MAX_ITERATIONS: usize = 1000;
+
+    let ln_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series representation of `P(a, x)`.
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITERATIONS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        1.0 - sum * ln_prefix.exp()
+    } else {
+        // Continued fraction representation of `Q(a, x)` (modified Lentz's method).
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITERATIONS {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        h * ln_prefix.exp()
+    }
+}
+
+/// Logarithm of the gamma function (Lanczos approximation).
+pub fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let mut a = COEFFICIENTS[0];
+        let t = x + G + 0.5;
+        for (i, &c) in COEFFICIENTS.iter().enumerate().skip(1) {
+            a += c / (x + i as f64);
+        }
+        0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_float_close(v0: f64, v1: f64) -> bool {
+        let rtol: f64 = 1e-5;
+        let atol: f64 = 1e-8;
+        (v0 - v1).abs() <= atol + rtol * v1.abs()
+    }
+
+    #[test]
+    fn erf_works() {
+        assert!(is_float_close(erf(0.1), 0.1124629160182849));
+        assert!(is_float_close(erf(0.5), 0.5204998778130465));
+        assert!(is_float_close(erf(-1.0), -0.8427007929497149));
+        assert!(is_float_close(erfc(2.0), 0.004677734981047266));
+    }
+
+    #[test]
+    fn ln_gamma_works() {
+        assert!(is_float_close(ln_gamma(0.1), 2.2527126517342055));
+        assert!(is_float_close(ln_gamma(0.5), 0.5723649429247004));
+        assert!(is_float_close(ln_gamma(1.0), 0.0));
+        assert!(is_float_close(ln_gamma(2.0), 0.0));
+        assert!(is_float_close(ln_gamma(10.0), 12.801827480081467));
+        assert!(is_float_close(ln_gamma(100.5), 361.4355404677776));
+    }
+
+    #[test]
+    fn chi_square_sf_works() {
+        assert!(is_float_close(chi_square_sf(0.0, 3.0), 1.0));
+        assert!(is_float_close(chi_square_sf(0.5, 1.0), 0.4795001221869535));
+        assert!(is_float_close(chi_square_sf(3.841458820694124, 1.0), 0.05));
+        assert!(is_float_close(chi_square_sf(1.0, 4.0), 0.9097959895689501));
+        assert!(is_float_close(chi_square_sf(10.0, 3.0), 0.018566135463043));
+        assert!(is_float_close(chi_square_sf(20.0, 10.0), 0.029252688076961));
+    }
+
+    #[test]
+    fn normal_sf_works() {
+        assert!(is_float_close(normal_sf(0.0), 0.5));
+        assert!(is_float_close(normal_sf(1.959963984540054), 0.025));
+        assert!(is_float_close(normal_sf(-1.959963984540054), 0.975));
+        assert!(is_float_close(normal_cdf(1.959963984540054), 0.975));
+    }
+}
@@ -66,6 +66,19 @@ pub struct EvaluatedTrial {
     pub current_step: u64,
 }
 
+/// A trial that was evaluated outside of the current study (e.g., in a previous study).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorTrial {
+    /// The evaluated parameters.
+    pub params: Params,
+
+    /// The evaluated objective values.
+    pub values: Values,
+
+    /// The evaluation step at which the values were obtained.
+    pub step: u64,
+}
+
 /// Trial ID generator.
 #[derive(Debug)]
 pub struct IdGen {
@@ -0,0 +1,148 @@
+//! A synthetic problem that has a conditional (hierarchical) search space.
+//!
+//! This problem imitates the hyperparameter optimization of a classifier:
+//! a model family is chosen first, and then the hyperparameters specific to the family are tuned.
+//! The objective value is a synthetic validation error whose global minimum is `0.0`
+//! (achieved by the `mlp` family).
+use kurobako_core::domain::{self, Constraint};
+use kurobako_core::problem::{
+    Evaluator, Problem, ProblemFactory, ProblemRecipe, ProblemSpec, ProblemSpecBuilder,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+const FAMILIES: [&str; 3] = ["svm", "forest", "mlp"];
+
+/// Recipe of `ConditionalProblem`.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ConditionalProblemRecipe {}
+impl ProblemRecipe for ConditionalProblemRecipe {
+    type Factory = ConditionalProblemFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        Ok(ConditionalProblemFactory {})
+    }
+}
+
+/// Factory of `ConditionalProblem`.
+#[derive(Debug)]
+pub struct ConditionalProblemFactory {}
+impl ProblemFactory for ConditionalProblemFactory {
+    type Problem = ConditionalProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let when = |family: &str| Constraint::new(&format!("family == {:?}", family));
+        let spec = ProblemSpecBuilder::new("Conditional")
+            .attr(
+                "version",
+                &format!("kurobako_problems={}", env!("CARGO_PKG_VERSION")),
+            )
+            .param(domain::var("family").categorical(&FAMILIES))
+            .param(
+                domain::var("svm_c")
+                    .continuous(1e-3, 1e3)
+                    .log_uniform()
+                    .constraint(when("svm")),
+            )
+            .param(
+                domain::var("svm_gamma")
+                    .continuous(1e-5, 1e1)
+                    .log_uniform()
+                    .constraint(when("svm")),
+            )
+            .param(
+                domain::var("forest_trees")
+                    .discrete(1, 512)
+                    .log_uniform()
+                    .constraint(when("forest")),
+            )
+            .param(
+                domain::var("forest_depth")
+                    .discrete(1, 33)
+                    .constraint(when("forest")),
+            )
+            .param(
+                domain::var("mlp_lr")
+                    .continuous(1e-5, 1.0)
+                    .log_uniform()
+                    .constraint(when("mlp")),
+            )
+            .param(
+                domain::var("mlp_layers")
+                    .discrete(1, 6)
+                    .constraint(when("mlp")),
+            )
+            .param(
+                domain::var("mlp_dropout")
+                    .continuous(0.0, 0.9)
+                    .constraint(when("mlp")),
+            )
+            .value(domain::var("Validation Error").continuous(0.0, 1.0));
+        track!(spec.finish())
+    }
+
+    fn create_problem(&self, _rng: ArcRng) -> Result<Self::Problem> {
+        Ok(ConditionalProblem {})
+    }
+}
+
+/// Problem that has a conditional search space.
+#[derive(Debug)]
+pub struct ConditionalProblem {}
+impl Problem for ConditionalProblem {
+    type Evaluator = ConditionalEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        Ok(ConditionalEvaluator { params })
+    }
+}
+
+/// Evaluator of `ConditionalProblem`.
+#[derive(Debug)]
+pub struct ConditionalEvaluator {
+    params: Params,
+}
+impl Evaluator for ConditionalEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        let p = self.params.get();
+        track_assert_eq!(p.len(), 8, ErrorKind::InvalidInput);
+
+        let active = |i: usize| -> Result<f64> {
+            track_assert!(p[i].is_finite(), ErrorKind::UnevaluableParams; i, p);
+            Ok(p[i])
+        };
+        let value = match p[0] as usize {
+            0 => {
+                let c = active(1)?.log10();
+                let gamma = active(2)?.log10();
+                0.05 + 0.9 * squash(((c - 1.0) / 3.0).powi(2) + ((gamma + 2.0) / 3.0).powi(2))
+            }
+            1 => {
+                let trees = active(3)?.log2();
+                let depth = active(4)?;
+                0.03 + 0.9 * squash(((trees - 7.0) / 4.0).powi(2) + ((depth - 12.0) / 10.0).powi(2))
+            }
+            2 => {
+                let lr = active(5)?.log10();
+                let layers = active(6)?;
+                let dropout = active(7)?;
+                0.9 * squash(
+                    ((lr + 3.0) / 2.0).powi(2)
+                        + ((layers - 3.0) / 2.0).powi(2)
+                        + ((dropout - 0.3) / 0.3).powi(2),
+                )
+            }
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown family: {}", p[0]),
+        };
+        Ok((next_step, Values::new(vec![value])))
+    }
+}
+
+fn squash(x: f64) -> f64 {
+    x / (1.0 + x)
+}
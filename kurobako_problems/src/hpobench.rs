@@ -63,6 +63,7 @@ impl ProblemFactory for HpobenchProblemFactory {
                 "version",
                 &format!("kurobako_problems={}", env!("CARGO_PKG_VERSION")),
             )
+            .attr("family", "hpobench")
             .attr(
                 "paper",
                 "Klein, Aaron, and Frank Hutter. \"Tabular Benchmarks \
@@ -4,6 +4,7 @@
 #[macro_use]
 extern crate trackable;
 
+pub mod conditional;
 pub mod hpobench;
 pub mod nasbench;
 pub mod sigopt;
@@ -83,6 +83,17 @@ pub struct NasbenchProblemFactory {
     encoding: Encoding,
     metrics: Vec<Metric>,
 }
+impl NasbenchProblemFactory {
+    // The best `1.0 - validation accuracy` among all the models, epochs and training runs in the dataset.
+    fn optimum(&self) -> Option<f64> {
+        self.nasbench
+            .models()
+            .values()
+            .flat_map(|model| model.epochs.values().flatten())
+            .map(|epoch| 1.0 - epoch.complete.validation_accuracy)
+            .min_by_key(|&v| OrderedFloat(v))
+    }
+}
 impl ProblemFactory for NasbenchProblemFactory {
     type Problem = NasbenchProblem;
 
@@ -92,6 +103,7 @@ impl ProblemFactory for NasbenchProblemFactory {
                 "version",
                 &format!("kurobako_problems={}", env!("CARGO_PKG_VERSION")),
             )
+            .attr("family", "nasbench")
             .attr(
                 "paper",
                 "Ying, Chris, et al. \"Nas-bench-101: Towards reproducible \
@@ -100,6 +112,11 @@ impl ProblemFactory for NasbenchProblemFactory {
             .attr("github", "https://github.com/automl/nas_benchmarks")
             .params(self.encoding.params())
             .steps(vec![4, 12, 36, 108]);
+        if self.metrics == [Metric::Accuracy] {
+            if let Some(optimum) = self.optimum() {
+                spec = spec.attr("optimum", &optimum.to_string());
+            }
+        }
         for metric in &self.metrics {
             match metric {
                 Metric::Accuracy => {
@@ -94,8 +94,14 @@ impl ProblemFactory for SigoptProblemFactory {
                 "version",
                 &format!("kurobako_problems={}", env!("CARGO_PKG_VERSION")),
             )
+            .attr("family", "sigopt")
             .attr("paper", paper)
             .attr("github", "https://github.com/sigopt/evalset");
+        if self.res.is_none() && self.int.is_empty() {
+            if let Some(optimum) = test_function.optimum(self.dim) {
+                spec = spec.attr("optimum", &optimum.to_string());
+            }
+        }
 
         for (i, (low, high)) in track!(test_function.bounds(self.dim))?
             .into_iter()
@@ -15,6 +15,11 @@ pub trait TestFunction: 'static + fmt::Debug + Send + Sync {
 
     fn bounds(&self, dim: usize) -> Result<Vec<(f64, f64)>>;
     fn evaluate(&self, xs: &[f64]) -> f64;
+
+    /// Returns the global minimum value of the function if it is known.
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        None
+    }
 }
 
 #[derive(Debug)]
@@ -35,6 +40,10 @@ impl TestFunction for Ackley {
         let g = (f / dim).exp();
         e - g + a + 1f64.exp()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -125,6 +134,10 @@ impl TestFunction for Csendes {
             .map(|&x| x.powi(6) * (2.0 + (1.0 / (x + EPSILON)).sin()))
             .sum()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -178,6 +191,10 @@ impl TestFunction for Easom {
         let e = (xs.iter().map(|&x| (c * x).cos()).sum::<f64>() / n).exp();
         -a * (-b * d).exp() - e + a + 1f64.exp()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -191,6 +208,10 @@ impl TestFunction for Exponential {
         let a = xs.iter().map(|&x| x * x).sum::<f64>();
         -(-0.5 * a).exp()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(-1.0)
+    }
 }
 
 #[derive(Debug)]
@@ -228,6 +249,10 @@ impl TestFunction for Hartmann3 {
             .sum::<f64>();
         -e
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(-3.86278)
+    }
 }
 
 #[derive(Debug)]
@@ -271,6 +296,10 @@ impl TestFunction for Hartmann6 {
             .sum::<f64>();
         -e
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(-3.32237)
+    }
 }
 
 #[derive(Debug)]
@@ -294,6 +323,10 @@ impl TestFunction for HelicalValley {
                 + ((x1 * x1 + x2 * x2).sqrt() - 1.0).powi(2))
             + x3 * x3
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -313,6 +346,10 @@ impl TestFunction for HimmelBlau {
         let x2 = xs[1];
         (x1.powi(2) + x2 - 11.0).powi(2) + (x1 + x2.powi(2) - 7.0).powi(2)
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1598,6 +1635,10 @@ impl TestFunction for Rastrigin {
                 .map(|&x| x * x - 10.0 * (2.0 * PI * x).cos())
                 .sum::<f64>()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1673,6 +1714,10 @@ impl TestFunction for Schwefel20 {
     fn evaluate(&self, xs: &[f64]) -> f64 {
         xs.iter().map(|&x| x.abs()).sum()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1788,6 +1833,10 @@ impl TestFunction for SixHumpCamel {
             + x1 * x2
             + (4.0 * x2.powi(2) - 4.0) * x2.powi(2)
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(-1.0316284534898774)
+    }
 }
 
 #[derive(Debug)]
@@ -1804,6 +1853,10 @@ impl TestFunction for Sphere {
     fn evaluate(&self, xs: &[f64]) -> f64 {
         xs.iter().map(|&x| x * x).sum()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1823,6 +1876,10 @@ impl TestFunction for StyblinskiTang {
             .sum::<f64>()
             / 2.0
     }
+
+    fn optimum(&self, dim: usize) -> Option<f64> {
+        Some(-39.16616570377142 * dim as f64)
+    }
 }
 
 #[derive(Debug)]
@@ -1847,6 +1904,10 @@ impl TestFunction for Trid {
             .sum::<f64>();
         a - b
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(-50.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1870,6 +1931,10 @@ impl TestFunction for Tripod {
             + (x1 + 50.0 * p2 * (1.0 - 2.0 * p1)).abs()
             + (x2 + 50.0 * (1.0 - 2.0 * p2)).abs()
     }
+
+    fn optimum(&self, _dim: usize) -> Option<f64> {
+        Some(0.0)
+    }
 }
 
 #[derive(Debug)]
@@ -1,8 +1,8 @@
 //! A problem for warm-starting optimizations.
 use kurobako_core::json::JsonRecipe;
 use kurobako_core::problem::{
-    BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory, ProblemRecipe,
-    ProblemSpec, ProblemSpecBuilder,
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec, ProblemSpecBuilder,
 };
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
@@ -133,4 +133,11 @@ impl Evaluator for WarmStartingEvaluator {
             track!(self.target_evaluator.evaluate(next_step))
         }
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        AbortHandle::all(vec![
+            self.source_evaluator.abort_handle(),
+            self.target_evaluator.abort_handle(),
+        ])
+    }
 }
@@ -17,6 +17,9 @@ use std::f64;
 use std::f64::consts::PI;
 use structopt::StructOpt;
 
+// The number of the points of the `pareto_front` attribute.
+const PARETO_FRONT_SAMPLES: usize = 100;
+
 /// Recipe of `ZdtProblem`.
 #[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
 #[structopt(rename_all = "kebab-case")]
@@ -50,12 +53,24 @@ impl ProblemFactory for ZdtProblemFactory {
                 "version",
                 &format!("kurobako_problems={}", env!("CARGO_PKG_VERSION")),
             )
+            .attr("family", "zdt")
             .attr(
                 "paper",
                 "Zitzler, Eckart, Kalyanmoy Deb, and Lothar Thiele. \"Comparison of multiobjective \
                  evolutionary algorithms: Empirical results.\" Evolutionary computation 8.2 (2000): 173-195."
             ).value(domain::var("f1")).value(domain::var("f2")).reference_point(Some(Params::new(vec![11.0, 11.0])));
 
+        if let Some(front) = self.zdt.pareto_front(PARETO_FRONT_SAMPLES) {
+            let front = front
+                .into_iter()
+                .map(|(f1, f2)| format!("{},{}", f1, f2))
+                .collect::<Vec<_>>();
+            spec = spec.attr("pareto_front", &front.join(" "));
+        }
+        if let Some((f1, f2)) = self.zdt.ideal_point() {
+            spec = spec.attr("ideal_point", &format!("{},{}", f1, f2));
+        }
+
         for (i, range) in self.zdt.ranges().into_iter().enumerate() {
             spec = spec.param(domain::var(&format!("x{}", i)).range(range));
         }
@@ -176,6 +191,60 @@ impl Zdt {
         }
     }
 
+    /// Returns points sampled from the true pareto front (sorted by `f1`).
+    ///
+    /// The front is obtained by fixing all parameters other than `x0` at the values minimizing `g`.
+    /// `None` is returned for the function 4 because `g` of its minimizer is zero
+    /// (i.e., `f2` isn't defined on the front).
+    fn pareto_front(self, samples: usize) -> Option<Vec<(f64, f64)>> {
+        let points = match self {
+            Self::Function1 | Self::Function2 | Self::Function3 | Self::Function6 => {
+                let dim = self.ranges().len();
+                (0..=samples)
+                    .map(|i| {
+                        let mut xs = vec![0.0; dim];
+                        xs[0] = i as f64 / samples as f64;
+                        self.evaluate(&xs)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            Self::Function4 => return None,
+            Self::Function5 => {
+                // `f1` takes only 31 values and `g` is minimized by the all-ones substrings.
+                (0..=30)
+                    .map(|ones| {
+                        let mut xs = vec![31.0; 11];
+                        xs[0] = ((1u64 << ones) - 1) as f64;
+                        self.evaluate(&xs)
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let mut points = points
+            .into_iter()
+            .map(|vs| (vs[0], vs[1]))
+            .collect::<Vec<_>>();
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap_or_else(|| unreachable!()));
+
+        let mut front: Vec<(f64, f64)> = Vec::new();
+        for p in points {
+            if front.last().map_or(true, |q| p.1 < q.1) {
+                front.push(p);
+            }
+        }
+        Some(front)
+    }
+
+    /// Returns the ideal point (i.e., the minimum value of each objective on the pareto front).
+    fn ideal_point(self) -> Option<(f64, f64)> {
+        const SAMPLES: usize = 100_000;
+
+        let front = self.pareto_front(SAMPLES)?;
+        let f1 = front.first()?.0;
+        let f2 = front.last()?.1;
+        Some((f1, f2))
+    }
+
     fn evaluate(self, xs: &[f64]) -> Vec<f64> {
         match self {
             Self::Function1 => self.evaluate_zdt1(xs),
@@ -249,3 +318,37 @@ impl Zdt {
         vec![f1, f2]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ideal_point_works() {
+        let close =
+            |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6;
+        assert!(close(Zdt::Function1.ideal_point().unwrap(), (0.0, 0.0)));
+        assert!(close(Zdt::Function2.ideal_point().unwrap(), (0.0, 0.0)));
+        assert!(close(
+            Zdt::Function3.ideal_point().unwrap(),
+            (0.0, -0.773369012)
+        ));
+        assert!(Zdt::Function4.ideal_point().is_none());
+        assert!(close(
+            Zdt::Function5.ideal_point().unwrap(),
+            (1.0, 10.0 / 31.0)
+        ));
+        assert!(close(
+            Zdt::Function6.ideal_point().unwrap(),
+            (0.280775319, 0.0)
+        ));
+    }
+
+    #[test]
+    fn pareto_front_of_zdt5_works() {
+        let front = Zdt::Function5.pareto_front(PARETO_FRONT_SAMPLES).unwrap();
+        assert_eq!(front.len(), 31);
+        assert_eq!(front[0], (1.0, 10.0));
+        assert_eq!(front[1], (2.0, 5.0));
+    }
+}
@@ -0,0 +1,343 @@
+//! A solver based on simulated annealing.
+//!
+//! # References
+//!
+//! - [Optimization by Simulated Annealing][Kirkpatrick et al., 1983]
+//!
+//! [Kirkpatrick et al., 1983]: https://doi.org/10.1126/science.220.4598.671
+use crate::scale::{denormalize, normalize};
+use kurobako_core::domain::{Range, Variable};
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Cooling schedule of `AnnealingSolver`.
+///
+/// `T0` is the initial temperature, `k` is the number of the evaluated candidates,
+/// and `r` is the cooling rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cooling {
+    /// `T0 * r^k`.
+    Exponential,
+
+    /// `T0 / (1 + r * ln(1 + k))`.
+    Logarithmic,
+
+    /// `T0 / (1 + r * k)`.
+    Fast,
+}
+impl Cooling {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["exponential", "logarithmic", "fast"];
+
+    fn temperature(self, initial: f64, rate: f64, k: u64) -> f64 {
+        let k = k as f64;
+        match self {
+            Self::Exponential => initial * rate.powf(k),
+            Self::Logarithmic => initial / (1.0 + rate * k.ln_1p()),
+            Self::Fast => initial / (1.0 + rate * k),
+        }
+    }
+}
+impl Default for Cooling {
+    fn default() -> Self {
+        Self::Exponential
+    }
+}
+impl FromStr for Cooling {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "exponential" => Ok(Self::Exponential),
+            "logarithmic" => Ok(Self::Logarithmic),
+            "fast" => Ok(Self::Fast),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown cooling schedule: {:?}", s),
+        }
+    }
+}
+
+fn default_initial_temperature() -> f64 {
+    1.0
+}
+
+fn default_cooling_rate() -> f64 {
+    0.95
+}
+
+fn default_jitter() -> f64 {
+    0.1
+}
+
+fn default_max_step() -> u64 {
+    1
+}
+
+/// Recipe of `AnnealingSolver`.
+///
+/// A candidate is a neighbor of the current parameters where one randomly chosen variable
+/// (and each of the others with the probability `--perturbation-rate`) is perturbed as follows:
+/// - a continuous variable is jittered by a normal distribution (in the log space if it is log-scaled),
+/// - a discrete variable is moved by a random step within `--max-step`, and
+/// - a categorical variable is resampled from the other choices.
+///
+/// The candidate replaces the current parameters with the probability `exp(-(new - current) / T)`
+/// (the temperature `T` is in the same unit as the objective value).
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct AnnealingSolverRecipe {
+    /// Initial temperature.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default = "default_initial_temperature")]
+    initial_temperature: f64,
+
+    /// Cooling schedule.
+    #[structopt(long, default_value = "exponential", possible_values = Cooling::POSSIBLE_VALUES)]
+    #[serde(default)]
+    cooling: Cooling,
+
+    /// Cooling rate (the meaning depends on the cooling schedule).
+    #[structopt(long, default_value = "0.95")]
+    #[serde(default = "default_cooling_rate")]
+    cooling_rate: f64,
+
+    /// Standard deviation of the jitter of continuous variables (relative to their ranges).
+    #[structopt(long, default_value = "0.1")]
+    #[serde(default = "default_jitter")]
+    jitter: f64,
+
+    /// Maximum step of discrete variables.
+    #[structopt(long, default_value = "1")]
+    #[serde(default = "default_max_step")]
+    max_step: u64,
+
+    /// Probability that each variable other than the chosen one is perturbed.
+    #[structopt(long, default_value = "0.0")]
+    #[serde(default)]
+    perturbation_rate: f64,
+}
+impl SolverRecipe for AnnealingSolverRecipe {
+    type Factory = AnnealingSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(
+            self.initial_temperature > 0.0,
+            ErrorKind::InvalidInput; self.initial_temperature
+        );
+        if self.cooling == Cooling::Exponential {
+            track_assert!(
+                0.0 < self.cooling_rate && self.cooling_rate <= 1.0,
+                ErrorKind::InvalidInput; self.cooling_rate
+            );
+        } else {
+            track_assert!(self.cooling_rate >= 0.0, ErrorKind::InvalidInput; self.cooling_rate);
+        }
+        track_assert!(self.jitter > 0.0, ErrorKind::InvalidInput; self.jitter);
+        track_assert!(self.max_step > 0, ErrorKind::InvalidInput; self.max_step);
+        track_assert!(
+            0.0 <= self.perturbation_rate && self.perturbation_rate <= 1.0,
+            ErrorKind::InvalidInput; self.perturbation_rate
+        );
+        Ok(AnnealingSolverFactory {
+            recipe: self.clone(),
+        })
+    }
+}
+
+/// Factory of `AnnealingSolver`.
+#[derive(Debug)]
+pub struct AnnealingSolverFactory {
+    recipe: AnnealingSolverRecipe,
+}
+impl SolverFactory for AnnealingSolverFactory {
+    type Solver = AnnealingSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("Simulated Annealing")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Kirkpatrick, Scott, C. Daniel Gelatt, and Mario P. Vecchi. \"Optimization by \
+                 simulated annealing.\" Science 220.4598 (1983): 671-680.",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Categorical)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "Simulated annealing solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
+        track_assert!(
+            !problem.params_domain.variables().is_empty(),
+            ErrorKind::InvalidInput
+        );
+
+        Ok(AnnealingSolver {
+            problem: problem.clone(),
+            rng,
+            recipe: self.recipe.clone(),
+            pending: None,
+            state: AnnealingState {
+                current: None,
+                iterations: 0,
+            },
+        })
+    }
+}
+
+/// Solver based on simulated annealing.
+#[derive(Debug)]
+pub struct AnnealingSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    recipe: AnnealingSolverRecipe,
+
+    // The trial being evaluated.
+    pending: Option<(TrialId, Vec<f64>)>,
+
+    state: AnnealingState,
+}
+impl AnnealingSolver {
+    fn neighbor(&mut self, current: &[f64]) -> Vec<f64> {
+        let variables = self.problem.params_domain.variables().to_vec();
+        let chosen = self.rng.gen_range(0..variables.len());
+        let mut params = current.to_vec();
+        for (i, (v, p)) in variables.iter().zip(params.iter_mut()).enumerate() {
+            if i == chosen || self.rng.gen_bool(self.recipe.perturbation_rate) {
+                *p = self.perturb(v, *p);
+            }
+        }
+        params
+    }
+
+    fn perturb(&mut self, var: &Variable, x: f64) -> f64 {
+        match var.range() {
+            Range::Continuous { .. } => {
+                let u = normalize(var, x) + self.recipe.jitter * standard_normal(&mut self.rng);
+                denormalize(var, u.max(0.0).min(1.0 - std::f64::EPSILON))
+            }
+            Range::Discrete { low, high } => {
+                let step = self.rng.gen_range(1..=self.recipe.max_step) as f64;
+                let step = if self.rng.gen() { step } else { -step };
+                (x + step).max(*low as f64).min(*high as f64 - 1.0)
+            }
+            Range::Categorical { choices } => {
+                if choices.len() == 1 {
+                    return x;
+                }
+                let i = self.rng.gen_range(0..choices.len() - 1) as f64;
+                if i < x {
+                    i
+                } else {
+                    i + 1.0
+                }
+            }
+        }
+    }
+}
+impl Solver for AnnealingSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let params = if let Some(current) = self.state.current.take() {
+            let params = self.neighbor(&current.params);
+            self.state.current = Some(current);
+            params
+        } else {
+            let variables = self.problem.params_domain.variables();
+            let mut params = Vec::with_capacity(variables.len());
+            for v in variables {
+                params.push(denormalize(v, self.rng.gen()));
+            }
+            params
+        };
+
+        let id = idg.generate();
+        self.pending = Some((id, params.clone()));
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        let params = match self.pending.take() {
+            Some((id, params)) if id == trial.id => params,
+            pending => {
+                // The trial was asked before the latest one (or before the solver state was restored).
+                self.pending = pending;
+                return Ok(());
+            }
+        };
+
+        // Unevaluable parameters are always rejected.
+        let value = if let Some(&v) = trial.values.first().filter(|v| v.is_finite()) {
+            v
+        } else {
+            return Ok(());
+        };
+
+        let accepted = if let Some(current) = &self.state.current {
+            let temperature = self.recipe.cooling.temperature(
+                self.recipe.initial_temperature,
+                self.recipe.cooling_rate,
+                self.state.iterations,
+            );
+            self.state.iterations += 1;
+            value <= current.value
+                || self.rng.gen::<f64>() < (-(value - current.value) / temperature).exp()
+        } else {
+            true
+        };
+        if accepted {
+            self.state.current = Some(Candidate { params, value });
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.state = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnnealingState {
+    current: Option<Candidate>,
+    iterations: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Candidate {
+    params: Vec<f64>,
+    value: f64,
+}
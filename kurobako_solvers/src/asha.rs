@@ -77,7 +77,8 @@ impl SolverFactory for AshaSolverFactory {
     fn specification(&self) -> Result<SolverSpec> {
         let mut base = track!(self.base.specification())?;
         base.capabilities
-            .remove_capability(Capability::MultiObjective);
+            .remove_capability(Capability::MultiObjective)
+            .remove_capability(Capability::Checkpointable);
 
         let spec = SolverSpecBuilder::new(&format!("ASHA with {}", base.name))
             .attr(
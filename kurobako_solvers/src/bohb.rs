@@ -0,0 +1,264 @@
+//! A solver based on BOHB (Bayesian Optimization and HyperBand).
+//!
+//! # References
+//!
+//! - [BOHB: Robust and Efficient Hyperparameter Optimization at Scale][Falkner et al., 2018]
+//!
+//! [Falkner et al., 2018]: https://arxiv.org/abs/1807.01774
+use crate::hyperband::{HyperbandSolver, Sampler};
+use crate::tpe;
+use kurobako_core::domain::{Range, Variable};
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capabilities, Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params};
+use kurobako_core::{ErrorKind, Result};
+use rand::distributions::Distribution as _;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use structopt::StructOpt;
+
+fn default_min_step_rate() -> f64 {
+    0.01
+}
+
+fn default_reduction_factor() -> usize {
+    3
+}
+
+fn default_random_fraction() -> f64 {
+    1.0 / 3.0
+}
+
+fn default_gamma() -> f64 {
+    0.15
+}
+
+fn default_ei_candidates() -> NonZeroUsize {
+    NonZeroUsize::new(64).unwrap_or_else(|| unreachable!())
+}
+
+fn default_prior_weight() -> f64 {
+    1.0
+}
+
+/// Recipe of `BohbSolver`.
+///
+/// The trials are scheduled in the same manner as `HyperbandSolverRecipe`,
+/// but the parameters of the new trials are sampled by the TPE model built from the observations
+/// at the largest step where enough observations have been collected.
+/// Unlike the original BOHB (which uses multivariate kernel density estimators),
+/// the model is univariate and shared with `TpeSolverRecipe`.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct BohbSolverRecipe {
+    /// Rate to determine the value of `min_step`.
+    ///
+    /// The value of `min_step` will be set to `problem.steps.last() * min_step_rate`.
+    /// If `min_step` is given, this field is ignored.
+    #[structopt(long, default_value = "0.01")]
+    #[serde(default = "default_min_step_rate")]
+    min_step_rate: f64,
+
+    /// Minimum step at which trials are evaluated.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_step: Option<u64>,
+
+    /// Reduction factor (a.k.a. `eta`) of the successive halving.
+    #[structopt(long, default_value = "3")]
+    #[serde(default = "default_reduction_factor")]
+    reduction_factor: usize,
+
+    /// Fraction of the trials whose parameters are sampled randomly (regardless of the model).
+    #[structopt(long, default_value = "0.3333333333333333")]
+    #[serde(default = "default_random_fraction")]
+    random_fraction: f64,
+
+    /// Minimum number of the observations at a step required to build the model.
+    ///
+    /// If omitted, the number of the variables plus one is used.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_points_in_model: Option<usize>,
+
+    /// Ratio of the observations regarded as good ones.
+    #[structopt(long, default_value = "0.15")]
+    #[serde(default = "default_gamma")]
+    gamma: f64,
+
+    /// Number of the candidates from which the parameter maximizing the expected improvement is selected.
+    #[structopt(long, default_value = "64")]
+    #[serde(default = "default_ei_candidates")]
+    ei_candidates: NonZeroUsize,
+
+    /// Weight of the prior distribution.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default = "default_prior_weight")]
+    prior_weight: f64,
+}
+impl SolverRecipe for BohbSolverRecipe {
+    type Factory = BohbSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.reduction_factor >= 2, ErrorKind::InvalidInput; self.reduction_factor);
+        track_assert!(
+            0.0 < self.min_step_rate && self.min_step_rate <= 1.0,
+            ErrorKind::InvalidInput; self.min_step_rate
+        );
+        track_assert!(
+            0.0 <= self.random_fraction && self.random_fraction <= 1.0,
+            ErrorKind::InvalidInput; self.random_fraction
+        );
+        track_assert!(
+            0.0 < self.gamma && self.gamma < 1.0,
+            ErrorKind::InvalidInput; self.gamma
+        );
+        track_assert!(self.prior_weight > 0.0, ErrorKind::InvalidInput; self.prior_weight);
+        Ok(BohbSolverFactory {
+            recipe: self.clone(),
+        })
+    }
+}
+
+/// Factory of `BohbSolver`.
+#[derive(Debug)]
+pub struct BohbSolverFactory {
+    recipe: BohbSolverRecipe,
+}
+impl SolverFactory for BohbSolverFactory {
+    type Solver = BohbSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("BOHB")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Falkner, Stefan, Aaron Klein, and Frank Hutter. \"BOHB: Robust and efficient \
+                 hyperparameter optimization at scale.\" International Conference on Machine \
+                 Learning. 2018.",
+            )
+            .capabilities(
+                Capabilities::all()
+                    .remove_capability(Capability::MultiObjective)
+                    .remove_capability(Capability::WarmStarting)
+                    .remove_capability(Capability::Checkpointable)
+                    .clone(),
+            );
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        let variables = problem.params_domain.variables();
+        for v in variables {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "BOHB solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
+
+        let recipe = &self.recipe;
+        let sampler = TpeSampler {
+            variables: variables.to_vec(),
+            random_fraction: recipe.random_fraction,
+            min_points_in_model: recipe
+                .min_points_in_model
+                .unwrap_or_else(|| variables.len() + 1),
+            gamma: recipe.gamma,
+            ei_candidates: recipe.ei_candidates.get(),
+            prior_weight: recipe.prior_weight,
+            observations: BTreeMap::new(),
+        };
+        let inner = track!(HyperbandSolver::new(
+            rng,
+            problem,
+            recipe.min_step_rate,
+            recipe.min_step,
+            recipe.reduction_factor,
+            Box::new(sampler),
+        ))?;
+        Ok(BohbSolver { inner })
+    }
+}
+
+/// Solver based on BOHB.
+#[derive(Debug)]
+pub struct BohbSolver {
+    inner: HyperbandSolver,
+}
+impl Solver for BohbSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        track!(self.inner.ask(idg))
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        track!(self.inner.tell(trial))
+    }
+}
+
+#[derive(Debug)]
+struct TpeSampler {
+    variables: Vec<Variable>,
+    random_fraction: f64,
+    min_points_in_model: usize,
+    gamma: f64,
+    ei_candidates: usize,
+    prior_weight: f64,
+
+    // Evaluated parameters and their values (`None` means unevaluable) grouped by the steps.
+    observations: BTreeMap<u64, Vec<(Params, Option<f64>)>>,
+}
+impl Sampler for TpeSampler {
+    fn sample(&mut self, rng: &mut ArcRng) -> Params {
+        let min_points = self.min_points_in_model;
+        let observations = self
+            .observations
+            .values()
+            .rev()
+            .find(|o| o.len() >= min_points.max(2));
+        let observations = match observations {
+            Some(o) if rng.gen::<f64>() >= self.random_fraction => o,
+            _ => {
+                let mut params = Vec::with_capacity(self.variables.len());
+                for v in &self.variables {
+                    params.push(v.sample(rng));
+                }
+                return Params::new(params);
+            }
+        };
+
+        let mut sorted = observations.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|o| OrderedFloat(o.1.unwrap_or(std::f64::INFINITY)));
+        let n = ((self.gamma * sorted.len() as f64).ceil() as usize).max(1);
+        let good = sorted[..n].iter().map(|o| &o.0).collect::<Vec<_>>();
+        let others = sorted[n..].iter().map(|o| &o.0).collect::<Vec<_>>();
+        Params::new(tpe::sample_params(
+            rng,
+            &self.variables,
+            &good,
+            &others,
+            self.ei_candidates,
+            self.prior_weight,
+        ))
+    }
+
+    fn observe(&mut self, params: &Params, step: u64, value: Option<f64>) {
+        self.observations
+            .entry(step)
+            .or_default()
+            .push((params.clone(), value));
+    }
+}
@@ -0,0 +1,707 @@
+//! A solver based on CMA-ES (Covariance Matrix Adaptation Evolution Strategy).
+//!
+//! # References
+//!
+//! - [The CMA Evolution Strategy: A Tutorial][Hansen, 2016]
+//! - [A Restart CMA Evolution Strategy With Increasing Population Size][Auger and Hansen, 2005]
+//! - [Benchmarking a BI-Population CMA-ES on the BBOB-2009 Function Testbed][Hansen, 2009]
+//!
+//! [Hansen, 2016]: https://arxiv.org/abs/1604.00772
+//! [Auger and Hansen, 2005]: https://doi.org/10.1109/CEC.2005.1554902
+//! [Hansen, 2009]: https://doi.org/10.1145/1570256.1570333
+use crate::scale::denormalize;
+use kurobako_core::domain::Range;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+// Maximum number of the resamplings of a candidate that violates the box constraints.
+const MAX_RESAMPLINGS: usize = 100;
+
+/// Restart strategy of `CmaEsSolver`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Restart {
+    /// Never restarts.
+    None,
+
+    /// Restarts with doubling the population size (IPOP-CMA-ES).
+    Ipop,
+
+    /// Restarts alternating a large population regime and a small population regime (BIPOP-CMA-ES).
+    Bipop,
+}
+impl Restart {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["none", "ipop", "bipop"];
+}
+impl Default for Restart {
+    fn default() -> Self {
+        Self::None
+    }
+}
+impl FromStr for Restart {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "ipop" => Ok(Self::Ipop),
+            "bipop" => Ok(Self::Bipop),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown restart strategy: {:?}", s),
+        }
+    }
+}
+
+fn is_default_restart(r: &Restart) -> bool {
+    *r == Restart::None
+}
+
+/// Recipe of `CmaEsSolver`.
+///
+/// The search space is normalized to the unit hypercube (in the log space for log-scaled variables),
+/// and the candidates that violate the bounds are resampled (and clipped if they still violate the bounds).
+/// Discrete variables are handled by rounding down the continuous values.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct CmaEsSolverRecipe {
+    /// Initial step size in the normalized search space (the default is `1/6`).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sigma0: Option<f64>,
+
+    /// Population size (the default is `4 + floor(3 * ln(dimensions))`).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    popsize: Option<usize>,
+
+    /// Restart strategy.
+    #[structopt(long, default_value = "none", possible_values = Restart::POSSIBLE_VALUES)]
+    #[serde(default, skip_serializing_if = "is_default_restart")]
+    restart: Restart,
+
+    /// Factor by which the population size is increased on each restart (of the large population regime).
+    #[structopt(long, default_value = "2.0")]
+    #[serde(default = "default_inc_popsize")]
+    inc_popsize: f64,
+}
+impl SolverRecipe for CmaEsSolverRecipe {
+    type Factory = CmaEsSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        let sigma0 = self.sigma0.unwrap_or(1.0 / 6.0);
+        track_assert!(sigma0 > 0.0, ErrorKind::InvalidInput; sigma0);
+        if let Some(popsize) = self.popsize {
+            track_assert!(popsize >= 2, ErrorKind::InvalidInput; popsize);
+        }
+        track_assert!(self.inc_popsize >= 1.0, ErrorKind::InvalidInput; self.inc_popsize);
+        Ok(CmaEsSolverFactory {
+            sigma0,
+            popsize: self.popsize,
+            restart: self.restart,
+            inc_popsize: self.inc_popsize,
+        })
+    }
+}
+
+fn default_inc_popsize() -> f64 {
+    2.0
+}
+
+/// Factory of `CmaEsSolver`.
+#[derive(Debug)]
+pub struct CmaEsSolverFactory {
+    sigma0: f64,
+    popsize: Option<usize>,
+    restart: Restart,
+    inc_popsize: f64,
+}
+impl SolverFactory for CmaEsSolverFactory {
+    type Solver = CmaEsSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("CMA-ES")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Hansen, Nikolaus. \"The CMA evolution strategy: A tutorial.\" \
+                 arXiv preprint arXiv:1604.00772 (2016).",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Concurrent)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        let dimensions = problem.params_domain.variables().len();
+        track_assert!(dimensions > 0, ErrorKind::InvalidInput);
+        for v in problem.params_domain.variables() {
+            match v.range() {
+                Range::Continuous { low, high } => {
+                    track_assert!(
+                        low.is_finite() && high.is_finite(),
+                        ErrorKind::InvalidInput,
+                        "CMA-ES solver doesn't support unbounded variables: {:?}",
+                        v
+                    );
+                }
+                Range::Discrete { .. } => {}
+                Range::Categorical { .. } => {
+                    track_panic!(ErrorKind::Incapable, "Categorical variable: {:?}", v)
+                }
+            }
+        }
+
+        let default_popsize = 4 + (3.0 * (dimensions as f64).ln()).floor() as usize;
+        let popsize = self.popsize.unwrap_or(default_popsize);
+        Ok(CmaEsSolver {
+            problem: problem.clone(),
+            rng,
+            sigma0: self.sigma0,
+            restart: self.restart,
+            inc_popsize: self.inc_popsize,
+            state: CmaEsState {
+                cma: Cma::new(vec![0.5; dimensions], self.sigma0, popsize),
+                default_popsize: popsize,
+                large_popsize: popsize,
+                large_evaluations: 0,
+                small_evaluations: 0,
+                restarts: 0,
+            },
+            evaluatings: HashMap::new(),
+        })
+    }
+}
+
+/// Solver based on CMA-ES.
+#[derive(Debug)]
+pub struct CmaEsSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    sigma0: f64,
+    restart: Restart,
+    inc_popsize: f64,
+    state: CmaEsState,
+
+    // Candidates being evaluated (the number of the restarts and the generation at the ask,
+    // and the point in the normalized space of each trial).
+    evaluatings: HashMap<TrialId, (usize, u64, Vec<f64>)>,
+}
+impl CmaEsSolver {
+    fn restart(&mut self) {
+        let state = &mut self.state;
+        let evaluations = state.cma.generation * state.cma.popsize as u64;
+        let is_large = state.cma.popsize >= state.large_popsize;
+        if is_large {
+            state.large_evaluations += evaluations;
+        } else {
+            state.small_evaluations += evaluations;
+        }
+        state.restarts += 1;
+
+        let dimensions = state.cma.mean.len();
+        let rng = &mut self.rng;
+        let mean = (0..dimensions).map(|_| rng.gen()).collect();
+        let (sigma, popsize) = match self.restart {
+            Restart::None => unreachable!(),
+            Restart::Ipop => {
+                state.large_popsize = (state.large_popsize as f64 * self.inc_popsize) as usize;
+                (self.sigma0, state.large_popsize)
+            }
+            Restart::Bipop => {
+                if state.restarts == 1 || state.large_evaluations <= state.small_evaluations {
+                    state.large_popsize = (state.large_popsize as f64 * self.inc_popsize) as usize;
+                    (self.sigma0, state.large_popsize)
+                } else {
+                    let u = self.rng.gen::<f64>();
+                    let ratio = 0.5 * state.large_popsize as f64 / state.default_popsize as f64;
+                    let popsize =
+                        (state.default_popsize as f64 * ratio.powf(u * u)).floor() as usize;
+                    let popsize = popsize
+                        .max(state.default_popsize)
+                        .min(state.large_popsize - 1);
+                    (
+                        self.sigma0 * 10f64.powf(-2.0 * self.rng.gen::<f64>()),
+                        popsize,
+                    )
+                }
+            }
+        };
+        state.cma = Cma::new(mean, sigma, popsize);
+    }
+}
+impl Solver for CmaEsSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let x = self.state.cma.sample(&mut self.rng);
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(&x)
+            .map(|(v, &u)| denormalize(v, u))
+            .collect();
+
+        let id = idg.generate();
+        self.evaluatings
+            .insert(id, (self.state.restarts, self.state.cma.generation, x));
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        let (restarts, generation, x) = if let Some(entry) = self.evaluatings.remove(&trial.id) {
+            entry
+        } else {
+            // The trial was asked before the solver state was restored.
+            return Ok(());
+        };
+        if restarts != self.state.restarts || generation != self.state.cma.generation {
+            // The trial belongs to an old generation (or an old run before a restart).
+            return Ok(());
+        }
+
+        // Unevaluable parameters are regarded as the worst ones.
+        let value = trial
+            .values
+            .first()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .unwrap_or(std::f64::INFINITY);
+        self.state.cma.tell(x, value);
+        if self.state.cma.should_stop() && self.restart != Restart::None {
+            self.restart();
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.state = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.evaluatings.clear();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CmaEsState {
+    cma: Cma,
+    default_popsize: usize,
+    large_popsize: usize,
+    large_evaluations: u64,
+    small_evaluations: u64,
+    restarts: usize,
+}
+
+// A single run of CMA-ES in the normalized search space `[0, 1]^n`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cma {
+    mean: Vec<f64>,
+    sigma: f64,
+    sigma0: f64,
+    popsize: usize,
+    generation: u64,
+
+    // Evolution paths.
+    pc: Vec<f64>,
+    ps: Vec<f64>,
+
+    // Covariance matrix `C = B * D^2 * B^T`.
+    c: Vec<Vec<f64>>,
+    b: Vec<Vec<f64>>,
+    d: Vec<f64>,
+
+    // Evaluated candidates of the current generation.
+    solutions: Vec<(Vec<f64>, f64)>,
+
+    // Best values of the past generations (used to detect stagnation).
+    best_values: Vec<f64>,
+}
+impl Cma {
+    fn new(mean: Vec<f64>, sigma: f64, popsize: usize) -> Self {
+        let n = mean.len();
+        let identity = (0..n)
+            .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect::<Vec<Vec<_>>>();
+        Self {
+            mean,
+            sigma,
+            sigma0: sigma,
+            popsize,
+            generation: 0,
+            pc: vec![0.0; n],
+            ps: vec![0.0; n],
+            c: identity.clone(),
+            b: identity,
+            d: vec![1.0; n],
+            solutions: Vec::new(),
+            best_values: Vec::new(),
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let n = self.mean.len();
+        let mut x = Vec::new();
+        for _ in 0..MAX_RESAMPLINGS {
+            let z = (0..n).map(|_| standard_normal(rng)).collect::<Vec<_>>();
+            x = (0..n)
+                .map(|i| {
+                    let y = (0..n).map(|j| self.b[i][j] * self.d[j] * z[j]).sum::<f64>();
+                    self.mean[i] + self.sigma * y
+                })
+                .collect();
+            if x.iter().all(|&v| (0.0..=1.0).contains(&v)) {
+                return x;
+            }
+        }
+        x.into_iter().map(|v| v.max(0.0).min(1.0)).collect()
+    }
+
+    fn tell(&mut self, x: Vec<f64>, value: f64) {
+        self.solutions.push((x, value));
+        if self.solutions.len() < self.popsize {
+            return;
+        }
+
+        let mut solutions = std::mem::take(&mut self.solutions);
+        solutions.sort_by_key(|s| OrderedFloat(s.1));
+        self.best_values.push(solutions[0].1);
+        self.update(&solutions);
+        self.generation += 1;
+    }
+
+    fn update(&mut self, solutions: &[(Vec<f64>, f64)]) {
+        let n = self.mean.len();
+        let nf = n as f64;
+
+        // Strategy parameters.
+        let mu = self.popsize / 2;
+        let weights = (1..=mu)
+            .map(|i| ((self.popsize as f64 + 1.0) / 2.0).ln() - (i as f64).ln())
+            .collect::<Vec<_>>();
+        let sum = weights.iter().sum::<f64>();
+        let weights = weights.into_iter().map(|w| w / sum).collect::<Vec<_>>();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+        let cc = (4.0 + mu_eff / nf) / (nf + 4.0 + 2.0 * mu_eff / nf);
+        let cs = (mu_eff + 2.0) / (nf + mu_eff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mu_eff);
+        let cmu =
+            (1.0 - c1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((nf + 2.0).powi(2) + mu_eff));
+        let damps = 1.0 + 2.0 * (((mu_eff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        // Mean.
+        let ys = solutions[..mu]
+            .iter()
+            .map(|(x, _)| {
+                x.iter()
+                    .zip(&self.mean)
+                    .map(|(x, m)| (x - m) / self.sigma)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let yw = (0..n)
+            .map(|i| ys.iter().zip(&weights).map(|(y, w)| w * y[i]).sum::<f64>())
+            .collect::<Vec<_>>();
+        for (m, y) in self.mean.iter_mut().zip(&yw) {
+            *m += self.sigma * y;
+        }
+
+        // Step-size path: `C^{-1/2} * yw = B * D^{-1} * B^T * yw`.
+        let bt_yw = (0..n)
+            .map(|j| (0..n).map(|i| self.b[i][j] * yw[i]).sum::<f64>() / self.d[j])
+            .collect::<Vec<_>>();
+        let c_inv_sqrt_yw = (0..n)
+            .map(|i| (0..n).map(|j| self.b[i][j] * bt_yw[j]).sum::<f64>())
+            .collect::<Vec<_>>();
+        let k = (cs * (2.0 - cs) * mu_eff).sqrt();
+        for (p, v) in self.ps.iter_mut().zip(&c_inv_sqrt_yw) {
+            *p = (1.0 - cs) * *p + k * v;
+        }
+        let ps_norm = self.ps.iter().map(|p| p * p).sum::<f64>().sqrt();
+
+        // Covariance path.
+        let threshold = (1.4 + 2.0 / (nf + 1.0)) * chi_n;
+        let denominator = (1.0 - (1.0 - cs).powf(2.0 * (self.generation as f64 + 1.0))).sqrt();
+        let h_sigma = if ps_norm / denominator < threshold {
+            1.0
+        } else {
+            0.0
+        };
+        let k = (cc * (2.0 - cc) * mu_eff).sqrt();
+        for (p, y) in self.pc.iter_mut().zip(&yw) {
+            *p = (1.0 - cc) * *p + h_sigma * k * y;
+        }
+
+        // Covariance matrix.
+        let delta = (1.0 - h_sigma) * cc * (2.0 - cc);
+        for i in 0..n {
+            for j in 0..=i {
+                let rank_mu = ys
+                    .iter()
+                    .zip(&weights)
+                    .map(|(y, w)| w * y[i] * y[j])
+                    .sum::<f64>();
+                let v = (1.0 + c1 * delta - c1 - cmu) * self.c[i][j]
+                    + c1 * self.pc[i] * self.pc[j]
+                    + cmu * rank_mu;
+                self.c[i][j] = v;
+                self.c[j][i] = v;
+            }
+        }
+
+        // Step size.
+        self.sigma *= ((cs / damps) * (ps_norm / chi_n - 1.0)).exp();
+
+        let (eigenvalues, eigenvectors) = jacobi_eigen(&self.c);
+        self.d = eigenvalues
+            .into_iter()
+            .map(|e| e.max(1e-20).sqrt())
+            .collect();
+        self.b = eigenvectors;
+    }
+
+    fn should_stop(&self) -> bool {
+        if self.generation == 0 {
+            return false;
+        }
+
+        // TolX.
+        let max_std = self
+            .c
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[i].sqrt())
+            .fold(0.0, f64::max);
+        if self.sigma * max_std < 1e-12 * self.sigma0 {
+            return true;
+        }
+
+        // TolFun (the best values have stagnated).
+        let n = self.mean.len() as f64;
+        let history = 10 + (30.0 * n / self.popsize as f64).ceil() as usize;
+        if self.best_values.len() >= history {
+            let recent = &self.best_values[self.best_values.len() - history..];
+            let max = recent
+                .iter()
+                .copied()
+                .fold(std::f64::NEG_INFINITY, f64::max);
+            let min = recent.iter().copied().fold(std::f64::INFINITY, f64::min);
+            if max - min < 1e-12 {
+                return true;
+            }
+        }
+
+        // ConditionCov.
+        let max_d = self.d.iter().copied().fold(0.0, f64::max);
+        let min_d = self.d.iter().copied().fold(std::f64::INFINITY, f64::min);
+        (max_d / min_d).powi(2) > 1e14
+    }
+}
+
+// Eigendecomposition of a symmetric matrix by the cyclic Jacobi method.
+//
+// Returns the eigenvalues and the matrix whose columns are the corresponding eigenvectors.
+fn jacobi_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut v = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect::<Vec<Vec<_>>>();
+
+    for _ in 0..100 {
+        let off = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum::<f64>();
+        if off < 1e-30 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (x, y) = (*apk, *aqk);
+                    *apk = c * x - s * y;
+                    *aqk = s * x + c * y;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurobako_core::domain;
+    use kurobako_core::problem::ProblemSpecBuilder;
+    use kurobako_core::trial::Values;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn is_float_close(v0: f64, v1: f64) -> bool {
+        let rtol: f64 = 1e-5;
+        let atol: f64 = 1e-8;
+        (v0 - v1).abs() <= atol + rtol * v1.abs()
+    }
+
+    // Checks that `matrix * v_i = e_i * v_i` and `V^T * V = I`.
+    fn assert_eigen(matrix: &[Vec<f64>], eigenvalues: &[f64], eigenvectors: &[Vec<f64>]) {
+        let n = matrix.len();
+        for (k, &e) in eigenvalues.iter().enumerate() {
+            for i in 0..n {
+                let av = (0..n)
+                    .map(|j| matrix[i][j] * eigenvectors[j][k])
+                    .sum::<f64>();
+                assert!(is_float_close(av, e * eigenvectors[i][k]));
+            }
+            for l in 0..n {
+                let dot = (0..n)
+                    .map(|i| eigenvectors[i][k] * eigenvectors[i][l])
+                    .sum::<f64>();
+                assert!(is_float_close(dot, if k == l { 1.0 } else { 0.0 }));
+            }
+        }
+    }
+
+    #[test]
+    fn jacobi_eigen_works() {
+        let matrix = vec![vec![2.0, 1.0], vec![1.0, 2.0]];
+        let (mut eigenvalues, eigenvectors) = jacobi_eigen(&matrix);
+        assert_eigen(&matrix, &eigenvalues, &eigenvectors);
+        eigenvalues.sort_by_key(|&e| OrderedFloat(e));
+        assert!(is_float_close(eigenvalues[0], 1.0));
+        assert!(is_float_close(eigenvalues[1], 3.0));
+
+        let matrix = vec![
+            vec![2.0, 0.0, 0.0],
+            vec![0.0, 3.0, 4.0],
+            vec![0.0, 4.0, 9.0],
+        ];
+        let (mut eigenvalues, eigenvectors) = jacobi_eigen(&matrix);
+        assert_eigen(&matrix, &eigenvalues, &eigenvectors);
+        eigenvalues.sort_by_key(|&e| OrderedFloat(e));
+        assert!(is_float_close(eigenvalues[0], 1.0));
+        assert!(is_float_close(eigenvalues[1], 2.0));
+        assert!(is_float_close(eigenvalues[2], 11.0));
+    }
+
+    #[test]
+    fn update_works() {
+        // The reference values are computed by the formulas of "The CMA Evolution Strategy: A Tutorial" (Hansen, 2016).
+        let mut cma = Cma::new(vec![0.5, 0.5], 0.1, 4);
+        cma.tell(vec![0.5, 0.4], 3.0);
+        cma.tell(vec![0.6, 0.5], 0.0);
+        cma.tell(vec![0.4, 0.5], 2.0);
+        cma.tell(vec![0.5, 0.6], 1.0);
+        assert_eq!(cma.generation, 1);
+        assert_eq!(cma.best_values, vec![0.0]);
+
+        assert!(is_float_close(cma.mean[0], 0.5804162859932729));
+        assert!(is_float_close(cma.mean[1], 0.5195837140067271));
+        assert!(is_float_close(cma.sigma, 0.09015965120307204));
+        assert!(is_float_close(cma.c[0][0], 0.9672112463444335));
+        assert!(is_float_close(cma.c[0][1], 0.03224474176270074));
+        assert!(is_float_close(cma.c[1][0], 0.03224474176270074));
+        assert!(is_float_close(cma.c[1][1], 0.8325662263457576));
+
+        // `C = B * D^2 * B^T`.
+        let eigenvalues = cma.d.iter().map(|d| d * d).collect::<Vec<_>>();
+        assert_eigen(&cma.c, &eigenvalues, &cma.b);
+    }
+
+    #[test]
+    fn trials_asked_before_restart_are_ignored() -> trackable::result::TopLevelResult {
+        let problem = track!(ProblemSpecBuilder::new("test")
+            .param(domain::var("x").continuous(0.0, 1.0))
+            .param(domain::var("y").continuous(0.0, 1.0))
+            .value(domain::var("z"))
+            .finish())?;
+        let factory = CmaEsSolverFactory {
+            sigma0: 0.3,
+            popsize: Some(4),
+            restart: Restart::Ipop,
+            inc_popsize: 2.0,
+        };
+        let mut solver = track!(factory.create_solver(ArcRng::new(0), &problem))?;
+        let mut idg = IdGen::new();
+        let stale = track!(solver.ask(&mut idg))?;
+
+        // The new run starts at the generation `0` again.
+        solver.restart();
+        assert_eq!(solver.state.cma.generation, 0);
+
+        track!(solver.tell(EvaluatedTrial {
+            id: stale.id,
+            values: Values::new(vec![1.0]),
+            current_step: 1,
+        }))?;
+        assert!(solver.state.cma.solutions.is_empty());
+
+        let fresh = track!(solver.ask(&mut idg))?;
+        track!(solver.tell(EvaluatedTrial {
+            id: fresh.id,
+            values: Values::new(vec![1.0]),
+            current_step: 1,
+        }))?;
+        assert_eq!(solver.state.cma.solutions.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn sphere_function_is_minimized() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut cma = Cma::new(vec![0.8, 0.8, 0.8], 0.3, 8);
+        for _ in 0..100 * cma.popsize {
+            let x = cma.sample(&mut rng);
+            let value = x.iter().map(|&x| (x - 0.3).powi(2)).sum::<f64>();
+            cma.tell(x, value);
+        }
+        for &m in &cma.mean {
+            assert!((m - 0.3).abs() < 1e-3, "{:?}", cma.mean);
+        }
+    }
+}
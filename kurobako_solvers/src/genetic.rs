@@ -0,0 +1,50 @@
+use kurobako_core::domain::{Range, Variable};
+use rand::Rng;
+
+// Recombines the genes (i.e., the parameters normalized to `[0, 1]`) of a variable.
+//
+// Numerical genes are recombined by the simulated binary crossover (SBX),
+// and categorical genes are exchanged randomly.
+pub fn crossover<R: Rng + ?Sized>(rng: &mut R, var: &Variable, x0: f64, x1: f64, eta: f64) -> f64 {
+    if let Range::Categorical { .. } = var.range() {
+        return if rng.gen() { x0 } else { x1 };
+    }
+    if (x0 - x1).abs() < 1e-14 {
+        return x0;
+    }
+
+    let (y0, y1) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
+    let u = rng.gen::<f64>();
+    let spread = |beta: f64| {
+        let alpha = 2.0 - beta.powf(-(eta + 1.0));
+        if u <= 1.0 / alpha {
+            (u * alpha).powf(1.0 / (eta + 1.0))
+        } else {
+            (1.0 / (2.0 - u * alpha)).powf(1.0 / (eta + 1.0))
+        }
+    };
+    let c0 = 0.5 * ((y0 + y1) - spread(1.0 + 2.0 * y0 / (y1 - y0)) * (y1 - y0));
+    let c1 = 0.5 * ((y0 + y1) + spread(1.0 + 2.0 * (1.0 - y1) / (y1 - y0)) * (y1 - y0));
+    let c = if rng.gen() { c0 } else { c1 };
+    c.max(0.0).min(1.0)
+}
+
+// Mutates the gene of a variable.
+//
+// Numerical genes are mutated by the polynomial mutation,
+// and categorical genes are resampled randomly.
+pub fn mutate<R: Rng + ?Sized>(rng: &mut R, var: &Variable, x: f64, eta: f64) -> f64 {
+    if let Range::Categorical { .. } = var.range() {
+        return rng.gen();
+    }
+
+    let u = rng.gen::<f64>();
+    let delta = if u < 0.5 {
+        let v = 2.0 * u + (1.0 - 2.0 * u) * (1.0 - x).powf(eta + 1.0);
+        v.powf(1.0 / (eta + 1.0)) - 1.0
+    } else {
+        let v = 2.0 * (1.0 - u) + 2.0 * (u - 0.5) * x.powf(eta + 1.0);
+        1.0 - v.powf(1.0 / (eta + 1.0))
+    };
+    (x + delta).max(0.0).min(1.0)
+}
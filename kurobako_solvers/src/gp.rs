@@ -0,0 +1,604 @@
+//! A solver based on Bayesian optimization with Gaussian processes.
+//!
+//! # References
+//!
+//! - [Gaussian Processes for Machine Learning][Rasmussen and Williams, 2006]
+//! - [Practical Bayesian Optimization of Machine Learning Algorithms][Snoek et al., 2012]
+//!
+//! [Rasmussen and Williams, 2006]: http://www.gaussianprocess.org/gpml/
+//! [Snoek et al., 2012]: https://arxiv.org/abs/1206.2944
+use crate::normal;
+use crate::scale::{denormalize, normalize};
+use kurobako_core::domain::Range;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, PriorTrial, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+// Number of the gradient ascent iterations to maximize the marginal likelihood.
+const MLE_ITERATIONS: usize = 50;
+
+// Learning rate of the gradient ascent (Adam) in the log space of the hyperparameters.
+const MLE_LEARNING_RATE: f64 = 0.05;
+
+// Bounds of the hyperparameters (length scales, signal variance and noise variance).
+const LENGTH_SCALE_BOUNDS: (f64, f64) = (1e-2, 1e1);
+const SIGNAL_VARIANCE_BOUNDS: (f64, f64) = (5e-2, 2e1);
+const NOISE_VARIANCE_BOUNDS: (f64, f64) = (1e-6, 1.0);
+
+const SQRT_5: f64 = 2.236_067_977_499_79;
+
+/// Acquisition function of `GpSolver`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Acquisition {
+    /// Expected improvement.
+    Ei,
+
+    /// (Lower) confidence bound.
+    Ucb,
+}
+impl Acquisition {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["ei", "ucb"];
+}
+impl Default for Acquisition {
+    fn default() -> Self {
+        Self::Ei
+    }
+}
+impl FromStr for Acquisition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ei" => Ok(Self::Ei),
+            "ucb" => Ok(Self::Ucb),
+            _ => track_panic!(
+                ErrorKind::InvalidInput,
+                "Unknown acquisition function: {:?}",
+                s
+            ),
+        }
+    }
+}
+
+fn default_startup_trials() -> usize {
+    10
+}
+
+fn default_candidates() -> NonZeroUsize {
+    NonZeroUsize::new(1000).unwrap_or_else(|| unreachable!())
+}
+
+fn default_kappa() -> f64 {
+    2.0
+}
+
+/// Recipe of `GpSolver`.
+///
+/// The objective function is modeled by a Gaussian process with an ARD Matérn 5/2 kernel
+/// in the search space normalized to the unit hypercube (in the log space for log-scaled variables),
+/// and the hyperparameters of the kernel are estimated by maximizing the marginal likelihood.
+/// Discrete variables are handled by rounding down the continuous values.
+///
+/// The acquisition function is maximized over random candidates
+/// (a half of them are sampled around the best observation).
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct GpSolverRecipe {
+    /// Acquisition function.
+    #[structopt(long, default_value = "ei", possible_values = Acquisition::POSSIBLE_VALUES)]
+    #[serde(default)]
+    acquisition: Acquisition,
+
+    /// Exploration weight of the confidence bound (used only if the acquisition function is `ucb`).
+    #[structopt(long, default_value = "2.0")]
+    #[serde(default = "default_kappa")]
+    kappa: f64,
+
+    /// Number of the trials whose parameters are sampled randomly before starting Bayesian optimization.
+    #[structopt(long, default_value = "10")]
+    #[serde(default = "default_startup_trials")]
+    startup_trials: usize,
+
+    /// Number of the candidates over which the acquisition function is maximized.
+    #[structopt(long, default_value = "1000")]
+    #[serde(default = "default_candidates")]
+    candidates: NonZeroUsize,
+}
+impl SolverRecipe for GpSolverRecipe {
+    type Factory = GpSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.kappa >= 0.0, ErrorKind::InvalidInput; self.kappa);
+        Ok(GpSolverFactory {
+            acquisition: self.acquisition,
+            kappa: self.kappa,
+            startup_trials: self.startup_trials.max(1),
+            candidates: self.candidates.get(),
+        })
+    }
+}
+
+/// Factory of `GpSolver`.
+#[derive(Debug)]
+pub struct GpSolverFactory {
+    acquisition: Acquisition,
+    kappa: f64,
+    startup_trials: usize,
+    candidates: usize,
+}
+impl SolverFactory for GpSolverFactory {
+    type Solver = GpSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("GP")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Snoek, Jasper, Hugo Larochelle, and Ryan P. Adams. \"Practical bayesian \
+                 optimization of machine learning algorithms.\" Advances in neural information \
+                 processing systems. 2012.",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Concurrent)
+            .capable(Capability::WarmStarting)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            match v.range() {
+                Range::Continuous { low, high } => {
+                    track_assert!(
+                        low.is_finite() && high.is_finite(),
+                        ErrorKind::InvalidInput,
+                        "GP solver doesn't support unbounded variables: {:?}",
+                        v
+                    );
+                }
+                Range::Discrete { .. } => {}
+                Range::Categorical { .. } => {
+                    track_panic!(ErrorKind::Incapable, "Categorical variable: {:?}", v)
+                }
+            }
+        }
+
+        let dimensions = problem.params_domain.variables().len();
+        Ok(GpSolver {
+            problem: problem.clone(),
+            rng,
+            acquisition: self.acquisition,
+            kappa: self.kappa,
+            startup_trials: self.startup_trials,
+            candidates: self.candidates,
+            evaluatings: HashMap::new(),
+            state: GpState {
+                observations: Vec::new(),
+                hyperparams: Hyperparams::new(dimensions),
+            },
+        })
+    }
+}
+
+/// Solver based on Bayesian optimization with Gaussian processes.
+#[derive(Debug)]
+pub struct GpSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    acquisition: Acquisition,
+    kappa: f64,
+    startup_trials: usize,
+    candidates: usize,
+    evaluatings: HashMap<TrialId, Vec<f64>>,
+    state: GpState,
+}
+impl GpSolver {
+    fn observe(&mut self, x: Vec<f64>, values: &[f64]) {
+        // Unevaluable parameters are ignored.
+        if let Some(&value) = values.first().filter(|v| v.is_finite()) {
+            self.state.observations.push(Observation { x, value });
+        }
+    }
+
+    fn suggest(&mut self) -> Vec<f64> {
+        let xs = self
+            .state
+            .observations
+            .iter()
+            .map(|o| o.x.clone())
+            .collect::<Vec<_>>();
+        let ys = self
+            .state
+            .observations
+            .iter()
+            .map(|o| o.value)
+            .collect::<Vec<_>>();
+        let hyperparams = maximize_likelihood(&xs, &ys, &self.state.hyperparams);
+        let gp = Gp::fit(xs, &ys, hyperparams.clone());
+        self.state.hyperparams = hyperparams;
+
+        let best = self
+            .state
+            .observations
+            .iter()
+            .min_by_key(|o| OrderedFloat(o.value))
+            .unwrap_or_else(|| unreachable!());
+        let best_value = gp.standardize(best.value);
+
+        let dimensions = best.x.len();
+        let rng = &mut self.rng;
+        let mut candidates = Vec::with_capacity(self.candidates);
+        for i in 0..self.candidates {
+            let x: Vec<f64> = if i % 2 == 0 {
+                (0..dimensions).map(|_| rng.gen()).collect()
+            } else {
+                best.x
+                    .iter()
+                    .map(|&u| (u + 0.1 * standard_normal(rng)).max(0.0).min(1.0))
+                    .collect()
+            };
+            candidates.push(x);
+        }
+
+        let acquisition = self.acquisition;
+        let kappa = self.kappa;
+        candidates
+            .into_iter()
+            .max_by_key(|x| {
+                let (mu, sigma) = gp.predict(x);
+                let score = match acquisition {
+                    Acquisition::Ei => expected_improvement(best_value, mu, sigma),
+                    Acquisition::Ucb => -(mu - kappa * sigma),
+                };
+                OrderedFloat(score)
+            })
+            .unwrap_or_else(|| unreachable!())
+    }
+}
+impl Solver for GpSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let dimensions = self.problem.params_domain.variables().len();
+        let x = if self.state.observations.len() < self.startup_trials {
+            (0..dimensions).map(|_| self.rng.gen()).collect()
+        } else {
+            self.suggest()
+        };
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(&x)
+            .map(|(v, &u)| denormalize(v, u.min(1.0 - std::f64::EPSILON)))
+            .collect();
+
+        let id = idg.generate();
+        self.evaluatings.insert(id, x);
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        if let Some(x) = self.evaluatings.remove(&trial.id) {
+            self.observe(x, &trial.values);
+        }
+        Ok(())
+    }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        let variables = self.problem.params_domain.variables().to_vec();
+        for trial in trials {
+            track_assert_eq!(trial.params.len(), variables.len(), ErrorKind::InvalidInput);
+            let x = variables
+                .iter()
+                .zip(trial.params.iter())
+                .map(|(v, &p)| normalize(v, p).max(0.0).min(1.0))
+                .collect();
+            self.observe(x, &trial.values);
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let state: GpState = track!(serde_json::from_value(state).map_err(Error::from))?;
+        let dimensions = self.problem.params_domain.variables().len();
+        track_assert_eq!(
+            state.hyperparams.ln_length_scales.len(),
+            dimensions,
+            ErrorKind::InvalidInput
+        );
+        self.state = state;
+        self.evaluatings.clear();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GpState {
+    observations: Vec<Observation>,
+    hyperparams: Hyperparams,
+}
+
+// An evaluated point in the normalized search space.
+#[derive(Debug, Serialize, Deserialize)]
+struct Observation {
+    x: Vec<f64>,
+    value: f64,
+}
+
+// Hyperparameters of the kernel (in the log space).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Hyperparams {
+    ln_length_scales: Vec<f64>,
+    ln_signal_variance: f64,
+    ln_noise_variance: f64,
+}
+impl Hyperparams {
+    fn new(dimensions: usize) -> Self {
+        Self {
+            ln_length_scales: vec![0.5f64.ln(); dimensions],
+            ln_signal_variance: 0.0,
+            ln_noise_variance: 1e-3f64.ln(),
+        }
+    }
+
+    fn to_vec(&self) -> Vec<f64> {
+        let mut v = self.ln_length_scales.clone();
+        v.push(self.ln_signal_variance);
+        v.push(self.ln_noise_variance);
+        v
+    }
+
+    fn from_vec(mut v: Vec<f64>) -> Self {
+        let ln_noise_variance = v.pop().unwrap_or_else(|| unreachable!());
+        let ln_signal_variance = v.pop().unwrap_or_else(|| unreachable!());
+        Self {
+            ln_length_scales: v,
+            ln_signal_variance,
+            ln_noise_variance,
+        }
+    }
+
+    fn clip(v: &mut [f64]) {
+        let n = v.len();
+        for (i, x) in v.iter_mut().enumerate() {
+            let (low, high) = if i + 2 < n {
+                LENGTH_SCALE_BOUNDS
+            } else if i + 2 == n {
+                SIGNAL_VARIANCE_BOUNDS
+            } else {
+                NOISE_VARIANCE_BOUNDS
+            };
+            *x = x.max(low.ln()).min(high.ln());
+        }
+    }
+
+    // Returns the kernel value and its gradients with respect to the log length scales.
+    fn kernel(&self, a: &[f64], b: &[f64]) -> (f64, Vec<f64>) {
+        let signal_variance = self.ln_signal_variance.exp();
+        let scaled = a
+            .iter()
+            .zip(b)
+            .zip(&self.ln_length_scales)
+            .map(|((a, b), l)| ((a - b) / l.exp()).powi(2))
+            .collect::<Vec<_>>();
+        let r = scaled.iter().sum::<f64>().sqrt();
+        let e = (-SQRT_5 * r).exp();
+        let k = signal_variance * (1.0 + SQRT_5 * r + 5.0 / 3.0 * r * r) * e;
+        let coefficient = signal_variance * 5.0 / 3.0 * (1.0 + SQRT_5 * r) * e;
+        let grads = scaled.into_iter().map(|s| coefficient * s).collect();
+        (k, grads)
+    }
+}
+
+// Gaussian process regression model (the targets are standardized).
+#[derive(Debug)]
+struct Gp {
+    xs: Vec<Vec<f64>>,
+    hyperparams: Hyperparams,
+    mean: f64,
+    std: f64,
+    cholesky: Vec<Vec<f64>>,
+    alpha: Vec<f64>,
+}
+impl Gp {
+    fn fit(xs: Vec<Vec<f64>>, ys: &[f64], hyperparams: Hyperparams) -> Self {
+        let (mean, std) = mean_and_std(ys);
+        let ys = ys.iter().map(|y| (y - mean) / std).collect::<Vec<_>>();
+        let (k, _) = gram_matrix(&xs, &hyperparams);
+        let cholesky = cholesky(k);
+        let alpha = cho_solve(&cholesky, &ys);
+        Self {
+            xs,
+            hyperparams,
+            mean,
+            std,
+            cholesky,
+            alpha,
+        }
+    }
+
+    fn standardize(&self, y: f64) -> f64 {
+        (y - self.mean) / self.std
+    }
+
+    // Returns the predictive mean and standard deviation (in the standardized scale).
+    fn predict(&self, x: &[f64]) -> (f64, f64) {
+        let ks = self
+            .xs
+            .iter()
+            .map(|xi| self.hyperparams.kernel(x, xi).0)
+            .collect::<Vec<_>>();
+        let mu = ks.iter().zip(&self.alpha).map(|(k, a)| k * a).sum();
+        let v = forward_substitution(&self.cholesky, &ks);
+        let variance =
+            self.hyperparams.ln_signal_variance.exp() - v.iter().map(|v| v * v).sum::<f64>();
+        (mu, variance.max(1e-12).sqrt())
+    }
+}
+
+// Estimates the hyperparameters by maximizing the log marginal likelihood with Adam
+// (starting from the previous estimation).
+fn maximize_likelihood(xs: &[Vec<f64>], ys: &[f64], init: &Hyperparams) -> Hyperparams {
+    let (mean, std) = mean_and_std(ys);
+    let ys = ys.iter().map(|y| (y - mean) / std).collect::<Vec<_>>();
+    let n = ys.len();
+
+    let mut theta = init.to_vec();
+    let mut m = vec![0.0; theta.len()];
+    let mut v = vec![0.0; theta.len()];
+    let (beta1, beta2) = (0.9, 0.999);
+    for t in 1..=MLE_ITERATIONS {
+        let hyperparams = Hyperparams::from_vec(theta.clone());
+        let (k, dk) = gram_matrix(xs, &hyperparams);
+        let l = cholesky(k.clone());
+        let alpha = cho_solve(&l, &ys);
+        let k_inv = (0..n)
+            .map(|i| {
+                let e = (0..n)
+                    .map(|j| if i == j { 1.0 } else { 0.0 })
+                    .collect::<Vec<_>>();
+                cho_solve(&l, &e)
+            })
+            .collect::<Vec<_>>();
+
+        // `d/dθ log p(y|X,θ) = 0.5 * tr((αα^T - K^{-1}) dK/dθ)`
+        let w = |i: usize, j: usize| alpha[i] * alpha[j] - k_inv[i][j];
+        let mut grads = vec![0.0; theta.len()];
+        for i in 0..n {
+            for j in 0..n {
+                let w = w(i, j);
+                for (d, g) in dk[i][j].iter().enumerate() {
+                    grads[d] += 0.5 * w * g;
+                }
+                let kernel = if i == j {
+                    k[i][j] - theta[theta.len() - 1].exp()
+                } else {
+                    k[i][j]
+                };
+                grads[theta.len() - 2] += 0.5 * w * kernel;
+            }
+            grads[theta.len() - 1] += 0.5 * w(i, i) * theta[theta.len() - 1].exp();
+        }
+
+        for (d, g) in grads.into_iter().enumerate() {
+            m[d] = beta1 * m[d] + (1.0 - beta1) * g;
+            v[d] = beta2 * v[d] + (1.0 - beta2) * g * g;
+            let m_hat = m[d] / (1.0 - beta1.powi(t as i32));
+            let v_hat = v[d] / (1.0 - beta2.powi(t as i32));
+            theta[d] += MLE_LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-8);
+        }
+        Hyperparams::clip(&mut theta);
+    }
+    Hyperparams::from_vec(theta)
+}
+
+// Returns the gram matrix (including the noise) and the gradients of its elements
+// with respect to the log length scales.
+fn gram_matrix(xs: &[Vec<f64>], hyperparams: &Hyperparams) -> (Vec<Vec<f64>>, Vec<Vec<Vec<f64>>>) {
+    let n = xs.len();
+    let noise_variance = hyperparams.ln_noise_variance.exp();
+    let mut k = vec![vec![0.0; n]; n];
+    let mut dk = vec![vec![Vec::new(); n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let (kij, grads) = hyperparams.kernel(&xs[i], &xs[j]);
+            k[i][j] = kij;
+            k[j][i] = kij;
+            dk[i][j] = grads.clone();
+            dk[j][i] = grads;
+        }
+        k[i][i] += noise_variance;
+    }
+    (k, dk)
+}
+
+// Cholesky decomposition (a small jitter is added to the diagonal if the matrix is ill-conditioned).
+fn cholesky(a: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = a.len();
+    let mut jitter = 0.0;
+    loop {
+        let mut l = vec![vec![0.0; n]; n];
+        let mut ok = true;
+        'outer: for i in 0..n {
+            for j in 0..=i {
+                let s = (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+                if i == j {
+                    let d = a[i][i] + jitter - s;
+                    if d <= 0.0 {
+                        ok = false;
+                        break 'outer;
+                    }
+                    l[i][i] = d.sqrt();
+                } else {
+                    l[i][j] = (a[i][j] - s) / l[j][j];
+                }
+            }
+        }
+        if ok {
+            return l;
+        }
+        jitter = if jitter == 0.0 { 1e-10 } else { jitter * 10.0 };
+    }
+}
+
+// Solves `L x = b`.
+fn forward_substitution(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let s = (0..i).map(|k| l[i][k] * x[k]).sum::<f64>();
+        x[i] = (b[i] - s) / l[i][i];
+    }
+    x
+}
+
+// Solves `L L^T x = b`.
+fn cho_solve(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let y = forward_substitution(l, b);
+    let n = y.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let s = (i + 1..n).map(|k| l[k][i] * x[k]).sum::<f64>();
+        x[i] = (y[i] - s) / l[i][i];
+    }
+    x
+}
+
+fn mean_and_std(ys: &[f64]) -> (f64, f64) {
+    let n = ys.len() as f64;
+    let mean = ys.iter().sum::<f64>() / n;
+    let variance = ys.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt().max(1e-12))
+}
+
+// Expected improvement for minimization.
+fn expected_improvement(best: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (best - mu) / sigma;
+    sigma * (z * normal::cdf(z) + normal::ln_pdf(z, 0.0, 1.0).exp())
+}
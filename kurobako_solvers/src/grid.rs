@@ -0,0 +1,197 @@
+//! A solver based on grid search.
+use kurobako_core::domain::{Distribution, Range, Variable};
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capabilities, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params};
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use structopt::StructOpt;
+use trackable::error::ErrorKindExt;
+
+fn default_resolution() -> NonZeroUsize {
+    NonZeroUsize::new(10).unwrap_or_else(|| unreachable!())
+}
+
+/// Recipe of `GridSolver`.
+///
+/// The grid of a numerical variable consists of the centers of the `resolution` cells
+/// that equally divide its range (in the log space if the variable is log-scaled).
+/// The grid of a discrete variable consists of all its values if they aren't more than the resolution
+/// (otherwise, the centers are rounded down),
+/// and the one of a categorical variable always consists of all its choices.
+///
+/// The grid points are asked in lexicographical order (the last variable changes fastest),
+/// and the grid is enumerated again from the beginning once all the points have been asked.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct GridSolverRecipe {
+    /// Default number of the grid points per numerical variable.
+    #[structopt(long, default_value = "10")]
+    #[serde(default = "default_resolution")]
+    resolution: NonZeroUsize,
+
+    /// Number of the grid points of a specific variable (`NAME=RESOLUTION`).
+    #[structopt(long, number_of_values = 1)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variable_resolution: Vec<String>,
+}
+impl SolverRecipe for GridSolverRecipe {
+    type Factory = GridSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        let mut resolutions = BTreeMap::new();
+        for r in &self.variable_resolution {
+            let mut tokens = r.splitn(2, '=');
+            let name = tokens.next().unwrap_or_else(|| unreachable!());
+            let resolution = track_assert_some!(tokens.next(), ErrorKind::InvalidInput; r);
+            let resolution: NonZeroUsize =
+                track!(resolution.parse().map_err(|e| ErrorKind::InvalidInput.cause(e)); r)?;
+            resolutions.insert(name.to_owned(), resolution);
+        }
+        Ok(GridSolverFactory {
+            resolution: self.resolution,
+            resolutions,
+        })
+    }
+}
+
+/// Factory of `GridSolver`.
+#[derive(Debug)]
+pub struct GridSolverFactory {
+    resolution: NonZeroUsize,
+    resolutions: BTreeMap<String, NonZeroUsize>,
+}
+impl SolverFactory for GridSolverFactory {
+    type Solver = GridSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("Grid")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .capabilities(Capabilities::all());
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, _rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        let vars = problem.params_domain.variables();
+        for name in self.resolutions.keys() {
+            track_assert!(
+                vars.iter().any(|v| v.name() == name),
+                ErrorKind::InvalidInput,
+                "Unknown variable: {:?}",
+                name
+            );
+        }
+
+        let grid = vars
+            .iter()
+            .map(|v| {
+                let resolution = self.resolutions.get(v.name()).unwrap_or(&self.resolution);
+                track!(grid_points(v, resolution.get()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(GridSolver {
+            problem: problem.clone(),
+            indices: vec![0; grid.len()],
+            grid,
+        })
+    }
+}
+
+/// Solver based on grid search.
+#[derive(Debug)]
+pub struct GridSolver {
+    problem: ProblemSpec,
+    grid: Vec<Vec<f64>>,
+    indices: Vec<usize>,
+}
+impl Solver for GridSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let params = self
+            .grid
+            .iter()
+            .zip(&self.indices)
+            .map(|(points, &i)| points[i])
+            .collect();
+
+        for (points, i) in self.grid.iter().zip(self.indices.iter_mut()).rev() {
+            *i += 1;
+            if *i < points.len() {
+                break;
+            }
+            *i = 0;
+        }
+
+        Ok(NextTrial {
+            id: idg.generate(),
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, _trial: EvaluatedTrial) -> Result<()> {
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.indices).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let indices: Vec<usize> = track!(serde_json::from_value(state).map_err(Error::from))?;
+        track_assert_eq!(indices.len(), self.grid.len(), ErrorKind::InvalidInput);
+        for (points, &i) in self.grid.iter().zip(&indices) {
+            track_assert!(i < points.len(), ErrorKind::InvalidInput; i);
+        }
+        self.indices = indices;
+        Ok(())
+    }
+}
+
+fn grid_points(var: &Variable, resolution: usize) -> Result<Vec<f64>> {
+    let centers = |low: f64, high: f64| {
+        (0..resolution).map(move |i| low + (high - low) * (i as f64 + 0.5) / resolution as f64)
+    };
+    let points = match var.range() {
+        Range::Continuous { low, high } => {
+            track_assert!(
+                low.is_finite() && high.is_finite(),
+                ErrorKind::InvalidInput,
+                "Grid solver doesn't support unbounded variables: {:?}",
+                var
+            );
+            match var.distribution() {
+                Distribution::Uniform => centers(*low, *high).collect(),
+                Distribution::LogUniform => {
+                    centers(low.log2(), high.log2()).map(|x| x.exp2()).collect()
+                }
+            }
+        }
+        Range::Discrete { low, high } => {
+            let (low, high) = (*low as f64, *high as f64);
+            let mut points: Vec<f64> = if high - low <= resolution as f64 {
+                (0..(high - low) as usize).map(|i| low + i as f64).collect()
+            } else {
+                match var.distribution() {
+                    Distribution::Uniform => centers(low, high).map(|x| x.floor()).collect(),
+                    Distribution::LogUniform => centers(low.log2(), high.log2())
+                        .map(|x| x.exp2().floor().min(high - 1.0))
+                        .collect(),
+                }
+            };
+            points.dedup();
+            points
+        }
+        Range::Categorical { choices } => (0..choices.len()).map(|i| i as f64).collect(),
+    };
+    track_assert!(!points.is_empty(), ErrorKind::InvalidInput; var);
+    Ok(points)
+}
@@ -0,0 +1,319 @@
+//! A solver based on Hyperband.
+//!
+//! # References
+//!
+//! - [Hyperband: A Novel Bandit-Based Approach to Hyperparameter Optimization][Li et al., 2018]
+//!
+//! [Li et al., 2018]: https://arxiv.org/abs/1603.06560
+use kurobako_core::domain::Variable;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capabilities, Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
+use kurobako_core::{ErrorKind, Result};
+use rand::distributions::Distribution as _;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use structopt::StructOpt;
+
+fn default_min_step_rate() -> f64 {
+    0.01
+}
+
+fn default_reduction_factor() -> usize {
+    3
+}
+
+/// Recipe of `HyperbandSolver`.
+///
+/// The brackets are run in descending order of their aggressiveness (i.e., the number of the rungs),
+/// and each bracket is a run of the successive halving with randomly sampled parameters.
+/// If all the trials of the running brackets are waiting for the evaluations of the other trials
+/// (e.g., when the trials are evaluated concurrently), the next bracket is started in parallel.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct HyperbandSolverRecipe {
+    /// Rate to determine the value of `min_step`.
+    ///
+    /// The value of `min_step` will be set to `problem.steps.last() * min_step_rate`.
+    /// If `min_step` is given, this field is ignored.
+    #[structopt(long, default_value = "0.01")]
+    #[serde(default = "default_min_step_rate")]
+    min_step_rate: f64,
+
+    /// Minimum step at which trials are evaluated.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_step: Option<u64>,
+
+    /// Reduction factor (a.k.a. `eta`) of the successive halving.
+    #[structopt(long, default_value = "3")]
+    #[serde(default = "default_reduction_factor")]
+    reduction_factor: usize,
+}
+impl SolverRecipe for HyperbandSolverRecipe {
+    type Factory = HyperbandSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.reduction_factor >= 2, ErrorKind::InvalidInput; self.reduction_factor);
+        track_assert!(
+            0.0 < self.min_step_rate && self.min_step_rate <= 1.0,
+            ErrorKind::InvalidInput; self.min_step_rate
+        );
+        Ok(HyperbandSolverFactory {
+            min_step_rate: self.min_step_rate,
+            min_step: self.min_step,
+            reduction_factor: self.reduction_factor,
+        })
+    }
+}
+
+/// Factory of `HyperbandSolver`.
+#[derive(Debug)]
+pub struct HyperbandSolverFactory {
+    min_step_rate: f64,
+    min_step: Option<u64>,
+    reduction_factor: usize,
+}
+impl SolverFactory for HyperbandSolverFactory {
+    type Solver = HyperbandSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("Hyperband")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Li, Lisha, et al. \"Hyperband: A novel bandit-based approach to hyperparameter \
+                 optimization.\" The Journal of Machine Learning Research 18.1 (2017): 6765-6816.",
+            )
+            .capabilities(
+                Capabilities::all()
+                    .remove_capability(Capability::MultiObjective)
+                    .remove_capability(Capability::WarmStarting)
+                    .remove_capability(Capability::Checkpointable)
+                    .clone(),
+            );
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        let sampler = RandomSampler {
+            variables: problem.params_domain.variables().to_vec(),
+        };
+        track!(HyperbandSolver::new(
+            rng,
+            problem,
+            self.min_step_rate,
+            self.min_step,
+            self.reduction_factor,
+            Box::new(sampler),
+        ))
+    }
+}
+
+/// Sampler of the parameters of the trials started at the first rungs of the brackets.
+pub(crate) trait Sampler: Send + std::fmt::Debug {
+    /// Samples new parameters.
+    fn sample(&mut self, rng: &mut ArcRng) -> Params;
+
+    /// Notifies the sampler of the result of an evaluation at the given step.
+    ///
+    /// `None` means that the parameters couldn't be evaluated.
+    fn observe(&mut self, params: &Params, step: u64, value: Option<f64>);
+}
+
+#[derive(Debug)]
+struct RandomSampler {
+    variables: Vec<Variable>,
+}
+impl Sampler for RandomSampler {
+    fn sample(&mut self, rng: &mut ArcRng) -> Params {
+        let mut params = Vec::with_capacity(self.variables.len());
+        for v in &self.variables {
+            params.push(v.sample(rng));
+        }
+        Params::new(params)
+    }
+
+    fn observe(&mut self, _params: &Params, _step: u64, _value: Option<f64>) {}
+}
+
+/// Solver based on Hyperband.
+#[derive(Debug)]
+pub struct HyperbandSolver {
+    rng: ArcRng,
+    sampler: Box<dyn Sampler>,
+    max_step: u64,
+    reduction_factor: usize,
+    max_bracket: usize,
+    next_bracket: usize,
+
+    // Running brackets (in the order of the starts).
+    brackets: BTreeMap<u64, Bracket>,
+    next_bracket_id: u64,
+
+    // Trials being evaluated (and the brackets to which they belong).
+    evaluatings: HashMap<TrialId, (u64, Params)>,
+
+    // Trials that were eliminated by the successive halving and will be pruned by the runner.
+    discards: VecDeque<NextTrial>,
+}
+impl HyperbandSolver {
+    pub(crate) fn new(
+        rng: ArcRng,
+        problem: &ProblemSpec,
+        min_step_rate: f64,
+        min_step: Option<u64>,
+        reduction_factor: usize,
+        sampler: Box<dyn Sampler>,
+    ) -> Result<Self> {
+        let max_step = problem.steps.last();
+        let min_step = min_step
+            .unwrap_or((max_step as f64 * min_step_rate) as u64)
+            .max(1);
+        track_assert!(min_step <= max_step, ErrorKind::InvalidInput; min_step, max_step);
+
+        let mut max_bracket = 0;
+        while min_step * (reduction_factor as u64).pow(max_bracket as u32 + 1) <= max_step {
+            max_bracket += 1;
+        }
+
+        Ok(Self {
+            rng,
+            sampler,
+            max_step,
+            reduction_factor,
+            max_bracket,
+            next_bracket: max_bracket,
+            brackets: BTreeMap::new(),
+            next_bracket_id: 0,
+            evaluatings: HashMap::new(),
+            discards: VecDeque::new(),
+        })
+    }
+
+    fn start_bracket(&mut self) {
+        let s = self.next_bracket;
+        self.next_bracket = if s == 0 { self.max_bracket } else { s - 1 };
+
+        let eta = self.reduction_factor as f64;
+        let n = ((self.max_bracket + 1) as f64 / (s + 1) as f64 * eta.powi(s as i32)).ceil();
+        let rungs = (0..=s)
+            .map(|i| {
+                let size = (n / eta.powi(i as i32)).floor().max(1.0) as usize;
+                let step = (self.max_step as f64 / eta.powi((s - i) as i32)).round() as u64;
+                (size, step.max(1))
+            })
+            .collect();
+
+        let id = self.next_bracket_id;
+        self.next_bracket_id += 1;
+        self.brackets.insert(
+            id,
+            Bracket {
+                rungs,
+                rung: 0,
+                unsampled: n as usize,
+                promoted: VecDeque::new(),
+                results: Vec::new(),
+            },
+        );
+    }
+}
+impl Solver for HyperbandSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        if let Some(trial) = self.discards.pop_front() {
+            return Ok(trial);
+        }
+
+        loop {
+            let mut next = None;
+            for (&bracket_id, bracket) in &mut self.brackets {
+                let step = bracket.rungs[bracket.rung].1;
+                if let Some((id, params)) = bracket.promoted.pop_front() {
+                    next = Some((bracket_id, id, Some(params), step));
+                    break;
+                }
+                if bracket.unsampled > 0 {
+                    bracket.unsampled -= 1;
+                    next = Some((bracket_id, idg.generate(), None, step));
+                    break;
+                }
+            }
+
+            if let Some((bracket_id, id, params, step)) = next {
+                let params = params.unwrap_or_else(|| self.sampler.sample(&mut self.rng));
+                self.evaluatings.insert(id, (bracket_id, params.clone()));
+                return Ok(NextTrial {
+                    id,
+                    params,
+                    next_step: Some(step),
+                });
+            }
+            self.start_bracket();
+        }
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        let (bracket_id, params) =
+            track_assert_some!(self.evaluatings.remove(&trial.id), ErrorKind::InvalidInput);
+        let bracket = track_assert_some!(self.brackets.get_mut(&bracket_id), ErrorKind::Bug);
+
+        let step = bracket.rungs[bracket.rung].1;
+        let value = trial.values.first().copied().filter(|v| !v.is_nan());
+        self.sampler.observe(&params, step, value);
+
+        // Unevaluable (or pruned) parameters are regarded as the worst ones.
+        let value = value.unwrap_or(std::f64::INFINITY);
+        bracket.results.push((trial.id, params, value));
+        if bracket.results.len() < bracket.rungs[bracket.rung].0 {
+            return Ok(());
+        }
+
+        // Promotes the best trials to the next rung.
+        let mut results = std::mem::take(&mut bracket.results);
+        results.sort_by_key(|r| OrderedFloat(r.2));
+        bracket.rung += 1;
+        if let Some(&(size, _)) = bracket.rungs.get(bracket.rung) {
+            for (i, (id, params, _)) in results.into_iter().enumerate() {
+                if i < size {
+                    bracket.promoted.push_back((id, params));
+                } else {
+                    self.discards.push_back(NextTrial {
+                        id,
+                        params,
+                        next_step: None,
+                    });
+                }
+            }
+        } else {
+            self.brackets.remove(&bracket_id);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Bracket {
+    // The number of the trials and the step of each rung.
+    rungs: Vec<(usize, u64)>,
+
+    // The current rung.
+    rung: usize,
+
+    // The number of the trials to be sampled at the first rung.
+    unsampled: usize,
+
+    // The trials waiting to be evaluated at the current rung.
+    promoted: VecDeque<(TrialId, Params)>,
+
+    // The results of the trials evaluated at the current rung.
+    results: Vec<(TrialId, Params, f64)>,
+}
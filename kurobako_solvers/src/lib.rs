@@ -4,10 +4,23 @@
 #[macro_use]
 extern crate trackable;
 
+pub mod annealing;
 pub mod asha;
+pub mod bohb;
+pub mod cmaes;
+pub mod gp;
+pub mod grid;
+pub mod hyperband;
+pub mod moead;
+pub mod nelder_mead;
 pub mod nsga2;
 pub mod optuna;
 pub mod random;
+pub mod sobol;
+pub mod tpe;
 
 mod error;
+mod genetic;
+mod normal;
+mod scale;
 mod yamakan_utils;
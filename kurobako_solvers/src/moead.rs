@@ -0,0 +1,401 @@
+//! A solver based on MOEA/D (Multi-Objective Evolutionary Algorithm based on Decomposition).
+//!
+//! # References
+//!
+//! - [MOEA/D: A Multiobjective Evolutionary Algorithm Based on Decomposition][Zhang and Li, 2007]
+//!
+//! [Zhang and Li, 2007]: https://doi.org/10.1109/TEVC.2007.892759
+use crate::genetic;
+use crate::scale::denormalize;
+use kurobako_core::domain::Range;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use structopt::StructOpt;
+
+// Lower bound of the weights (used to avoid zero weights in the Chebyshev scalarization).
+const MIN_WEIGHT: f64 = 1e-6;
+
+fn default_population() -> usize {
+    100
+}
+
+fn default_neighborhood() -> usize {
+    20
+}
+
+fn default_crossover() -> f64 {
+    1.0
+}
+
+fn default_eta() -> f64 {
+    20.0
+}
+
+/// Recipe of `MoeadSolver`.
+///
+/// The weight vectors of the subproblems are generated by the simplex-lattice design
+/// with the smallest number of divisions that yields at least `--population` vectors,
+/// and each subproblem is scalarized by the Chebyshev (Tchebycheff) approach.
+///
+/// The genes of an individual are the parameters normalized to `[0, 1]`
+/// (in the log space for log-scaled variables),
+/// and they are varied by the same operators as `Nsga2Solver`.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct MoeadSolverRecipe {
+    /// Minimum number of the subproblems (i.e., the population size).
+    #[structopt(long, default_value = "100")]
+    #[serde(default = "default_population")]
+    population: usize,
+
+    /// Number of the neighbors of each subproblem (the parents are selected from them,
+    /// and the offspring can replace their solutions).
+    #[structopt(long, default_value = "20")]
+    #[serde(default = "default_neighborhood")]
+    neighborhood: usize,
+
+    /// Cossover probability of each parameter.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default = "default_crossover")]
+    crossover: f64,
+
+    /// Mutation probability of each parameter (the default is `1 / the number of parameters`).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mutation: Option<f64>,
+
+    /// Distribution index of the simulated binary crossover.
+    #[structopt(long, default_value = "20.0")]
+    #[serde(default = "default_eta")]
+    eta_crossover: f64,
+
+    /// Distribution index of the polynomial mutation.
+    #[structopt(long, default_value = "20.0")]
+    #[serde(default = "default_eta")]
+    eta_mutation: f64,
+}
+impl SolverRecipe for MoeadSolverRecipe {
+    type Factory = MoeadSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.population >= 2, ErrorKind::InvalidInput; self.population);
+        track_assert!(self.neighborhood >= 2, ErrorKind::InvalidInput; self.neighborhood);
+        track_assert!(
+            0.0 <= self.crossover && self.crossover <= 1.0,
+            ErrorKind::InvalidInput; self.crossover
+        );
+        if let Some(mutation) = self.mutation {
+            track_assert!((0.0..=1.0).contains(&mutation), ErrorKind::InvalidInput; mutation);
+        }
+        track_assert!(self.eta_crossover >= 0.0, ErrorKind::InvalidInput; self.eta_crossover);
+        track_assert!(self.eta_mutation >= 0.0, ErrorKind::InvalidInput; self.eta_mutation);
+        Ok(MoeadSolverFactory {
+            recipe: self.clone(),
+        })
+    }
+}
+
+/// Factory of `MoeadSolver`.
+#[derive(Debug)]
+pub struct MoeadSolverFactory {
+    recipe: MoeadSolverRecipe,
+}
+impl SolverFactory for MoeadSolverFactory {
+    type Solver = MoeadSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("MOEA/D")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Zhang, Qingfu, and Hui Li. \"MOEA/D: A multiobjective evolutionary algorithm \
+                 based on decomposition.\" IEEE Transactions on evolutionary computation 11.6 \
+                 (2007): 712-731.",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Categorical)
+            .capable(Capability::MultiObjective)
+            .capable(Capability::Concurrent)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "MOEA/D solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
+
+        let dimensions = problem.params_domain.variables().len();
+        track_assert!(dimensions > 0, ErrorKind::InvalidInput);
+        let objectives = problem.values_domain.variables().len();
+        let weights = weight_vectors(objectives, self.recipe.population);
+        let neighbors = neighbors(&weights, self.recipe.neighborhood);
+        let mutation = self
+            .recipe
+            .mutation
+            .unwrap_or_else(|| 1.0 / dimensions as f64);
+
+        Ok(MoeadSolver {
+            problem: problem.clone(),
+            rng,
+            recipe: self.recipe.clone(),
+            mutation,
+            state: MoeadState {
+                solutions: (0..weights.len()).map(|_| None).collect(),
+                ideal_point: Vec::new(),
+                next_subproblem: 0,
+            },
+            weights,
+            neighbors,
+            evaluatings: HashMap::new(),
+        })
+    }
+}
+
+/// Solver based on MOEA/D.
+#[derive(Debug)]
+pub struct MoeadSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    recipe: MoeadSolverRecipe,
+    mutation: f64,
+    weights: Vec<Vec<f64>>,
+    neighbors: Vec<Vec<usize>>,
+
+    // Candidates being evaluated (the subproblem and the genes of each trial).
+    evaluatings: HashMap<TrialId, (usize, Vec<f64>)>,
+
+    state: MoeadState,
+}
+impl MoeadSolver {
+    fn offspring(&mut self, subproblem: usize) -> Vec<f64> {
+        let solutions = &self.state.solutions;
+        let candidates = self.neighbors[subproblem]
+            .iter()
+            .filter_map(|&j| solutions[j].as_ref())
+            .collect::<Vec<_>>();
+        let p0 = candidates[self.rng.gen_range(0..candidates.len())];
+        let p1 = candidates[self.rng.gen_range(0..candidates.len())];
+
+        let variables = self.problem.params_domain.variables();
+        let mut genes = Vec::with_capacity(variables.len());
+        for (i, v) in variables.iter().enumerate() {
+            let (x0, x1) = (p0.genes[i], p1.genes[i]);
+            let mut x = if self.rng.gen_bool(self.recipe.crossover) {
+                genetic::crossover(&mut self.rng, v, x0, x1, self.recipe.eta_crossover)
+            } else {
+                x0
+            };
+            if self.rng.gen_bool(self.mutation) {
+                x = genetic::mutate(&mut self.rng, v, x, self.recipe.eta_mutation);
+            }
+            genes.push(x);
+        }
+        genes
+    }
+
+    fn chebyshev(&self, subproblem: usize, values: &[f64]) -> f64 {
+        values
+            .iter()
+            .zip(&self.state.ideal_point)
+            .zip(&self.weights[subproblem])
+            .map(|((v, z), w)| w.max(MIN_WEIGHT) * (v - z).abs())
+            .fold(0.0, f64::max)
+    }
+}
+impl Solver for MoeadSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let subproblem = self.state.next_subproblem;
+        self.state.next_subproblem = (subproblem + 1) % self.weights.len();
+
+        let genes = if self.state.solutions[subproblem].is_some() {
+            self.offspring(subproblem)
+        } else {
+            let dimensions = self.problem.params_domain.variables().len();
+            (0..dimensions).map(|_| self.rng.gen()).collect()
+        };
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(&genes)
+            .map(|(v, &u)| denormalize(v, u.min(1.0 - std::f64::EPSILON)))
+            .collect();
+
+        let id = idg.generate();
+        self.evaluatings.insert(id, (subproblem, genes));
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        let (subproblem, genes) = if let Some(entry) = self.evaluatings.remove(&trial.id) {
+            entry
+        } else {
+            // The trial was asked before the solver state was restored.
+            return Ok(());
+        };
+        let values = trial.values.into_vec();
+        if values.is_empty() {
+            // Unevaluable params.
+            return Ok(());
+        }
+
+        if self.state.ideal_point.is_empty() {
+            self.state.ideal_point = values.clone();
+        }
+        for (z, v) in self.state.ideal_point.iter_mut().zip(&values) {
+            *z = z.min(*v);
+        }
+
+        if self.state.solutions[subproblem].is_none() {
+            self.state.solutions[subproblem] = Some(Individual { genes, values });
+            return Ok(());
+        }
+
+        let mut replaced = Vec::new();
+        for &j in &self.neighbors[subproblem] {
+            let better = match &self.state.solutions[j] {
+                None => true,
+                Some(s) => self.chebyshev(j, &values) <= self.chebyshev(j, &s.values),
+            };
+            if better {
+                replaced.push(j);
+            }
+        }
+        for j in replaced {
+            self.state.solutions[j] = Some(Individual {
+                genes: genes.clone(),
+                values: values.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let state: MoeadState = track!(serde_json::from_value(state).map_err(Error::from))?;
+        track_assert_eq!(
+            state.solutions.len(),
+            self.weights.len(),
+            ErrorKind::InvalidInput
+        );
+        self.state = state;
+        self.evaluatings.clear();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MoeadState {
+    // The best solution of each subproblem.
+    solutions: Vec<Option<Individual>>,
+
+    // The best value of each objective found so far (empty until the first evaluation).
+    ideal_point: Vec<f64>,
+
+    next_subproblem: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Individual {
+    genes: Vec<f64>,
+    values: Vec<f64>,
+}
+
+// Generates the weight vectors by the simplex-lattice design
+// (the number of the divisions is the smallest one that yields at least `min_count` vectors).
+fn weight_vectors(objectives: usize, min_count: usize) -> Vec<Vec<f64>> {
+    if objectives == 1 {
+        return vec![vec![1.0]; min_count];
+    }
+
+    let mut divisions = 1;
+    while binomial(divisions + objectives - 1, objectives - 1) < min_count {
+        divisions += 1;
+    }
+
+    let mut weights = Vec::new();
+    let mut current = Vec::with_capacity(objectives);
+    lattice(objectives, divisions, divisions, &mut current, &mut weights);
+    weights
+}
+
+fn lattice(
+    objectives: usize,
+    divisions: usize,
+    rest: usize,
+    current: &mut Vec<usize>,
+    weights: &mut Vec<Vec<f64>>,
+) {
+    if current.len() + 1 == objectives {
+        current.push(rest);
+        weights.push(
+            current
+                .iter()
+                .map(|&c| c as f64 / divisions as f64)
+                .collect(),
+        );
+        current.pop();
+        return;
+    }
+    for c in 0..=rest {
+        current.push(c);
+        lattice(objectives, divisions, rest - c, current, weights);
+        current.pop();
+    }
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+// Returns the indices of the `size` closest weight vectors (including itself) of each weight vector.
+fn neighbors(weights: &[Vec<f64>], size: usize) -> Vec<Vec<usize>> {
+    weights
+        .iter()
+        .map(|w| {
+            let mut indices = (0..weights.len()).collect::<Vec<_>>();
+            indices.sort_by_key(|&j| {
+                let d = w
+                    .iter()
+                    .zip(&weights[j])
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f64>();
+                OrderedFloat(d)
+            });
+            indices.truncate(size);
+            indices
+        })
+        .collect()
+}
@@ -0,0 +1,378 @@
+//! A solver based on the Nelder-Mead simplex method.
+//!
+//! # References
+//!
+//! - [A Simplex Method for Function Minimization][Nelder and Mead, 1965]
+//! - [Implementing the Nelder-Mead simplex algorithm with adaptive parameters][Gao and Han, 2012]
+//!
+//! [Nelder and Mead, 1965]: https://doi.org/10.1093/comjnl/7.4.308
+//! [Gao and Han, 2012]: https://doi.org/10.1007/s10589-010-9329-3
+use crate::scale::denormalize;
+use kurobako_core::domain::Range;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+// Tolerances used to detect the convergence of a simplex.
+const TOL_X: f64 = 1e-8;
+const TOL_F: f64 = 1e-12;
+
+// The value of unevaluable parameters
+// (this isn't infinity because the simplex is serialized as JSON when saving the state).
+const WORST_VALUE: f64 = std::f64::MAX;
+
+fn default_initial_step() -> f64 {
+    0.2
+}
+
+/// Recipe of `NelderMeadSolver`.
+///
+/// The simplex is built in the search space normalized to the unit hypercube
+/// (in the log space for log-scaled variables), and the points outside of the hypercube
+/// are projected onto its boundary.
+/// Discrete variables are handled by rounding down the continuous values.
+///
+/// The coefficients of the reflection, expansion, contraction and shrinkage are adapted
+/// to the number of the dimensions as proposed by Gao and Han (2012).
+/// When the simplex has converged, the search is restarted from a random point.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct NelderMeadSolverRecipe {
+    /// Size of the initial simplex in the normalized search space.
+    #[structopt(long, default_value = "0.2")]
+    #[serde(default = "default_initial_step")]
+    initial_step: f64,
+}
+impl SolverRecipe for NelderMeadSolverRecipe {
+    type Factory = NelderMeadSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(
+            0.0 < self.initial_step && self.initial_step < 1.0,
+            ErrorKind::InvalidInput; self.initial_step
+        );
+        Ok(NelderMeadSolverFactory {
+            initial_step: self.initial_step,
+        })
+    }
+}
+
+/// Factory of `NelderMeadSolver`.
+#[derive(Debug)]
+pub struct NelderMeadSolverFactory {
+    initial_step: f64,
+}
+impl SolverFactory for NelderMeadSolverFactory {
+    type Solver = NelderMeadSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("Nelder-Mead")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Gao, Fuchang, and Lixing Han. \"Implementing the Nelder-Mead simplex algorithm \
+                 with adaptive parameters.\" Computational Optimization and Applications 51.1 \
+                 (2012): 259-277.",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            match v.range() {
+                Range::Continuous { low, high } => {
+                    track_assert!(
+                        low.is_finite() && high.is_finite(),
+                        ErrorKind::InvalidInput,
+                        "Nelder-Mead solver doesn't support unbounded variables: {:?}",
+                        v
+                    );
+                }
+                Range::Discrete { .. } => {}
+                Range::Categorical { .. } => {
+                    track_panic!(ErrorKind::Incapable, "Categorical variable: {:?}", v)
+                }
+            }
+        }
+
+        let dimensions = problem.params_domain.variables().len();
+        track_assert!(dimensions > 0, ErrorKind::InvalidInput);
+        let mut solver = NelderMeadSolver {
+            problem: problem.clone(),
+            rng,
+            initial_step: self.initial_step,
+            coefficients: Coefficients::new(dimensions),
+            pending: None,
+            state: NelderMeadState {
+                simplex: Vec::new(),
+                phase: Phase::Reflect,
+            },
+        };
+        solver.restart();
+        Ok(solver)
+    }
+}
+
+/// Solver based on the Nelder-Mead simplex method.
+#[derive(Debug)]
+pub struct NelderMeadSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    initial_step: f64,
+    coefficients: Coefficients,
+
+    // The trial being evaluated.
+    pending: Option<TrialId>,
+
+    state: NelderMeadState,
+}
+impl NelderMeadSolver {
+    fn restart(&mut self) {
+        let dimensions = self.problem.params_domain.variables().len();
+        let step = self.initial_step;
+        let x0 = (0..dimensions)
+            .map(|_| self.rng.gen_range(0.0..1.0 - step))
+            .collect::<Vec<f64>>();
+        let mut points = vec![x0.clone()];
+        for i in 0..dimensions {
+            let mut x = x0.clone();
+            x[i] += step;
+            points.push(x);
+        }
+        points.reverse();
+        self.state.simplex.clear();
+        self.state.phase = Phase::Initialize { points };
+    }
+
+    // Sorts the simplex and starts the next iteration (or restarts if the simplex has converged).
+    fn next_iteration(&mut self) {
+        let simplex = &mut self.state.simplex;
+        simplex.sort_by_key(|v| OrderedFloat(v.value));
+
+        let best = &simplex[0];
+        let diameter = simplex
+            .iter()
+            .flat_map(|v| v.x.iter().zip(&best.x).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        let spread = simplex[simplex.len() - 1].value - best.value;
+        if diameter < TOL_X || spread < TOL_F {
+            self.restart();
+        } else {
+            self.state.phase = Phase::Reflect;
+        }
+    }
+
+    fn centroid(&self) -> Vec<f64> {
+        let simplex = &self.state.simplex;
+        let n = simplex.len() - 1;
+        (0..simplex[0].x.len())
+            .map(|i| simplex[..n].iter().map(|v| v.x[i]).sum::<f64>() / n as f64)
+            .collect()
+    }
+
+    fn worst(&self) -> &Vertex {
+        &self.state.simplex[self.state.simplex.len() - 1]
+    }
+
+    // Returns `c + t * (x - c)` projected onto the unit hypercube.
+    fn towards(c: &[f64], x: &[f64], t: f64) -> Vec<f64> {
+        c.iter()
+            .zip(x)
+            .map(|(c, x)| (c + t * (x - c)).max(0.0).min(1.0))
+            .collect()
+    }
+
+    fn next_point(&self) -> Vec<f64> {
+        let coefficients = &self.coefficients;
+        match &self.state.phase {
+            Phase::Initialize { points } => points[points.len() - 1].clone(),
+            Phase::Reflect => {
+                Self::towards(&self.centroid(), &self.worst().x, -coefficients.reflection)
+            }
+            Phase::Expand { reflected } => {
+                Self::towards(&self.centroid(), &reflected.x, coefficients.expansion)
+            }
+            Phase::OutsideContract { reflected } => {
+                Self::towards(&self.centroid(), &reflected.x, coefficients.contraction)
+            }
+            Phase::InsideContract => {
+                Self::towards(&self.centroid(), &self.worst().x, coefficients.contraction)
+            }
+            Phase::Shrink { index } => Self::towards(
+                &self.state.simplex[0].x,
+                &self.state.simplex[*index].x,
+                coefficients.shrinkage,
+            ),
+        }
+    }
+
+    fn update(&mut self, vertex: Vertex) {
+        let simplex = &mut self.state.simplex;
+        let n = simplex.len().saturating_sub(1);
+        let phase = std::mem::replace(&mut self.state.phase, Phase::Reflect);
+        match phase {
+            Phase::Initialize { mut points } => {
+                points.pop();
+                simplex.push(vertex);
+                if points.is_empty() {
+                    self.next_iteration();
+                } else {
+                    self.state.phase = Phase::Initialize { points };
+                }
+            }
+            Phase::Reflect => {
+                if vertex.value < simplex[0].value {
+                    self.state.phase = Phase::Expand { reflected: vertex };
+                } else if vertex.value < simplex[n - 1].value {
+                    simplex[n] = vertex;
+                    self.next_iteration();
+                } else if vertex.value < simplex[n].value {
+                    self.state.phase = Phase::OutsideContract { reflected: vertex };
+                } else {
+                    self.state.phase = Phase::InsideContract;
+                }
+            }
+            Phase::Expand { reflected } => {
+                simplex[n] = if vertex.value < reflected.value {
+                    vertex
+                } else {
+                    reflected
+                };
+                self.next_iteration();
+            }
+            Phase::OutsideContract { reflected } => {
+                if vertex.value <= reflected.value {
+                    simplex[n] = vertex;
+                    self.next_iteration();
+                } else {
+                    self.state.phase = Phase::Shrink { index: 1 };
+                }
+            }
+            Phase::InsideContract => {
+                if vertex.value < simplex[n].value {
+                    simplex[n] = vertex;
+                    self.next_iteration();
+                } else {
+                    self.state.phase = Phase::Shrink { index: 1 };
+                }
+            }
+            Phase::Shrink { index } => {
+                simplex[index] = vertex;
+                if index == n {
+                    self.next_iteration();
+                } else {
+                    self.state.phase = Phase::Shrink { index: index + 1 };
+                }
+            }
+        }
+    }
+}
+impl Solver for NelderMeadSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let x = self.next_point();
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(&x)
+            .map(|(v, &u)| denormalize(v, u.min(1.0 - std::f64::EPSILON)))
+            .collect();
+
+        let id = idg.generate();
+        self.pending = Some(id);
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        if self.pending != Some(trial.id) {
+            // The trial was asked before the latest one (or before the solver state was restored).
+            return Ok(());
+        }
+        self.pending = None;
+
+        let value = trial
+            .values
+            .first()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .map_or(WORST_VALUE, |v| v.min(WORST_VALUE));
+        let x = self.next_point();
+        self.update(Vertex { x, value });
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.state = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Coefficients {
+    reflection: f64,
+    expansion: f64,
+    contraction: f64,
+    shrinkage: f64,
+}
+impl Coefficients {
+    fn new(dimensions: usize) -> Self {
+        // The coefficients for two dimensions are the same as the standard ones.
+        let n = (dimensions as f64).max(2.0);
+        Self {
+            reflection: 1.0,
+            expansion: 1.0 + 2.0 / n,
+            contraction: 0.75 - 1.0 / (2.0 * n),
+            shrinkage: 1.0 - 1.0 / n,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NelderMeadState {
+    simplex: Vec<Vertex>,
+    phase: Phase,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Vertex {
+    x: Vec<f64>,
+    value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Phase {
+    // Evaluating the vertices of the initial simplex (the last point is evaluated first).
+    Initialize { points: Vec<Vec<f64>> },
+    Reflect,
+    Expand { reflected: Vertex },
+    OutsideContract { reflected: Vertex },
+    InsideContract,
+    Shrink { index: usize },
+}
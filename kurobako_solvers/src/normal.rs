@@ -0,0 +1,9 @@
+use std::f64::consts::PI;
+
+pub use kurobako_core::special::normal_cdf as cdf;
+
+// Logarithm of the probability density function of `N(mu, sigma^2)`.
+pub fn ln_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (x - mu) / sigma;
+    -0.5 * z * z - sigma.ln() - 0.5 * (2.0 * PI).ln()
+}
@@ -1,32 +1,41 @@
-//! A solver based on nsga2 search.
-use crate::error::from_yamakan;
-use crate::yamakan_utils::{KurobakoDomain, YamakanIdGen};
+//! A solver based on NSGA-II (Non-dominated Sorting Genetic Algorithm II).
+//!
+//! # References
+//!
+//! - [A fast and elitist multiobjective genetic algorithm: NSGA-II][Deb et al., 2002]
+//! - [Simulated Binary Crossover for Continuous Search Space][Deb and Agrawal, 1995]
+//!
+//! [Deb et al., 2002]: https://ieeexplore.ieee.org/document/996017
+//! [Deb and Agrawal, 1995]: https://www.complex-systems.com/abstracts/v09_i02_a02/
+use crate::genetic;
+use crate::scale::{denormalize, normalize};
+use kurobako_core::domain::Range;
+use kurobako_core::num::OrderedFloat;
 use kurobako_core::problem::ProblemSpec;
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
 use kurobako_core::solver::{
     Capabilities, Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
 };
-use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, TrialId};
-use kurobako_core::{ErrorKind, Result};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, PriorTrial, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use structopt::StructOpt;
-use yamakan::domains::VecDomain;
-use yamakan::optimizers::nsga2;
-use yamakan::{Obs, ObsId, Optimizer};
 
-type Nsga2Strategy = nsga2::Nsga2Strategy<
-    VecDomain<KurobakoDomain>,
-    nsga2::RandomGenerator,
-    nsga2::TournamentSelector,
-    nsga2::ExchangeVec,
-    nsga2::ReplaceVec,
->;
-
-type Nsga2Optimizer = nsga2::Nsga2Optimizer<VecDomain<KurobakoDomain>, Nsga2Strategy>;
+fn default_eta() -> f64 {
+    20.0
+}
 
 /// Recipe of `Nsga2Solver`.
+///
+/// The genes of an individual are the parameters normalized to `[0, 1]`
+/// (in the log space for log-scaled variables).
+/// The numerical genes are recombined by the simulated binary crossover (SBX)
+/// and mutated by the polynomial mutation,
+/// and the categorical genes are exchanged between the parents and mutated by random resampling.
 #[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
 #[structopt(rename_all = "kebab-case")]
 pub struct Nsga2SolverRecipe {
@@ -45,16 +54,35 @@ pub struct Nsga2SolverRecipe {
     /// Mutation probability of each parameter.
     #[structopt(long, default_value = "0.3")]
     mutation: f64,
+
+    /// Distribution index of the simulated binary crossover.
+    #[structopt(long, default_value = "20.0")]
+    #[serde(default = "default_eta")]
+    eta_crossover: f64,
+
+    /// Distribution index of the polynomial mutation.
+    #[structopt(long, default_value = "20.0")]
+    #[serde(default = "default_eta")]
+    eta_mutation: f64,
 }
 impl SolverRecipe for Nsga2SolverRecipe {
     type Factory = Nsga2SolverFactory;
 
     fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.population >= 2, ErrorKind::InvalidInput; self.population);
+        track_assert!(self.tournament > 0, ErrorKind::InvalidInput; self.tournament);
+        track_assert!(
+            0.0 <= self.crossover && self.crossover <= 1.0,
+            ErrorKind::InvalidInput; self.crossover
+        );
+        track_assert!(
+            0.0 <= self.mutation && self.mutation <= 1.0,
+            ErrorKind::InvalidInput; self.mutation
+        );
+        track_assert!(self.eta_crossover >= 0.0, ErrorKind::InvalidInput; self.eta_crossover);
+        track_assert!(self.eta_mutation >= 0.0, ErrorKind::InvalidInput; self.eta_mutation);
         Ok(Nsga2SolverFactory {
-            population: self.population,
-            tournament: self.tournament,
-            crossover: self.crossover,
-            mutation: self.mutation,
+            recipe: self.clone(),
         })
     }
 }
@@ -62,10 +90,7 @@ impl SolverRecipe for Nsga2SolverRecipe {
 /// Factory of `Nsga2Solver`.
 #[derive(Debug)]
 pub struct Nsga2SolverFactory {
-    population: usize,
-    tournament: usize,
-    crossover: f64,
-    mutation: f64,
+    recipe: Nsga2SolverRecipe,
 }
 impl SolverFactory for Nsga2SolverFactory {
     type Solver = Nsga2Solver;
@@ -76,6 +101,11 @@ impl SolverFactory for Nsga2SolverFactory {
                 "version",
                 &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
             )
+            .attr(
+                "paper",
+                "Deb, Kalyanmoy, et al. \"A fast and elitist multiobjective genetic algorithm: \
+                 NSGA-II.\" IEEE transactions on evolutionary computation 6.2 (2002): 182-197.",
+            )
             .capabilities(
                 Capabilities::all()
                     .remove_capability(Capability::Conditional)
@@ -85,69 +115,245 @@ impl SolverFactory for Nsga2SolverFactory {
     }
 
     fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
-        let params_domain = problem
-            .params_domain
-            .variables()
-            .iter()
-            .map(|v| KurobakoDomain::new(v.range().clone(), v.distribution()))
-            .collect();
-        let params_domain = VecDomain(params_domain);
-        let selector =
-            track!(nsga2::TournamentSelector::new(self.tournament).map_err(from_yamakan))?;
-        let crossover = track!(nsga2::ExchangeVec::new(self.crossover).map_err(from_yamakan))?;
-        let mutator = track!(nsga2::ReplaceVec::new(self.mutation).map_err(from_yamakan))?;
-        let strategy = Nsga2Strategy::new(nsga2::RandomGenerator, selector, crossover, mutator);
-        let optimizer = track!(
-            Nsga2Optimizer::new(params_domain, self.population, strategy).map_err(from_yamakan)
-        )?;
+        for v in problem.params_domain.variables() {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "NSGA-II solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
 
         Ok(Nsga2Solver {
             problem: problem.clone(),
             rng,
-            optimizer,
+            recipe: self.recipe.clone(),
             evaluatings: HashMap::new(),
+            state: Nsga2State {
+                parents: Vec::new(),
+                children: Vec::new(),
+            },
+            fitness: Vec::new(),
         })
     }
 }
 
-/// Solver based on nsga2 search.
+/// Solver based on NSGA-II.
 #[derive(Debug)]
 pub struct Nsga2Solver {
-    rng: ArcRng,
     problem: ProblemSpec,
-    optimizer: Nsga2Optimizer,
+    rng: ArcRng,
+    recipe: Nsga2SolverRecipe,
     evaluatings: HashMap<TrialId, Vec<f64>>,
+    state: Nsga2State,
+
+    // The ranks and the crowding distances of the parents.
+    fitness: Vec<(usize, f64)>,
+}
+impl Nsga2Solver {
+    fn random_genes(&mut self) -> Vec<f64> {
+        let dimensions = self.problem.params_domain.variables().len();
+        (0..dimensions).map(|_| self.rng.gen()).collect()
+    }
+
+    fn select_parent(&mut self) -> usize {
+        let mut winner = self.rng.gen_range(0..self.state.parents.len());
+        for _ in 1..self.recipe.tournament {
+            let i = self.rng.gen_range(0..self.state.parents.len());
+            let (rank, crowding) = self.fitness[i];
+            let (winner_rank, winner_crowding) = self.fitness[winner];
+            if (rank, OrderedFloat(-crowding)) < (winner_rank, OrderedFloat(-winner_crowding)) {
+                winner = i;
+            }
+        }
+        winner
+    }
+
+    fn offspring(&mut self) -> Vec<f64> {
+        let p0 = self.select_parent();
+        let p1 = self.select_parent();
+        let variables = self.problem.params_domain.variables();
+        let mut genes = Vec::with_capacity(variables.len());
+        for (i, v) in variables.iter().enumerate() {
+            let x0 = self.state.parents[p0].genes[i];
+            let x1 = self.state.parents[p1].genes[i];
+            let mut x = if self.rng.gen_bool(self.recipe.crossover) {
+                genetic::crossover(&mut self.rng, v, x0, x1, self.recipe.eta_crossover)
+            } else {
+                x0
+            };
+            if self.rng.gen_bool(self.recipe.mutation) {
+                x = genetic::mutate(&mut self.rng, v, x, self.recipe.eta_mutation);
+            }
+            genes.push(x);
+        }
+        genes
+    }
+
+    fn observe(&mut self, genes: Vec<f64>, values: Vec<f64>) {
+        if values.is_empty() {
+            // Unevaluable params.
+            return;
+        }
+
+        self.state.children.push(Individual { genes, values });
+        if self.state.children.len() >= self.recipe.population {
+            let mut population = std::mem::take(&mut self.state.parents);
+            population.append(&mut self.state.children);
+            self.state.parents = select_survivors(population, self.recipe.population);
+            self.update_fitness();
+        }
+    }
+
+    fn update_fitness(&mut self) {
+        self.fitness = fitness(&self.state.parents);
+    }
 }
 impl Solver for Nsga2Solver {
     fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
-        let mut idg = YamakanIdGen(idg);
-        let obs = track!(self
-            .optimizer
-            .ask(&mut self.rng, &mut idg)
-            .map_err(from_yamakan))?;
-
-        let trial = NextTrial {
-            id: TrialId::new(obs.id.get()),
-            params: Params::new(obs.param.clone()),
-            next_step: Some(self.problem.steps.last()),
+        let genes = if self.state.parents.is_empty() {
+            self.random_genes()
+        } else {
+            self.offspring()
         };
-        self.evaluatings.insert(trial.id, obs.param);
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(&genes)
+            .map(|(v, &u)| denormalize(v, u.min(1.0 - std::f64::EPSILON)))
+            .collect();
 
-        Ok(trial)
+        let id = idg.generate();
+        self.evaluatings.insert(id, genes);
+        Ok(NextTrial {
+            id,
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
     }
 
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
-        let param = track_assert_some!(self.evaluatings.remove(&trial.id), ErrorKind::InvalidInput);
-        let obs = Obs {
-            id: ObsId::new(trial.id.get()),
-            param,
-            value: trial.values.into_vec(),
-        };
-        if !obs.value.is_empty() {
-            track!(self.optimizer.tell(obs).map_err(from_yamakan))
-        } else {
-            // Unevaluable params.
-            Ok(())
+        let genes = track_assert_some!(self.evaluatings.remove(&trial.id), ErrorKind::InvalidInput);
+        self.observe(genes, trial.values.into_vec());
+        Ok(())
+    }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        let variables = self.problem.params_domain.variables().to_vec();
+        for trial in trials {
+            track_assert_eq!(trial.params.len(), variables.len(), ErrorKind::InvalidInput);
+            let genes = variables
+                .iter()
+                .zip(trial.params.iter())
+                .map(|(v, &p)| normalize(v, p).max(0.0).min(1.0))
+                .collect();
+            self.observe(genes, trial.values.into_vec());
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.state = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.evaluatings.clear();
+        self.update_fitness();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Nsga2State {
+    parents: Vec<Individual>,
+    children: Vec<Individual>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Individual {
+    genes: Vec<f64>,
+    values: Vec<f64>,
+}
+
+// Selects the best `n` individuals by the non-dominated sorting and the crowding distance.
+fn select_survivors(population: Vec<Individual>, n: usize) -> Vec<Individual> {
+    let fitness = fitness(&population);
+    let mut population = population.into_iter().zip(fitness).collect::<Vec<_>>();
+    population.sort_by(|(_, a), (_, b)| {
+        a.0.cmp(&b.0)
+            .then_with(|| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal))
+    });
+    population.truncate(n);
+    population.into_iter().map(|(i, _)| i).collect()
+}
+
+// Returns the rank (the index of the non-dominated front) and the crowding distance of each individual.
+fn fitness(population: &[Individual]) -> Vec<(usize, f64)> {
+    let n = population.len();
+    let mut dominated_by = vec![Vec::new(); n];
+    let mut domination_counts = vec![0; n];
+    for i in 0..n {
+        for j in 0..n {
+            if dominates(&population[i].values, &population[j].values) {
+                dominated_by[i].push(j);
+                domination_counts[j] += 1;
+            }
+        }
+    }
+
+    let mut fitness = vec![(0, 0.0); n];
+    let mut front = (0..n)
+        .filter(|&i| domination_counts[i] == 0)
+        .collect::<Vec<_>>();
+    let mut rank = 0;
+    while !front.is_empty() {
+        for (i, crowding) in crowding_distances(population, &front) {
+            fitness[i] = (rank, crowding);
         }
+
+        let mut next = Vec::new();
+        for &i in &front {
+            for &j in &dominated_by[i] {
+                domination_counts[j] -= 1;
+                if domination_counts[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        front = next;
+        rank += 1;
     }
+    fitness
+}
+
+fn crowding_distances(population: &[Individual], front: &[usize]) -> Vec<(usize, f64)> {
+    let mut distances = front.iter().map(|&i| (i, 0.0)).collect::<Vec<_>>();
+    let objectives = population[front[0]].values.len();
+    for k in 0..objectives {
+        distances.sort_by_key(|&(i, _)| OrderedFloat(population[i].values[k]));
+        let min = population[distances[0].0].values[k];
+        let max = population[distances[distances.len() - 1].0].values[k];
+        let last = distances.len() - 1;
+        distances[0].1 = std::f64::INFINITY;
+        distances[last].1 = std::f64::INFINITY;
+        if max - min <= 0.0 {
+            continue;
+        }
+        for j in 1..last {
+            let prev = population[distances[j - 1].0].values[k];
+            let next = population[distances[j + 1].0].values[k];
+            distances[j].1 += (next - prev) / (max - min);
+        }
+    }
+    distances
+}
+
+// Returns `true` if `a` dominates `b` (all the objectives are minimized).
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a <= b) && a.iter().zip(b).any(|(a, b)| a < b)
 }
@@ -6,7 +6,7 @@ use kurobako_core::problem::ProblemSpec;
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
 use kurobako_core::solver::{Solver, SolverFactory, SolverRecipe, SolverSpec};
-use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, PriorTrial};
 use kurobako_core::Result;
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
@@ -161,4 +161,16 @@ impl Solver for OptunaSolver {
     fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
         track!(self.inner.tell(trial))
     }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        track!(self.inner.tell_priors(trials))
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(self.inner.save_state())
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        track!(self.inner.load_state(state))
+    }
 }
@@ -1,12 +1,12 @@
 //! A solver based on random search.
 use kurobako_core::problem::ProblemSpec;
 use kurobako_core::registry::FactoryRegistry;
-use kurobako_core::rng::ArcRng;
+use kurobako_core::rng::{ArcRng, Rng as _};
 use kurobako_core::solver::{
     Capabilities, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
 };
 use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params};
-use kurobako_core::{ErrorKind, Result};
+use kurobako_core::{Error, ErrorKind, Result};
 use rand::distributions::Distribution as _;
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
@@ -95,4 +95,29 @@ impl Solver for RandomSolver {
         }
         Ok(())
     }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        // The internal state of the RNG can't be serialized,
+        // so the RNG is reseeded with a seed drawn from itself and the seed is saved instead.
+        let seed = self.rng.gen();
+        self.rng = ArcRng::new(seed);
+        let state = RandomState {
+            current_step: self.current_step,
+            seed,
+        };
+        track!(serde_json::to_value(state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let state: RandomState = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.current_step = state.current_step;
+        self.rng = ArcRng::new(state.seed);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RandomState {
+    current_step: Option<u64>,
+    seed: u64,
 }
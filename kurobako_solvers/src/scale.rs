@@ -0,0 +1,46 @@
+use kurobako_core::domain::{Distribution, Range, Variable};
+
+// Maps `u` in `[0, 1]` onto the range of the given variable
+// (in the log space if the variable is log-scaled).
+pub fn denormalize(var: &Variable, u: f64) -> f64 {
+    match var.range() {
+        Range::Continuous { low, high } => match var.distribution() {
+            Distribution::Uniform => low + u * (high - low),
+            Distribution::LogUniform => (low.log2() + u * (high.log2() - low.log2())).exp2(),
+        },
+        Range::Discrete { low, high } => {
+            let (low, high) = (*low as f64, *high as f64);
+            let x = match var.distribution() {
+                Distribution::Uniform => low + u * (high - low),
+                Distribution::LogUniform => (low.log2() + u * (high.log2() - low.log2())).exp2(),
+            };
+            x.floor().min(high - 1.0)
+        }
+        Range::Categorical { choices } => {
+            ((u * choices.len() as f64) as usize).min(choices.len() - 1) as f64
+        }
+    }
+}
+
+// Maps a value of the given variable onto `[0, 1]` (the inverse of `denormalize`).
+//
+// Discrete and categorical values are mapped onto the centers of their intervals.
+pub fn normalize(var: &Variable, x: f64) -> f64 {
+    match var.range() {
+        Range::Continuous { low, high } => match var.distribution() {
+            Distribution::Uniform => (x - low) / (high - low),
+            Distribution::LogUniform => (x.log2() - low.log2()) / (high.log2() - low.log2()),
+        },
+        Range::Discrete { low, high } => {
+            let (low, high) = (*low as f64, *high as f64);
+            match var.distribution() {
+                Distribution::Uniform => (x + 0.5 - low) / (high - low),
+                Distribution::LogUniform => {
+                    let center = 0.5 * (x.log2() + (x + 1.0).log2());
+                    (center - low.log2()) / (high.log2() - low.log2())
+                }
+            }
+        }
+        Range::Categorical { choices } => (x + 0.5) / choices.len() as f64,
+    }
+}
@@ -0,0 +1,485 @@
+//! A solver based on (scrambled) Sobol sequences.
+//!
+//! # References
+//!
+//! - [Constructing Sobol sequences with better two-dimensional projections][Joe and Kuo, 2008]
+//! - [Implementation of Sobol sequence generators][Joe and Kuo, 2003]
+//!
+//! [Joe and Kuo, 2008]: https://doi.org/10.1137/070709359
+//! [Joe and Kuo, 2003]: https://doi.org/10.1145/641876.641879
+use crate::scale::denormalize;
+use kurobako_core::domain::Range;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{
+    Capabilities, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+const BITS: usize = 32;
+
+// Initial direction numbers of the second and subsequent dimensions taken from `new-joe-kuo-6.21201`.
+//
+// The `i`-th entry corresponds to the `i`-th primitive polynomial
+// (in ascending order of the degree and then the coefficients).
+const INITIAL_DIRECTION_NUMBERS: &[&[u32]] = &[
+    &[1],
+    &[1, 3],
+    &[1, 3, 1],
+    &[1, 1, 1],
+    &[1, 1, 3, 3],
+    &[1, 3, 5, 13],
+    &[1, 1, 5, 5, 17],
+    &[1, 1, 5, 5, 5],
+    &[1, 1, 7, 11, 19],
+    &[1, 1, 5, 1, 1],
+    &[1, 1, 1, 3, 11],
+    &[1, 3, 5, 5, 31],
+    &[1, 3, 3, 9, 7, 49],
+    &[1, 1, 1, 15, 21, 21],
+    &[1, 3, 1, 13, 27, 49],
+    &[1, 1, 1, 15, 7, 5],
+    &[1, 3, 1, 15, 13, 25],
+    &[1, 1, 5, 5, 19, 61],
+    &[1, 3, 7, 11, 23, 15, 103],
+    &[1, 3, 7, 13, 13, 15, 69],
+];
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+/// Recipe of `SobolSolver`.
+///
+/// The parameters are sampled from a Sobol sequence whose points are mapped onto the parameter domain
+/// in the same manner as `RandomSolver`
+/// (e.g., log-scaled variables are sampled uniformly in the log space).
+///
+/// Because a Sobol sequence is deterministic, the sequence is scrambled
+/// by a random linear matrix scrambling and a random digital shift by default.
+/// If there are more than 21 parameters,
+/// the initial direction numbers of the rest dimensions are chosen randomly.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct SobolSolverRecipe {
+    /// If this flag is set, the sequence isn't scrambled.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "is_false")]
+    no_scramble: bool,
+}
+impl SolverRecipe for SobolSolverRecipe {
+    type Factory = SobolSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        Ok(SobolSolverFactory {
+            scramble: !self.no_scramble,
+        })
+    }
+}
+
+/// Factory of `SobolSolver`.
+#[derive(Debug)]
+pub struct SobolSolverFactory {
+    scramble: bool,
+}
+impl SolverFactory for SobolSolverFactory {
+    type Solver = SobolSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("Sobol")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Joe, Stephen, and Frances Y. Kuo. \"Constructing Sobol sequences with \
+                 better two-dimensional projections.\" SIAM Journal on Scientific Computing \
+                 30.5 (2008): 2635-2654.",
+            )
+            .capabilities(Capabilities::all());
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, mut rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "Sobol solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
+
+        let dimensions = problem.params_domain.variables().len();
+        let mut directions = direction_numbers(dimensions, &mut rng);
+        let mut shifts = vec![0; dimensions];
+        if self.scramble {
+            for (v, shift) in directions.iter_mut().zip(shifts.iter_mut()) {
+                scramble(v, &mut rng);
+                *shift = rng.gen();
+            }
+        }
+
+        Ok(SobolSolver {
+            problem: problem.clone(),
+            index: 0,
+            state: vec![0; dimensions],
+            directions,
+            shifts,
+        })
+    }
+}
+
+/// Solver based on (scrambled) Sobol sequences.
+#[derive(Debug)]
+pub struct SobolSolver {
+    problem: ProblemSpec,
+    index: u32,
+    state: Vec<u32>,
+    directions: Vec<[u32; BITS]>,
+    shifts: Vec<u32>,
+}
+impl Solver for SobolSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let params = self
+            .problem
+            .params_domain
+            .variables()
+            .iter()
+            .zip(self.state.iter().zip(&self.shifts))
+            .map(|(var, (x, shift))| {
+                let u = f64::from(x ^ shift) / (1u64 << BITS) as f64;
+                denormalize(var, u)
+            })
+            .collect();
+
+        // Gray code ordering: the next point differs from the current one by a single direction number.
+        let c = self.index.trailing_ones() as usize;
+        track_assert!(
+            c < BITS,
+            ErrorKind::Other,
+            "The Sobol sequence has been exhausted"
+        );
+        for (x, v) in self.state.iter_mut().zip(&self.directions) {
+            *x ^= v[c];
+        }
+        self.index += 1;
+
+        Ok(NextTrial {
+            id: idg.generate(),
+            params: Params::new(params),
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, _trial: EvaluatedTrial) -> Result<()> {
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        let state = SobolState {
+            index: self.index,
+            state: self.state.clone(),
+        };
+        track!(serde_json::to_value(state).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let state: SobolState = track!(serde_json::from_value(state).map_err(Error::from))?;
+        track_assert_eq!(state.state.len(), self.state.len(), ErrorKind::InvalidInput);
+        self.index = state.index;
+        self.state = state.state;
+        Ok(())
+    }
+}
+
+// The direction numbers and the shifts aren't saved
+// because they are generated again from the same random seed when the solver is re-created.
+#[derive(Debug, Serialize, Deserialize)]
+struct SobolState {
+    index: u32,
+    state: Vec<u32>,
+}
+
+// Returns the direction numbers (`v_1, ..., v_32` scaled by `2^32`) of each dimension.
+fn direction_numbers(dimensions: usize, rng: &mut ArcRng) -> Vec<[u32; BITS]> {
+    let mut directions = Vec::with_capacity(dimensions);
+    if dimensions == 0 {
+        return directions;
+    }
+
+    // The first dimension is the van der Corput sequence.
+    let mut v = [0; BITS];
+    for (i, v) in v.iter_mut().enumerate() {
+        *v = 1 << (BITS - 1 - i);
+    }
+    directions.push(v);
+
+    let mut polynomials = PrimitivePolynomials::new();
+    for i in 1..dimensions {
+        let (s, a) = polynomials.next_polynomial();
+        let m = if let Some(m) = INITIAL_DIRECTION_NUMBERS.get(i - 1) {
+            m.to_vec()
+        } else {
+            // Odd integers `m_k` such that `m_k < 2^k`.
+            (1..=s)
+                .map(|k| (rng.gen_range(0..1u32 << (k - 1)) << 1) | 1)
+                .collect()
+        };
+
+        let mut v = [0; BITS];
+        for k in 0..BITS {
+            if k < s {
+                v[k] = m[k] << (BITS - 1 - k);
+            } else {
+                v[k] = v[k - s] ^ (v[k - s] >> s);
+                for j in 1..s {
+                    if (a >> (s - 1 - j)) & 1 == 1 {
+                        v[k] ^= v[k - j];
+                    }
+                }
+            }
+        }
+        directions.push(v);
+    }
+    directions
+}
+
+// Linear matrix scrambling: left-multiplies the generator matrix by a random lower triangular matrix
+// whose diagonal elements are ones.
+fn scramble(v: &mut [u32; BITS], rng: &mut ArcRng) {
+    let lower = (0..BITS)
+        .map(|i| {
+            let diagonal = 1u32 << (BITS - 1 - i);
+            let left = !(diagonal << 1).wrapping_sub(1);
+            (rng.gen::<u32>() & left) | diagonal
+        })
+        .collect::<Vec<_>>();
+    for x in v.iter_mut() {
+        let mut scrambled = 0;
+        for (i, row) in lower.iter().enumerate() {
+            if (row & *x).count_ones() % 2 == 1 {
+                scrambled |= 1 << (BITS - 1 - i);
+            }
+        }
+        *x = scrambled;
+    }
+}
+
+// Generator of the primitive polynomials over GF(2)
+// in ascending order of the degree and then the coefficients.
+//
+// A polynomial of degree `s` is represented by `(s, a)` where the bits of `a` are the coefficients
+// of `x^{s-1}, ..., x^1` (the most significant bit is the coefficient of `x^{s-1}`).
+#[derive(Debug)]
+struct PrimitivePolynomials {
+    degree: usize,
+    coefficients: u64,
+}
+impl PrimitivePolynomials {
+    fn new() -> Self {
+        Self {
+            degree: 1,
+            coefficients: 0,
+        }
+    }
+
+    fn next_polynomial(&mut self) -> (usize, u64) {
+        loop {
+            if self.coefficients == 1 << (self.degree - 1) {
+                self.degree += 1;
+                self.coefficients = 0;
+            }
+            let (s, a) = (self.degree, self.coefficients);
+            self.coefficients += 1;
+            if is_primitive((1 << s) | (a << 1) | 1, s) {
+                return (s, a);
+            }
+        }
+    }
+}
+
+fn is_primitive(p: u64, degree: usize) -> bool {
+    let order = (1u64 << degree) - 1;
+    if pow_mod(2, order, p, degree) != 1 {
+        return false;
+    }
+    prime_factors(order)
+        .into_iter()
+        .all(|q| pow_mod(2, order / q, p, degree) != 1)
+}
+
+// `x^e mod p` over GF(2) where `x` and `p` are polynomials.
+fn pow_mod(x: u64, mut e: u64, p: u64, degree: usize) -> u64 {
+    let mut base = reduce(x, p, degree);
+    let mut result = 1;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = mul_mod(result, base, p, degree);
+        }
+        base = mul_mod(base, base, p, degree);
+        e >>= 1;
+    }
+    result
+}
+
+fn mul_mod(mut a: u64, mut b: u64, p: u64, degree: usize) -> u64 {
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result ^= a;
+        }
+        b >>= 1;
+        a = reduce(a << 1, p, degree);
+    }
+    result
+}
+
+fn reduce(a: u64, p: u64, degree: usize) -> u64 {
+    if a & (1 << degree) != 0 {
+        a ^ p
+    } else {
+        a
+    }
+}
+
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut q = 2;
+    while q * q <= n {
+        if n % q == 0 {
+            factors.push(q);
+            while n % q == 0 {
+                n /= q;
+            }
+        }
+        q += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurobako_core::domain;
+    use kurobako_core::problem::ProblemSpecBuilder;
+
+    fn solver(dimensions: usize, scramble: bool) -> Result<SobolSolver> {
+        let params = (0..dimensions)
+            .map(|i| domain::var(&format!("x{}", i)).continuous(0.0, 1.0))
+            .collect();
+        let problem = track!(ProblemSpecBuilder::new("test")
+            .params(params)
+            .value(domain::var("y"))
+            .finish())?;
+        let factory = SobolSolverFactory { scramble };
+        track!(factory.create_solver(ArcRng::new(0), &problem))
+    }
+
+    fn ask(solver: &mut SobolSolver, idg: &mut IdGen) -> Result<Vec<f64>> {
+        let trial = track!(solver.ask(idg))?;
+        Ok(trial.params.get().to_vec())
+    }
+
+    #[test]
+    fn primitive_polynomials_work() {
+        // The `s` and `a` columns of `new-joe-kuo-6.21201`.
+        let expected = [
+            (1, 0),
+            (2, 1),
+            (3, 1),
+            (3, 2),
+            (4, 1),
+            (4, 4),
+            (5, 2),
+            (5, 4),
+            (5, 7),
+            (5, 11),
+            (5, 13),
+            (5, 14),
+            (6, 1),
+            (6, 13),
+            (6, 16),
+            (6, 19),
+            (6, 22),
+            (6, 25),
+            (7, 1),
+            (7, 4),
+        ];
+        let mut polynomials = PrimitivePolynomials::new();
+        for &(s, a) in &expected {
+            assert_eq!(polynomials.next_polynomial(), (s, a));
+        }
+        assert_eq!(expected.len(), INITIAL_DIRECTION_NUMBERS.len());
+    }
+
+    #[test]
+    fn unscrambled_sequence_works() -> trackable::result::TopLevelResult {
+        // The points of `scipy.stats.qmc.Sobol(d=3, scramble=False)` (which also uses the Gray code ordering).
+        let expected = [
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.5],
+            [0.75, 0.25, 0.25],
+            [0.25, 0.75, 0.75],
+            [0.375, 0.375, 0.625],
+            [0.875, 0.875, 0.125],
+            [0.625, 0.125, 0.875],
+            [0.125, 0.625, 0.375],
+        ];
+        let mut solver = track!(solver(3, false))?;
+        let mut idg = IdGen::new();
+        for x in &expected {
+            assert_eq!(track!(ask(&mut solver, &mut idg))?, x.to_vec());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_direction_numbers_work() {
+        let mut rng = ArcRng::new(0);
+        let directions = direction_numbers(30, &mut rng);
+        let mut polynomials = PrimitivePolynomials::new();
+        for v in &directions[1..] {
+            let (s, _) = polynomials.next_polynomial();
+            for (k, &v) in v.iter().enumerate().take(s) {
+                // `m_k` is odd and less than `2^k` (`k` is zero-origin here).
+                let m = v >> (BITS - 1 - k);
+                assert_eq!(m & 1, 1);
+                assert!(m < 1 << (k + 1));
+                assert_eq!(v & ((1 << (BITS - 1 - k)) - 1), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn save_and_load_state_work() -> trackable::result::TopLevelResult {
+        let mut solver0 = track!(solver(25, true))?;
+        let mut idg = IdGen::new();
+        for _ in 0..5 {
+            track!(ask(&mut solver0, &mut idg))?;
+        }
+        let state = track!(solver0.save_state())?;
+
+        let mut solver1 = track!(solver(25, true))?;
+        track!(solver1.load_state(state))?;
+        for _ in 0..5 {
+            assert_eq!(
+                track!(ask(&mut solver0, &mut idg))?,
+                track!(ask(&mut solver1, &mut idg))?
+            );
+        }
+        Ok(())
+    }
+}
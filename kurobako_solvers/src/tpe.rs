@@ -0,0 +1,466 @@
+//! A solver based on TPE (Tree-structured Parzen Estimator).
+//!
+//! # References
+//!
+//! - [Algorithms for Hyper-Parameter Optimization][Bergstra et al., 2011]
+//!
+//! [Bergstra et al., 2011]: https://papers.nips.cc/paper/4443-algorithms-for-hyper-parameter-optimization
+use crate::normal;
+use crate::scale::denormalize;
+use kurobako_core::domain::{Distribution, Range, Variable};
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::ProblemSpec;
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::solver::{
+    Capability, Solver, SolverFactory, SolverRecipe, SolverSpec, SolverSpecBuilder,
+};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, Params, PriorTrial, TrialId};
+use kurobako_core::{Error, ErrorKind, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use structopt::StructOpt;
+
+// Maximum number of the observations regarded as good ones.
+const MAX_GOOD_OBSERVATIONS: usize = 25;
+
+// Maximum number of the resamplings of a candidate that is out of the range of a variable.
+const MAX_RESAMPLINGS: usize = 100;
+
+fn default_startup_trials() -> usize {
+    10
+}
+
+fn default_ei_candidates() -> NonZeroUsize {
+    NonZeroUsize::new(24).unwrap_or_else(|| unreachable!())
+}
+
+fn default_gamma() -> f64 {
+    0.1
+}
+
+fn default_prior_weight() -> f64 {
+    1.0
+}
+
+/// Recipe of `TpeSolver`.
+///
+/// The parameters are sampled independently for each variable (i.e., univariate TPE)
+/// in the same manner as the default sampler of Optuna.
+/// Numerical variables are modeled by mixtures of truncated normal distributions
+/// (in the log space if the variables are log-scaled),
+/// and categorical variables are modeled by smoothed categorical distributions.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct TpeSolverRecipe {
+    /// Number of the trials whose parameters are sampled randomly before starting TPE.
+    #[structopt(long, default_value = "10")]
+    #[serde(default = "default_startup_trials")]
+    startup_trials: usize,
+
+    /// Number of the candidates from which the parameter maximizing the expected improvement is selected.
+    #[structopt(long, default_value = "24")]
+    #[serde(default = "default_ei_candidates")]
+    ei_candidates: NonZeroUsize,
+
+    /// Ratio of the observations regarded as good ones (at most 25 observations).
+    #[structopt(long, default_value = "0.1")]
+    #[serde(default = "default_gamma")]
+    gamma: f64,
+
+    /// Weight of the prior distribution.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default = "default_prior_weight")]
+    prior_weight: f64,
+}
+impl SolverRecipe for TpeSolverRecipe {
+    type Factory = TpeSolverFactory;
+
+    fn create_factory(&self, _registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(
+            0.0 < self.gamma && self.gamma <= 1.0,
+            ErrorKind::InvalidInput; self.gamma
+        );
+        track_assert!(self.prior_weight > 0.0, ErrorKind::InvalidInput; self.prior_weight);
+        Ok(TpeSolverFactory {
+            startup_trials: self.startup_trials,
+            ei_candidates: self.ei_candidates.get(),
+            gamma: self.gamma,
+            prior_weight: self.prior_weight,
+        })
+    }
+}
+
+/// Factory of `TpeSolver`.
+#[derive(Debug)]
+pub struct TpeSolverFactory {
+    startup_trials: usize,
+    ei_candidates: usize,
+    gamma: f64,
+    prior_weight: f64,
+}
+impl SolverFactory for TpeSolverFactory {
+    type Solver = TpeSolver;
+
+    fn specification(&self) -> Result<SolverSpec> {
+        let spec = SolverSpecBuilder::new("TPE")
+            .attr(
+                "version",
+                &format!("kurobako_solvers={}", env!("CARGO_PKG_VERSION")),
+            )
+            .attr(
+                "paper",
+                "Bergstra, James S., et al. \"Algorithms for hyper-parameter optimization.\" \
+                 Advances in neural information processing systems. 2011.",
+            )
+            .capable(Capability::UniformContinuous)
+            .capable(Capability::UniformDiscrete)
+            .capable(Capability::LogUniformContinuous)
+            .capable(Capability::LogUniformDiscrete)
+            .capable(Capability::Categorical)
+            .capable(Capability::Conditional)
+            .capable(Capability::Concurrent)
+            .capable(Capability::WarmStarting)
+            .capable(Capability::Checkpointable);
+        Ok(spec.finish())
+    }
+
+    fn create_solver(&self, rng: ArcRng, problem: &ProblemSpec) -> Result<Self::Solver> {
+        for v in problem.params_domain.variables() {
+            if let Range::Continuous { low, high } = v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput,
+                    "TPE solver doesn't support unbounded variables: {:?}",
+                    v
+                );
+            }
+        }
+
+        Ok(TpeSolver {
+            problem: problem.clone(),
+            rng,
+            startup_trials: self.startup_trials,
+            ei_candidates: self.ei_candidates,
+            gamma: self.gamma,
+            prior_weight: self.prior_weight,
+            evaluatings: HashMap::new(),
+            observations: Vec::new(),
+        })
+    }
+}
+
+/// Solver based on TPE.
+#[derive(Debug)]
+pub struct TpeSolver {
+    problem: ProblemSpec,
+    rng: ArcRng,
+    startup_trials: usize,
+    ei_candidates: usize,
+    gamma: f64,
+    prior_weight: f64,
+    evaluatings: HashMap<TrialId, Params>,
+    observations: Vec<Observation>,
+}
+impl TpeSolver {
+    fn observe(&mut self, params: Params, values: &[f64]) {
+        // Unevaluable parameters are regarded as the worst ones.
+        let value = values.first().copied().filter(|v| !v.is_nan());
+        self.observations.push(Observation { params, value });
+    }
+
+    // Splits the observations into the good ones and the others.
+    fn split(&self) -> (Vec<&Observation>, Vec<&Observation>) {
+        let mut observations = self.observations.iter().collect::<Vec<_>>();
+        observations.sort_by_key(|o| OrderedFloat(o.value.unwrap_or(std::f64::INFINITY)));
+
+        let n = (self.gamma * observations.len() as f64).ceil() as usize;
+        let others = observations.split_off(n.min(MAX_GOOD_OBSERVATIONS));
+        (observations, others)
+    }
+}
+impl Solver for TpeSolver {
+    fn ask(&mut self, idg: &mut IdGen) -> Result<NextTrial> {
+        let params = if self.observations.len() < self.startup_trials {
+            let mut params = Vec::new();
+            for v in self.problem.params_domain.variables() {
+                params.push(denormalize(v, self.rng.gen()));
+            }
+            params
+        } else {
+            let (good, others) = self.split();
+            let good = good.iter().map(|o| &o.params).collect::<Vec<_>>();
+            let others = others.iter().map(|o| &o.params).collect::<Vec<_>>();
+            let mut rng = self.rng.clone(); // The clones of `ArcRng` share the same state.
+            sample_params(
+                &mut rng,
+                self.problem.params_domain.variables(),
+                &good,
+                &others,
+                self.ei_candidates,
+                self.prior_weight,
+            )
+        };
+
+        let id = idg.generate();
+        let params = Params::new(params);
+        self.evaluatings.insert(id, params.clone());
+        Ok(NextTrial {
+            id,
+            params,
+            next_step: Some(self.problem.steps.last()),
+        })
+    }
+
+    fn tell(&mut self, trial: EvaluatedTrial) -> Result<()> {
+        if let Some(params) = self.evaluatings.remove(&trial.id) {
+            self.observe(params, &trial.values);
+        }
+        Ok(())
+    }
+
+    fn tell_priors(&mut self, trials: Vec<PriorTrial>) -> Result<()> {
+        let dimensions = self.problem.params_domain.variables().len();
+        for trial in trials {
+            track_assert_eq!(trial.params.len(), dimensions, ErrorKind::InvalidInput);
+            self.observe(trial.params, &trial.values);
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        track!(serde_json::to_value(&self.observations).map_err(Error::from))
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<()> {
+        self.observations = track!(serde_json::from_value(state).map_err(Error::from))?;
+        self.evaluatings.clear();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Observation {
+    params: Params,
+
+    // `None` means that the parameters couldn't be evaluated.
+    value: Option<f64>,
+}
+
+// Samples the parameters independently for each variable by using `sample_param`.
+pub(crate) fn sample_params<R: Rng>(
+    rng: &mut R,
+    variables: &[Variable],
+    good: &[&Params],
+    others: &[&Params],
+    ei_candidates: usize,
+    prior_weight: f64,
+) -> Vec<f64> {
+    let mut params = Vec::with_capacity(variables.len());
+    for (i, v) in variables.iter().enumerate() {
+        // Conditional parameters are NaN in the observations where their conditions didn't hold.
+        let column = |observations: &[&Params]| {
+            observations
+                .iter()
+                .map(|p| p[i])
+                .filter(|p| !p.is_nan())
+                .collect::<Vec<_>>()
+        };
+        params.push(sample_param(
+            rng,
+            v,
+            &column(good),
+            &column(others),
+            ei_candidates,
+            prior_weight,
+        ));
+    }
+    params
+}
+
+// Samples the parameter that maximizes `l(x) / g(x)` among the candidates sampled from `l(x)`
+// (this is equivalent to maximizing the expected improvement).
+fn sample_param<R: Rng>(
+    rng: &mut R,
+    var: &Variable,
+    good: &[f64],
+    others: &[f64],
+    ei_candidates: usize,
+    prior_weight: f64,
+) -> f64 {
+    if let Range::Categorical { choices } = var.range() {
+        let l = Categorical::new(good, choices.len(), prior_weight);
+        let g = Categorical::new(others, choices.len(), prior_weight);
+        let x = (0..ei_candidates)
+            .map(|_| l.sample(rng))
+            .max_by_key(|&x| OrderedFloat(l.ln_pmf(x) - g.ln_pmf(x)))
+            .unwrap_or_else(|| unreachable!());
+        return x as f64;
+    }
+
+    let (low, high) = internal_range(var);
+    let good = good
+        .iter()
+        .map(|&p| to_internal(var, p))
+        .collect::<Vec<_>>();
+    let others = others
+        .iter()
+        .map(|&p| to_internal(var, p))
+        .collect::<Vec<_>>();
+    let l = Parzen::new(&good, low, high, prior_weight);
+    let g = Parzen::new(&others, low, high, prior_weight);
+    let x = (0..ei_candidates)
+        .map(|_| l.sample(rng))
+        .max_by_key(|&x| OrderedFloat(l.ln_pdf(x) - g.ln_pdf(x)))
+        .unwrap_or_else(|| unreachable!());
+    from_internal(var, x)
+}
+
+// Returns the range of a numerical variable in the space where the Parzen estimators are built
+// (i.e., the log space for log-scaled variables).
+fn internal_range(var: &Variable) -> (f64, f64) {
+    let (low, high) = match var.range() {
+        Range::Continuous { low, high } => (*low, *high),
+        Range::Discrete { low, high } => (*low as f64, *high as f64),
+        Range::Categorical { .. } => unreachable!(),
+    };
+    (to_internal(var, low), to_internal(var, high))
+}
+
+fn to_internal(var: &Variable, x: f64) -> f64 {
+    match var.distribution() {
+        Distribution::Uniform => x,
+        Distribution::LogUniform => x.log2(),
+    }
+}
+
+fn from_internal(var: &Variable, x: f64) -> f64 {
+    let x = match var.distribution() {
+        Distribution::Uniform => x,
+        Distribution::LogUniform => x.exp2(),
+    };
+    match var.range() {
+        Range::Continuous { low, high } => x.max(*low).min(*high),
+        Range::Discrete { low, high } => x.floor().max(*low as f64).min(*high as f64 - 1.0),
+        Range::Categorical { .. } => unreachable!(),
+    }
+}
+
+// Mixture of truncated normal distributions whose components are centered at the observations
+// (and the prior component is centered at the middle of the range).
+#[derive(Debug)]
+struct Parzen {
+    low: f64,
+    high: f64,
+    weights: Vec<f64>,
+    mus: Vec<f64>,
+    sigmas: Vec<f64>,
+
+    // Logarithms of the probability masses of the components within the range.
+    ln_masses: Vec<f64>,
+}
+impl Parzen {
+    fn new(xs: &[f64], low: f64, high: f64, prior_weight: f64) -> Self {
+        let width = high - low;
+        let mut xs = xs.to_vec();
+        xs.sort_by_key(|&x| OrderedFloat(x));
+
+        // The bandwidth of each observation is the larger distance to its neighbors.
+        let min_sigma = width / 100f64.min(1.0 + xs.len() as f64);
+        let mut mus = Vec::with_capacity(xs.len() + 1);
+        let mut sigmas = Vec::with_capacity(xs.len() + 1);
+        let mut weights = Vec::with_capacity(xs.len() + 1);
+        for (i, &x) in xs.iter().enumerate() {
+            let left = if i == 0 { low } else { xs[i - 1] };
+            let right = xs.get(i + 1).copied().unwrap_or(high);
+            mus.push(x);
+            sigmas.push((x - left).max(right - x).max(min_sigma).min(width));
+            weights.push(1.0);
+        }
+        mus.push(0.5 * (low + high));
+        sigmas.push(width);
+        weights.push(prior_weight);
+
+        let total = weights.iter().sum::<f64>();
+        let weights = weights.into_iter().map(|w| w / total).collect();
+        let ln_masses = mus
+            .iter()
+            .zip(&sigmas)
+            .map(|(&mu, &sigma)| {
+                (normal::cdf((high - mu) / sigma) - normal::cdf((low - mu) / sigma)).ln()
+            })
+            .collect();
+        Self {
+            low,
+            high,
+            weights,
+            mus,
+            sigmas,
+            ln_masses,
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        let i = choose(&self.weights, rng);
+        let (mu, sigma) = (self.mus[i], self.sigmas[i]);
+        let mut x = mu;
+        for _ in 0..MAX_RESAMPLINGS {
+            x = mu + sigma * standard_normal(rng);
+            if self.low <= x && x < self.high {
+                return x;
+            }
+        }
+        x.max(self.low).min(self.high)
+    }
+
+    fn ln_pdf(&self, x: f64) -> f64 {
+        let terms = (0..self.mus.len())
+            .map(|i| {
+                self.weights[i].ln() + normal::ln_pdf(x, self.mus[i], self.sigmas[i])
+                    - self.ln_masses[i]
+            })
+            .collect::<Vec<_>>();
+        let max = terms.iter().copied().fold(std::f64::NEG_INFINITY, f64::max);
+        max + terms.iter().map(|t| (t - max).exp()).sum::<f64>().ln()
+    }
+}
+
+// Categorical distribution estimated from the observations
+// (the prior weight is distributed equally among the choices).
+#[derive(Debug)]
+struct Categorical {
+    weights: Vec<f64>,
+}
+impl Categorical {
+    fn new(xs: &[f64], choices: usize, prior_weight: f64) -> Self {
+        let mut weights = vec![prior_weight / choices as f64; choices];
+        for &x in xs {
+            weights[x as usize] += 1.0;
+        }
+        let total = weights.iter().sum::<f64>();
+        let weights = weights.into_iter().map(|w| w / total).collect();
+        Self { weights }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        choose(&self.weights, rng)
+    }
+
+    fn ln_pmf(&self, x: usize) -> f64 {
+        self.weights[x].ln()
+    }
+}
+
+// Chooses an index with the probability proportional to its weight (the weights must sum to one).
+fn choose<R: Rng>(weights: &[f64], rng: &mut R) -> usize {
+    let mut u = rng.gen::<f64>();
+    for (i, w) in weights.iter().enumerate() {
+        if u < *w {
+            return i;
+        }
+        u -= w;
+    }
+    weights.len() - 1
+}
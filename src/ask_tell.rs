@@ -0,0 +1,254 @@
+//! `kurobako ask` and `kurobako tell` commands.
+use crate::best::named_params;
+use crate::problem::KurobakoProblemRecipe;
+use crate::solver::KurobakoSolverRecipe;
+use kurobako_core::json;
+use kurobako_core::problem::{ProblemFactory as _, ProblemRecipe as _, ProblemSpec};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::solver::{BoxSolver, Solver as _, SolverFactory as _, SolverRecipe as _};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, Params, TrialId, Values};
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write as _};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+/// Options of the `kurobako ask` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct AskOpt {
+    /// Study state file (JSON).
+    ///
+    /// If the file doesn't exist, a new study is created with the given solver and problem.
+    #[structopt(long)]
+    pub state: PathBuf,
+
+    /// Solver recipe JSON (required only when creating a new study).
+    #[structopt(long, parse(try_from_str = json::parse_json))]
+    pub solver: Option<KurobakoSolverRecipe>,
+
+    /// Problem recipe JSON (required only when creating a new study).
+    ///
+    /// The problem is used only to define the search space,
+    /// and the asked parameters are evaluated by the user.
+    #[structopt(long, parse(try_from_str = json::parse_json))]
+    pub problem: Option<KurobakoProblemRecipe>,
+
+    /// Random seed (used only when creating a new study).
+    #[structopt(long)]
+    pub seed: Option<u64>,
+}
+impl AskOpt {
+    /// Asks the solver for the next trial, and records it in the state file.
+    pub fn ask(&self) -> Result<AskedTrial> {
+        let state = if self.state.exists() {
+            track!(StudyState::load(&self.state))?
+        } else {
+            let solver =
+                track_assert_some!(self.solver.clone(), ErrorKind::InvalidInput; self.state);
+            let problem =
+                track_assert_some!(self.problem.clone(), ErrorKind::InvalidInput; self.state);
+            StudyState {
+                solver,
+                problem,
+                seed: self.seed.unwrap_or_else(rand::random),
+                events: Vec::new(),
+            }
+        };
+
+        let mut session = track!(Session::replay(state))?;
+        let trial = track!(session.solver.ask(&mut session.idg))?;
+        let next_step = track_assert_some!(trial.next_step, ErrorKind::Other; trial.id);
+        session.state.events.push(Event::Ask {
+            trial_id: trial.id,
+            params: trial.params.clone(),
+            next_step,
+        });
+        track!(session.state.save(&self.state))?;
+
+        Ok(AskedTrial {
+            trial_id: trial.id,
+            params: named_params(&session.problem_spec.params_domain, &trial.params),
+            next_step,
+        })
+    }
+}
+
+/// Options of the `kurobako tell` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct TellOpt {
+    /// Study state file (JSON) created by `kurobako ask`.
+    #[structopt(long)]
+    pub state: PathBuf,
+
+    /// ID of the trial to be told.
+    #[structopt(long)]
+    pub trial_id: u64,
+
+    /// Evaluated values (JSON array).
+    ///
+    /// If the parameters couldn't be evaluated, specify an empty array.
+    #[structopt(long, parse(try_from_str = json::parse_json))]
+    pub values: Values,
+
+    /// Current evaluation step. If omitted, the step requested by the last ask of the trial is used.
+    #[structopt(long)]
+    pub step: Option<u64>,
+}
+impl TellOpt {
+    /// Tells the evaluation result of a trial to the solver, and records it in the state file.
+    pub fn tell(&self) -> Result<()> {
+        let state = track!(StudyState::load(&self.state))?;
+        let trial_id = TrialId::new(self.trial_id);
+        let asked_step = state.events.iter().rev().find_map(|e| match e {
+            Event::Ask {
+                trial_id: id,
+                next_step,
+                ..
+            } if *id == trial_id => Some(*next_step),
+            _ => None,
+        });
+        let asked_step = track_assert_some!(
+            asked_step,
+            ErrorKind::InvalidInput,
+            "Unknown trial: {}",
+            self.trial_id
+        );
+
+        let mut session = track!(Session::replay(state))?;
+        let trial = EvaluatedTrial {
+            id: trial_id,
+            values: self.values.clone(),
+            current_step: self.step.unwrap_or(asked_step),
+        };
+        track!(session.solver.tell(trial.clone()))?;
+        session.state.events.push(Event::Tell {
+            trial_id: trial.id,
+            values: trial.values,
+            current_step: trial.current_step,
+        });
+        track!(session.state.save(&self.state))?;
+        Ok(())
+    }
+}
+
+/// Trial asked by `kurobako ask`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AskedTrial {
+    /// Trial ID (passed to `kurobako tell`).
+    pub trial_id: TrialId,
+
+    /// Parameters (the inactive ones are `null` and the categorical ones are choice names).
+    pub params: BTreeMap<String, serde_json::Value>,
+
+    /// Step until which the parameters should be evaluated.
+    pub next_step: u64,
+}
+
+/// State of a study driven by `kurobako ask` and `kurobako tell`.
+///
+/// The state of the solver isn't saved.
+/// Instead, the solver is re-created with the same seed and the recorded events are replayed on it,
+/// so the solver must be deterministic given a seed.
+#[derive(Debug, Serialize, Deserialize)]
+struct StudyState {
+    solver: KurobakoSolverRecipe,
+    problem: KurobakoProblemRecipe,
+    seed: u64,
+    events: Vec<Event>,
+}
+impl StudyState {
+    fn load(path: &Path) -> Result<Self> {
+        let file = track!(File::open(path).map_err(Error::from); path)?;
+        track!(serde_json::from_reader(BufReader::new(file)).map_err(Error::from); path)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let file = track!(File::create(path).map_err(Error::from); path)?;
+        let mut writer = BufWriter::new(file);
+        track!(serde_json::to_writer_pretty(&mut writer, self).map_err(Error::from))?;
+        track!(writer.flush().map_err(Error::from))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event {
+    Ask {
+        trial_id: TrialId,
+        params: Params,
+        next_step: u64,
+    },
+    Tell {
+        trial_id: TrialId,
+        values: Values,
+        current_step: u64,
+    },
+}
+
+#[derive(Debug)]
+struct Session {
+    state: StudyState,
+    problem_spec: ProblemSpec,
+    solver: BoxSolver,
+    idg: IdGen,
+}
+impl Session {
+    fn replay(state: StudyState) -> Result<Self> {
+        let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
+        let problem_factory = track!(state.problem.create_factory(&registry))?;
+        let problem_spec = track!(problem_factory.specification())?;
+
+        let solver_factory = track!(state.solver.create_factory(&registry))?;
+        let solver_spec = track!(solver_factory.specification())?;
+        let incapables = solver_spec
+            .capabilities
+            .incapables(&problem_spec.requirements())
+            .collect::<Vec<_>>();
+        track_assert!(incapables.is_empty(), ErrorKind::Incapable; incapables);
+
+        let rng = ArcRng::new(state.seed);
+        let mut solver = track!(solver_factory.create_solver(rng, &problem_spec))?;
+        let mut idg = IdGen::new();
+        for event in &state.events {
+            match event {
+                Event::Ask {
+                    trial_id, params, ..
+                } => {
+                    let trial = track!(solver.ask(&mut idg))?;
+                    track_assert!(
+                        trial.id == *trial_id && trial.params == *params,
+                        ErrorKind::Other,
+                        "The solver isn't deterministic: the replayed trial differs from the recorded one \
+                         (trial_id={})",
+                        trial_id.get()
+                    );
+                }
+                Event::Tell {
+                    trial_id,
+                    values,
+                    current_step,
+                } => {
+                    let trial = EvaluatedTrial {
+                        id: *trial_id,
+                        values: values.clone(),
+                        current_step: *current_step,
+                    };
+                    track!(solver.tell(trial))?;
+                }
+            }
+        }
+
+        Ok(Self {
+            state,
+            problem_spec,
+            solver,
+            idg,
+        })
+    }
+}
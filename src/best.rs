@@ -0,0 +1,135 @@
+//! `kurobako best` command.
+use crate::record::StudyRecord;
+use kurobako_core::domain::{Domain, Range};
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+/// Options of the `kurobako best` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct BestOpt {
+    /// Number of the best trials extracted from each study.
+    ///
+    /// For multi-objective problems, up to this number of the Pareto optimal trials are extracted.
+    #[structopt(long, default_value = "1")]
+    pub top_k: usize,
+}
+impl BestOpt {
+    /// Extracts the best trials of each of the given studies.
+    ///
+    /// Only the trials evaluated at the last step of the problem are considered.
+    pub fn best_trials(&self, studies: &[StudyRecord]) -> Result<Vec<BestTrials>> {
+        studies
+            .iter()
+            .map(|study| {
+                Ok(BestTrials {
+                    study: track!(study.id())?,
+                    solver: study.solver.spec.name.clone(),
+                    problem: study.problem.spec.name.clone(),
+                    seed: study.seed,
+                    regret: study.simple_regret(),
+                    log_regret: study.log_regret(),
+                    trials: self.extract(study),
+                })
+            })
+            .collect()
+    }
+
+    fn extract(&self, study: &StudyRecord) -> Vec<BestTrial> {
+        let last_step = study.problem.spec.steps.last();
+        let mut candidates = study
+            .trials
+            .iter()
+            .filter_map(|t| {
+                t.values(last_step)
+                    .filter(|vs| !vs.is_empty())
+                    .map(|vs| (t, vs))
+            })
+            .collect::<Vec<_>>();
+
+        if study.problem.spec.values_domain.len() > 1 {
+            let all = candidates.clone();
+            candidates.retain(|(_, a)| !all.iter().any(|(_, b)| dominates(b, a)));
+        }
+        candidates.sort_by_key(|(_, vs)| vs.iter().copied().map(OrderedFloat).collect::<Vec<_>>());
+
+        candidates
+            .into_iter()
+            .take(self.top_k)
+            .map(|(t, vs)| BestTrial {
+                params: named_params(&study.problem.spec.params_domain, &t.params),
+                values: vs.to_owned(),
+                step: t.steps(),
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| x <= y) && a.iter().zip(b.iter()).any(|(x, y)| x < y)
+}
+
+pub(crate) fn named_params(domain: &Domain, params: &[f64]) -> BTreeMap<String, serde_json::Value> {
+    domain
+        .variables()
+        .iter()
+        .zip(params.iter())
+        .map(|(var, &v)| {
+            let v = match var.range() {
+                _ if v.is_nan() => serde_json::Value::Null,
+                Range::Categorical { choices } if (v as usize) < choices.len() => {
+                    serde_json::Value::String(choices[v as usize].clone())
+                }
+                _ => serde_json::json!(v),
+            };
+            (var.name().to_owned(), v)
+        })
+        .collect()
+}
+
+/// Best trials of a study.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BestTrials {
+    /// Study ID.
+    pub study: String,
+
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Random seed of the study.
+    pub seed: u64,
+
+    /// Simple regret (i.e., the difference between the best value and the known optimum value of the problem).
+    ///
+    /// This is available only if the problem has the `optimum` attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regret: Option<f64>,
+
+    /// Base-10 logarithm of the simple regret.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_regret: Option<f64>,
+
+    /// Best trials (the best one comes first).
+    pub trials: Vec<BestTrial>,
+}
+
+/// Best trial.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BestTrial {
+    /// Parameters (the inactive ones are `null` and the categorical ones are choice names).
+    ///
+    /// This can be passed to the `--params` option of `kurobako evaluate` as is.
+    pub params: BTreeMap<String, serde_json::Value>,
+
+    /// Evaluated values.
+    pub values: Vec<f64>,
+
+    /// Number of the steps consumed by the trial.
+    pub step: u64,
+}
@@ -0,0 +1,72 @@
+//! `kurobako check` command.
+use crate::problem::KurobakoProblemRecipe;
+use crate::solver::KurobakoSolverRecipe;
+use crate::study::StudyRecipe;
+use kurobako_core::problem::{ProblemFactory as _, ProblemRecipe as _, ProblemSpec};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::solver::{Capability, SolverFactory as _, SolverRecipe as _, SolverSpec};
+use kurobako_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use structopt::StructOpt;
+
+/// Options of the `kurobako check` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct CheckOpt {}
+
+impl CheckOpt {
+    /// Instantiates the solvers and problems of the given study recipes without running the studies.
+    ///
+    /// The result contains an entry for each distinct pair of a solver and a problem.
+    pub fn check(&self, studies: &[StudyRecipe]) -> Result<Vec<Checked>> {
+        let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
+
+        let mut visited = BTreeSet::new();
+        let mut checked = Vec::new();
+        for (i, study) in studies.iter().enumerate() {
+            let key = track!(
+                serde_json::to_string(&(&study.solver, &study.problem)).map_err(Error::from)
+            )?;
+            if !visited.insert(key) {
+                continue;
+            }
+
+            let problem_factory = track!(study.problem.create_factory(&registry); i)?;
+            let problem = track!(problem_factory.specification(); i)?;
+
+            let solver_factory = track!(study.solver.create_factory(&registry); i)?;
+            let solver = track!(solver_factory.specification(); i)?;
+
+            let incapables = solver
+                .capabilities
+                .incapables(&problem.requirements())
+                .collect();
+            checked.push(Checked {
+                solver,
+                problem,
+                incapables,
+            });
+        }
+        Ok(checked)
+    }
+}
+
+/// Check result of a pair of a solver and a problem.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checked {
+    /// Solver specification.
+    pub solver: SolverSpec,
+
+    /// Problem specification.
+    pub problem: ProblemSpec,
+
+    /// Capabilities required by the problem but not supported by the solver.
+    pub incapables: Vec<Capability>,
+}
+impl Checked {
+    /// Returns `true` if the solver can handle the problem.
+    pub fn is_ok(&self) -> bool {
+        self.incapables.is_empty()
+    }
+}
@@ -0,0 +1,160 @@
+//! `kurobako compare` command.
+use crate::record::{self, StudyRecord};
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{Error, Result};
+use rustats::fundamental::{average, stddev};
+use rustats::hypothesis_testings::MannWhitneyU;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Options of the `kurobako compare` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct CompareOpt {
+    /// Baseline benchmark result file (JSON Lines).
+    #[structopt(long)]
+    pub baseline: PathBuf,
+
+    /// Significance level of the Mann-Whitney U test.
+    #[structopt(long, default_value = "0.05")]
+    pub alpha: f64,
+}
+impl CompareOpt {
+    /// Compares the best values of the given studies with the ones of the baseline studies.
+    ///
+    /// The studies are paired by the recipes of the solver and the problem
+    /// (so the results of different versions of a solver can be compared).
+    /// Pairs that exist in only one of the results are ignored,
+    /// and so are pairs that have less than two best values on either side (with a message).
+    pub fn compare(&self, studies: &[StudyRecord]) -> Result<Vec<Compared>> {
+        let file = track!(File::open(&self.baseline).map_err(Error::from); self.baseline)?;
+        let baselines = track!(record::load_studies(BufReader::new(file)); self.baseline)?;
+
+        let baselines = track!(group(&baselines))?;
+        let currents = track!(group(studies))?;
+
+        let mut compared = Vec::new();
+        for (key, (solver, problem, current)) in currents {
+            let baseline = if let Some((_, _, baseline)) = baselines.get(&key) {
+                baseline
+            } else {
+                continue;
+            };
+            if baseline.len() < 2 || current.len() < 2 {
+                eprintln!(
+                    "Skipped {:?} on {:?}: too few best values to compare (baseline: {}, current: {})",
+                    solver,
+                    problem,
+                    baseline.len(),
+                    current.len()
+                );
+                continue;
+            }
+
+            let order = MannWhitneyU::new(
+                current.iter().copied().map(OrderedFloat),
+                baseline.iter().copied().map(OrderedFloat),
+            )
+            .order(self.alpha);
+            let verdict = match order {
+                Ordering::Less => Verdict::Improved,
+                Ordering::Equal => Verdict::Unchanged,
+                Ordering::Greater => Verdict::Regressed,
+            };
+            compared.push(Compared {
+                solver: solver.to_owned(),
+                problem: key.1,
+                baseline: Summary::new(baseline),
+                current: Summary::new(&current),
+                verdict,
+            });
+        }
+        Ok(compared)
+    }
+}
+
+// Best values of studies keyed by the recipe IDs of the solvers and the problems
+// (with the names of the solvers and the problems).
+type BestValues<'a> = BTreeMap<(String, String), (&'a str, &'a str, Vec<f64>)>;
+
+// Groups the best values of the studies by the recipe IDs of the solvers and the problems.
+fn group(studies: &[StudyRecord]) -> Result<BestValues<'_>> {
+    let mut groups = BTreeMap::new();
+    for study in studies {
+        let key = (
+            track!(study.solver.recipe_id())?,
+            track!(study.problem.recipe_id())?,
+        );
+        let (_, _, best_values) = groups.entry(key).or_insert_with(|| {
+            (
+                study.solver.spec.name.as_str(),
+                study.problem.spec.name.as_str(),
+                Vec::new(),
+            )
+        });
+        if let Some(v) = study.best_value() {
+            best_values.push(v);
+        }
+    }
+    Ok(groups)
+}
+
+/// Comparison result of a pair of a solver and a problem.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Compared {
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem recipe ID.
+    pub problem: String,
+
+    /// Summary of the best values of the baseline studies.
+    pub baseline: Summary,
+
+    /// Summary of the best values of the current studies.
+    pub current: Summary,
+
+    /// Verdict of the comparison.
+    pub verdict: Verdict,
+}
+
+/// Summary of best values.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Summary {
+    /// Number of studies.
+    pub count: usize,
+
+    /// Average.
+    pub avg: f64,
+
+    /// Standard deviation.
+    pub sd: f64,
+}
+impl Summary {
+    fn new(values: &[f64]) -> Self {
+        Self {
+            count: values.len(),
+            avg: average(values.iter().copied()),
+            sd: stddev(values.iter().copied()),
+        }
+    }
+}
+
+/// Verdict of a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The current result is significantly better than the baseline.
+    Improved,
+
+    /// There is no significant difference.
+    Unchanged,
+
+    /// The current result is significantly worse than the baseline.
+    Regressed,
+}
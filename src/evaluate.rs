@@ -1,6 +1,7 @@
 //! `kurobako evaluate` command.
 use crate::problem::KurobakoProblemRecipe;
 use crate::solver::KurobakoSolverRecipe;
+use kurobako_core::domain::{Domain, Range};
 use kurobako_core::json;
 use kurobako_core::problem::{
     Evaluator as _, Problem as _, ProblemFactory as _, ProblemRecipe as _,
@@ -10,6 +11,7 @@ use kurobako_core::rng::ArcRng;
 use kurobako_core::trial::{Params, Values};
 use kurobako_core::{ErrorKind, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use structopt::StructOpt;
 
 /// Options of the `kurobako evaluate` command.
@@ -21,8 +23,12 @@ pub struct EvaluateOpt {
     pub problem: KurobakoProblemRecipe,
 
     /// Parameters to be evaluated.
+    ///
+    /// This is either an array of parameter values (in the order of the problem parameters)
+    /// or an object that maps parameter names to values (a categorical value can be given as its choice name,
+    /// and an inactive conditional parameter as `null`).
     #[structopt(long, parse(try_from_str = json::parse_json))]
-    pub params: Params,
+    pub params: ParamsInput,
 
     /// Evaluation step. If omitted, the maximum step of the problem is used.
     #[structopt(long)]
@@ -41,15 +47,26 @@ impl EvaluateOpt {
         let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
         let problem_factory = track!(self.problem.create_factory(&registry))?;
         let problem_spec = track!(problem_factory.specification())?;
-        track_assert_eq!(
-            self.params.len(),
-            problem_spec.params_domain.variables().len(),
-            ErrorKind::InvalidInput
-        );
+        let params = track!(self.params.resolve(&problem_spec.params_domain))?;
+        for (var, &v) in problem_spec
+            .params_domain
+            .variables()
+            .iter()
+            .zip(params.iter())
+        {
+            track_assert!(
+                v.is_nan() || var.range().contains(v),
+                ErrorKind::InvalidInput,
+                "Out of range: name={:?}, value={}, range={:?}",
+                var.name(),
+                v,
+                var.range()
+            );
+        }
 
         let problem = track!(problem_factory.create_problem(rng))?;
 
-        let mut evaluator = track!(problem.create_evaluator(self.params.clone()))?;
+        let mut evaluator = track!(problem.create_evaluator(params))?;
         let step = self.step.unwrap_or_else(|| problem_spec.steps.last());
         let (current_step, values) = track!(evaluator.evaluate(step))?;
 
@@ -73,3 +90,59 @@ pub struct Evaluated {
     /// Random seed.
     pub seed: u64,
 }
+
+/// Parameters given to the `kurobako evaluate` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamsInput {
+    /// Parameter values in the order of the problem parameters.
+    Values(Params),
+
+    /// Map from parameter names to values.
+    Named(BTreeMap<String, serde_json::Value>),
+}
+impl ParamsInput {
+    fn resolve(&self, domain: &Domain) -> Result<Params> {
+        match self {
+            Self::Values(params) => {
+                track_assert_eq!(params.len(), domain.len(), ErrorKind::InvalidInput);
+                Ok(params.clone())
+            }
+            Self::Named(params) => {
+                track_assert_eq!(params.len(), domain.len(), ErrorKind::InvalidInput);
+
+                let mut values = Vec::with_capacity(domain.len());
+                for var in domain.variables() {
+                    let v = track_assert_some!(
+                        params.get(var.name()),
+                        ErrorKind::InvalidInput,
+                        "Missing parameter: {:?}",
+                        var.name()
+                    );
+                    let v = match (v, var.range()) {
+                        (serde_json::Value::String(choice), Range::Categorical { choices }) => {
+                            let i = track_assert_some!(
+                                choices.iter().position(|c| c == choice),
+                                ErrorKind::InvalidInput,
+                                "Unknown choice: name={:?}, choice={:?}",
+                                var.name(),
+                                choice
+                            );
+                            i as f64
+                        }
+                        (serde_json::Value::Null, _) => std::f64::NAN, // Inactive parameter.
+                        (v, _) => track_assert_some!(
+                            v.as_f64(),
+                            ErrorKind::InvalidInput,
+                            "Not a number: name={:?}, value={}",
+                            var.name(),
+                            v
+                        ),
+                    };
+                    values.push(v);
+                }
+                Ok(Params::new(values))
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! `kurobako export` command.
+use crate::record::StudyRecord;
+use kurobako_core::domain::Domain;
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Options of the `kurobako export` command.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum ExportOpt {
+    /// Exports trial-level data as CSV (one row per trial).
+    Csv(ExportCsvOpt),
+
+    /// Exports the best-value trajectories of studies in long format (one row per improvement).
+    Trajectory(ExportTrajectoryOpt),
+}
+impl ExportOpt {
+    /// Exports the given benchmark results.
+    pub fn export(&self, studies: &[StudyRecord], writer: impl Write) -> Result<()> {
+        match self {
+            Self::Csv(opt) => track!(opt.export(studies, writer)),
+            Self::Trajectory(opt) => track!(opt.export(studies, writer)),
+        }
+    }
+}
+
+/// Options of the `kurobako export csv` command.
+///
+/// The columns are `study_id`, `solver`, `problem`, `seed`, `trial`, `thread_id`,
+/// `start_step`, `end_step`, the parameters (`param:${NAME}`), the values (`value:${NAME}`),
+/// `ask_elapsed`, `tell_elapsed` and `evaluate_elapsed`.
+///
+/// If the studies have different parameters (or values), the union of them is used as the columns
+/// and the cells of missing (or inactive) parameters are left empty.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ExportCsvOpt {
+    /// Doesn't output the header row.
+    #[structopt(long)]
+    pub no_header: bool,
+}
+impl ExportCsvOpt {
+    fn export(&self, studies: &[StudyRecord], mut writer: impl Write) -> Result<()> {
+        let mut param_names = Vec::new();
+        let mut value_names = Vec::new();
+        for study in studies {
+            add_names(&mut param_names, &study.problem.spec.params_domain);
+            add_names(&mut value_names, &study.problem.spec.values_domain);
+        }
+
+        if !self.no_header {
+            let mut header = [
+                "study_id",
+                "solver",
+                "problem",
+                "seed",
+                "trial",
+                "thread_id",
+                "start_step",
+                "end_step",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+            header.extend(param_names.iter().map(|n| format!("param:{}", n)));
+            header.extend(value_names.iter().map(|n| format!("value:{}", n)));
+            header.extend(
+                ["ask_elapsed", "tell_elapsed", "evaluate_elapsed"]
+                    .iter()
+                    .map(|s| s.to_string()),
+            );
+            track!(write_row(&mut writer, &header))?;
+        }
+
+        for study in studies {
+            let study_id = track!(study.id())?;
+            let params_domain = &study.problem.spec.params_domain;
+            let values_domain = &study.problem.spec.values_domain;
+            for (i, trial) in study.trials.iter().enumerate() {
+                let mut row = vec![
+                    study_id.clone(),
+                    study.solver.spec.name.clone(),
+                    study.problem.spec.name.clone(),
+                    study.seed.to_string(),
+                    i.to_string(),
+                    trial.thread_id.to_string(),
+                    trial
+                        .start_step()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                    trial.end_step().map(|s| s.to_string()).unwrap_or_default(),
+                ];
+
+                let values = trial
+                    .evaluations
+                    .last()
+                    .map(|e| e.values.to_vec())
+                    .unwrap_or_default();
+                row.extend(param_names.iter().map(|name| {
+                    let i = params_domain
+                        .variables()
+                        .iter()
+                        .position(|v| v.name() == name);
+                    cell(i.and_then(|i| trial.params.get().get(i).copied()))
+                }));
+                row.extend(value_names.iter().map(|name| {
+                    let i = values_domain
+                        .variables()
+                        .iter()
+                        .position(|v| v.name() == name);
+                    cell(i.and_then(|i| values.get(i).copied()))
+                }));
+
+                let (mut ask, mut tell, mut evaluate) = (0.0, 0.0, 0.0);
+                for e in &trial.evaluations {
+                    ask += e.ask_elapsed.get();
+                    tell += e.tell_elapsed.get();
+                    evaluate += e.evaluate_elapsed.get();
+                }
+                row.extend(vec![
+                    ask.to_string(),
+                    tell.to_string(),
+                    evaluate.to_string(),
+                ]);
+
+                track!(write_row(&mut writer, &row))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options of the `kurobako export trajectory` command.
+///
+/// Each row (or JSON line) consists of `study_id`, `solver`, `problem`, `seed`,
+/// `step` (the number of the steps consumed by the study) and `best` (the best value found so far).
+/// A row is output only when the best value is improved.
+///
+/// Multi-objective studies are skipped.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ExportTrajectoryOpt {
+    /// Output format.
+    #[structopt(
+        long,
+        default_value = "csv",
+        possible_values = TrajectoryFormat::POSSIBLE_VALUES
+    )]
+    pub format: TrajectoryFormat,
+
+    /// Doesn't output the header row (CSV only).
+    #[structopt(long)]
+    pub no_header: bool,
+}
+impl ExportTrajectoryOpt {
+    fn export(&self, studies: &[StudyRecord], mut writer: impl Write) -> Result<()> {
+        if self.format == TrajectoryFormat::Csv && !self.no_header {
+            let header = ["study_id", "solver", "problem", "seed", "step", "best"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>();
+            track!(write_row(&mut writer, &header))?;
+        }
+
+        for study in studies {
+            if study.problem.spec.values_domain.len() != 1 {
+                continue;
+            }
+
+            let study_id = track!(study.id())?;
+            for (step, best) in study.best_values() {
+                let point = TrajectoryPoint {
+                    study_id: &study_id,
+                    solver: &study.solver.spec.name,
+                    problem: &study.problem.spec.name,
+                    seed: study.seed,
+                    step,
+                    best,
+                };
+                match self.format {
+                    TrajectoryFormat::Csv => {
+                        let row = vec![
+                            point.study_id.to_owned(),
+                            point.solver.to_owned(),
+                            point.problem.to_owned(),
+                            point.seed.to_string(),
+                            point.step.to_string(),
+                            cell(Some(point.best)),
+                        ];
+                        track!(write_row(&mut writer, &row))?;
+                    }
+                    TrajectoryFormat::Json => {
+                        track!(serde_json::to_writer(&mut writer, &point).map_err(Error::from))?;
+                        track_writeln!(writer)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Output format of the `kurobako export trajectory` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    /// CSV.
+    Csv,
+
+    /// JSON Lines.
+    Json,
+}
+impl TrajectoryFormat {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["csv", "json"];
+}
+impl FromStr for TrajectoryFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(TrajectoryFormat::Csv),
+            "json" => Ok(TrajectoryFormat::Json),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format name: {:?}", s),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TrajectoryPoint<'a> {
+    study_id: &'a str,
+    solver: &'a str,
+    problem: &'a str,
+    seed: u64,
+    step: u64,
+    best: f64,
+}
+
+pub(crate) fn add_names(names: &mut Vec<String>, domain: &Domain) {
+    for var in domain.variables() {
+        if !names.iter().any(|n| n == var.name()) {
+            names.push(var.name().to_owned());
+        }
+    }
+}
+
+pub(crate) fn cell(v: Option<f64>) -> String {
+    v.filter(|v| !v.is_nan())
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_row<W: Write>(writer: &mut W, row: &[String]) -> Result<()> {
+    let row = row
+        .iter()
+        .map(|s| {
+            if s.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    track_writeln!(writer, "{}", row.join(","))
+}
@@ -0,0 +1,88 @@
+//! `kurobako filter` command.
+use crate::record::StudyRecord;
+use kurobako_core::{Error, ErrorKind, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use structopt::StructOpt;
+use trackable::error::ErrorKindExt;
+
+/// Options of the `kurobako filter` command.
+///
+/// A study is kept only if it satisfies all the specified conditions.
+/// If the same option is specified more than once, a study is kept if it satisfies any of them.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct FilterOpt {
+    /// Regular expression that the solver name of a study must match.
+    #[structopt(long, number_of_values = 1, parse(try_from_str = parse_regex))]
+    pub solver: Vec<Regex>,
+
+    /// Regular expression that the problem name of a study must match.
+    #[structopt(long, number_of_values = 1, parse(try_from_str = parse_regex))]
+    pub problem: Vec<Regex>,
+
+    /// Random seed of a study.
+    #[structopt(long, number_of_values = 1)]
+    pub seed: Vec<u64>,
+
+    /// Condition on the attributes of the solver or the problem of a study (`KEY=REGEX`).
+    #[structopt(long, number_of_values = 1)]
+    pub attr: Vec<AttrFilter>,
+
+    /// Inverts the condition (i.e., keeps the studies that don't satisfy the conditions).
+    #[structopt(long)]
+    pub invert: bool,
+}
+impl FilterOpt {
+    /// Returns the studies that satisfy the conditions.
+    pub fn filter<'a>(&self, studies: &'a [StudyRecord]) -> Vec<&'a StudyRecord> {
+        studies
+            .iter()
+            .filter(|s| self.is_match(s) != self.invert)
+            .collect()
+    }
+
+    fn is_match(&self, study: &StudyRecord) -> bool {
+        let name_matches = |patterns: &[Regex], name: &str| {
+            patterns.is_empty() || patterns.iter().any(|p| p.is_match(name))
+        };
+
+        name_matches(&self.solver, &study.solver.spec.name)
+            && name_matches(&self.problem, &study.problem.spec.name)
+            && (self.seed.is_empty() || self.seed.contains(&study.seed))
+            && (self.attr.is_empty()
+                || self.attr.iter().any(|a| {
+                    a.is_match(&study.solver.spec.attrs) || a.is_match(&study.problem.spec.attrs)
+                }))
+    }
+}
+
+/// Condition on an attribute (`KEY=REGEX`).
+#[derive(Debug)]
+pub struct AttrFilter {
+    key: String,
+    value: Regex,
+}
+impl AttrFilter {
+    fn is_match(&self, attrs: &BTreeMap<String, String>) -> bool {
+        attrs
+            .get(&self.key)
+            .map_or(false, |v| self.value.is_match(v))
+    }
+}
+impl FromStr for AttrFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut tokens = s.splitn(2, '=');
+        let key = tokens.next().unwrap_or_else(|| unreachable!()).to_owned();
+        let value = track_assert_some!(tokens.next(), ErrorKind::InvalidInput; s);
+        let value = track!(parse_regex(value))?;
+        Ok(Self { key, value })
+    }
+}
+
+fn parse_regex(s: &str) -> Result<Regex> {
+    track!(Regex::new(s).map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))); s)
+}
@@ -19,18 +19,33 @@ macro_rules! track_writeln {
     }
 }
 
+pub mod ask_tell;
+pub mod best;
+pub mod check;
+pub mod compare;
 pub mod dataset;
 pub mod evaluate;
+pub mod export;
+pub mod filter;
+pub mod list;
+pub mod merge;
+pub mod migrate;
+pub mod pareto;
 pub mod plot;
 pub mod problem;
 pub mod problem_suites;
+pub mod replay;
 pub mod report;
 pub mod runner;
 pub mod solver;
 pub mod spec;
+pub mod stats;
 pub mod study;
 pub mod time;
 pub mod variable;
+pub mod worker;
+
+pub use self::record::load_studies;
 
 mod markdown;
 mod record;
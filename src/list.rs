@@ -0,0 +1,58 @@
+//! `kurobako list` command.
+use crate::record::StudyRecord;
+use kurobako_core::Result;
+use std::io::Write;
+use structopt::StructOpt;
+
+const HEADER: [&str; 7] = [
+    "STUDY", "SOLVER", "PROBLEM", "SEED", "BUDGET", "TRIALS", "BEST",
+];
+
+/// Options of the `kurobako list` command.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ListOpt {
+    /// Shows the full study IDs instead of the first 8 characters.
+    #[structopt(long)]
+    pub full_id: bool,
+}
+impl ListOpt {
+    /// Writes a table that summarizes the given studies.
+    ///
+    /// The best value of a multi-objective study is shown as `-`.
+    pub fn write_table<W: Write>(&self, studies: &[StudyRecord], mut writer: W) -> Result<()> {
+        let mut rows = vec![HEADER.iter().map(|h| (*h).to_owned()).collect::<Vec<_>>()];
+        for study in studies {
+            let mut id = track!(study.id())?;
+            if !self.full_id {
+                id.truncate(8);
+            }
+            let best = study
+                .best_value()
+                .map_or_else(|| "-".to_owned(), |v| v.to_string());
+            rows.push(vec![
+                id,
+                study.solver.spec.name.clone(),
+                study.problem.spec.name.clone(),
+                study.seed.to_string(),
+                study.budget.to_string(),
+                study.trials.len().to_string(),
+                best,
+            ]);
+        }
+
+        let widths = (0..HEADER.len())
+            .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+            .collect::<Vec<_>>();
+        for row in rows {
+            let line = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            track_writeln!(writer, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
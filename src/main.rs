@@ -1,19 +1,33 @@
 #[macro_use]
 extern crate trackable;
 
+use kurobako::ask_tell::{AskOpt, TellOpt};
+use kurobako::best::BestOpt;
+use kurobako::check::CheckOpt;
+use kurobako::compare::{CompareOpt, Verdict};
 use kurobako::dataset::DatasetOpt;
 use kurobako::evaluate::EvaluateOpt;
+use kurobako::export::ExportOpt;
+use kurobako::filter::FilterOpt;
+use kurobako::list::ListOpt;
+use kurobako::load_studies;
+use kurobako::merge::MergeOpt;
+use kurobako::migrate::MigrateOpt;
+use kurobako::pareto::ParetoOpt;
 use kurobako::plot::PlotOpt;
 use kurobako::problem::KurobakoProblemRecipe;
 use kurobako::problem_suites::ProblemSuite;
+use kurobako::replay::ReplayOpt;
 use kurobako::report::{ReportOpt, Reporter};
 use kurobako::runner::{Runner, RunnerOpt};
 use kurobako::solver::KurobakoSolverRecipe;
-use kurobako::spec::SpecOpt;
+use kurobako::spec::{self, SpecOpt};
+use kurobako::stats::StatsOpt;
 use kurobako::study::StudiesRecipe;
 use kurobako::variable::Var;
+use kurobako::worker::WorkerOpt;
 use kurobako_core::json;
-use kurobako_core::Error;
+use kurobako_core::{Error, ErrorKind};
 use std::io;
 use structopt::StructOpt;
 
@@ -48,22 +62,83 @@ enum Opt {
     Studies(StudiesRecipe),
 
     /// Takes study recipes (JSONs), then runs the studies and outputs the results (JSONs).
+    ///
+    /// Each result is written as a JSON line as soon as the corresponding study finishes.
+    ///
+    /// A recipe can be a template that has the `"vars"` field and `{{NAME}}` placeholders.
+    /// If a variable is an array, the recipe is expanded for each element of the array.
     Run(RunnerOpt),
 
-    /// Generates a markdown report from benchmark results (JSONs).
+    /// Generates a report (markdown, HTML or LaTeX) from benchmark results (JSONs).
     Report(ReportOpt),
 
     /// Generates visualization images from benchmark results (JSONs).
     Plot(PlotOpt),
 
+    /// Exports benchmark results (JSONs) to other formats.
+    Export(ExportOpt),
+
+    /// Outputs the benchmark results (JSONs) that satisfy the given conditions.
+    Filter(FilterOpt),
+
+    /// Merges benchmark result files into one, removing duplicate studies.
+    Merge(MergeOpt),
+
+    /// Upgrades benchmark results (JSONs) produced by older versions of kurobako to the current schema.
+    Migrate(MigrateOpt),
+
+    /// Outputs the best trials of each study in benchmark results (JSONs).
+    Best(BestOpt),
+
+    /// Lists the studies in benchmark results (JSONs) as a table.
+    List(ListOpt),
+
+    /// Computes the statistics of benchmark results (JSONs), then outputs them (JSON or markdown).
+    ///
+    /// The summaries of the best values of each pair of a solver and a problem are always output,
+    /// and the other sections (e.g., the Friedman test) are added by the corresponding flags.
+    Stats(StatsOpt),
+
+    /// Extracts the Pareto optimal trials of each study in benchmark results (JSONs),
+    /// then outputs them (JSON or CSV).
+    Pareto(ParetoOpt),
+
+    /// Compares benchmark results (JSONs) with baseline results.
+    ///
+    /// The comparison results are output as JSONs,
+    /// and the command fails if any of the results is regressed.
+    Compare(CompareOpt),
+
     /// Dataset management.
     Dataset(DatasetOpt),
 
     /// Evaluates parameters of a problem.
     Evaluate(EvaluateOpt),
 
+    /// Re-evaluates the parameters of benchmark results (JSONs) on another problem,
+    /// then outputs the results (JSONs).
+    Replay(ReplayOpt),
+
+    /// Asks the solver of a study (driven from outside) for the next trial, and outputs it (JSON).
+    Ask(AskOpt),
+
+    /// Tells the evaluation result of a trial asked by `kurobako ask` to the solver.
+    Tell(TellOpt),
+
     /// Show problem or solver specification.
     Spec(SpecOpt),
+
+    /// Takes study recipes (JSONs), then validates them without running the studies.
+    ///
+    /// The specifications of the solvers and problems are output as JSONs.
+    Check(CheckOpt),
+
+    /// Runs a worker that executes the studies sent from `kurobako run --workers`.
+    ///
+    /// WARNING: a worker runs arbitrary programs given by the clients (e.g., `command` solvers),
+    /// so only the clients presenting the shared token (`--token`) are accepted.
+    /// Listen on a non-loopback address only within a trusted network.
+    Worker(WorkerOpt),
 }
 
 fn main() -> trackable::result::TopLevelResult {
@@ -93,16 +168,81 @@ fn main() -> trackable::result::TopLevelResult {
             track!(Runner::new(opt).run())?;
         }
         Opt::Report(opt) => {
-            let studies = track!(json::load(io::stdin().lock()))?;
+            let studies = track!(load_studies(io::stdin().lock()))?;
             let reporter = Reporter::new(studies, opt);
             let stdout = io::stdout();
             let stdout = stdout.lock();
             track!(reporter.report_all(stdout))?;
         }
         Opt::Plot(opt) => {
-            let studies = track!(json::load(io::stdin().lock()))?;
+            let studies = track!(load_studies(io::stdin().lock()))?;
             track!(opt.plot(&studies))?;
         }
+        Opt::Export(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            let stdout = io::stdout();
+            let stdout = stdout.lock();
+            track!(opt.export(&studies, stdout))?;
+        }
+        Opt::Filter(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            for study in opt.filter(&studies) {
+                print_json!(study);
+            }
+        }
+        Opt::Stats(opt) => {
+            let stdout = io::stdout();
+            if opt.stream {
+                track!(opt.stream(io::stdin().lock(), stdout.lock()))?;
+            } else {
+                let studies = track!(load_studies(io::stdin().lock()))?;
+                track!(opt.write(&studies, stdout.lock()))?;
+            }
+        }
+        Opt::Pareto(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            let stdout = io::stdout();
+            track!(opt.write(&studies, stdout.lock()))?;
+        }
+        Opt::Compare(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            let compared = track!(opt.compare(&studies))?;
+            for c in &compared {
+                print_json!(c);
+            }
+
+            let regressions = compared
+                .iter()
+                .filter(|c| c.verdict == Verdict::Regressed)
+                .count();
+            track_assert_eq!(
+                regressions,
+                0,
+                ErrorKind::Other,
+                "Some results are regressed"
+            );
+        }
+        Opt::Merge(opt) => {
+            for study in track!(opt.merge())? {
+                print_json!(study);
+            }
+        }
+        Opt::Migrate(opt) => {
+            for entry in track!(opt.migrate(io::stdin().lock()))? {
+                print_json!(entry);
+            }
+        }
+        Opt::Best(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            for best in track!(opt.best_trials(&studies))? {
+                print_json!(best);
+            }
+        }
+        Opt::List(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            let stdout = io::stdout();
+            track!(opt.write_table(&studies, stdout.lock()))?;
+        }
         Opt::Dataset(opt) => {
             track!(opt.run())?;
         }
@@ -110,9 +250,49 @@ fn main() -> trackable::result::TopLevelResult {
             let evaluated = track!(opt.evaluate())?;
             print_json!(evaluated);
         }
+        Opt::Replay(opt) => {
+            let studies = track!(load_studies(io::stdin().lock()))?;
+            for study in &studies {
+                let replayed = track!(opt.replay(study))?;
+                print_json!(replayed);
+            }
+        }
+        Opt::Ask(opt) => {
+            let asked = track!(opt.ask())?;
+            print_json!(asked);
+        }
+        Opt::Tell(opt) => {
+            track!(opt.tell())?;
+        }
         Opt::Spec(opt) => {
             let spec = track!(opt.get_spec())?;
-            print_json!(spec);
+            match opt.format() {
+                spec::Format::Json => {
+                    print_json!(spec);
+                }
+                spec::Format::Table => {
+                    let stdout = io::stdout();
+                    track!(spec.write_table(stdout.lock()))?;
+                }
+            }
+        }
+        Opt::Check(opt) => {
+            let studies = track!(json::load_recipes(io::stdin().lock()))?;
+            let checked = track!(opt.check(&studies))?;
+            for c in &checked {
+                print_json!(c);
+            }
+
+            let incapables = checked.iter().filter(|c| !c.is_ok()).count();
+            track_assert_eq!(
+                incapables,
+                0,
+                ErrorKind::Incapable,
+                "Some solvers can't handle the problems"
+            );
+        }
+        Opt::Worker(opt) => {
+            track!(opt.run())?;
         }
     }
 
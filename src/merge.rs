@@ -0,0 +1,75 @@
+//! `kurobako merge` command.
+use crate::record::{self, StudyRecord};
+use kurobako_core::{Error, ErrorKind, Result};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Options of the `kurobako merge` command.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct MergeOpt {
+    /// Benchmark result files (JSON Lines) to be merged.
+    #[structopt(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Fails if the studies were run in incomparable environments
+    /// (i.e., different kurobako versions, hosts or CPU models).
+    ///
+    /// The studies that have no environment records (e.g., the ones run by old versions) are regarded
+    /// as being incomparable with the others.
+    #[structopt(long)]
+    pub require_same_env: bool,
+
+    /// Truncates all the studies to the given budget so that the studies run with different budgets can be
+    /// compared fairly (if the value is omitted, the minimum budget among the studies is used).
+    ///
+    /// It's an error if the given budget exceeds the budget of any study.
+    #[structopt(long)]
+    pub truncate_budget: Option<Option<u64>>,
+}
+impl MergeOpt {
+    /// Loads the result files and returns the merged studies.
+    ///
+    /// If there are two or more studies that have the same study ID
+    /// (i.e., solver, problem, budget, concurrency and scheduling) and seed,
+    /// only the first one is kept.
+    pub fn merge(&self) -> Result<Vec<StudyRecord>> {
+        let mut merged = Vec::<StudyRecord>::new();
+        for path in &self.files {
+            let file = track!(File::open(path).map_err(Error::from); path)?;
+            let studies = track!(record::load_studies(BufReader::new(file)); path)?;
+            for study in studies {
+                if self.require_same_env {
+                    if let Some(first) = merged.first() {
+                        track_assert!(
+                            first.has_comparable_env(&study),
+                            ErrorKind::InvalidInput,
+                            "Incomparable environments: {:?} and {:?}",
+                            first.env,
+                            study.env
+                        );
+                    }
+                }
+                merged.push(study);
+            }
+        }
+
+        if let Some(budget) = self.truncate_budget {
+            track!(record::truncate_studies(&mut merged, budget))?;
+        }
+
+        // The study IDs must be computed after the truncation because they depend on the budgets.
+        let mut seen = HashSet::new();
+        let mut deduplicated = Vec::new();
+        for study in merged {
+            let key = (track!(study.id())?, study.seed);
+            if seen.insert(key) {
+                deduplicated.push(study);
+            }
+        }
+        Ok(deduplicated)
+    }
+}
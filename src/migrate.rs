@@ -0,0 +1,33 @@
+//! `kurobako migrate` command.
+use crate::record::{self, StudyRecord};
+use kurobako_core::{json, Error, Result};
+use std::io::Read;
+use structopt::StructOpt;
+
+/// Options of the `kurobako migrate` command.
+///
+/// The records of the failed studies are output as they are.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct MigrateOpt {}
+impl MigrateOpt {
+    /// Reads benchmark results from `reader`, and returns the ones upgraded to the current schema.
+    pub fn migrate<R: Read>(&self, reader: R) -> Result<Vec<serde_json::Value>> {
+        let entries: Vec<serde_json::Value> = track!(json::load(reader))?;
+        entries
+            .into_iter()
+            .map(|mut entry| {
+                if entry.get("error").is_some() {
+                    return Ok(entry);
+                }
+
+                track!(record::migrate(&mut entry))?;
+
+                // Round-trips the record so that the omitted fields are filled with their default values.
+                let study: StudyRecord =
+                    track!(serde_json::from_value(entry).map_err(Error::from))?;
+                track!(serde_json::to_value(&study).map_err(Error::from))
+            })
+            .collect()
+    }
+}
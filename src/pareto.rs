@@ -0,0 +1,201 @@
+//! `kurobako pareto` command.
+use crate::best::{dominates, named_params};
+use crate::export::{add_names, cell, write_row};
+use crate::record::{StudyRecord, TrialRecord};
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Options of the `kurobako pareto` command.
+///
+/// The Pareto optimal (i.e., non-dominated) trials of each study are extracted
+/// from the trials evaluated at the last step of the problem.
+///
+/// In the CSV format, the columns are `study_id`, `solver`, `problem`, `seed`, `trial`,
+/// the parameters (`param:${NAME}`) and the values (`value:${NAME}`).
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ParetoOpt {
+    /// Output format.
+    #[structopt(
+        long,
+        default_value = "json",
+        possible_values = Format::POSSIBLE_VALUES
+    )]
+    pub format: Format,
+
+    /// Doesn't output the header row (CSV only).
+    #[structopt(long)]
+    pub no_header: bool,
+}
+impl ParetoOpt {
+    /// Extracts the Pareto optimal trials of each of the given studies.
+    pub fn pareto_trials(&self, studies: &[StudyRecord]) -> Result<Vec<ParetoTrials>> {
+        studies
+            .iter()
+            .map(|study| {
+                let trials = pareto_optimal_trials(study)
+                    .into_iter()
+                    .map(|(i, t, vs)| ParetoTrial {
+                        trial: i,
+                        params: named_params(&study.problem.spec.params_domain, &t.params),
+                        values: vs.to_owned(),
+                        step: t.steps(),
+                    })
+                    .collect();
+                Ok(ParetoTrials {
+                    study: track!(study.id())?,
+                    solver: study.solver.spec.name.clone(),
+                    problem: study.problem.spec.name.clone(),
+                    seed: study.seed,
+                    trials,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the Pareto optimal trials of the given studies in the format specified by the options.
+    pub fn write<W: Write>(&self, studies: &[StudyRecord], mut writer: W) -> Result<()> {
+        match self.format {
+            Format::Json => {
+                for trials in track!(self.pareto_trials(studies))? {
+                    track!(serde_json::to_writer(&mut writer, &trials).map_err(Error::from))?;
+                    track_writeln!(writer)?;
+                }
+            }
+            Format::Csv => track!(self.write_csv(studies, writer))?,
+        }
+        Ok(())
+    }
+
+    fn write_csv<W: Write>(&self, studies: &[StudyRecord], mut writer: W) -> Result<()> {
+        let mut param_names = Vec::new();
+        let mut value_names = Vec::new();
+        for study in studies {
+            add_names(&mut param_names, &study.problem.spec.params_domain);
+            add_names(&mut value_names, &study.problem.spec.values_domain);
+        }
+
+        if !self.no_header {
+            let mut header = ["study_id", "solver", "problem", "seed", "trial"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>();
+            header.extend(param_names.iter().map(|n| format!("param:{}", n)));
+            header.extend(value_names.iter().map(|n| format!("value:{}", n)));
+            track!(write_row(&mut writer, &header))?;
+        }
+
+        for study in studies {
+            let study_id = track!(study.id())?;
+            let params_domain = &study.problem.spec.params_domain;
+            let values_domain = &study.problem.spec.values_domain;
+            for (i, trial, values) in pareto_optimal_trials(study) {
+                let mut row = vec![
+                    study_id.clone(),
+                    study.solver.spec.name.clone(),
+                    study.problem.spec.name.clone(),
+                    study.seed.to_string(),
+                    i.to_string(),
+                ];
+                row.extend(param_names.iter().map(|name| {
+                    let i = params_domain
+                        .variables()
+                        .iter()
+                        .position(|v| v.name() == name);
+                    cell(i.and_then(|i| trial.params.get().get(i).copied()))
+                }));
+                row.extend(value_names.iter().map(|name| {
+                    let i = values_domain
+                        .variables()
+                        .iter()
+                        .position(|v| v.name() == name);
+                    cell(i.and_then(|i| values.get(i).copied()))
+                }));
+                track!(write_row(&mut writer, &row))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn pareto_optimal_trials(study: &StudyRecord) -> Vec<(usize, &TrialRecord, &[f64])> {
+    let last_step = study.problem.spec.steps.last();
+    let candidates = study
+        .trials
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| {
+            t.values(last_step)
+                .filter(|vs| !vs.is_empty())
+                .map(|vs| (i, t, vs))
+        })
+        .collect::<Vec<_>>();
+    candidates
+        .iter()
+        .filter(|(_, _, a)| !candidates.iter().any(|(_, _, b)| dominates(b, a)))
+        .copied()
+        .collect()
+}
+
+/// Output format of the `kurobako pareto` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON Lines (one line per study).
+    Json,
+
+    /// CSV (one row per trial).
+    Csv,
+}
+impl Format {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["json", "csv"];
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format name: {:?}", s),
+        }
+    }
+}
+
+/// Pareto optimal trials of a study.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParetoTrials {
+    /// Study ID.
+    pub study: String,
+
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Random seed of the study.
+    pub seed: u64,
+
+    /// Pareto optimal trials (in the order of the trials in the study).
+    pub trials: Vec<ParetoTrial>,
+}
+
+/// Pareto optimal trial.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParetoTrial {
+    /// Index of the trial in the study.
+    pub trial: usize,
+
+    /// Parameters (the inactive ones are `null` and the categorical ones are choice names).
+    pub params: BTreeMap<String, serde_json::Value>,
+
+    /// Evaluated values.
+    pub values: Vec<f64>,
+
+    /// Number of the steps consumed by the trial.
+    pub step: u64,
+}
@@ -1,13 +1,21 @@
 //! `kurobako plot` command.
 use crate::record::StudyRecord;
 use kurobako_core::{Error, ErrorKind, Result};
+use serde_json::json;
+use std::fs::File;
+use std::path::Path;
 use std::process::Command;
+use std::str::FromStr;
 use structopt::StructOpt;
 
+pub mod boxplot;
 pub mod curve;
 pub mod pareto_front;
+pub mod rank;
 pub mod slice;
 
+const VEGA_LITE_SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
 /// Options of the `kurobako plot` command.
 #[derive(Debug, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
@@ -18,8 +26,14 @@ pub enum PlotOpt {
     /// Generates slice plots.
     Slice(self::slice::PlotSliceOpt),
 
-    /// Generates 2D pareto front plots.
+    /// Generates 2D pareto front plots (of each study, or of the solvers on each problem).
     ParetoFront(self::pareto_front::PlotParetoFrontOpt),
+
+    /// Generates box plots of the best values obtained by solvers.
+    Box(self::boxplot::PlotBoxOpt),
+
+    /// Generates a heatmap of the ranks of solvers on problems.
+    Rank(self::rank::PlotRankOpt),
 }
 impl PlotOpt {
     /// Plots a graph.
@@ -28,6 +42,53 @@ impl PlotOpt {
             Self::Curve(opt) => track!(opt.plot(studies)),
             Self::Slice(opt) => track!(opt.plot(studies)),
             Self::ParetoFront(opt) => track!(opt.plot(studies)),
+            Self::Box(opt) => track!(opt.plot(studies)),
+            Self::Rank(opt) => track!(opt.plot(studies)),
+        }
+    }
+}
+
+/// Format of generated images.
+///
+/// `vega` emits Vega-Lite specifications (JSONs) instead of rasterizing the plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+    Vega,
+}
+impl ImageFormat {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["png", "svg", "vega"];
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+            Self::Vega => "vl.json",
+        }
+    }
+
+    fn terminal(self, width: usize, height: usize) -> String {
+        match self {
+            Self::Png => format!(
+                "set terminal pngcairo size {},{} noenhanced;",
+                width, height
+            ),
+            Self::Svg => format!("set terminal svg size {},{} noenhanced;", width, height),
+            Self::Vega => unreachable!(),
+        }
+    }
+}
+impl FromStr for ImageFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "png" => Ok(Self::Png),
+            "svg" => Ok(Self::Svg),
+            "vega" => Ok(Self::Vega),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown image format: {:?}", s),
         }
     }
 }
@@ -45,6 +106,27 @@ fn execute_gnuplot(script: &str) -> Result<()> {
     Ok(())
 }
 
+fn write_vega_spec(path: &Path, spec: &serde_json::Value) -> Result<()> {
+    let file = track!(File::create(path).map_err(Error::from); path)?;
+    track!(serde_json::to_writer_pretty(file, spec).map_err(Error::from); path)?;
+    Ok(())
+}
+
+// Scale of a Vega-Lite axis.
+fn vega_scale(min: Option<f64>, max: Option<f64>, logscale: bool) -> serde_json::Value {
+    let mut scale = json!({"zero": false});
+    if let Some(min) = min {
+        scale["domainMin"] = json!(min);
+    }
+    if let Some(max) = max {
+        scale["domainMax"] = json!(max);
+    }
+    if logscale {
+        scale["type"] = json!("log");
+    }
+    scale
+}
+
 fn normalize_filename(s: &str) -> String {
     let mut t = String::new();
     let mut replaced = false;
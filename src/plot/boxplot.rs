@@ -0,0 +1,209 @@
+//! `kurobako plot box` command.
+#![allow(clippy::format_push_string)]
+use super::{
+    execute_gnuplot, normalize_filename, vega_scale, write_vega_spec, ImageFormat, VEGA_LITE_SCHEMA,
+};
+use crate::record::{ProblemRecord, StudyRecord};
+use indicatif::{ProgressBar, ProgressStyle};
+use kurobako_core::{Error, Result};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use tempfile::{NamedTempFile, TempPath};
+
+/// Options of the `kurobako plot box` command.
+///
+/// For each problem, the distributions of the best values of the studies (i.e., seeds) of the solvers
+/// are drawn as box plots in the same image.
+///
+/// Multi-objective problems are ignored.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct PlotBoxOpt {
+    /// Output directory where generated images are stored.
+    #[structopt(long, short = "o", default_value = "images/box/")]
+    pub output_dir: PathBuf,
+
+    /// Image width in pixels.
+    #[structopt(long, default_value = "800")]
+    pub width: usize,
+
+    /// Image height in pixels.
+    #[structopt(long, default_value = "600")]
+    pub height: usize,
+
+    /// Makes Y axis log scale.
+    #[structopt(long)]
+    pub ylogscale: bool,
+
+    /// Format of generated images.
+    #[structopt(long, default_value = "png", possible_values = ImageFormat::POSSIBLE_VALUES)]
+    pub format: ImageFormat,
+}
+impl PlotBoxOpt {
+    pub(crate) fn plot(&self, studies: &[StudyRecord]) -> Result<()> {
+        let mut problems = BTreeMap::<_, Vec<_>>::new();
+        for study in studies {
+            if study.problem.spec.values_domain.variables().len() != 1 {
+                continue;
+            }
+            problems
+                .entry(track!(study.problem.id())?)
+                .or_default()
+                .push(study);
+        }
+
+        let pb = ProgressBar::new(problems.len() as u64);
+        let template =
+            "(PLOT) [{elapsed_precise}] [{pos}/{len} {percent:>3}%] [ETA {eta:>3}] {msg}";
+        pb.set_style(ProgressStyle::default_bar().template(template));
+
+        track!(fs::create_dir_all(&self.output_dir).map_err(Error::from); self.output_dir)?;
+
+        for (problem_id, studies) in problems {
+            let problem = Problem::new(problem_id, studies, self);
+            track!(problem.plot())?;
+            pb.inc(1);
+        }
+        pb.finish_with_message(&format!("done (dir={:?})", self.output_dir));
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Problem<'a> {
+    problem_id: String,
+    problem: &'a ProblemRecord,
+    solvers: BTreeMap<&'a str, Vec<f64>>,
+    opt: &'a PlotBoxOpt,
+}
+impl<'a> Problem<'a> {
+    fn new(problem_id: String, studies: Vec<&'a StudyRecord>, opt: &'a PlotBoxOpt) -> Self {
+        let problem = &studies[0].problem;
+        let mut solvers = BTreeMap::<_, Vec<_>>::new();
+        for study in studies {
+            if let Some(v) = study.best_value() {
+                solvers
+                    .entry(study.solver.spec.name.as_str())
+                    .or_default()
+                    .push(v);
+            }
+        }
+        Self {
+            problem_id,
+            problem,
+            solvers,
+            opt,
+        }
+    }
+
+    fn plot(&self) -> Result<()> {
+        if self.solvers.is_empty() {
+            return Ok(());
+        }
+        if self.opt.format == ImageFormat::Vega {
+            return track!(write_vega_spec(&self.output(), &self.make_vega_spec()));
+        }
+
+        let data_path = track!(self.generate_data())?;
+        let script = self.make_gnuplot_script(&data_path);
+        track!(execute_gnuplot(&script))?;
+        std::mem::drop(data_path);
+
+        Ok(())
+    }
+
+    fn make_gnuplot_script(&self, data_path: &TempPath) -> String {
+        let mut s = format!(
+            "set title {:?}; set ylabel {:?}; set grid;",
+            self.problem.spec.name,
+            self.problem.spec.values_domain.variables()[0].name()
+        );
+        s += "set datafile missing \"NaN\";";
+        s += "set style fill solid 0.25 border -1;";
+
+        let xtics = self
+            .solvers
+            .keys()
+            .enumerate()
+            .map(|(i, name)| format!("{:?} {}", name, i + 1))
+            .collect::<Vec<_>>();
+        s += &format!("set xtics ({}) nomirror;", xtics.join(", "));
+
+        if self.opt.ylogscale {
+            s += "set logscale y;"
+        }
+
+        s += &self.opt.format.terminal(self.opt.width, self.opt.height);
+        s += &format!("set output {:?};", self.output());
+
+        s += &format!("plot [0:{}] []", self.solvers.len() + 1);
+        for i in 0..self.solvers.len() {
+            if i == 0 {
+                s += &format!(" {:?}", data_path);
+            } else {
+                s += ", \"\"";
+            }
+            s += &format!(" u ({}):{} w boxplot notitle lc {}", i + 1, i + 1, i + 1);
+        }
+
+        s
+    }
+
+    fn make_vega_spec(&self) -> serde_json::Value {
+        let mut data = Vec::new();
+        for (name, values) in &self.solvers {
+            data.extend(values.iter().map(|v| json!({"solver": name, "value": v})));
+        }
+
+        json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "title": self.problem.spec.name,
+            "width": self.opt.width,
+            "height": self.opt.height,
+            "data": {"values": data},
+            "mark": {"type": "boxplot", "extent": 1.5},
+            "encoding": {
+                "x": {"field": "solver", "type": "nominal", "title": "Solver"},
+                "y": {
+                    "field": "value",
+                    "type": "quantitative",
+                    "title": self.problem.spec.values_domain.variables()[0].name(),
+                    "scale": vega_scale(None, None, self.opt.ylogscale),
+                },
+                "color": {"field": "solver", "type": "nominal", "legend": null},
+            },
+        })
+    }
+
+    fn output(&self) -> PathBuf {
+        self.opt.output_dir.join(format!(
+            "{}-{}.{}",
+            normalize_filename(&self.problem.spec.name),
+            self.problem_id,
+            self.opt.format.extension()
+        ))
+    }
+
+    fn generate_data(&self) -> Result<TempPath> {
+        let mut temp_file = track!(NamedTempFile::new().map_err(Error::from))?;
+
+        let max_len = self.solvers.values().map(|vs| vs.len()).max().unwrap_or(0);
+        for i in 0..max_len {
+            for values in self.solvers.values() {
+                if let Some(v) = values.get(i) {
+                    track_write!(temp_file, "{} ", v)?;
+                } else {
+                    track_write!(temp_file, "NaN ")?;
+                }
+            }
+            track_writeln!(temp_file)?;
+        }
+
+        Ok(temp_file.into_temp_path())
+    }
+}
@@ -1,11 +1,18 @@
 //! `kurobako plot curve` command.
+//!
+//! Like the other `kurobako plot` commands, the images are rendered by gnuplot
+//! (or emitted as Vega-Lite specifications) instead of a Rust plotting library such as `plotters`,
+//! so that all the plots share one rendering backend and look alike.
 #![allow(clippy::format_push_string)]
-use super::{execute_gnuplot, normalize_filename};
+use super::{
+    execute_gnuplot, normalize_filename, vega_scale, write_vega_spec, ImageFormat, VEGA_LITE_SCHEMA,
+};
 use crate::record::{ProblemRecord, StudyRecord};
 use indicatif::{ProgressBar, ProgressStyle};
 use kurobako_core::num::OrderedFloat;
 use kurobako_core::{Error, ErrorKind, Result};
 use rustats::fundamental::{average, stddev};
+use serde_json::json;
 use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write as _;
@@ -86,6 +93,16 @@ pub struct PlotCurveOpt {
     #[structopt(long)]
     pub errorbar: bool,
 
+    /// Displays errorbar showing the 95% confidence interval of the mean instead of standard deviation.
+    ///
+    /// This implies `--errorbar`.
+    #[structopt(long)]
+    pub ci: bool,
+
+    /// Format of generated images.
+    #[structopt(long, default_value = "png", possible_values = ImageFormat::POSSIBLE_VALUES)]
+    pub format: ImageFormat,
+
     /// Metric of X axis.
     #[structopt(
         long,
@@ -163,6 +180,11 @@ impl<'a> Problem<'a> {
             return Ok(false);
         }
 
+        if self.opt.format == ImageFormat::Vega {
+            track!(write_vega_spec(&self.output(), &self.make_vega_spec()))?;
+            return Ok(true);
+        }
+
         let data_path = track!(self.generate_data())?;
         let script = self.make_gnuplot_script(&data_path);
         track!(execute_gnuplot(&script))?;
@@ -172,13 +194,7 @@ impl<'a> Problem<'a> {
     }
 
     fn make_gnuplot_script(&self, data_path: &TempPath) -> String {
-        let ylabel = match self.opt.metric {
-            Metric::BestValue => self.problem.spec.values_domain.variables()[0].name(),
-            Metric::Hypervolume => "Hypervolume",
-            Metric::ElapsedTime => "Cumulative Elapsed Seconds (Ask + Evaluate + Tell)",
-            Metric::SolverElapsedTime => "Cumulative Elapsed Seconds (Ask + Tell)",
-        };
-
+        let ylabel = self.ylabel();
         let mut s = format!(
             "set title {:?}; set ylabel {:?}; set xlabel \"Budget\"; set grid;",
             self.problem.spec.name, ylabel
@@ -189,17 +205,11 @@ impl<'a> Problem<'a> {
             s += "set logscale y;"
         }
 
-        let output = self.opt.output_dir.join(format!(
-            "{}-{}.png",
-            normalize_filename(&self.problem.spec.name),
-            self.problem_id
-        ));
-        s += &format!(
-            "set terminal pngcairo size {},{} noenhanced; set output {:?};",
-            self.opt.width, self.opt.height, output
-        );
+        s += &self.opt.format.terminal(self.opt.width, self.opt.height);
+        s += &format!("set output {:?};", self.output());
 
-        if self.opt.errorbar {
+        let errorbar = self.opt.errorbar || self.opt.ci;
+        if errorbar {
             s += "set style fill transparent solid 0.2;";
             s += "set style fill noborder;";
         }
@@ -225,7 +235,7 @@ impl<'a> Problem<'a> {
                 (i * 2) + 1,
                 i + 1
             );
-            if self.opt.errorbar {
+            if errorbar {
                 s += &format!(
                     ", \"\" u ($0/{}):(${}-${}):(${}+${}) with filledcurves notitle lc {}",
                     problem_steps,
@@ -241,9 +251,100 @@ impl<'a> Problem<'a> {
         s
     }
 
+    fn make_vega_spec(&self) -> serde_json::Value {
+        let problem_steps = self.problem.spec.steps.last() as f64;
+        let mut data = Vec::new();
+        for ((name, _), solver) in &self.solvers {
+            data.extend(
+                solver
+                    .ys
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(step, v)| v.as_ref().map(|v| (step, v)))
+                    .map(|(step, v)| {
+                        let error = if self.opt.ci { v.ci95() } else { v.sd };
+                        json!({
+                            "solver": name,
+                            "budget": step as f64 / problem_steps,
+                            "value": v.avg,
+                            "lower": v.avg - error,
+                            "upper": v.avg + error,
+                        })
+                    }),
+            );
+        }
+
+        let ylabel = self.ylabel();
+        let y_scale = vega_scale(self.opt.ymin, self.default_ymax(), self.opt.ylogscale);
+        let mut layers = Vec::new();
+        if self.opt.errorbar || self.opt.ci {
+            layers.push(json!({
+                "mark": {"type": "errorband", "opacity": 0.2},
+                "encoding": {
+                    "y": {"field": "lower", "type": "quantitative", "title": ylabel, "scale": y_scale.clone()},
+                    "y2": {"field": "upper"},
+                },
+            }));
+        }
+        layers.push(json!({
+            "mark": "line",
+            "params": [{"name": "zoom", "select": "interval", "bind": "scales"}],
+            "encoding": {
+                "y": {"field": "value", "type": "quantitative", "title": ylabel, "scale": y_scale},
+                "tooltip": [
+                    {"field": "solver"},
+                    {"field": "budget", "format": ".3f"},
+                    {"field": "value", "title": "avg"},
+                ],
+            },
+        }));
+
+        json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "title": self.problem.spec.name,
+            "width": self.opt.width,
+            "height": self.opt.height,
+            "data": {"values": data},
+            "encoding": {
+                "x": {
+                    "field": "budget",
+                    "type": "quantitative",
+                    "title": "Budget",
+                    "scale": vega_scale(self.opt.xmin, self.opt.xmax, false),
+                },
+                "color": {"field": "solver", "type": "nominal", "title": "Solver"},
+            },
+            "layer": layers,
+        })
+    }
+
+    fn ylabel(&self) -> &str {
+        match self.opt.metric {
+            Metric::BestValue => self.problem.spec.values_domain.variables()[0].name(),
+            Metric::Hypervolume => "Hypervolume",
+            Metric::ElapsedTime => "Cumulative Elapsed Seconds (Ask + Evaluate + Tell)",
+            Metric::SolverElapsedTime => "Cumulative Elapsed Seconds (Ask + Tell)",
+        }
+    }
+
+    fn output(&self) -> PathBuf {
+        self.opt.output_dir.join(format!(
+            "{}-{}.{}",
+            normalize_filename(&self.problem.spec.name),
+            self.problem_id,
+            self.opt.format.extension()
+        ))
+    }
+
     fn ymax(&self) -> String {
-        if let Some(y) = self.opt.ymax {
-            y.to_string()
+        self.default_ymax()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "".to_string())
+    }
+
+    fn default_ymax(&self) -> Option<f64> {
+        if self.opt.ymax.is_some() {
+            self.opt.ymax
         } else if self.opt.metric == Metric::BestValue {
             let max_step = self
                 .solvers
@@ -252,18 +353,13 @@ impl<'a> Problem<'a> {
                 .max()
                 .unwrap_or_else(|| unreachable!());
             let step = max_step / 10;
-            if let Some(y) = self
-                .solvers
+            self.solvers
                 .values()
                 .filter_map(|s| s.y(step).map(|v| OrderedFloat(v.avg)))
                 .max()
-            {
-                y.0.to_string()
-            } else {
-                "".to_string()
-            }
+                .map(|y| y.0)
         } else {
-            "".to_string()
+            None
         }
     }
 
@@ -306,7 +402,8 @@ impl<'a> Problem<'a> {
         for step in 0..max_step {
             for s in self.solvers.values() {
                 if let Some(v) = s.y(step) {
-                    track_write!(temp_file, "{} {} ", v.avg, v.sd)?;
+                    let error = if self.opt.ci { v.ci95() } else { v.sd };
+                    track_write!(temp_file, "{} {} ", v.avg, error)?;
                 } else {
                     track_write!(temp_file, "NaN NaN ")?;
                 }
@@ -342,9 +439,10 @@ impl Solver {
             if values.is_empty() {
                 ys.push(None);
             } else {
+                let n = values.len();
                 let avg = average(values.iter().copied());
                 let sd = stddev(values.into_iter());
-                ys.push(Some(Value { avg, sd }));
+                ys.push(Some(Value { avg, sd, n }));
             }
         }
         Self { ys }
@@ -359,6 +457,13 @@ impl Solver {
 struct Value {
     avg: f64,
     sd: f64,
+    n: usize,
+}
+impl Value {
+    /// Half width of the 95% confidence interval of the mean (normal approximation).
+    fn ci95(&self) -> f64 {
+        1.96 * self.sd / (self.n as f64).sqrt()
+    }
 }
 
 #[derive(Debug)]
@@ -1,17 +1,29 @@
 //! `kurobako plot pareto-front` command.
 #![allow(clippy::format_push_string)]
-use super::{execute_gnuplot, normalize_filename};
-use crate::record::StudyRecord;
+use super::{
+    execute_gnuplot, normalize_filename, vega_scale, write_vega_spec, ImageFormat, VEGA_LITE_SCHEMA,
+};
+use crate::record::{ProblemRecord, StudyRecord};
 use indicatif::{ProgressBar, ProgressStyle};
 use kurobako_core::{Error, ErrorKind, Result};
+use serde_json::json;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use tempfile::{NamedTempFile, TempPath};
 
 /// Options of the `kurobako plot pareto-front` command.
+///
+/// By default, an image is generated for each study, in which the trials are colored by their budgets.
+/// If `--compare-solvers` is specified, an image is generated for each problem instead,
+/// in which the pareto fronts obtained by the solvers are drawn.
+/// The front of a solver is computed from the trials of all the studies (i.e., seeds) of the solver.
+///
+/// If the problem has the `pareto_front` attribute (a space separated list of `f1,f2` points; e.g., ZDT problems),
+/// the true pareto front is overlaid.
 #[derive(Debug, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 pub struct PlotParetoFrontOpt {
@@ -42,6 +54,18 @@ pub struct PlotParetoFrontOpt {
     /// Maximum value of X axis.
     #[structopt(long)]
     pub xmax: Option<f64>,
+
+    /// Draws the pareto fronts obtained by the solvers in an image for each problem.
+    #[structopt(long)]
+    pub compare_solvers: bool,
+
+    /// Doesn't overlay the true pareto front even if the problem exposes it.
+    #[structopt(long)]
+    pub no_true_front: bool,
+
+    /// Format of generated images.
+    #[structopt(long, default_value = "png", possible_values = ImageFormat::POSSIBLE_VALUES)]
+    pub format: ImageFormat,
 }
 impl PlotParetoFrontOpt {
     pub(crate) fn plot(&self, study_records: &[StudyRecord]) -> Result<()> {
@@ -53,7 +77,11 @@ impl PlotParetoFrontOpt {
                 ErrorKind::InvalidInput
             );
 
-            let id = track!(record.id())?;
+            let id = if self.compare_solvers {
+                track!(record.problem.id())?
+            } else {
+                track!(record.id())?
+            };
             studies
                 .entry(id)
                 .or_insert_with(Study::new)
@@ -68,8 +96,13 @@ impl PlotParetoFrontOpt {
 
         track!(fs::create_dir_all(&self.output_dir).map_err(Error::from); self.output_dir)?;
 
-        for (_study_id, study) in studies {
-            track!(study.plot(self))?;
+        for (id, study) in studies {
+            if self.compare_solvers {
+                let problem = track!(Problem::new(id, study.instances, self))?;
+                track!(problem.plot())?;
+            } else {
+                track!(study.plot(self))?;
+            }
             pb.inc(1);
         }
 
@@ -77,6 +110,46 @@ impl PlotParetoFrontOpt {
 
         Ok(())
     }
+
+    fn true_front(&self, problem: &ProblemRecord) -> Result<Option<Vec<(f64, f64)>>> {
+        if self.no_true_front {
+            return Ok(None);
+        }
+        problem
+            .spec
+            .attrs
+            .get("pareto_front")
+            .map(|s| track!(parse_front(s)))
+            .transpose()
+    }
+
+    fn plot_range(&self) -> String {
+        format!(
+            "[{}:{}] [{}:{}]",
+            self.xmin.map(|v| v.to_string()).unwrap_or_default(),
+            self.xmax.map(|v| v.to_string()).unwrap_or_default(),
+            self.ymin.map(|v| v.to_string()).unwrap_or_default(),
+            self.ymax.map(|v| v.to_string()).unwrap_or_default(),
+        )
+    }
+
+    fn vega_encoding(&self, problem: &ProblemRecord) -> serde_json::Value {
+        let values = problem.spec.values_domain.variables();
+        json!({
+            "x": {
+                "field": "f1",
+                "type": "quantitative",
+                "title": values[0].name(),
+                "scale": vega_scale(self.xmin, self.xmax, false),
+            },
+            "y": {
+                "field": "f2",
+                "type": "quantitative",
+                "title": values[1].name(),
+                "scale": vega_scale(self.ymin, self.ymax, false),
+            },
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -91,79 +164,337 @@ impl<'a> Study<'a> {
     }
 
     fn plot(&self, opt: &PlotParetoFrontOpt) -> Result<()> {
-        let data_path = track!(self.generate_data())?;
-        let script = track!(self.make_gnuplot_script(&data_path, opt))?;
+        let problem = &self.instances[0].problem;
+        let solver = &self.instances[0].solver;
+        let output = opt.output_dir.join(format!(
+            "{}-{}-{}.{}",
+            normalize_filename(&problem.spec.name),
+            normalize_filename(&solver.spec.name),
+            track!(self.instances[0].id())?,
+            opt.format.extension()
+        ));
+        let true_front = track!(opt.true_front(problem))?;
+
+        if opt.format == ImageFormat::Vega {
+            let spec = self.make_vega_spec(opt, true_front.as_ref());
+            return track!(write_vega_spec(&output, &spec));
+        }
+
+        let data_path = track!(self.generate_data(true_front.as_ref()))?;
+        let script = self.make_gnuplot_script(&data_path, opt, &output, true_front.is_some());
         track!(execute_gnuplot(&script))?;
         std::mem::drop(data_path);
 
         Ok(())
     }
 
+    fn title(&self) -> String {
+        let problem = &self.instances[0].problem;
+        let solver = &self.instances[0].solver;
+        format!(
+            "Problem: {}, Solver: {}",
+            problem.spec.name, solver.spec.name
+        )
+    }
+
     fn make_gnuplot_script(
         &self,
         data_path: &TempPath,
         opt: &PlotParetoFrontOpt,
-    ) -> Result<String> {
+        output: &Path,
+        has_true_front: bool,
+    ) -> String {
         let problem = &self.instances[0].problem;
-        let solver = &self.instances[0].solver;
-        let title = format!(
-            "Problem: {}, Solver: {}",
-            problem.spec.name, solver.spec.name
-        );
         let mut s = format!(
             "set title {:?}; \
              set ylabel {:?}; \
              set xlabel {:?}; \
              set grid;",
-            title,
+            self.title(),
             problem.spec.values_domain.variables()[1].name(),
             problem.spec.values_domain.variables()[0].name(),
         );
 
-        let output = opt.output_dir.join(format!(
-            "{}-{}-{}.png",
-            normalize_filename(&problem.spec.name),
-            normalize_filename(&solver.spec.name),
-            track!(self.instances[0].id())?
-        ));
-
-        s += &format!(
-            "set terminal pngcairo size {},{} noenhanced; set output {:?};",
-            opt.width, opt.height, output
-        );
+        s += &opt.format.terminal(opt.width, opt.height);
+        s += &format!("set output {:?};", output);
         s += "set palette defined (0 'blue', 1 'grey', 2 'red');";
 
         s += &format!(
-            "plot [{}:{}] [{}:{}] {:?} u 3:2:1 palette pt 7 notitle",
-            opt.xmin.map(|v| v.to_string()).unwrap_or_default(),
-            opt.xmax.map(|v| v.to_string()).unwrap_or_default(),
-            opt.ymin.map(|v| v.to_string()).unwrap_or_default(),
-            opt.ymax.map(|v| v.to_string()).unwrap_or_default(),
+            "plot {} {:?} index 0 u 3:2:1 palette pt 7 notitle",
+            opt.plot_range(),
             data_path
         );
+        if has_true_front {
+            s += ", \"\" index 1 u 1:2 w l t \"True Pareto Front\" lc rgb \"black\" dt 2";
+        }
 
-        Ok(s)
+        s
     }
 
-    fn generate_data(&self) -> Result<TempPath> {
+    fn make_vega_spec(
+        &self,
+        opt: &PlotParetoFrontOpt,
+        true_front: Option<&Vec<(f64, f64)>>,
+    ) -> serde_json::Value {
+        let problem = &self.instances[0].problem;
+        let problem_steps = problem.spec.steps.last();
+        let mut data = Vec::new();
+        for study in &self.instances {
+            for trial in &study.trials {
+                if let Some(vs) = trial.values(problem_steps) {
+                    let end_step = trial.end_step().unwrap_or_else(|| unreachable!());
+                    let budget = end_step as f64 / problem_steps as f64;
+                    data.push(json!({"budget": budget, "f1": vs[0], "f2": vs[1]}));
+                }
+            }
+        }
+
+        let encoding = opt.vega_encoding(problem);
+        let mut layers = vec![json!({
+            "data": {"values": data},
+            "mark": {"type": "point", "filled": true},
+            "encoding": {
+                "x": encoding["x"],
+                "y": encoding["y"],
+                "color": {
+                    "field": "budget",
+                    "type": "quantitative",
+                    "title": "Budget",
+                    "scale": {"range": ["blue", "grey", "red"]},
+                },
+                "tooltip": [{"field": "budget"}, {"field": "f1"}, {"field": "f2"}],
+            },
+        })];
+        if let Some(front) = true_front {
+            layers.push(true_front_layer(front, encoding));
+        }
+
+        json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "title": self.title(),
+            "width": opt.width,
+            "height": opt.height,
+            "layer": layers,
+        })
+    }
+
+    fn generate_data(&self, true_front: Option<&Vec<(f64, f64)>>) -> Result<TempPath> {
         let mut temp_file = track!(NamedTempFile::new().map_err(Error::from))?;
 
         let problem_steps = self.instances[0].problem.spec.steps.last();
         for study in &self.instances {
-            eprintln!("trials: {:?}", study.trials.len());
-            dbg!(problem_steps);
-            let mut c = 0;
             for trial in &study.trials {
                 if let Some(vs) = trial.values(problem_steps) {
-                    c += 1;
                     let end_step = trial.end_step().unwrap_or_else(|| unreachable!());
                     let budget = end_step as f64 / problem_steps as f64;
                     track_writeln!(temp_file, "{} {} {}", budget, vs[1], vs[0])?;
                 }
             }
-            dbg!(c);
+        }
+
+        if let Some(front) = true_front {
+            track_writeln!(temp_file)?;
+            track_writeln!(temp_file)?;
+            for (f1, f2) in front {
+                track_writeln!(temp_file, "{} {}", f1, f2)?;
+            }
+        }
+
+        Ok(temp_file.into_temp_path())
+    }
+}
+
+#[derive(Debug)]
+struct Problem<'a> {
+    problem_id: String,
+    problem: &'a ProblemRecord,
+    fronts: BTreeMap<(&'a str, String), Vec<(f64, f64)>>,
+    true_front: Option<Vec<(f64, f64)>>,
+    opt: &'a PlotParetoFrontOpt,
+}
+impl<'a> Problem<'a> {
+    fn new(
+        problem_id: String,
+        studies: Vec<&'a StudyRecord>,
+        opt: &'a PlotParetoFrontOpt,
+    ) -> Result<Self> {
+        let problem = &studies[0].problem;
+        let problem_steps = problem.spec.steps.last();
+
+        let mut points = BTreeMap::<_, Vec<_>>::new();
+        for study in studies {
+            let study_id = track!(study.id())?;
+            points
+                .entry((study.solver.spec.name.as_str(), study_id))
+                .or_default()
+                .extend(
+                    study
+                        .trials
+                        .iter()
+                        .filter_map(|t| t.values(problem_steps))
+                        .filter(|vs| vs.iter().all(|v| v.is_finite()))
+                        .map(|vs| (vs[0], vs[1])),
+                );
+        }
+
+        Ok(Self {
+            problem_id,
+            problem,
+            fronts: points
+                .into_iter()
+                .map(|(k, v)| (k, pareto_front(v)))
+                .filter(|(_, v)| !v.is_empty())
+                .collect(),
+            true_front: track!(opt.true_front(problem))?,
+            opt,
+        })
+    }
+
+    fn plot(&self) -> Result<()> {
+        if self.opt.format == ImageFormat::Vega {
+            return track!(write_vega_spec(&self.output(), &self.make_vega_spec()));
+        }
+
+        let data_path = track!(self.generate_data())?;
+        let script = self.make_gnuplot_script(&data_path);
+        track!(execute_gnuplot(&script))?;
+        std::mem::drop(data_path);
+
+        Ok(())
+    }
+
+    fn make_gnuplot_script(&self, data_path: &TempPath) -> String {
+        let values = self.problem.spec.values_domain.variables();
+        let mut s = format!(
+            "set title {:?}; set xlabel {:?}; set ylabel {:?}; set grid;",
+            self.problem.spec.name,
+            values[0].name(),
+            values[1].name()
+        );
+
+        s += &self.opt.format.terminal(self.opt.width, self.opt.height);
+        s += &format!("set output {:?};", self.output());
+        s += &format!("plot {}", self.opt.plot_range());
+
+        let mut plots = Vec::new();
+        for (i, (name, _)) in self.fronts.keys().enumerate() {
+            plots.push(format!("index {} w steps t {:?} lc {}", i, name, i + 1));
+            plots.push(format!("index {} w p pt 7 notitle lc {}", i, i + 1));
+        }
+        if self.true_front.is_some() {
+            plots.push(format!(
+                "index {} w l t \"True Pareto Front\" lc rgb \"black\" dt 2",
+                self.fronts.len()
+            ));
+        }
+        for (i, plot) in plots.into_iter().enumerate() {
+            if i == 0 {
+                s += &format!(" {:?} {}", data_path, plot);
+            } else {
+                s += &format!(", \"\" {}", plot);
+            }
+        }
+
+        s
+    }
+
+    fn make_vega_spec(&self) -> serde_json::Value {
+        let mut data = Vec::new();
+        for ((name, _), front) in &self.fronts {
+            data.extend(
+                front
+                    .iter()
+                    .map(|(f1, f2)| json!({"solver": name, "f1": f1, "f2": f2})),
+            );
+        }
+        let encoding = self.opt.vega_encoding(self.problem);
+
+        let mut layers = vec![json!({
+            "data": {"values": data},
+            "encoding": encoding.clone(),
+            "layer": [
+                {
+                    "mark": {"type": "line", "interpolate": "step-after"},
+                    "encoding": {"color": {"field": "solver", "type": "nominal", "title": "Solver"}},
+                },
+                {
+                    "mark": {"type": "point", "filled": true},
+                    "encoding": {
+                        "color": {"field": "solver", "type": "nominal"},
+                        "tooltip": [{"field": "solver"}, {"field": "f1"}, {"field": "f2"}],
+                    },
+                },
+            ],
+        })];
+        if let Some(front) = &self.true_front {
+            layers.push(true_front_layer(front, encoding));
+        }
+
+        json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "title": self.problem.spec.name,
+            "width": self.opt.width,
+            "height": self.opt.height,
+            "layer": layers,
+        })
+    }
+
+    fn output(&self) -> PathBuf {
+        self.opt.output_dir.join(format!(
+            "{}-{}.{}",
+            normalize_filename(&self.problem.spec.name),
+            self.problem_id,
+            self.opt.format.extension()
+        ))
+    }
+
+    fn generate_data(&self) -> Result<TempPath> {
+        let mut temp_file = track!(NamedTempFile::new().map_err(Error::from))?;
+
+        for front in self.fronts.values().chain(self.true_front.iter()) {
+            for (f1, f2) in front {
+                track_writeln!(temp_file, "{} {}", f1, f2)?;
+            }
+            track_writeln!(temp_file)?;
+            track_writeln!(temp_file)?;
         }
 
         Ok(temp_file.into_temp_path())
     }
 }
+
+fn true_front_layer(front: &[(f64, f64)], encoding: serde_json::Value) -> serde_json::Value {
+    let data = front
+        .iter()
+        .map(|(f1, f2)| json!({"f1": f1, "f2": f2}))
+        .collect::<Vec<_>>();
+    json!({
+        "data": {"values": data},
+        "encoding": encoding,
+        "mark": {"type": "line", "color": "black", "strokeDash": [4, 4]},
+    })
+}
+
+/// Returns the non-dominated points sorted by the first objective value.
+fn pareto_front(mut points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut front: Vec<(f64, f64)> = Vec::new();
+    for p in points {
+        if front.last().map_or(true, |q| p.1 < q.1) {
+            front.push(p);
+        }
+    }
+    front
+}
+
+fn parse_front(s: &str) -> Result<Vec<(f64, f64)>> {
+    s.split_whitespace()
+        .map(|point| {
+            let mut vs = point.splitn(2, ',');
+            let f1 = track!(vs.next().unwrap_or_default().parse().map_err(Error::from); point)?;
+            let f2 = track!(vs.next().unwrap_or_default().parse().map_err(Error::from); point)?;
+            Ok((f1, f2))
+        })
+        .collect()
+}
@@ -0,0 +1,200 @@
+//! `kurobako plot rank` command.
+#![allow(clippy::format_push_string)]
+use super::{execute_gnuplot, write_vega_spec, ImageFormat, VEGA_LITE_SCHEMA};
+use crate::record::StudyRecord;
+use crate::stats::friedman::ranks;
+use kurobako_core::{Error, Result};
+use rustats::fundamental::average;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use tempfile::{NamedTempFile, TempPath};
+
+/// Options of the `kurobako plot rank` command.
+///
+/// The solvers are ranked on each problem by the average of the best values of their studies
+/// (`1` is the best, and ties get the average rank), and the ranks are drawn as a heatmap
+/// whose rows are the problems and whose columns are the solvers.
+///
+/// Multi-objective problems are ignored.
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct PlotRankOpt {
+    /// Output directory where the generated image is stored.
+    #[structopt(long, short = "o", default_value = "images/rank/")]
+    pub output_dir: PathBuf,
+
+    /// Image width in pixels.
+    #[structopt(long, default_value = "800")]
+    pub width: usize,
+
+    /// Image height in pixels.
+    #[structopt(long, default_value = "600")]
+    pub height: usize,
+
+    /// Format of generated images.
+    #[structopt(long, default_value = "png", possible_values = ImageFormat::POSSIBLE_VALUES)]
+    pub format: ImageFormat,
+}
+impl PlotRankOpt {
+    pub(crate) fn plot(&self, studies: &[StudyRecord]) -> Result<()> {
+        let mut problems = BTreeMap::<_, BTreeMap<_, Vec<_>>>::new();
+        for study in studies {
+            if study.problem.spec.values_domain.variables().len() != 1 {
+                continue;
+            }
+            if let Some(v) = study.best_value() {
+                problems
+                    .entry(study.problem.spec.name.as_str())
+                    .or_default()
+                    .entry(study.solver.spec.name.as_str())
+                    .or_default()
+                    .push(v);
+            }
+        }
+
+        let mut cells = Vec::new();
+        for (problem, solvers) in &problems {
+            let averages = solvers.values().map(|vs| average(vs.iter().copied()));
+            for (solver, rank) in solvers.keys().zip(ranks(averages)) {
+                cells.push(Cell {
+                    problem: *problem,
+                    solver: *solver,
+                    rank,
+                });
+            }
+        }
+        if cells.is_empty() {
+            return Ok(());
+        }
+
+        track!(fs::create_dir_all(&self.output_dir).map_err(Error::from); self.output_dir)?;
+        if self.format == ImageFormat::Vega {
+            track!(write_vega_spec(
+                &self.output(),
+                &self.make_vega_spec(&cells)
+            ))?;
+        } else {
+            let data_path = track!(self.generate_data(&cells))?;
+            let script = self.make_gnuplot_script(&data_path, &cells);
+            track!(execute_gnuplot(&script))?;
+            std::mem::drop(data_path);
+        }
+        eprintln!("done (file={:?})", self.output());
+
+        Ok(())
+    }
+
+    fn make_gnuplot_script(&self, data_path: &TempPath, cells: &[Cell]) -> String {
+        let (problems, solvers) = axes(cells);
+
+        let mut s =
+            "set title \"Ranks\"; set xlabel \"Solver\"; set ylabel \"Problem\";".to_owned();
+        s += &format!("set xtics ({}) rotate by -30;", tics(&solvers));
+        s += &format!("set ytics ({});", tics(&problems));
+        s += "set cblabel \"Rank\"; set palette defined (0 'forest-green', 1 'white', 2 'red');";
+        s += "set style fill solid 1.0 noborder;";
+
+        s += &self.format.terminal(self.width, self.height);
+        s += &format!("set output {:?};", self.output());
+
+        s += &format!(
+            "plot [-0.5:{}] [{}:-0.5] {:?} u 1:2:(0.5):(0.5):3 w boxxyerror lc palette notitle, \
+             \"\" u 1:2:(sprintf(\"%.2f\", $3)) w labels notitle",
+            solvers.len() as f64 - 0.5,
+            problems.len() as f64 - 0.5,
+            data_path
+        );
+
+        s
+    }
+
+    fn make_vega_spec(&self, cells: &[Cell]) -> serde_json::Value {
+        let data = cells
+            .iter()
+            .map(|c| json!({"problem": c.problem, "solver": c.solver, "rank": c.rank}))
+            .collect::<Vec<_>>();
+        json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "title": "Ranks",
+            "width": self.width,
+            "height": self.height,
+            "data": {"values": data},
+            "encoding": {
+                "x": {"field": "solver", "type": "nominal", "title": "Solver"},
+                "y": {"field": "problem", "type": "nominal", "title": "Problem"},
+            },
+            "layer": [
+                {
+                    "mark": "rect",
+                    "encoding": {
+                        "color": {
+                            "field": "rank",
+                            "type": "quantitative",
+                            "title": "Rank",
+                            "scale": {"scheme": "redyellowgreen", "reverse": true},
+                        },
+                        "tooltip": [{"field": "problem"}, {"field": "solver"}, {"field": "rank"}],
+                    },
+                },
+                {
+                    "mark": "text",
+                    "encoding": {"text": {"field": "rank", "type": "quantitative", "format": ".2f"}},
+                },
+            ],
+        })
+    }
+
+    fn output(&self) -> PathBuf {
+        self.output_dir
+            .join(format!("ranks.{}", self.format.extension()))
+    }
+
+    fn generate_data(&self, cells: &[Cell]) -> Result<TempPath> {
+        let mut temp_file = track!(NamedTempFile::new().map_err(Error::from))?;
+
+        let (problems, solvers) = axes(cells);
+        for c in cells {
+            let x = solvers.iter().position(|&s| s == c.solver);
+            let y = problems.iter().position(|&p| p == c.problem);
+            track_writeln!(
+                temp_file,
+                "{} {} {}",
+                x.unwrap_or_else(|| unreachable!()),
+                y.unwrap_or_else(|| unreachable!()),
+                c.rank
+            )?;
+        }
+
+        Ok(temp_file.into_temp_path())
+    }
+}
+
+#[derive(Debug)]
+struct Cell<'a> {
+    problem: &'a str,
+    solver: &'a str,
+    rank: f64,
+}
+
+// Returns the problem names and the solver names in ascending order.
+fn axes<'a>(cells: &[Cell<'a>]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let problems = cells.iter().map(|c| c.problem).collect::<BTreeSet<_>>();
+    let solvers = cells.iter().map(|c| c.solver).collect::<BTreeSet<_>>();
+    (
+        problems.into_iter().collect(),
+        solvers.into_iter().collect(),
+    )
+}
+
+fn tics(names: &[&str]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("{:?} {}", name, i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -6,13 +6,26 @@ use kurobako_core::problem::{
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
 use kurobako_core::Result;
-use kurobako_problems::{hpobench, nasbench, sigopt, surrogate, warm_starting, zdt};
+use kurobako_problems::{conditional, hpobench, nasbench, sigopt, surrogate, warm_starting, zdt};
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
+pub(crate) use self::cache::with_shared_cache;
+
 mod average;
+mod cache;
+mod compose;
+mod constrain;
+mod discretize;
+mod drift;
+mod embed;
+mod fidelity;
 mod ln;
+mod normalize;
+mod pad;
 mod rank;
+mod resample;
+mod rotate;
 mod study;
 
 /// Problem recipe.
@@ -23,10 +36,26 @@ pub struct KurobakoProblemRecipe {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
+    /// Tags of the problem (used to select studies by `kurobako run --only/--skip`).
+    #[structopt(long = "tag", number_of_values = 1)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
     #[structopt(flatten)]
     #[serde(flatten)]
     inner: InnerRecipe,
 }
+impl KurobakoProblemRecipe {
+    /// Returns the name of this recipe if specified.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the tags of this recipe.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
 impl ProblemRecipe for KurobakoProblemRecipe {
     type Factory = KurobakoProblemFactory;
 
@@ -42,6 +71,7 @@ impl From<hpobench::HpobenchProblemRecipe> for KurobakoProblemRecipe {
     fn from(f: hpobench::HpobenchProblemRecipe) -> Self {
         Self {
             name: None,
+            tags: Vec::new(),
             inner: InnerRecipe::Hpobench(f),
         }
     }
@@ -50,6 +80,7 @@ impl From<sigopt::SigoptProblemRecipe> for KurobakoProblemRecipe {
     fn from(f: sigopt::SigoptProblemRecipe) -> Self {
         Self {
             name: None,
+            tags: Vec::new(),
             inner: InnerRecipe::Sigopt(f),
         }
     }
@@ -58,6 +89,7 @@ impl From<zdt::ZdtProblemRecipe> for KurobakoProblemRecipe {
     fn from(f: zdt::ZdtProblemRecipe) -> Self {
         Self {
             name: None,
+            tags: Vec::new(),
             inner: InnerRecipe::Zdt(f),
         }
     }
@@ -66,6 +98,7 @@ impl From<surrogate::SurrogateProblemRecipe> for KurobakoProblemRecipe {
     fn from(f: surrogate::SurrogateProblemRecipe) -> Self {
         Self {
             name: None,
+            tags: Vec::new(),
             inner: InnerRecipe::Surrogate(f),
         }
     }
@@ -82,11 +115,23 @@ enum InnerRecipe {
     Hpobench(hpobench::HpobenchProblemRecipe),
     Zdt(zdt::ZdtProblemRecipe),
     Surrogate(surrogate::SurrogateProblemRecipe),
+    Conditional(conditional::ConditionalProblemRecipe),
     Study(self::study::StudyProblemRecipe),
     Rank(self::rank::RankProblemRecipe),
     Average(self::average::AverageProblemRecipe),
     Ln(self::ln::LnProblemRecipe),
+    Rotate(self::rotate::RotateProblemRecipe),
+    Pad(self::pad::PadProblemRecipe),
     WarmStarting(warm_starting::WarmStartingProblemRecipe),
+    Normalize(self::normalize::NormalizeProblemRecipe),
+    Discretize(self::discretize::DiscretizeProblemRecipe),
+    Constrain(self::constrain::ConstrainProblemRecipe),
+    Fidelity(self::fidelity::FidelityProblemRecipe),
+    Compose(self::compose::ComposeProblemRecipe),
+    Embed(self::embed::EmbedProblemRecipe),
+    Cache(self::cache::CacheProblemRecipe),
+    Drift(self::drift::DriftProblemRecipe),
+    Resample(self::resample::ResampleProblemRecipe),
 }
 impl ProblemRecipe for InnerRecipe {
     type Factory = BoxProblemFactory;
@@ -99,11 +144,23 @@ impl ProblemRecipe for InnerRecipe {
             Self::Hpobench(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Zdt(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Surrogate(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Conditional(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Study(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Rank(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Average(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::Ln(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Rotate(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Pad(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
             Self::WarmStarting(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Normalize(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Discretize(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Constrain(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Fidelity(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Compose(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Embed(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Cache(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Drift(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
+            Self::Resample(p) => track!(p.create_factory(registry).map(BoxProblemFactory::new)),
         }
     }
 }
@@ -1,8 +1,8 @@
 use kurobako_core::domain::VariableBuilder;
 use kurobako_core::json::JsonRecipe;
 use kurobako_core::problem::{
-    BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory, ProblemRecipe,
-    ProblemSpec, ProblemSpecBuilder,
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec, ProblemSpecBuilder,
 };
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
@@ -183,6 +183,10 @@ impl Evaluator for StudyEvaluator {
             }
         }
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        AbortHandle::all(self.evaluators.iter().map(|e| e.inner.abort_handle()))
+    }
 }
 
 #[derive(Debug)]
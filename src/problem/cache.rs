@@ -0,0 +1,192 @@
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{Error, Result};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use structopt::StructOpt;
+
+lazy_static! {
+    static ref SHARED_CACHES: Mutex<HashMap<PathBuf, Arc<Mutex<Cache>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Wraps the given problem to memoize its evaluation results in the cache file at `path`.
+///
+/// The cache is shared (in memory) by all the problems wrapped with the same path in this process.
+pub fn with_shared_cache(problem: BoxProblem, path: &Path) -> Result<BoxProblem> {
+    let mut caches = track!(SHARED_CACHES.lock().map_err(Error::from))?;
+    let cache = if let Some(cache) = caches.get(path) {
+        Arc::clone(cache)
+    } else {
+        let cache = Arc::new(Mutex::new(track!(Cache::new(Some(path.to_owned())))?));
+        caches.insert(path.to_owned(), Arc::clone(&cache));
+        cache
+    };
+    Ok(BoxProblem::new(CacheProblem { problem, cache }))
+}
+
+/// Recipe to memoize the evaluation results of a problem.
+///
+/// If the same parameter set is evaluated at the same step more than once,
+/// the inner problem is evaluated only the first time.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct CacheProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Path of the file to persist the cache (JSON Lines).
+    ///
+    /// If the file already exists, the cached entries in it are loaded when the problem is created.
+    /// Note that the file must not be shared by different problems.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+impl ProblemRecipe for CacheProblemRecipe {
+    type Factory = CacheProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let cache = track!(Cache::new(self.path.clone()))?;
+        Ok(CacheProblemFactory {
+            problem,
+            cache: Arc::new(Mutex::new(cache)),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheProblemFactory {
+    problem: BoxProblemFactory,
+    cache: Arc<Mutex<Cache>>,
+}
+impl ProblemFactory for CacheProblemFactory {
+    type Problem = CacheProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        track!(self.problem.specification())
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(CacheProblem {
+            problem,
+            cache: Arc::clone(&self.cache),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheProblem {
+    problem: BoxProblem,
+    cache: Arc<Mutex<Cache>>,
+}
+impl Problem for CacheProblem {
+    type Evaluator = CacheEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let evaluator = track!(self.problem.create_evaluator(params.clone()))?;
+        Ok(CacheEvaluator {
+            evaluator,
+            cache: Arc::clone(&self.cache),
+            params,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheEvaluator {
+    evaluator: BoxEvaluator,
+    cache: Arc<Mutex<Cache>>,
+    params: Params,
+}
+impl Evaluator for CacheEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        if let Some(entry) =
+            track!(self.cache.lock().map_err(Error::from))?.get(&self.params, next_step)
+        {
+            return Ok(entry);
+        }
+
+        let (current_step, values) = track!(self.evaluator.evaluate(next_step))?;
+
+        let mut cache = track!(self.cache.lock().map_err(Error::from))?;
+        track!(cache.insert(CacheEntry {
+            params: self.params.clone(),
+            step: next_step,
+            current_step,
+            values: values.clone(),
+        }))?;
+        Ok((current_step, values))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    params: Params,
+    step: u64,
+    current_step: u64,
+    values: Values,
+}
+
+#[derive(Debug)]
+struct Cache {
+    entries: HashMap<(Params, u64), (u64, Values)>,
+    file: Option<File>,
+}
+impl Cache {
+    fn new(path: Option<PathBuf>) -> Result<Self> {
+        let mut entries = HashMap::new();
+        let file = if let Some(path) = path {
+            if path.exists() {
+                let file = track!(File::open(&path).map_err(Error::from); path)?;
+                let loaded: Vec<CacheEntry> =
+                    track!(kurobako_core::json::load(BufReader::new(file)); path)?;
+                for e in loaded {
+                    entries.insert((e.params, e.step), (e.current_step, e.values));
+                }
+            }
+            let file = track!(OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(Error::from); path)?;
+            Some(file)
+        } else {
+            None
+        };
+        Ok(Self { entries, file })
+    }
+
+    fn get(&self, params: &Params, step: u64) -> Option<(u64, Values)> {
+        self.entries.get(&(params.clone(), step)).cloned()
+    }
+
+    fn insert(&mut self, entry: CacheEntry) -> Result<()> {
+        if let Some(file) = &mut self.file {
+            track!(serde_json::to_writer(&mut *file, &entry).map_err(Error::from))?;
+            track!(writeln!(file).map_err(Error::from))?;
+        }
+        self.entries.insert(
+            (entry.params, entry.step),
+            (entry.current_step, entry.values),
+        );
+        Ok(())
+    }
+}
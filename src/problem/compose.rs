@@ -0,0 +1,232 @@
+use kurobako_core::domain::var;
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec, ProblemSpecBuilder,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Recipe for composing multiple single-objective problems that share the same search space.
+///
+/// The objective value of the composed problem is the weighted sum (or the weighted maximum)
+/// of the objective values of the inner problems.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ComposeProblemRecipe {
+    /// Problem recipe JSONs.
+    pub problems: Vec<JsonRecipe>,
+
+    /// Weights of the inner problems (the default weight is `1.0`).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weights: Vec<f64>,
+
+    /// Aggregation function.
+    #[structopt(long, default_value = "sum", possible_values = &["sum", "max"])]
+    #[serde(default)]
+    pub aggregation: Aggregation,
+}
+impl ProblemRecipe for ComposeProblemRecipe {
+    type Factory = ComposeProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(!self.problems.is_empty(), ErrorKind::InvalidInput);
+        track_assert!(
+            self.weights.is_empty() || self.weights.len() == self.problems.len(),
+            ErrorKind::InvalidInput,
+            "Expected {} weights, but got {:?}",
+            self.problems.len(),
+            self.weights
+        );
+
+        let problems = self
+            .problems
+            .iter()
+            .map(|p| track!(registry.create_problem_factory_from_json(p)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut specs = Vec::<ProblemSpec>::new();
+        for p in &problems {
+            let spec = track!(p.specification())?;
+            track_assert_eq!(spec.values_domain.len(), 1, ErrorKind::InvalidInput; spec.name);
+            if let Some(first) = specs.first() {
+                track_assert_eq!(
+                    first.params_domain,
+                    spec.params_domain,
+                    ErrorKind::InvalidInput
+                );
+                track_assert_eq!(first.steps, spec.steps, ErrorKind::InvalidInput);
+            }
+            specs.push(spec);
+        }
+
+        let weights = if self.weights.is_empty() {
+            vec![1.0; problems.len()]
+        } else {
+            self.weights.clone()
+        };
+        Ok(ComposeProblemFactory {
+            problems,
+            specs,
+            weights,
+            aggregation: self.aggregation,
+        })
+    }
+}
+
+/// Aggregation function used to compose objective values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Aggregation {
+    /// Weighted sum.
+    Sum,
+
+    /// Weighted maximum.
+    Max,
+}
+impl Default for Aggregation {
+    fn default() -> Self {
+        Self::Sum
+    }
+}
+impl FromStr for Aggregation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sum" => Ok(Self::Sum),
+            "max" => Ok(Self::Max),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown aggregation: {:?}", s),
+        }
+    }
+}
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sum => write!(f, "sum"),
+            Self::Max => write!(f, "max"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ComposeProblemFactory {
+    problems: Vec<BoxProblemFactory>,
+    specs: Vec<ProblemSpec>,
+    weights: Vec<f64>,
+    aggregation: Aggregation,
+}
+impl ProblemFactory for ComposeProblemFactory {
+    type Problem = ComposeProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let names = self
+            .specs
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        let mut builder = ProblemSpecBuilder::new(&format!(
+            "Composition ({}) of {}",
+            self.aggregation,
+            names.join(", ")
+        ))
+        .attr("compose.weights", &format!("{:?}", self.weights))
+        .value(var("Objective Value"))
+        .steps(self.specs[0].steps.iter());
+
+        for inner_spec in &self.specs {
+            for (k, v) in &inner_spec.attrs {
+                builder = builder.attr(&format!("{}.{}", inner_spec.name, k), v);
+            }
+        }
+
+        builder = builder.params(
+            self.specs[0]
+                .params_domain
+                .variables()
+                .iter()
+                .cloned()
+                .map(From::from)
+                .collect(),
+        );
+
+        track!(builder.finish())
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problems = self
+            .problems
+            .iter()
+            .map(|p| track!(p.create_problem(rng.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ComposeProblem {
+            problems,
+            weights: self.weights.clone(),
+            aggregation: self.aggregation,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ComposeProblem {
+    problems: Vec<BoxProblem>,
+    weights: Vec<f64>,
+    aggregation: Aggregation,
+}
+impl Problem for ComposeProblem {
+    type Evaluator = ComposeEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let evaluators = self
+            .problems
+            .iter()
+            .map(|p| track!(p.create_evaluator(params.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ComposeEvaluator {
+            evaluators,
+            weights: self.weights.clone(),
+            aggregation: self.aggregation,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ComposeEvaluator {
+    evaluators: Vec<BoxEvaluator>,
+    weights: Vec<f64>,
+    aggregation: Aggregation,
+}
+impl Evaluator for ComposeEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        let mut current_step = next_step;
+        let mut weighted_values = Vec::with_capacity(self.evaluators.len());
+        for (evaluator, weight) in self.evaluators.iter_mut().zip(self.weights.iter()) {
+            let (step, values) = track!(evaluator.evaluate(next_step))?;
+            if values.is_empty() {
+                return Ok((step, values));
+            }
+            current_step = step;
+            weighted_values.push(values[0] * weight);
+        }
+
+        let value = match self.aggregation {
+            Aggregation::Sum => weighted_values.iter().sum(),
+            Aggregation::Max => weighted_values
+                .iter()
+                .copied()
+                .fold(std::f64::NEG_INFINITY, f64::max),
+        };
+        Ok((current_step, Values::new(vec![value])))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        AbortHandle::all(self.evaluators.iter().map(|e| e.abort_handle()))
+    }
+}
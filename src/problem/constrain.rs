@@ -0,0 +1,117 @@
+use kurobako_core::domain::{Constraint, ConstraintViolation, Variable};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+use trackable::error::ErrorKindExt;
+
+/// Recipe to attach analytic constraints to a problem.
+///
+/// Each constraint is a Lua expression in which the parameters of the inner problem can be accessed by their names.
+/// The expression returns either a boolean value (e.g., `x1^2 + x2^2 <= 1.0`)
+/// or a number `g` that represents the constraint `g <= 0` (e.g., `x1^2 + x2^2 - 1.0`).
+///
+/// Because the trial protocol doesn't have a channel for constraint values yet,
+/// a parameter set that violates any of the constraints is reported as unevaluable to the solver.
+/// The violated trials are counted in the `infeasible_trials` field of the study record,
+/// and the sum of their violation amounts (`g` for numeric constraints and `1` for boolean ones)
+/// is recorded in the `constraint_violation` field.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ConstrainProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Constraint expressions (Lua scripts that return a boolean value).
+    #[structopt(long)]
+    pub constraints: Vec<String>,
+}
+impl ProblemRecipe for ConstrainProblemRecipe {
+    type Factory = ConstrainProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(!self.constraints.is_empty(), ErrorKind::InvalidInput);
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        Ok(ConstrainProblemFactory {
+            problem,
+            constraints: self.constraints.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ConstrainProblemFactory {
+    problem: BoxProblemFactory,
+    constraints: Vec<String>,
+}
+impl ProblemFactory for ConstrainProblemFactory {
+    type Problem = ConstrainProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        for (i, c) in self.constraints.iter().enumerate() {
+            spec.attrs.insert(format!("constrain.{}", i), c.clone());
+        }
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let spec = track!(self.problem.specification())?;
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(ConstrainProblem {
+            problem,
+            vars: spec.params_domain.variables().to_vec(),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|c| Constraint::new(c))
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ConstrainProblem {
+    problem: BoxProblem,
+    vars: Vec<Variable>,
+    constraints: Vec<Constraint>,
+}
+impl Problem for ConstrainProblem {
+    type Evaluator = ConstrainEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let mut violation = 0.0;
+        for c in &self.constraints {
+            violation += track!(c.violation(&self.vars, &params))?;
+        }
+        if violation > 0.0 {
+            let e = ErrorKind::UnevaluableParams.cause(ConstraintViolation(violation));
+            return Err(track!(Error::from(e); params));
+        }
+
+        let evaluator = track!(self.problem.create_evaluator(params))?;
+        Ok(ConstrainEvaluator { evaluator })
+    }
+}
+
+#[derive(Debug)]
+pub struct ConstrainEvaluator {
+    evaluator: BoxEvaluator,
+}
+impl Evaluator for ConstrainEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        track!(self.evaluator.evaluate(next_step))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
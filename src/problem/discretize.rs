@@ -0,0 +1,143 @@
+use kurobako_core::domain::Range;
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+/// Recipe to snap the continuous parameters and/or the objective values of a problem to a grid.
+///
+/// A resolution `res` means that the grid has `res` points per unit (i.e., the grid interval is `1 / res`).
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct DiscretizeProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Resolution of the grid to which the continuous parameters are snapped (rounded).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub param_res: Option<f64>,
+
+    /// Resolution of the grid to which the objective values are snapped (floored).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_res: Option<f64>,
+}
+impl ProblemRecipe for DiscretizeProblemRecipe {
+    type Factory = DiscretizeProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(
+            self.param_res.is_some() || self.value_res.is_some(),
+            ErrorKind::InvalidInput,
+            "At least one of `param_res` or `value_res` must be specified"
+        );
+        for res in self.param_res.iter().chain(self.value_res.iter()) {
+            track_assert!(*res > 0.0, ErrorKind::InvalidInput; res);
+        }
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        Ok(DiscretizeProblemFactory {
+            problem,
+            param_res: self.param_res,
+            value_res: self.value_res,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DiscretizeProblemFactory {
+    problem: BoxProblemFactory,
+    param_res: Option<f64>,
+    value_res: Option<f64>,
+}
+impl ProblemFactory for DiscretizeProblemFactory {
+    type Problem = DiscretizeProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        if let Some(res) = self.param_res {
+            spec.attrs
+                .insert("discretize.param_res".to_owned(), res.to_string());
+        }
+        if let Some(res) = self.value_res {
+            spec.attrs
+                .insert("discretize.value_res".to_owned(), res.to_string());
+        }
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let spec = track!(self.problem.specification())?;
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(DiscretizeProblem {
+            problem,
+            ranges: spec
+                .params_domain
+                .variables()
+                .iter()
+                .map(|v| v.range().clone())
+                .collect(),
+            param_res: self.param_res,
+            value_res: self.value_res,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DiscretizeProblem {
+    problem: BoxProblem,
+    ranges: Vec<Range>,
+    param_res: Option<f64>,
+    value_res: Option<f64>,
+}
+impl Problem for DiscretizeProblem {
+    type Evaluator = DiscretizeEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let mut params = params.into_vec();
+        if let Some(res) = self.param_res {
+            for (p, range) in params.iter_mut().zip(self.ranges.iter()) {
+                if let Range::Continuous { low, high } = *range {
+                    if p.is_finite() {
+                        *p = ((*p * res).round() / res).max(low).min(high);
+                    }
+                }
+            }
+        }
+
+        let evaluator = track!(self.problem.create_evaluator(Params::new(params)))?;
+        Ok(DiscretizeEvaluator {
+            evaluator,
+            value_res: self.value_res,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DiscretizeEvaluator {
+    evaluator: BoxEvaluator,
+    value_res: Option<f64>,
+}
+impl Evaluator for DiscretizeEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        let (current_step, values) = track!(self.evaluator.evaluate(next_step))?;
+        if let Some(res) = self.value_res {
+            let values = values.iter().map(|v| (v * res).floor() / res).collect();
+            Ok((current_step, Values::new(values)))
+        } else {
+            Ok((current_step, values))
+        }
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
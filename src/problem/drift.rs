@@ -0,0 +1,153 @@
+use kurobako_core::domain::{Distribution, Range};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{ArcRng, Rng};
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Recipe to make the optimum of a problem drift as the number of evaluated trials increases.
+///
+/// The continuous parameters are shifted by `t * speed` (relative to the width of each range)
+/// along a seeded random direction before being passed to the inner problem,
+/// where `t` is the number of the trials created so far by the problem instance.
+/// The shifted parameters wrap around within their ranges.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct DriftProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Shift amount per trial, relative to the width of each parameter range.
+    #[structopt(long, default_value = "0.001")]
+    pub speed: f64,
+
+    /// Random seed used to generate the drift direction.
+    #[structopt(long, default_value = "0")]
+    #[serde(default)]
+    pub seed: u64,
+}
+impl ProblemRecipe for DriftProblemRecipe {
+    type Factory = DriftProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(self.speed.is_finite(), ErrorKind::InvalidInput; self.speed);
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let spec = track!(problem.specification())?;
+
+        let mut rng = ArcRng::new(self.seed);
+        let targets = spec
+            .params_domain
+            .variables()
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.constraint().is_none() && v.distribution() == Distribution::Uniform)
+            .filter_map(|(i, v)| {
+                if let Range::Continuous { low, high } = *v.range() {
+                    if low.is_finite() && high.is_finite() {
+                        return Some((i, low, high));
+                    }
+                }
+                None
+            })
+            .map(|(i, low, high)| {
+                let direction = if rng.gen() { 1.0 } else { -1.0 };
+                (i, low, high, direction * rng.gen_range(0.5..=1.0))
+            })
+            .collect::<Vec<_>>();
+        track_assert!(
+            !targets.is_empty(),
+            ErrorKind::InvalidInput,
+            "The problem {:?} has no drift-able continuous parameters",
+            spec.name
+        );
+
+        Ok(DriftProblemFactory {
+            problem,
+            speed: self.speed,
+            seed: self.seed,
+            targets: Arc::new(targets),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DriftProblemFactory {
+    problem: BoxProblemFactory,
+    speed: f64,
+    seed: u64,
+    targets: Arc<Vec<(usize, f64, f64, f64)>>,
+}
+impl ProblemFactory for DriftProblemFactory {
+    type Problem = DriftProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        spec.attrs
+            .insert("drift.speed".to_owned(), self.speed.to_string());
+        spec.attrs
+            .insert("drift.seed".to_owned(), self.seed.to_string());
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(DriftProblem {
+            problem,
+            speed: self.speed,
+            targets: Arc::clone(&self.targets),
+            trials: AtomicU64::new(0),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DriftProblem {
+    problem: BoxProblem,
+    speed: f64,
+    targets: Arc<Vec<(usize, f64, f64, f64)>>,
+    trials: AtomicU64,
+}
+impl Problem for DriftProblem {
+    type Evaluator = DriftEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let t = self.trials.fetch_add(1, atomic::Ordering::SeqCst) as f64;
+
+        let mut params = params.into_vec();
+        for &(i, low, high, direction) in self.targets.iter() {
+            if !params[i].is_finite() {
+                continue;
+            }
+
+            let width = high - low;
+            let shifted = params[i] - low + t * self.speed * direction * width;
+            params[i] = low + shifted.rem_euclid(width);
+        }
+
+        let evaluator = track!(self.problem.create_evaluator(Params::new(params)))?;
+        Ok(DriftEvaluator { evaluator })
+    }
+}
+
+#[derive(Debug)]
+pub struct DriftEvaluator {
+    evaluator: BoxEvaluator,
+}
+impl Evaluator for DriftEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        track!(self.evaluator.evaluate(next_step))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
@@ -0,0 +1,172 @@
+use kurobako_core::domain::{self, Distribution, Range};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Recipe to embed a low-dimensional problem into a high-dimensional search space.
+///
+/// The parameters of the embedded problem are `y ∈ [-1.0, 1.0]^dims` and
+/// the (normalized) parameters of the inner problem are computed as `clip(A y)`,
+/// where `A` is a seeded random Gaussian matrix scaled by `1 / sqrt(dims)`.
+/// Thus, the effective dimension of the resulting problem equals to that of the inner problem.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct EmbedProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Number of the parameters of the embedded problem.
+    #[structopt(long)]
+    pub dims: usize,
+
+    /// Random seed used to generate the embedding matrix.
+    #[structopt(long, default_value = "0")]
+    #[serde(default)]
+    pub seed: u64,
+}
+impl ProblemRecipe for EmbedProblemRecipe {
+    type Factory = EmbedProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let spec = track!(problem.specification())?;
+
+        let mut bounds = Vec::new();
+        for v in spec.params_domain.variables() {
+            track_assert!(
+                v.constraint().is_none() && v.distribution() == Distribution::Uniform,
+                ErrorKind::InvalidInput,
+                "Unembeddable parameter: {:?}",
+                v
+            );
+            if let Range::Continuous { low, high } = *v.range() {
+                track_assert!(
+                    low.is_finite() && high.is_finite(),
+                    ErrorKind::InvalidInput; v
+                );
+                bounds.push((low, high));
+            } else {
+                track_panic!(ErrorKind::InvalidInput, "Unembeddable parameter: {:?}", v);
+            }
+        }
+        track_assert!(
+            bounds.len() <= self.dims,
+            ErrorKind::InvalidInput,
+            "`dims` must be greater than or equal to {}",
+            bounds.len()
+        );
+
+        let mut rng = ArcRng::new(self.seed);
+        let scale = (self.dims as f64).sqrt();
+        let matrix = (0..bounds.len())
+            .map(|_| {
+                (0..self.dims)
+                    .map(|_| standard_normal(&mut rng) / scale)
+                    .collect()
+            })
+            .collect();
+
+        Ok(EmbedProblemFactory {
+            problem,
+            dims: self.dims,
+            seed: self.seed,
+            embedding: Arc::new(Embedding { bounds, matrix }),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EmbedProblemFactory {
+    problem: BoxProblemFactory,
+    dims: usize,
+    seed: u64,
+    embedding: Arc<Embedding>,
+}
+impl ProblemFactory for EmbedProblemFactory {
+    type Problem = EmbedProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        let vars = (0..self.dims)
+            .map(|i| domain::var(&format!("y{}", i)).continuous(-1.0, 1.0))
+            .collect();
+        spec.params_domain = track!(domain::Domain::new(vars))?;
+        spec.attrs
+            .insert("embed.dims".to_owned(), self.dims.to_string());
+        spec.attrs
+            .insert("embed.seed".to_owned(), self.seed.to_string());
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(EmbedProblem {
+            problem,
+            embedding: Arc::clone(&self.embedding),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EmbedProblem {
+    problem: BoxProblem,
+    embedding: Arc<Embedding>,
+}
+impl Problem for EmbedProblem {
+    type Evaluator = EmbedEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let params = self.embedding.apply(&params);
+        let evaluator = track!(self.problem.create_evaluator(Params::new(params)))?;
+        Ok(EmbedEvaluator { evaluator })
+    }
+}
+
+#[derive(Debug)]
+pub struct EmbedEvaluator {
+    evaluator: BoxEvaluator,
+}
+impl Evaluator for EmbedEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        track!(self.evaluator.evaluate(next_step))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
+
+#[derive(Debug)]
+struct Embedding {
+    bounds: Vec<(f64, f64)>,
+    matrix: Vec<Vec<f64>>,
+}
+impl Embedding {
+    fn apply(&self, params: &[f64]) -> Vec<f64> {
+        self.bounds
+            .iter()
+            .zip(self.matrix.iter())
+            .map(|(&(low, high), row)| {
+                let x = row
+                    .iter()
+                    .zip(params.iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    .max(-1.0)
+                    .min(1.0);
+                let center = (low + high) / 2.0;
+                let half_width = (high - low) / 2.0;
+                (center + x * half_width).max(low).min(high)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,155 @@
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, EvaluableSteps, Evaluator, Problem,
+    ProblemFactory, ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng};
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use structopt::StructOpt;
+
+/// Recipe to turn a single-step problem into a multi-step (multi-fidelity) problem.
+///
+/// The value reported at step `s` of `S` is `v + (1 - s / S)^exponent * (bias + noise * z)`,
+/// where `v` is the value of the inner problem and `z` is a standard normal random number.
+/// That is, the approximation error vanishes at the final step.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct FidelityProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Number of the evaluation steps.
+    #[structopt(long, default_value = "100")]
+    pub steps: u64,
+
+    /// Bias added to the objective values at the first step.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default)]
+    pub bias: f64,
+
+    /// Standard deviation of the noise added to the objective values at the first step.
+    #[structopt(long, default_value = "0.0")]
+    #[serde(default)]
+    pub noise: f64,
+
+    /// Exponent that controls how fast the approximation error decays.
+    #[structopt(long, default_value = "1.0")]
+    #[serde(default = "default_exponent")]
+    pub exponent: f64,
+}
+impl ProblemRecipe for FidelityProblemRecipe {
+    type Factory = FidelityProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert_ne!(self.steps, 0, ErrorKind::InvalidInput);
+        track_assert!(self.noise >= 0.0, ErrorKind::InvalidInput; self.noise);
+        track_assert!(self.exponent > 0.0, ErrorKind::InvalidInput; self.exponent);
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let spec = track!(problem.specification())?;
+        track_assert_eq!(
+            spec.steps.last(),
+            1,
+            ErrorKind::InvalidInput,
+            "The problem {:?} isn't a single-step problem",
+            spec.name
+        );
+
+        Ok(FidelityProblemFactory {
+            problem,
+            recipe: self.clone(),
+        })
+    }
+}
+
+fn default_exponent() -> f64 {
+    1.0
+}
+
+#[derive(Debug)]
+pub struct FidelityProblemFactory {
+    problem: BoxProblemFactory,
+    recipe: FidelityProblemRecipe,
+}
+impl ProblemFactory for FidelityProblemFactory {
+    type Problem = FidelityProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        spec.steps = track!(EvaluableSteps::new((1..=self.recipe.steps).collect()))?;
+        spec.attrs
+            .insert("fidelity.bias".to_owned(), self.recipe.bias.to_string());
+        spec.attrs
+            .insert("fidelity.noise".to_owned(), self.recipe.noise.to_string());
+        spec.attrs.insert(
+            "fidelity.exponent".to_owned(),
+            self.recipe.exponent.to_string(),
+        );
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng.clone()))?;
+        Ok(FidelityProblem {
+            problem,
+            rng,
+            recipe: self.recipe.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FidelityProblem {
+    problem: BoxProblem,
+    rng: ArcRng,
+    recipe: FidelityProblemRecipe,
+}
+impl Problem for FidelityProblem {
+    type Evaluator = FidelityEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let evaluator = track!(self.problem.create_evaluator(params))?;
+        Ok(FidelityEvaluator {
+            evaluator,
+            values: None,
+            rng: self.rng.clone(),
+            recipe: self.recipe.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FidelityEvaluator {
+    evaluator: BoxEvaluator,
+    values: Option<Values>,
+    rng: ArcRng,
+    recipe: FidelityProblemRecipe,
+}
+impl Evaluator for FidelityEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        if self.values.is_none() {
+            let (_, values) = track!(self.evaluator.evaluate(1))?;
+            self.values = Some(values);
+        }
+        let values = self.values.as_ref().unwrap_or_else(|| unreachable!());
+
+        let current_step = cmp::min(next_step, self.recipe.steps);
+        let scale =
+            (1.0 - current_step as f64 / self.recipe.steps as f64).powf(self.recipe.exponent);
+        let rng = &mut self.rng;
+        let recipe = &self.recipe;
+        let values = values
+            .iter()
+            .map(|v| v + scale * (recipe.bias + recipe.noise * standard_normal(rng)))
+            .collect();
+        Ok((current_step, Values::new(values)))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
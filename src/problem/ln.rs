@@ -1,8 +1,8 @@
 use kurobako_core::domain::{self, Distribution, Domain, Range, VariableBuilder};
 use kurobako_core::json::JsonRecipe;
 use kurobako_core::problem::{
-    BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory, ProblemRecipe,
-    ProblemSpec,
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
 };
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
@@ -105,4 +105,8 @@ impl Evaluator for LnEvaluator {
     fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
         track!(self.evaluator.evaluate(next_step))
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
 }
@@ -0,0 +1,167 @@
+use kurobako_core::domain;
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Recipe to rescale the objective values of a problem into `[0.0, 1.0]`.
+///
+/// The bounds used for the rescaling are determined as follows (in order of priority):
+/// 1. The `--min` and `--max` options (e.g., known optimum and worst values)
+/// 2. The finite range of the value domain of the inner problem
+///
+/// The bounds are fixed so that the normalized values of different studies are comparable.
+/// It is an error if the bounds of an objective can't be determined (or `min >= max`).
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct NormalizeProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Known minimum (optimum) of each objective value.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub min: Vec<f64>,
+
+    /// Known maximum (worst) of each objective value.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub max: Vec<f64>,
+}
+impl ProblemRecipe for NormalizeProblemRecipe {
+    type Factory = NormalizeProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let spec = track!(problem.specification())?;
+        let dim = spec.values_domain.len();
+
+        for bounds in &[&self.min, &self.max] {
+            track_assert!(
+                bounds.is_empty() || bounds.len() == dim,
+                ErrorKind::InvalidInput,
+                "Expected {} bounds, but got {:?}",
+                dim,
+                bounds
+            );
+        }
+
+        let mut bounds = Vec::with_capacity(dim);
+        for (i, v) in spec.values_domain.variables().iter().enumerate() {
+            let low = self.min.get(i).copied().unwrap_or_else(|| v.range().low());
+            let high = self.max.get(i).copied().unwrap_or_else(|| v.range().high());
+            track_assert!(
+                low.is_finite() && high.is_finite(),
+                ErrorKind::InvalidInput,
+                "The bounds of {:?} are unknown (please specify `--min` and `--max`): [{}, {}]",
+                v.name(),
+                low,
+                high
+            );
+            track_assert!(
+                low < high,
+                ErrorKind::InvalidInput,
+                "The bounds of {:?} are empty: [{}, {}]",
+                v.name(),
+                low,
+                high
+            );
+            bounds.push(Bounds { low, high });
+        }
+
+        Ok(NormalizeProblemFactory { problem, bounds })
+    }
+}
+
+#[derive(Debug)]
+pub struct NormalizeProblemFactory {
+    problem: BoxProblemFactory,
+    bounds: Vec<Bounds>,
+}
+impl ProblemFactory for NormalizeProblemFactory {
+    type Problem = NormalizeProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+
+        let vars = spec
+            .values_domain
+            .variables()
+            .iter()
+            .map(|v| domain::var(&format!("normalized({})", v.name())).continuous(0.0, 1.0))
+            .collect();
+        spec.values_domain = track!(domain::Domain::new(vars))?;
+        spec.reference_point = None;
+
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(NormalizeProblem {
+            problem,
+            bounds: Arc::new(self.bounds.clone()),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NormalizeProblem {
+    problem: BoxProblem,
+    bounds: Arc<Vec<Bounds>>,
+}
+impl Problem for NormalizeProblem {
+    type Evaluator = NormalizeEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let evaluator = track!(self.problem.create_evaluator(params))?;
+        Ok(NormalizeEvaluator {
+            evaluator,
+            bounds: Arc::clone(&self.bounds),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NormalizeEvaluator {
+    evaluator: BoxEvaluator,
+    bounds: Arc<Vec<Bounds>>,
+}
+impl Evaluator for NormalizeEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        let (current_step, values) = track!(self.evaluator.evaluate(next_step))?;
+
+        let values = values
+            .iter()
+            .zip(self.bounds.iter())
+            .map(|(&v, b)| b.normalize(v))
+            .collect();
+        Ok((current_step, Values::new(values)))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bounds {
+    low: f64,
+    high: f64,
+}
+impl Bounds {
+    fn normalize(&self, v: f64) -> f64 {
+        if !v.is_finite() {
+            return v;
+        }
+        ((v - self.low) / (self.high - self.low)).clamp(0.0, 1.0)
+    }
+}
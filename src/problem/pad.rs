@@ -0,0 +1,126 @@
+use kurobako_core::domain::{self, VariableBuilder};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+/// Recipe to append irrelevant (dummy) parameters to the search space of a problem.
+///
+/// The values of the dummy parameters are ignored when evaluating the inner problem.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct PadProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Number of dummy parameters to be appended.
+    #[structopt(long, default_value = "1")]
+    #[serde(default = "default_dims")]
+    pub dims: usize,
+}
+impl ProblemRecipe for PadProblemRecipe {
+    type Factory = PadProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert_ne!(self.dims, 0, ErrorKind::InvalidInput);
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let mut spec = track!(problem.specification())?;
+        let inner_dims = spec.params_domain.len();
+
+        let mut vars = spec
+            .params_domain
+            .variables()
+            .iter()
+            .cloned()
+            .map(VariableBuilder::from)
+            .collect::<Vec<_>>();
+        for i in 0..self.dims {
+            let name = format!("dummy{}", i);
+            track_assert!(
+                spec.params_domain
+                    .variables()
+                    .iter()
+                    .all(|v| v.name() != name),
+                ErrorKind::InvalidInput,
+                "The dummy parameter name {:?} collides with a parameter of the problem {:?}",
+                name,
+                spec.name
+            );
+            vars.push(domain::var(&name).continuous(0.0, 1.0));
+        }
+        spec.params_domain = track!(domain::Domain::new(vars))?;
+        spec.attrs
+            .insert("pad.dims".to_owned(), self.dims.to_string());
+
+        Ok(PadProblemFactory {
+            problem,
+            spec,
+            inner_dims,
+        })
+    }
+}
+
+fn default_dims() -> usize {
+    1
+}
+
+#[derive(Debug)]
+pub struct PadProblemFactory {
+    problem: BoxProblemFactory,
+    spec: ProblemSpec,
+    inner_dims: usize,
+}
+impl ProblemFactory for PadProblemFactory {
+    type Problem = PadProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        Ok(self.spec.clone())
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(PadProblem {
+            problem,
+            inner_dims: self.inner_dims,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PadProblem {
+    problem: BoxProblem,
+    inner_dims: usize,
+}
+impl Problem for PadProblem {
+    type Evaluator = PadEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let mut params = params.into_vec();
+        params.truncate(self.inner_dims);
+
+        let evaluator = track!(self.problem.create_evaluator(Params::new(params)))?;
+        Ok(PadEvaluator { evaluator })
+    }
+}
+
+#[derive(Debug)]
+pub struct PadEvaluator {
+    evaluator: BoxEvaluator,
+}
+impl Evaluator for PadEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        track!(self.evaluator.evaluate(next_step))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
@@ -1,9 +1,9 @@
-use crate::record::{ProblemRecord, StudyRecord};
+use crate::record::{self, ProblemRecord, StudyRecord};
 use kurobako_core::domain;
-use kurobako_core::json::{self, JsonRecipe};
+use kurobako_core::json::JsonRecipe;
 use kurobako_core::problem::{
-    BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory, ProblemRecipe,
-    ProblemSpec, ProblemSpecBuilder,
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec, ProblemSpecBuilder,
 };
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
@@ -39,7 +39,7 @@ impl RankProblemRecipe {
         let mut studies = Vec::new();
         for path in &self.baselines {
             let file = track!(File::open(path).map_err(Error::from); path)?;
-            let temp_studies: Vec<StudyRecord> = track!(json::load(BufReader::new(file)); path)?;
+            let temp_studies = track!(record::load_studies(BufReader::new(file)); path)?;
             for study in temp_studies {
                 if track!(study.problem.id())? == inner_problem_id {
                     studies.push(study);
@@ -143,6 +143,10 @@ impl Evaluator for StudyEvaluator {
         let ranks = self.baseline.rank_values(current_step, &values);
         Ok((current_step, ranks))
     }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.inner_evaluator.abort_handle()
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,153 @@
+use kurobako_core::domain::{self, VariableBuilder};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{ArcRng, Rng};
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use rustats::fundamental::{average, stddev};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+/// Recipe to evaluate a stochastic problem multiple times with different random streams
+/// and report the mean of the objective values.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ResampleProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Number of evaluations per parameter set.
+    #[structopt(long, default_value = "10")]
+    pub samples: usize,
+
+    /// If specified, the standard deviation of the objective value is reported as the second objective.
+    ///
+    /// This is only available for single-objective problems.
+    #[structopt(long)]
+    #[serde(default)]
+    pub with_stddev: bool,
+}
+impl ProblemRecipe for ResampleProblemRecipe {
+    type Factory = ResampleProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert_ne!(self.samples, 0, ErrorKind::InvalidInput);
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        if self.with_stddev {
+            let spec = track!(problem.specification())?;
+            track_assert_eq!(
+                spec.values_domain.len(),
+                1,
+                ErrorKind::InvalidInput,
+                "`with_stddev` is only available for single-objective problems"
+            );
+        }
+
+        Ok(ResampleProblemFactory {
+            problem,
+            samples: self.samples,
+            with_stddev: self.with_stddev,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ResampleProblemFactory {
+    problem: BoxProblemFactory,
+    samples: usize,
+    with_stddev: bool,
+}
+impl ProblemFactory for ResampleProblemFactory {
+    type Problem = ResampleProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        if self.with_stddev {
+            let mut vars = spec
+                .values_domain
+                .variables()
+                .iter()
+                .cloned()
+                .map(VariableBuilder::from)
+                .collect::<Vec<_>>();
+            vars.push(domain::var("Standard Deviation").continuous(0.0, std::f64::INFINITY));
+            spec.values_domain = track!(domain::Domain::new(vars))?;
+            spec.reference_point = None;
+        }
+        spec.attrs
+            .insert("resample.samples".to_owned(), self.samples.to_string());
+        Ok(spec)
+    }
+
+    fn create_problem(&self, mut rng: ArcRng) -> Result<Self::Problem> {
+        let problems = (0..self.samples)
+            .map(|_| {
+                let rng = ArcRng::new(rng.gen());
+                track!(self.problem.create_problem(rng))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ResampleProblem {
+            problems,
+            with_stddev: self.with_stddev,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ResampleProblem {
+    problems: Vec<BoxProblem>,
+    with_stddev: bool,
+}
+impl Problem for ResampleProblem {
+    type Evaluator = ResampleEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let evaluators = self
+            .problems
+            .iter()
+            .map(|p| track!(p.create_evaluator(params.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ResampleEvaluator {
+            evaluators,
+            with_stddev: self.with_stddev,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ResampleEvaluator {
+    evaluators: Vec<BoxEvaluator>,
+    with_stddev: bool,
+}
+impl Evaluator for ResampleEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        let mut current_step = next_step;
+        let mut samples = Vec::with_capacity(self.evaluators.len());
+        for evaluator in &mut self.evaluators {
+            let (step, values) = track!(evaluator.evaluate(next_step))?;
+            if values.is_empty() {
+                return Ok((step, values));
+            }
+            current_step = step;
+            samples.push(values);
+        }
+
+        let dim = samples[0].len();
+        let mut values = (0..dim)
+            .map(|i| average(samples.iter().map(|s| s[i])))
+            .collect::<Vec<_>>();
+        if self.with_stddev {
+            values.push(stddev(samples.iter().map(|s| s[0])));
+        }
+        Ok((current_step, Values::new(values)))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        AbortHandle::all(self.evaluators.iter().map(|e| e.abort_handle()))
+    }
+}
@@ -0,0 +1,239 @@
+use kurobako_core::domain::{Distribution, Range};
+use kurobako_core::json::JsonRecipe;
+use kurobako_core::problem::{
+    AbortHandle, BoxEvaluator, BoxProblem, BoxProblemFactory, Evaluator, Problem, ProblemFactory,
+    ProblemRecipe, ProblemSpec,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::{standard_normal, ArcRng, Rng};
+use kurobako_core::trial::{Params, Values};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Recipe to apply a random orthogonal rotation and shift to the continuous parameters of a problem.
+///
+/// This is useful to remove the axis-alignment bias from benchmarks of separable functions.
+///
+/// The rotation and the shift are applied to the parameters normalized into `[-1.0, 1.0]`,
+/// and the coordinates that go out of the range are reflected back at its bounds.
+/// Unlike clamping, the reflection doesn't create flat regions on the bounds,
+/// and unlike rotating within the ball inscribed in the range, it covers the whole range of the inner problem.
+/// In exchange, the regions near the corners of the range are folded,
+/// so a point of the inner problem (e.g., its optimum) may be reached from more than one parameter set.
+#[derive(Debug, Clone, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct RotateProblemRecipe {
+    /// Problem recipe JSON.
+    pub problem: JsonRecipe,
+
+    /// Random seed used to generate the rotation matrix and the shift vector.
+    #[structopt(long, default_value = "0")]
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Maximum shift of each parameter, relative to the half width of its range.
+    #[structopt(long, default_value = "0.0")]
+    #[serde(default)]
+    pub shift: f64,
+}
+impl ProblemRecipe for RotateProblemRecipe {
+    type Factory = RotateProblemFactory;
+
+    fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
+        track_assert!(
+            0.0 <= self.shift && self.shift <= 1.0,
+            ErrorKind::InvalidInput; self.shift
+        );
+
+        let problem = track!(registry.create_problem_factory_from_json(&self.problem))?;
+        let spec = track!(problem.specification())?;
+
+        let bounds = spec
+            .params_domain
+            .variables()
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.constraint().is_none() && v.distribution() == Distribution::Uniform)
+            .filter_map(|(i, v)| {
+                if let Range::Continuous { low, high } = *v.range() {
+                    if low.is_finite() && high.is_finite() {
+                        return Some((i, low, high));
+                    }
+                }
+                None
+            })
+            .collect::<Vec<_>>();
+        track_assert!(
+            !bounds.is_empty(),
+            ErrorKind::InvalidInput,
+            "The problem {:?} has no rotatable continuous parameters",
+            spec.name
+        );
+
+        let mut rng = ArcRng::new(self.seed);
+        let rotation = random_orthogonal_matrix(&mut rng, bounds.len());
+        let shift = (0..bounds.len())
+            .map(|_| rng.gen_range(-1.0..=1.0) * self.shift)
+            .collect();
+
+        Ok(RotateProblemFactory {
+            problem,
+            seed: self.seed,
+            shift_ratio: self.shift,
+            transform: Arc::new(Transform {
+                bounds,
+                rotation,
+                shift,
+            }),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RotateProblemFactory {
+    problem: BoxProblemFactory,
+    seed: u64,
+    shift_ratio: f64,
+    transform: Arc<Transform>,
+}
+impl ProblemFactory for RotateProblemFactory {
+    type Problem = RotateProblem;
+
+    fn specification(&self) -> Result<ProblemSpec> {
+        let mut spec = track!(self.problem.specification())?;
+        spec.attrs
+            .insert("rotate.seed".to_owned(), self.seed.to_string());
+        spec.attrs
+            .insert("rotate.shift".to_owned(), self.shift_ratio.to_string());
+        Ok(spec)
+    }
+
+    fn create_problem(&self, rng: ArcRng) -> Result<Self::Problem> {
+        let problem = track!(self.problem.create_problem(rng))?;
+        Ok(RotateProblem {
+            problem,
+            transform: Arc::clone(&self.transform),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RotateProblem {
+    problem: BoxProblem,
+    transform: Arc<Transform>,
+}
+impl Problem for RotateProblem {
+    type Evaluator = RotateEvaluator;
+
+    fn create_evaluator(&self, params: Params) -> Result<Self::Evaluator> {
+        let params = self.transform.apply(params.into_vec());
+        let evaluator = track!(self.problem.create_evaluator(Params::new(params)))?;
+        Ok(RotateEvaluator { evaluator })
+    }
+}
+
+#[derive(Debug)]
+pub struct RotateEvaluator {
+    evaluator: BoxEvaluator,
+}
+impl Evaluator for RotateEvaluator {
+    fn evaluate(&mut self, next_step: u64) -> Result<(u64, Values)> {
+        track!(self.evaluator.evaluate(next_step))
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        self.evaluator.abort_handle()
+    }
+}
+
+#[derive(Debug)]
+struct Transform {
+    bounds: Vec<(usize, f64, f64)>,
+    rotation: Vec<Vec<f64>>,
+    shift: Vec<f64>,
+}
+impl Transform {
+    fn apply(&self, mut params: Vec<f64>) -> Vec<f64> {
+        // Normalizes the target parameters into `[-1.0, 1.0]`.
+        let normalized = self
+            .bounds
+            .iter()
+            .map(|&(i, low, high)| {
+                let center = (low + high) / 2.0;
+                let half_width = (high - low) / 2.0;
+                (params[i] - center) / half_width
+            })
+            .collect::<Vec<_>>();
+
+        for ((&(i, low, high), row), shift) in self
+            .bounds
+            .iter()
+            .zip(self.rotation.iter())
+            .zip(self.shift.iter())
+        {
+            let rotated = row
+                .iter()
+                .zip(normalized.iter())
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                + shift;
+            let center = (low + high) / 2.0;
+            let half_width = (high - low) / 2.0;
+            params[i] = (center + reflect(rotated) * half_width).max(low).min(high);
+        }
+        params
+    }
+}
+
+// Reflects `x` back into `[-1.0, 1.0]` at the bounds (as many times as needed).
+fn reflect(x: f64) -> f64 {
+    let y = (x + 1.0).rem_euclid(4.0);
+    if y <= 2.0 {
+        y - 1.0
+    } else {
+        3.0 - y
+    }
+}
+
+fn random_orthogonal_matrix<R: Rng>(rng: &mut R, dim: usize) -> Vec<Vec<f64>> {
+    // Applies the Gram-Schmidt process to a random Gaussian matrix.
+    let mut rows: Vec<Vec<f64>> = Vec::with_capacity(dim);
+    while rows.len() < dim {
+        let mut v = (0..dim).map(|_| standard_normal(rng)).collect::<Vec<_>>();
+        for row in &rows {
+            let dot = row.iter().zip(v.iter()).map(|(a, b)| a * b).sum::<f64>();
+            for (x, r) in v.iter_mut().zip(row.iter()) {
+                *x -= dot * r;
+            }
+        }
+
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm < 1e-8 {
+            continue;
+        }
+        rows.push(v.into_iter().map(|x| x / norm).collect());
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflect_works() {
+        fn is_close(a: f64, b: f64) -> bool {
+            (a - b).abs() < 1e-12
+        }
+
+        assert!(is_close(reflect(0.3), 0.3));
+        assert!(is_close(reflect(-1.0), -1.0));
+        assert!(is_close(reflect(1.0), 1.0));
+        assert!(is_close(reflect(1.2), 0.8));
+        assert!(is_close(reflect(-1.5), -0.5));
+        assert!(is_close(reflect(3.5), -0.5));
+        assert!(is_close(reflect(-4.2), -0.2));
+    }
+}
@@ -1,9 +1,54 @@
+use kurobako_core::{json, Error, ErrorKind, Result};
+use std::io::Read;
+
+pub use self::env::EnvRecord;
+pub use self::failed::FailedStudyRecord;
 pub use self::problem::ProblemRecord;
+pub use self::schema::{migrate, SCHEMA_VERSION};
 pub use self::solver::SolverRecord;
 pub use self::study::{StudyRecord, StudyRecordBuilder};
-pub use self::trial::{EvaluationRecord, TrialRecord, TrialRecordBuilder};
+pub use self::trial::{EvaluationRecord, RungRecord, TrialRecord, TrialRecordBuilder};
 
+mod env;
+mod failed;
 mod problem;
+mod schema;
 mod solver;
 mod study;
 mod trial;
+
+/// Loads the study records from the given reader.
+///
+/// The records of the failed studies (see `FailedStudyRecord`) are skipped,
+/// and the records of older schema versions are migrated to the current one (see `migrate`).
+pub fn load_studies<R: Read>(reader: R) -> Result<Vec<StudyRecord>> {
+    let entries: Vec<serde_json::Value> = track!(json::load(reader))?;
+    entries
+        .into_iter()
+        .filter(|entry| entry.get("error").is_none())
+        .map(|mut entry| {
+            track!(migrate(&mut entry))?;
+            track!(serde_json::from_value(entry).map_err(Error::from))
+        })
+        .collect()
+}
+
+/// Truncates all the studies to the given budget (or the minimum budget among the studies if `None`)
+/// so that the studies run with different budgets can be compared fairly.
+///
+/// It's an error if the given budget exceeds the budget of any study.
+pub fn truncate_studies(studies: &mut [StudyRecord], budget: Option<u64>) -> Result<()> {
+    let min_budget = studies.iter().map(|s| s.budget).min().unwrap_or(0);
+    let budget = budget.unwrap_or(min_budget);
+    track_assert!(
+        budget <= min_budget,
+        ErrorKind::InvalidInput,
+        "The budget {} exceeds the minimum budget {} of the studies",
+        budget,
+        min_budget
+    );
+    for study in studies {
+        study.truncate(budget);
+    }
+    Ok(())
+}
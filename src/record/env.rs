@@ -0,0 +1,84 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::process::Command;
+
+lazy_static! {
+    static ref CURRENT: EnvRecord = EnvRecord::collect();
+}
+
+/// Provenance of a study (i.e., the environment in which it was run).
+///
+/// The fields that couldn't be detected are omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvRecord {
+    pub kurobako_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_describe: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_model: Option<String>,
+}
+impl EnvRecord {
+    /// Returns the environment of the current process.
+    pub fn current() -> Self {
+        CURRENT.clone()
+    }
+
+    /// Returns `true` if the results run in the both environments can be compared
+    /// (i.e., the kurobako versions, the hosts and the CPU models are the same).
+    ///
+    /// The revisions of the recipes are not considered.
+    pub fn is_comparable(&self, other: &Self) -> bool {
+        self.kurobako_version == other.kurobako_version
+            && self.hostname == other.hostname
+            && self.cpu_model == other.cpu_model
+    }
+
+    fn collect() -> Self {
+        Self {
+            kurobako_version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_describe: git_describe(),
+            hostname: hostname(),
+            cpu_model: cpu_model(),
+        }
+    }
+}
+
+// The revision of the git repository of the current directory (where the recipes are supposed to be).
+fn git_describe() -> Option<String> {
+    let output = Command::new("git")
+        .args(&["describe", "--always", "--dirty"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    non_empty(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .and_then(|s| non_empty(s.trim()))
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("model name"))
+        .filter_map(|line| line.split_once(':').map(|(_, s)| s))
+        .find_map(|s| non_empty(s.trim()))
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_owned())
+    }
+}
@@ -0,0 +1,22 @@
+use crate::study::StudyRecipe;
+use crate::time::DateTime;
+use chrono::Local;
+use kurobako_core::Error;
+use serde::{Deserialize, Serialize};
+
+/// Record of a study that failed and was skipped (see the `kurobako run --on-error` option).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedStudyRecord {
+    pub time: DateTime,
+    pub recipe: StudyRecipe,
+    pub error: String,
+}
+impl FailedStudyRecord {
+    pub fn new(recipe: StudyRecipe, error: &Error) -> Self {
+        Self {
+            time: Local::now(),
+            recipe,
+            error: error.to_string(),
+        }
+    }
+}
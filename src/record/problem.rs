@@ -24,4 +24,21 @@ impl ProblemRecord {
         }
         Ok(id)
     }
+
+    /// Returns the ID computed only from the recipe.
+    ///
+    /// Unlike `id`, this doesn't depend on the specification (e.g., the version attribute of the problem),
+    /// so the same problem run by different versions of kurobako has the same recipe ID.
+    pub fn recipe_id(&self) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(&track!(
+            serde_json::to_vec(&self.recipe).map_err(Error::from)
+        )?);
+
+        let mut id = String::with_capacity(64);
+        for b in hasher.finalize().as_slice() {
+            track_write!(&mut id, "{:02x}", b)?;
+        }
+        Ok(id)
+    }
 }
@@ -0,0 +1,124 @@
+use kurobako_core::{ErrorKind, Result};
+use serde_json::Value;
+
+/// Current version of the schema of the study records.
+///
+/// The records produced by the versions of kurobako that don't embed the schema versions
+/// are regarded as version `0`.
+///
+/// The fields added to the records so far (e.g., `infeasible_trials` and `env`) have default values
+/// and don't change the study IDs, so the records of those versions are still version `0`.
+/// When an incompatible change is made, this version is incremented
+/// and the migration from the previous version is added to `MIGRATIONS`.
+pub const SCHEMA_VERSION: u32 = 0;
+
+// `MIGRATIONS[i]` upgrades a record from version `i` to version `i + 1`.
+const MIGRATIONS: [fn(&mut Value) -> Result<()>; SCHEMA_VERSION as usize] = [];
+
+/// Upgrades the given study record (JSON) to the current schema.
+///
+/// It's an error if the record was produced by a newer version of kurobako.
+pub fn migrate(record: &mut Value) -> Result<()> {
+    let version = track!(schema_version(record))?;
+    let migrations = track_assert_some!(
+        MIGRATIONS.get(version as usize..),
+        ErrorKind::InvalidInput,
+        "Unsupported schema version: {} (the latest version is {})",
+        version,
+        SCHEMA_VERSION
+    );
+
+    for migration in migrations {
+        track!(migration(record))?;
+    }
+    record["schema_version"] = Value::from(SCHEMA_VERSION);
+    Ok(())
+}
+
+fn schema_version(record: &Value) -> Result<u32> {
+    match record.get("schema_version") {
+        None => Ok(0),
+        Some(v) => {
+            let v = track_assert_some!(v.as_u64(), ErrorKind::InvalidInput; v);
+            Ok(v as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::{load_studies, StudyRecord};
+    use kurobako_core::Error;
+
+    // A record in the format of the versions of kurobako that don't embed the schema versions.
+    const V0_RECORD: &str = r#"{
+        "start_time": "2020-06-01T10:16:14.165403208+00:00",
+        "end_time": "2020-06-01T10:16:14.170026392+00:00",
+        "seed": 16836235411143549400,
+        "budget": 20,
+        "concurrency": 1,
+        "scheduling": "RANDOM",
+        "solver": {
+            "recipe": {"random": {}},
+            "spec": {"name": "Random", "attrs": {"version": "kurobako_solvers=0.1.9"}, "capabilities": ["UNIFORM_CONTINUOUS", "CONCURRENT"]}
+        },
+        "problem": {
+            "recipe": {"sigopt": {"name": "ACKLEY"}},
+            "spec": {
+                "name": "sigopt/evalset/Ackley(dim=2)",
+                "attrs": {},
+                "params_domain": [
+                    {"name": "p0", "range": {"type": "CONTINUOUS", "low": -10.0, "high": 30.0}, "distribution": "UNIFORM"},
+                    {"name": "p1", "range": {"type": "CONTINUOUS", "low": -10.0, "high": 30.0}, "distribution": "UNIFORM"}
+                ],
+                "values_domain": [{"name": "Objective Value", "range": {"type": "CONTINUOUS"}, "distribution": "UNIFORM"}],
+                "steps": 1
+            }
+        },
+        "trials": [{
+            "thread_id": 0,
+            "params": [16.724108762499412, 17.115300781949685],
+            "evaluations": [{
+                "values": [20.69912021363307],
+                "start_step": 0,
+                "end_step": 1,
+                "ask_elapsed": 2.2235e-05,
+                "tell_elapsed": 1.009e-06,
+                "evaluate_elapsed": 3.718e-06
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn migrate_v0_record_works() -> trackable::result::TopLevelResult {
+        let original: Value = track!(serde_json::from_str(V0_RECORD).map_err(Error::from))?;
+
+        let mut migrated = original.clone();
+        track!(migrate(&mut migrated))?;
+        assert_eq!(migrated["schema_version"], Value::from(SCHEMA_VERSION));
+        for (key, value) in track_assert_some!(original.as_object(), ErrorKind::Bug) {
+            assert_eq!(&migrated[key], value);
+        }
+
+        let studies = track!(load_studies(V0_RECORD.as_bytes()))?;
+        assert_eq!(studies.len(), 1);
+        let study = &studies[0];
+        assert_eq!(study.schema_version, SCHEMA_VERSION);
+        assert_eq!(study.infeasible_trials, 0);
+        assert!(study.env.is_none());
+        assert_eq!(study.best_value(), Some(20.69912021363307));
+
+        // The study ID isn't changed by a round-trip.
+        let json = track!(serde_json::to_value(study).map_err(Error::from))?;
+        let reloaded: StudyRecord = track!(serde_json::from_value(json).map_err(Error::from))?;
+        assert_eq!(track!(reloaded.id())?, track!(study.id())?);
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_rejects_newer_versions() {
+        let mut record = serde_json::json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(migrate(&mut record).is_err());
+    }
+}
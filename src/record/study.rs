@@ -1,7 +1,8 @@
 use crate::record::{
-    EvaluationRecord, ProblemRecord, SolverRecord, TrialRecord, TrialRecordBuilder,
+    EnvRecord, EvaluationRecord, ProblemRecord, SolverRecord, TrialRecord, TrialRecordBuilder,
+    SCHEMA_VERSION,
 };
-use crate::study::{Scheduling, StudyRecipe};
+use crate::study::{Pruner, Scheduling, StudyRecipe};
 use crate::time::DateTime;
 use chrono::Local;
 use kurobako_core::hypervolume;
@@ -16,8 +17,11 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, BinaryHeap};
 use std::fmt::Write as _;
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::time::Duration;
 
+const MIN_REGRET: f64 = 1e-12;
+
 #[derive(Debug)]
 pub struct StudyRecordBuilder {
     recipe: StudyRecipe,
@@ -26,6 +30,8 @@ pub struct StudyRecordBuilder {
     start_time: DateTime,
     trials: BTreeMap<TrialId, TrialRecord>,
     pareto_frontier: BTreeMap<TrialId, (Params, Values)>,
+    infeasible_trials: u64,
+    constraint_violation: f64,
 }
 impl StudyRecordBuilder {
     pub fn new(recipe: StudyRecipe, solver: SolverSpec, problem: ProblemSpec) -> Self {
@@ -36,9 +42,36 @@ impl StudyRecordBuilder {
             start_time: Local::now(),
             trials: BTreeMap::new(),
             pareto_frontier: BTreeMap::new(),
+            infeasible_trials: 0,
+            constraint_violation: 0.0,
         }
     }
 
+    /// Counts a trial whose parameters were rejected as unevaluable (e.g., by constraints).
+    ///
+    /// `violation` is the amount of the constraint violation of the trial (`0.0` if unknown).
+    pub fn add_infeasible_trial(&mut self, violation: f64) {
+        self.infeasible_trials += 1;
+        self.constraint_violation += violation;
+    }
+
+    /// Returns the number of the infeasible trials counted so far.
+    pub fn infeasible_trials(&self) -> u64 {
+        self.infeasible_trials
+    }
+
+    /// Returns the total amount of the constraint violations counted so far.
+    pub fn constraint_violation(&self) -> f64 {
+        self.constraint_violation
+    }
+
+    /// Restores the values returned by `infeasible_trials` and `constraint_violation` methods
+    /// (e.g., when resuming a study).
+    pub fn restore_infeasible_trials(&mut self, n: u64, violation: f64) {
+        self.infeasible_trials = n;
+        self.constraint_violation = violation;
+    }
+
     pub fn add_trial(&mut self, trial: TrialRecordBuilder) {
         let t = self.trials.entry(trial.id).or_insert_with(|| TrialRecord {
             thread_id: trial.thread_id,
@@ -53,27 +86,49 @@ impl StudyRecordBuilder {
             ask_elapsed: trial.ask_elapsed,
             tell_elapsed: trial.tell_elapsed,
             evaluate_elapsed: trial.evaluate_elapsed,
+            rung: trial.rung,
         });
 
         if t.steps() == self.problem.steps.last() {
-            let is_dominated = self
-                .pareto_frontier
-                .values()
-                .any(|(_, vs)| vs.partial_cmp(&trial.values) == Some(Ordering::Less));
-            if !is_dominated {
-                let dominated = self
-                    .pareto_frontier
-                    .iter()
-                    .filter(|(_, (_, vs))| trial.values.partial_cmp(vs) == Some(Ordering::Less))
-                    .map(|(&id, _)| id)
-                    .collect::<Vec<_>>();
-
-                self.pareto_frontier
-                    .insert(trial.id, (trial.params, trial.values));
-                for id in dominated {
-                    self.pareto_frontier.remove(&id);
+            self.update_pareto_frontier(trial.id, trial.params, trial.values);
+        }
+    }
+
+    /// Returns the trials recorded so far.
+    pub fn trials(&self) -> impl '_ + Iterator<Item = (TrialId, &TrialRecord)> {
+        self.trials.iter().map(|(&id, t)| (id, t))
+    }
+
+    /// Restores the trials returned by `trials` method (e.g., when resuming a study).
+    pub fn restore_trials(&mut self, trials: Vec<(TrialId, TrialRecord)>) {
+        let last_step = self.problem.steps.last();
+        for (id, trial) in trials {
+            if trial.steps() == last_step {
+                if let Some(values) = trial.evaluations.last().map(|e| e.values.clone()) {
+                    self.update_pareto_frontier(id, trial.params.clone(), values);
                 }
             }
+            self.trials.insert(id, trial);
+        }
+    }
+
+    fn update_pareto_frontier(&mut self, trial_id: TrialId, params: Params, values: Values) {
+        let is_dominated = self
+            .pareto_frontier
+            .values()
+            .any(|(_, vs)| vs.partial_cmp(&values) == Some(Ordering::Less));
+        if !is_dominated {
+            let dominated = self
+                .pareto_frontier
+                .iter()
+                .filter(|(_, (_, vs))| values.partial_cmp(vs) == Some(Ordering::Less))
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>();
+
+            self.pareto_frontier.insert(trial_id, (params, values));
+            for id in dominated {
+                self.pareto_frontier.remove(&id);
+            }
         }
     }
 
@@ -85,12 +140,17 @@ impl StudyRecordBuilder {
 
     pub fn finish(self) -> StudyRecord {
         StudyRecord {
+            schema_version: SCHEMA_VERSION,
             start_time: self.start_time,
             end_time: Local::now(),
             budget: self.recipe.budget,
             seed: self.recipe.seed.unwrap_or_else(|| unreachable!()),
             concurrency: self.recipe.concurrency,
             scheduling: self.recipe.scheduling,
+            time_budget: self.recipe.time_budget,
+            batch_size: self.recipe.batch_size,
+            pruner: self.recipe.pruner,
+            prior: self.recipe.prior,
             solver: SolverRecord {
                 recipe: self.recipe.solver,
                 spec: self.solver,
@@ -100,21 +160,43 @@ impl StudyRecordBuilder {
                 spec: self.problem,
             },
             trials: self.trials.into_iter().map(|(_, v)| v).collect(),
+            infeasible_trials: self.infeasible_trials,
+            constraint_violation: self.constraint_violation,
+            env: Some(EnvRecord::current()),
+            stderr: None,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudyRecord {
+    #[serde(default)]
+    pub schema_version: u32,
     pub start_time: DateTime,
     pub end_time: DateTime,
     pub seed: u64,
     pub budget: u64,
     pub concurrency: NonZeroUsize,
     pub scheduling: Scheduling,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_budget: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<NonZeroUsize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruner: Option<Pruner>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior: Option<PathBuf>,
     pub solver: SolverRecord,
     pub problem: ProblemRecord,
     pub trials: Vec<TrialRecord>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub infeasible_trials: u64,
+    #[serde(default, skip_serializing_if = "is_zero_f64")]
+    pub constraint_violation: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<EnvRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
 }
 impl StudyRecord {
     pub fn id(&self) -> Result<String> {
@@ -128,6 +210,22 @@ impl StudyRecord {
         hasher.update(&track!(
             serde_json::to_vec(&self.scheduling).map_err(Error::from)
         )?);
+        if let Some(time_budget) = self.time_budget {
+            hasher.update(&track!(
+                serde_json::to_vec(&time_budget).map_err(Error::from)
+            )?);
+        }
+        if let Some(batch_size) = self.batch_size {
+            hasher.update(&track!(
+                serde_json::to_vec(&batch_size).map_err(Error::from)
+            )?);
+        }
+        if let Some(pruner) = self.pruner {
+            hasher.update(&track!(serde_json::to_vec(&pruner).map_err(Error::from))?);
+        }
+        if let Some(prior) = &self.prior {
+            hasher.update(&track!(serde_json::to_vec(prior).map_err(Error::from))?);
+        }
         hasher.update(&track!(
             serde_json::to_vec(&self.solver).map_err(Error::from)
         )?);
@@ -142,6 +240,33 @@ impl StudyRecord {
         Ok(id)
     }
 
+    /// Returns `true` if the study was run in an environment comparable with the one of `other`.
+    ///
+    /// The studies that have no environment records are comparable only with each other.
+    pub fn has_comparable_env(&self, other: &Self) -> bool {
+        match (&self.env, &other.env) {
+            (Some(a), Some(b)) => a.is_comparable(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the recipe from which the study was run.
+    pub fn recipe(&self) -> StudyRecipe {
+        StudyRecipe {
+            solver: self.solver.recipe.clone(),
+            problem: self.problem.recipe.clone(),
+            budget: self.budget,
+            concurrency: self.concurrency,
+            scheduling: self.scheduling,
+            seed: Some(self.seed),
+            time_budget: self.time_budget,
+            batch_size: self.batch_size,
+            pruner: self.pruner,
+            prior: self.prior.clone(),
+        }
+    }
+
     pub fn study_steps(&self) -> u64 {
         self.problem.spec.steps.last() * self.budget
     }
@@ -287,6 +412,27 @@ impl StudyRecord {
             .map(|x| x.0)
     }
 
+    /// Returns the known optimum value of the problem (the `optimum` attribute of the problem specification).
+    pub fn optimum(&self) -> Option<f64> {
+        self.problem
+            .spec
+            .attrs
+            .get("optimum")
+            .and_then(|v| v.parse().ok())
+    }
+
+    /// Returns the difference between the best value of the study and the known optimum value of the problem.
+    pub fn simple_regret(&self) -> Option<f64> {
+        Some((self.best_value()? - self.optimum()?).max(0.0))
+    }
+
+    /// Returns the base-10 logarithm of the simple regret.
+    ///
+    /// The regret is clamped to `1e-12` so that the optimum value yields a finite number.
+    pub fn log_regret(&self) -> Option<f64> {
+        self.simple_regret().map(|r| r.max(MIN_REGRET).log10())
+    }
+
     pub fn auc(&self, start_step: u64) -> Option<f64> {
         let vars = self.problem.spec.values_domain.variables();
         if vars.len() != 1 {
@@ -341,6 +487,25 @@ impl StudyRecord {
         self.trials.iter().map(|t| t.solver_elapsed()).sum()
     }
 
+    /// Truncates the study to the given budget as if it had been run with the budget.
+    ///
+    /// The evaluations that end after the budget are removed
+    /// (as well as the trials that have no remaining evaluations).
+    /// Note that the number of the infeasible trials (and their constraint violation) isn't changed
+    /// because when they were asked isn't recorded.
+    pub fn truncate(&mut self, budget: u64) {
+        if budget >= self.budget {
+            return;
+        }
+
+        let limit = budget * self.problem.spec.steps.last();
+        for trial in &mut self.trials {
+            trial.evaluations.retain(|e| e.end_step <= limit);
+        }
+        self.trials.retain(|t| !t.evaluations.is_empty());
+        self.budget = budget;
+    }
+
     pub fn first_complete_trial(&self) -> Option<&TrialRecord> {
         let problem_steps = self.problem.spec.steps.last();
         self.trials
@@ -349,3 +514,11 @@ impl StudyRecord {
             .min_by_key(|t| t.start_step())
     }
 }
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+fn is_zero_f64(n: &f64) -> bool {
+    *n == 0.0
+}
@@ -15,6 +15,7 @@ pub struct TrialRecordBuilder {
     pub ask_elapsed: ElapsedSeconds,
     pub tell_elapsed: ElapsedSeconds,
     pub evaluate_elapsed: ElapsedSeconds,
+    pub rung: Option<RungRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,9 +96,20 @@ pub struct EvaluationRecord {
     pub ask_elapsed: ElapsedSeconds,
     pub tell_elapsed: ElapsedSeconds,
     pub evaluate_elapsed: ElapsedSeconds,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rung: Option<RungRecord>,
 }
 impl EvaluationRecord {
     pub fn elapsed_steps(&self) -> u64 {
         self.end_step - self.start_step
     }
 }
+
+/// Rung-level data of the successive halving done by the `hyperband` runner-side pruner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RungRecord {
+    pub bracket: usize,
+    pub rung: usize,
+    pub step: u64,
+    pub promoted: bool,
+}
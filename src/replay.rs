@@ -0,0 +1,83 @@
+//! `kurobako replay` command.
+use crate::problem::KurobakoProblemRecipe;
+use crate::record::{StudyRecord, StudyRecordBuilder, TrialRecordBuilder};
+use crate::solver::KurobakoSolverRecipe;
+use crate::study::StudyRecipe;
+use crate::time::ElapsedSeconds;
+use kurobako_core::json;
+use kurobako_core::problem::{
+    Evaluator as _, Problem as _, ProblemFactory as _, ProblemRecipe as _,
+};
+use kurobako_core::registry::FactoryRegistry;
+use kurobako_core::rng::ArcRng;
+use kurobako_core::trial::{TrialId, Values};
+use kurobako_core::{ErrorKind, Result};
+use structopt::StructOpt;
+
+/// Options of the `kurobako replay` command.
+///
+/// The parameters of each trial in the given studies are re-evaluated on the given problem
+/// at its last step, and the results are output as new studies.
+/// The trials are re-evaluated in the order of their IDs regardless of their original steps,
+/// so a trial pruned in the original study is fully evaluated in the replayed one.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ReplayOpt {
+    /// Problem on which the parameters are re-evaluated.
+    ///
+    /// The problem must have the same parameters as the problems of the given studies.
+    #[structopt(long, parse(try_from_str = json::parse_json))]
+    pub problem: KurobakoProblemRecipe,
+}
+impl ReplayOpt {
+    /// Re-evaluates the trials of the given study.
+    pub fn replay(&self, study: &StudyRecord) -> Result<StudyRecord> {
+        let rng = ArcRng::new(study.seed);
+        let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
+        let problem_factory = track!(self.problem.create_factory(&registry))?;
+        let problem_spec = track!(problem_factory.specification())?;
+        track_assert_eq!(
+            problem_spec.params_domain,
+            study.problem.spec.params_domain,
+            ErrorKind::InvalidInput
+        );
+        let problem = track!(problem_factory.create_problem(rng))?;
+
+        let recipe = StudyRecipe {
+            problem: self.problem.clone(),
+            ..study.recipe()
+        };
+        let mut builder =
+            StudyRecordBuilder::new(recipe, study.solver.spec.clone(), problem_spec.clone());
+
+        let last_step = problem_spec.steps.last();
+        let mut current_step = 0;
+        for (i, trial) in study.trials.iter().enumerate() {
+            let ((end_step, values), evaluate_elapsed) = ElapsedSeconds::try_time(|| {
+                let result = problem
+                    .create_evaluator(trial.params.clone())
+                    .and_then(|mut evaluator| evaluator.evaluate(last_step));
+                match result {
+                    Err(e) if *e.kind() == ErrorKind::UnevaluableParams => {
+                        Ok((0, Values::new(Vec::new())))
+                    }
+                    result => track!(result),
+                }
+            })?;
+            builder.add_trial(TrialRecordBuilder {
+                id: TrialId::new(i as u64),
+                thread_id: trial.thread_id,
+                params: trial.params.clone(),
+                values,
+                start_step: current_step,
+                end_step: current_step + end_step,
+                ask_elapsed: ElapsedSeconds::zero(),
+                tell_elapsed: ElapsedSeconds::zero(),
+                evaluate_elapsed,
+                rung: None,
+            });
+            current_step += end_step;
+        }
+        Ok(builder.finish())
+    }
+}
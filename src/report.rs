@@ -1,20 +1,30 @@
 //! `kurobako report` command.
-use self::rankings::{Borda, Firsts};
+use self::rankings::{Borda, Firsts, Score};
 use crate::markdown as md;
 use crate::markdown::MarkdownWriter;
 use crate::record::{ProblemRecord, SolverRecord, StudyRecord};
+use crate::stats::summary::quantile;
 use kurobako_core::num::OrderedFloat;
 use kurobako_core::{Error, ErrorKind, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rustats::fundamental::{average, stddev};
 use rustats::hypothesis_testings::MannWhitneyU;
 use serde::Serialize;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::io::Write;
+use std::num::NonZeroUsize;
 use std::str::FromStr;
 use std::time::Duration;
 use structopt::StructOpt;
 
+const DEFAULT_BUDGET_FRACTIONS: [f64; 4] = [0.1, 0.25, 0.5, 1.0];
+
+const PERCENTILES: [f64; 7] = [0.0, 0.05, 0.25, 0.5, 0.75, 0.95, 1.0];
+
+mod html;
+mod latex;
 mod rankings;
 
 /// Options of the `kurobako report` command.
@@ -27,6 +37,70 @@ pub struct ReportOpt {
         possible_values = Metric::POSSIBLE_VALUES
     )]
     pub metrics: Vec<Metric>,
+
+    /// Output format.
+    #[structopt(
+        long,
+        default_value = "markdown",
+        possible_values = Format::POSSIBLE_VALUES
+    )]
+    pub format: Format,
+
+    /// Names of the solvers to be included in the report (by default, all the solvers are included).
+    #[structopt(long, number_of_values = 1)]
+    pub include_solver: Vec<String>,
+
+    /// Names of the solvers to be excluded from the report.
+    #[structopt(long, number_of_values = 1)]
+    pub exclude_solver: Vec<String>,
+
+    /// Number of the bootstrap resamples used to compute the confidence intervals of
+    /// the average best values and AUCs of the individual results.
+    ///
+    /// If `0` is specified, the standard deviations are shown instead of the confidence intervals.
+    #[structopt(long, default_value = "0")]
+    pub bootstrap: usize,
+
+    /// Confidence level of the bootstrap confidence intervals.
+    #[structopt(long, default_value = "0.95")]
+    pub confidence_level: f64,
+
+    /// Fractions of the budgets at which the solvers are ranked by their best values
+    /// (the default is `0.1`, `0.25`, `0.5` and `1.0`).
+    #[structopt(long, number_of_values = 1)]
+    pub budget_fraction: Vec<f64>,
+
+    /// Number of the bins of the histograms of the observed values.
+    #[structopt(long, default_value = "10")]
+    pub histogram_bins: NonZeroUsize,
+}
+
+/// Output format of the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Format {
+    /// Markdown document.
+    Markdown,
+
+    /// Self-contained HTML document including interactive convergence plots.
+    Html,
+
+    /// LaTeX tables (booktabs style).
+    Latex,
+}
+impl Format {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["markdown", "html", "latex"];
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            "latex" => Ok(Format::Latex),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format name: {:?}", s),
+        }
+    }
 }
 
 /// Evaluation metric.
@@ -65,15 +139,31 @@ pub struct Reporter {
 }
 impl Reporter {
     /// Makes a `Reporter` instance.
-    pub fn new(studies: Vec<StudyRecord>, mut opt: ReportOpt) -> Self {
+    pub fn new(mut studies: Vec<StudyRecord>, mut opt: ReportOpt) -> Self {
         if opt.metrics.is_empty() {
             opt.metrics = vec![Metric::BestValue, Metric::Auc];
         }
+        if opt.budget_fraction.is_empty() {
+            opt.budget_fraction = DEFAULT_BUDGET_FRACTIONS.to_vec();
+        }
+        studies.retain(|s| {
+            let name = &s.solver.spec.name;
+            (opt.include_solver.is_empty() || opt.include_solver.contains(name))
+                && !opt.exclude_solver.contains(name)
+        });
         Self { studies, opt }
     }
 
     /// Prints a full report.
-    pub fn report_all(&self, mut writer: impl Write) -> Result<()> {
+    pub fn report_all(&self, writer: impl Write) -> Result<()> {
+        match self.opt.format {
+            Format::Markdown => track!(self.report_all_markdown(writer)),
+            Format::Html => track!(self.report_all_html(writer)),
+            Format::Latex => track!(self.report_all_latex(writer)),
+        }
+    }
+
+    fn report_all_markdown(&self, mut writer: impl Write) -> Result<()> {
         let mut writer = MarkdownWriter::new(&mut writer);
         let mut writer = track!(writer.heading("Benchmark Result Report"))?;
 
@@ -91,17 +181,16 @@ impl Reporter {
             "Number of Problems: {}",
             track!(self.problems())?.count()
         )))?;
-        let metrics = self
-            .opt
-            .metrics
-            .iter()
-            .map(|m| match m {
-                Metric::BestValue => "best value",
-                Metric::Auc => "AUC",
-                Metric::ElapsedTime => "elapsed time",
-            })
-            .collect::<Vec<_>>();
-        track!(list.item(&format!("Metrics Precedence: `{}`", metrics.join(" -> "))))?;
+        track!(list.item(&format!(
+            "Metrics Precedence: `{}`",
+            self.metrics_precedence()
+        )))?;
+        if !self.is_same_env() {
+            track!(list.item(
+                "**Warning**: The studies were run in different environments \
+                 (kurobako versions, hosts or CPU models)"
+            ))?;
+        }
         track_writeln!(writer.inner_mut())?;
 
         track_writeln!(
@@ -118,6 +207,7 @@ impl Reporter {
             let mut list = writer.list().numbered();
             track!(list.item("[Overall Results](#overall-results)"))?;
             track!(list.item("[Individual Results](#individual-results)"))?;
+            track!(list.item("[Value Distributions](#value-distributions)"))?;
             track!(list.item("[Solvers](#solvers)"))?;
             track!(list.item("[Problems](#problems)"))?;
             track!(list.item("[Studies](#studies)"))?;
@@ -126,6 +216,7 @@ impl Reporter {
 
         track!(self.report_overall_results(&mut writer))?;
         track!(self.report_individual_results(&mut writer))?;
+        track!(self.report_value_distributions(&mut writer))?;
         track!(self.report_solvers(&mut writer))?;
         track!(self.report_problems(&mut writer))?;
         track!(self.report_studies(&mut writer))?;
@@ -137,33 +228,7 @@ impl Reporter {
         let mut writer = track!(writer.heading("Overall Results"))?;
         track_writeln!(writer.inner_mut())?;
 
-        let contests = track!(self.contests())?;
-        let (solver_ids, solvers): (Vec<_>, Vec<_>) = track!(self.solvers())?.unzip();
-        let mut borda_ranking = Borda::new(solver_ids.iter());
-        let mut firsts_ranking = Firsts::new(solver_ids.iter());
-        let mut excluded_problems = Vec::new();
-        let alpha = self.alpha(solvers.len());
-        for (problem_id, contest) in contests {
-            if !solver_ids
-                .iter()
-                .all(|s| contest.competitors.contains_key(s))
-            {
-                excluded_problems.push((problem_id, contest.problem));
-                continue;
-            }
-
-            borda_ranking.compete(|&a, &b| {
-                let a = &contest.competitors[a];
-                let b = &contest.competitors[b];
-                self.compete(a, b, contest.auc_start_step, alpha)
-            });
-            firsts_ranking.compete(|&a, &b| {
-                let a = &contest.competitors[a];
-                let b = &contest.competitors[b];
-                self.compete(a, b, contest.auc_start_step, alpha)
-            });
-        }
-
+        let results = track!(self.overall_results())?;
         let mut table = md::Table::new(
             vec![
                 md::ColumnHeader::new("Solver", md::Align::Left),
@@ -172,23 +237,19 @@ impl Reporter {
             ]
             .into_iter(),
         );
-
-        for (((solver_id, solver), borda), firsts) in solver_ids
-            .iter()
-            .zip(solvers.iter())
-            .zip(borda_ranking.scores())
-            .zip(firsts_ranking.scores())
-        {
+        for row in &results.rows {
             table
                 .row()
-                .item(format!("[{}](#id-{})", solver.spec.name, solver_id))
-                .item(borda)
-                .item(firsts);
+                .item(format!("[{}](#id-{})", row.solver.spec.name, row.solver_id))
+                .item(row.borda)
+                .item(row.firsts);
         }
         track!(writer.write_table(&table))?;
         track!(writer.newline())?;
 
-        if !excluded_problems.is_empty() {
+        track!(self.report_budget_rankings(&mut writer))?;
+
+        if !results.excluded_problems.is_empty() {
             let mut writer = track!(writer.heading("Note"))?;
             track!(writer.newline())?;
             track_writeln!(
@@ -198,7 +259,7 @@ impl Reporter {
             )?;
 
             let mut list = writer.list();
-            for (problem_id, problem) in excluded_problems {
+            for (problem_id, problem) in results.excluded_problems {
                 track!(list.item(&format!("[{}](#id-{})", problem.spec.name, problem_id)))?;
             }
             track!(writer.newline())?;
@@ -207,23 +268,241 @@ impl Reporter {
         Ok(())
     }
 
+    fn report_budget_rankings<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut writer = track!(writer.heading("Rankings by Budget"))?;
+        track!(writer.newline())?;
+        track_writeln!(
+            writer.inner_mut(),
+            "Borda scores of the best values found within the fractions of the budgets:"
+        )?;
+        track!(writer.newline())?;
+
+        let rankings = track!(self.budget_rankings())?;
+        let mut table = md::Table::new(
+            std::iter::once(md::ColumnHeader::new("Solver", md::Align::Left)).chain(
+                rankings
+                    .fractions
+                    .iter()
+                    .map(|f| md::ColumnHeader::new(&budget_label(*f), md::Align::Right)),
+            ),
+        );
+        for row in &rankings.rows {
+            let r = table.row();
+            r.item(format!("[{}](#id-{})", row.solver.spec.name, row.solver_id));
+            for borda in &row.bordas {
+                r.item(borda);
+            }
+        }
+        track!(writer.write_table(&table))?;
+        track!(writer.newline())?;
+        Ok(())
+    }
+
     fn report_individual_results<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
         let mut writer = track!(writer.heading("Individual Results"))?;
         track_writeln!(writer.inner_mut())?;
 
-        let contests = track!(self.contests())?;
-        for (problem_no, (problem_id, contest)) in contests.into_iter().enumerate() {
+        let results = track!(self.individual_results())?;
+        for (problem_no, result) in results.into_iter().enumerate() {
+            let mut writer = track!(writer.heading(&format!(
+                "({}) Problem: [{}](#id-{})",
+                problem_no + 1,
+                result.contest.problem.spec.name,
+                result.problem_id
+            )))?;
+
+            let mut table = md::Table::new(
+                vec![
+                    md::ColumnHeader::new("Ranking", md::Align::Right),
+                    md::ColumnHeader::new("Solver", md::Align::Left),
+                    md::ColumnHeader::new(
+                        &format!("Best ({})", self.summary_label()),
+                        md::Align::Right,
+                    ),
+                    md::ColumnHeader::new(
+                        &format!("AUC ({})", self.summary_label()),
+                        md::Align::Right,
+                    ),
+                    md::ColumnHeader::new("Elapsed (avg +- sd)", md::Align::Right),
+                ]
+                .into_iter(),
+            );
+            for row in result.rows {
+                let solver = format!(
+                    "[{}](#id-{}) ([study](#id-{}))",
+                    row.solver.spec.name, row.solver_id, row.study_id
+                );
+                table
+                    .row()
+                    .item(row.ranking)
+                    .item(solver)
+                    .item(row.best_value)
+                    .item(row.auc)
+                    .item(row.elapsed_time);
+            }
+
+            track!(writer.write_table(&table))?;
+            track_writeln!(writer.inner_mut())?;
+        }
+
+        Ok(())
+    }
+
+    fn report_value_distributions<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut writer = track!(writer.heading("Value Distributions"))?;
+        track!(writer.newline())?;
+        track_writeln!(
+            writer.inner_mut(),
+            "Distributions of the last values of all the trials \
+             (multi-objective problems are omitted):"
+        )?;
+        track!(writer.newline())?;
+
+        let distributions = track!(self.value_distributions())?;
+        for (problem_no, d) in distributions.into_iter().enumerate() {
             let mut writer = track!(writer.heading(&format!(
                 "({}) Problem: [{}](#id-{})",
                 problem_no + 1,
-                contest.problem.spec.name,
-                problem_id
+                d.problem.spec.name,
+                d.problem_id
             )))?;
+            track!(writer.newline())?;
+            track_writeln!(writer.inner_mut(), "Observed values: {}", d.count)?;
+            track!(writer.newline())?;
 
+            let mut table = md::Table::new(
+                d.percentiles
+                    .iter()
+                    .map(|(q, _)| md::ColumnHeader::new(&percentile_label(*q), md::Align::Right)),
+            );
+            let r = table.row();
+            for (_, v) in &d.percentiles {
+                r.item(format!("{:.6}", v));
+            }
+            track!(writer.write_table(&table))?;
+            track!(writer.newline())?;
+
+            let mut table = md::Table::new(
+                std::iter::once(md::ColumnHeader::new("Bin", md::Align::Left)).chain(
+                    d.solvers.iter().map(|(solver_id, solver)| {
+                        md::ColumnHeader::new(
+                            &format!("[{}](#id-{})", solver.spec.name, solver_id),
+                            md::Align::Right,
+                        )
+                    }),
+                ),
+            );
+            for bin in &d.bins {
+                let r = table.row();
+                r.item(bin_label(bin));
+                for count in &bin.counts {
+                    r.item(count);
+                }
+            }
+            track!(writer.write_table(&table))?;
+            track!(writer.newline())?;
+        }
+        Ok(())
+    }
+
+    fn overall_results(&self) -> Result<OverallResults> {
+        let contests = track!(self.contests())?;
+        let (solver_ids, solvers): (Vec<_>, Vec<_>) = track!(self.solvers())?.unzip();
+        let mut borda_ranking = Borda::new(solver_ids.iter());
+        let mut firsts_ranking = Firsts::new(solver_ids.iter());
+        let mut excluded_problems = Vec::new();
+        let alpha = self.alpha(solvers.len());
+        for (problem_id, contest) in contests {
+            if !solver_ids
+                .iter()
+                .all(|s| contest.competitors.contains_key(s))
+            {
+                excluded_problems.push((problem_id, contest.problem));
+                continue;
+            }
+
+            borda_ranking.compete(|&a, &b| {
+                let a = &contest.competitors[a];
+                let b = &contest.competitors[b];
+                self.compete(a, b, contest.auc_start_step, alpha)
+            });
+            firsts_ranking.compete(|&a, &b| {
+                let a = &contest.competitors[a];
+                let b = &contest.competitors[b];
+                self.compete(a, b, contest.auc_start_step, alpha)
+            });
+        }
+
+        let rows = solver_ids
+            .iter()
+            .zip(solvers)
+            .zip(borda_ranking.scores())
+            .zip(firsts_ranking.scores())
+            .map(|(((solver_id, solver), borda), firsts)| OverallRow {
+                solver_id: solver_id.clone(),
+                solver,
+                borda,
+                firsts,
+            })
+            .collect();
+        Ok(OverallResults {
+            rows,
+            excluded_problems,
+        })
+    }
+
+    fn budget_rankings(&self) -> Result<BudgetRankings> {
+        let fractions = self.opt.budget_fraction.clone();
+        for &f in &fractions {
+            track_assert!(0.0 < f && f <= 1.0, ErrorKind::InvalidInput; f);
+        }
+
+        let contests = track!(self.contests())?;
+        let (solver_ids, solvers): (Vec<_>, Vec<_>) = track!(self.solvers())?.unzip();
+        let alpha = self.alpha(solvers.len());
+        let mut bordas = vec![vec![0; fractions.len()]; solver_ids.len()];
+        for (i, &fraction) in fractions.iter().enumerate() {
+            let mut borda_ranking = Borda::new(solver_ids.iter());
+            for contest in contests.values() {
+                if !solver_ids
+                    .iter()
+                    .all(|s| contest.competitors.contains_key(s))
+                {
+                    continue;
+                }
+                borda_ranking.compete(|&a, &b| {
+                    let a = contest.competitors[a].best_values_within(fraction);
+                    let b = contest.competitors[b].best_values_within(fraction);
+                    MannWhitneyU::new(a, b).order(alpha)
+                });
+            }
+            for (scores, borda) in bordas.iter_mut().zip(borda_ranking.scores()) {
+                scores[i] = borda;
+            }
+        }
+
+        let rows = solver_ids
+            .into_iter()
+            .zip(solvers)
+            .zip(bordas)
+            .map(|((solver_id, solver), bordas)| BudgetRow {
+                solver_id,
+                solver,
+                bordas,
+            })
+            .collect();
+        Ok(BudgetRankings { fractions, rows })
+    }
+
+    fn individual_results(&self) -> Result<Vec<IndividualResult>> {
+        let contests = track!(self.contests())?;
+        let mut results = Vec::new();
+        for (problem_id, contest) in contests {
             // FIXME: Reduce redundant calculation.
             let auc_start_step = contest.auc_start_step;
             let mut rankings = BTreeMap::new();
             let alpha = self.alpha(contest.competitors.len());
+            let mut wins = BTreeMap::new();
             for (solver_id0, competitor0) in &contest.competitors {
                 let mut ranking = 1;
                 for (solver_id1, competitor1) in &contest.competitors {
@@ -231,10 +510,10 @@ impl Reporter {
                         continue;
                     }
 
-                    if self.compete(competitor0, competitor1, auc_start_step, alpha)
-                        == Ordering::Greater
-                    {
-                        ranking += 1;
+                    match self.compete(competitor0, competitor1, auc_start_step, alpha) {
+                        Ordering::Greater => ranking += 1,
+                        Ordering::Less => *wins.entry(solver_id0).or_insert(0) += 1,
+                        Ordering::Equal => {}
                     }
                 }
                 rankings.insert(solver_id0, ranking);
@@ -242,39 +521,15 @@ impl Reporter {
             let mut rankings = rankings.into_iter().map(|x| (x.1, x.0)).collect::<Vec<_>>();
             rankings.sort();
 
-            let mut table = md::Table::new(
-                vec![
-                    md::ColumnHeader::new("Ranking", md::Align::Right),
-                    md::ColumnHeader::new("Solver", md::Align::Left),
-                    md::ColumnHeader::new("Best (avg +- sd)", md::Align::Right),
-                    md::ColumnHeader::new("AUC (avg +- sd)", md::Align::Right),
-                    md::ColumnHeader::new("Elapsed (avg +- sd)", md::Align::Right),
-                ]
-                .into_iter(),
-            );
+            let mut rows = Vec::new();
             for (ranking, solver_id) in rankings {
                 let c = &contest.competitors[solver_id];
 
-                let solver = format!(
-                    "[{}](#id-{}) ([study](#id-{}))",
-                    c.solver.spec.name,
-                    solver_id,
-                    track!(c.studies[0].id())?
-                );
-
                 let best_values = c.best_values().map(|x| x.0).collect::<Vec<_>>();
-                let best_value = format!(
-                    "{:.06} +- {:.06}",
-                    average(best_values.iter().copied()),
-                    stddev(best_values.iter().copied())
-                );
+                let best_value = self.summarize(&best_values, 6);
 
                 let aucs = c.aucs(auc_start_step).map(|x| x.0).collect::<Vec<_>>();
-                let auc = format!(
-                    "{:.03} +- {:.03}",
-                    average(aucs.iter().copied()),
-                    stddev(aucs.iter().copied())
-                );
+                let auc = self.summarize(&aucs, 3);
 
                 let elapsed_times = c
                     .elapsed_times()
@@ -285,20 +540,95 @@ impl Reporter {
                     average(elapsed_times.iter().copied()),
                     stddev(elapsed_times.iter().copied())
                 );
-                table
-                    .row()
-                    .item(ranking)
-                    .item(solver)
-                    .item(best_value)
-                    .item(auc)
-                    .item(elapsed_time);
-            }
 
-            track!(writer.write_table(&table))?;
-            track_writeln!(writer.inner_mut())?;
+                let significantly_best = contest.competitors.len() > 1
+                    && wins.get(solver_id).copied().unwrap_or(0) == contest.competitors.len() - 1;
+                rows.push(IndividualRow {
+                    ranking,
+                    significantly_best,
+                    solver_id: solver_id.clone(),
+                    solver: c.solver,
+                    study_id: track!(c.studies[0].id())?,
+                    best_value,
+                    auc,
+                    elapsed_time,
+                });
+            }
+            results.push(IndividualResult {
+                problem_id,
+                contest,
+                rows,
+            });
         }
+        Ok(results)
+    }
 
-        Ok(())
+    fn value_distributions(&self) -> Result<Vec<ValueDistribution>> {
+        let contests = track!(self.contests())?;
+        let mut distributions = Vec::new();
+        for (problem_id, contest) in contests {
+            if contest.problem.spec.values_domain.variables().len() != 1 {
+                continue;
+            }
+
+            let solver_values = contest
+                .competitors
+                .values()
+                .map(|c| c.observed_values().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let mut values = solver_values.iter().flatten().copied().collect::<Vec<_>>();
+            if values.is_empty() {
+                continue;
+            }
+            values.sort_by_key(|&v| OrderedFloat(v));
+
+            let min = values[0];
+            let max = values[values.len() - 1];
+            let n = if min < max {
+                self.opt.histogram_bins.get()
+            } else {
+                1
+            };
+            let width = (max - min) / n as f64;
+            let mut bins = (0..n)
+                .map(|i| Bin {
+                    lower: min + width * i as f64,
+                    upper: if i + 1 == n {
+                        max
+                    } else {
+                        min + width * (i + 1) as f64
+                    },
+                    counts: vec![0; solver_values.len()],
+                })
+                .collect::<Vec<_>>();
+            for (j, vs) in solver_values.iter().enumerate() {
+                for &v in vs {
+                    let i = if min < max {
+                        (((v - min) / width) as usize).min(n - 1)
+                    } else {
+                        0
+                    };
+                    bins[i].counts[j] += 1;
+                }
+            }
+
+            distributions.push(ValueDistribution {
+                problem_id,
+                problem: contest.problem,
+                count: values.len(),
+                percentiles: PERCENTILES
+                    .iter()
+                    .map(|&q| (q, quantile(&values, q).expect("unreachable")))
+                    .collect(),
+                solvers: contest
+                    .competitors
+                    .iter()
+                    .map(|(id, c)| (id.clone(), c.solver))
+                    .collect(),
+                bins,
+            });
+        }
+        Ok(distributions)
     }
 
     fn report_solvers<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
@@ -371,6 +701,25 @@ impl Reporter {
         Ok(())
     }
 
+    fn is_same_env(&self) -> bool {
+        self.studies
+            .iter()
+            .all(|s| s.has_comparable_env(&self.studies[0]))
+    }
+
+    fn metrics_precedence(&self) -> String {
+        self.opt
+            .metrics
+            .iter()
+            .map(|m| match m {
+                Metric::BestValue => "best value",
+                Metric::Auc => "AUC",
+                Metric::ElapsedTime => "elapsed time",
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
     fn solvers(&self) -> Result<impl '_ + Iterator<Item = (String, &SolverRecord)>> {
         let mut map = BTreeMap::new();
         for study in &self.studies {
@@ -423,6 +772,29 @@ impl Reporter {
         0.000_01
     }
 
+    fn summary_label(&self) -> String {
+        if self.opt.bootstrap == 0 {
+            "avg +- sd".to_owned()
+        } else {
+            format!("avg [{}% CI]", self.opt.confidence_level * 100.0)
+        }
+    }
+
+    fn summarize(&self, values: &[f64], precision: usize) -> String {
+        let avg = average(values.iter().copied());
+        if self.opt.bootstrap == 0 {
+            let sd = stddev(values.iter().copied());
+            format!("{:.*} +- {:.*}", precision, avg, precision, sd)
+        } else {
+            let (lower, upper) =
+                bootstrap_interval(values, self.opt.bootstrap, self.opt.confidence_level);
+            format!(
+                "{:.*} [{:.*}, {:.*}]",
+                precision, avg, precision, lower, precision, upper
+            )
+        }
+    }
+
     fn contests(&self) -> Result<BTreeMap<String, Contest>> {
         let mut contests = BTreeMap::new();
         for study in &self.studies {
@@ -455,6 +827,103 @@ impl Reporter {
     }
 }
 
+fn budget_label(fraction: f64) -> String {
+    format!("{}%", fraction * 100.0)
+}
+
+fn percentile_label(q: f64) -> String {
+    if q == 0.0 {
+        "min".to_owned()
+    } else if q == 1.0 {
+        "max".to_owned()
+    } else {
+        format!("{}%", q * 100.0)
+    }
+}
+
+fn bin_label(bin: &Bin) -> String {
+    format!("[{:.6}, {:.6}]", bin.lower, bin.upper)
+}
+
+// Percentile bootstrap confidence interval of the mean.
+//
+// A fixed seed is used so that the same report is generated from the same results.
+fn bootstrap_interval(values: &[f64], resamples: usize, confidence_level: f64) -> (f64, f64) {
+    if values.is_empty() {
+        return (std::f64::NAN, std::f64::NAN);
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut means = (0..resamples)
+        .map(|_| {
+            (0..values.len())
+                .map(|_| values[rng.gen_range(0..values.len())])
+                .sum::<f64>()
+                / values.len() as f64
+        })
+        .collect::<Vec<_>>();
+    means.sort_by_key(|&m| OrderedFloat(m));
+
+    let tail = (1.0 - confidence_level) / 2.0;
+    let index = |q: f64| ((q * (resamples - 1) as f64).round() as usize).min(resamples - 1);
+    (means[index(tail)], means[index(1.0 - tail)])
+}
+
+struct OverallResults<'a> {
+    rows: Vec<OverallRow<'a>>,
+    excluded_problems: Vec<(String, &'a ProblemRecord)>,
+}
+
+struct OverallRow<'a> {
+    solver_id: String,
+    solver: &'a SolverRecord,
+    borda: Score,
+    firsts: Score,
+}
+
+struct BudgetRankings<'a> {
+    fractions: Vec<f64>,
+    rows: Vec<BudgetRow<'a>>,
+}
+
+struct BudgetRow<'a> {
+    solver_id: String,
+    solver: &'a SolverRecord,
+    bordas: Vec<Score>,
+}
+
+struct IndividualResult<'a> {
+    problem_id: String,
+    contest: Contest<'a>,
+    rows: Vec<IndividualRow<'a>>,
+}
+
+struct IndividualRow<'a> {
+    ranking: usize,
+    significantly_best: bool,
+    solver_id: String,
+    solver: &'a SolverRecord,
+    study_id: String,
+    best_value: String,
+    auc: String,
+    elapsed_time: String,
+}
+
+struct ValueDistribution<'a> {
+    problem_id: String,
+    problem: &'a ProblemRecord,
+    count: usize,
+    percentiles: Vec<(f64, f64)>,
+    solvers: Vec<(String, &'a SolverRecord)>,
+    bins: Vec<Bin>,
+}
+
+struct Bin {
+    lower: f64,
+    upper: f64,
+    counts: Vec<usize>,
+}
+
 struct Contest<'a> {
     problem: &'a ProblemRecord,
     competitors: BTreeMap<String, Competitor<'a>>,
@@ -473,6 +942,27 @@ impl<'a> Competitor<'a> {
             .map(OrderedFloat)
     }
 
+    // The studies that have no evaluated trials within the fraction of their budgets are ignored.
+    fn best_values_within(&self, fraction: f64) -> impl '_ + Iterator<Item = OrderedFloat<f64>> {
+        self.studies
+            .iter()
+            .filter_map(move |s| {
+                let step = (s.study_steps() as f64 * fraction).ceil() as u64;
+                s.best_values().range(..=step).next_back().map(|(_, &v)| v)
+            })
+            .map(OrderedFloat)
+    }
+
+    // The values of the last evaluations of the trials.
+    fn observed_values(&self) -> impl '_ + Iterator<Item = f64> {
+        self.studies
+            .iter()
+            .flat_map(|s| s.trials.iter())
+            .filter_map(|t| t.evaluations.last())
+            .filter_map(|e| e.values.first().copied())
+            .filter(|v| v.is_finite())
+    }
+
     fn aucs(&self, start_step: u64) -> impl '_ + Iterator<Item = OrderedFloat<f64>> {
         self.studies
             .iter()
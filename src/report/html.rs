@@ -0,0 +1,333 @@
+//! HTML output of the `kurobako report` command.
+use super::{bin_label, budget_label, percentile_label, Competitor, Reporter};
+use kurobako_core::{Error, Result};
+use rustats::fundamental::{average, stddev};
+use serde_json::json;
+use std::io::Write;
+
+/// Maximum number of points per solver in a convergence plot.
+const MAX_CURVE_POINTS: u64 = 200;
+
+const VEGA_SCRIPTS: &[&str] = &[
+    "https://cdn.jsdelivr.net/npm/vega@5",
+    "https://cdn.jsdelivr.net/npm/vega-lite@5",
+    "https://cdn.jsdelivr.net/npm/vega-embed@6",
+];
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em auto; max-width: 1000px; } \
+                     table { border-collapse: collapse; margin: 1em 0; } \
+                     th, td { border: 1px solid #ccc; padding: 4px 8px; } \
+                     td.num { text-align: right; } \
+                     pre { background: #f6f8fa; padding: 1em; overflow: auto; }";
+
+impl Reporter {
+    /// Prints a full report as a self-contained HTML document.
+    ///
+    /// The convergence plots are embedded as inlined Vega-Lite specifications.
+    pub(super) fn report_all_html(&self, mut writer: impl Write) -> Result<()> {
+        let w = &mut writer;
+        track_writeln!(w, "<!DOCTYPE html>")?;
+        track_writeln!(w, "<html>")?;
+        track_writeln!(w, "<head>")?;
+        track_writeln!(w, "<meta charset=\"utf-8\">")?;
+        track_writeln!(w, "<title>Benchmark Result Report</title>")?;
+        track_writeln!(w, "<style>{}</style>", STYLE)?;
+        for script in VEGA_SCRIPTS {
+            track_writeln!(w, "<script src=\"{}\"></script>", script)?;
+        }
+        track_writeln!(w, "</head>")?;
+        track_writeln!(w, "<body>")?;
+        track_writeln!(w, "<h1>Benchmark Result Report</h1>")?;
+
+        track_writeln!(w, "<ul>")?;
+        track_writeln!(
+            w,
+            "<li>Kurobako Version: {}</li>",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        track_writeln!(
+            w,
+            "<li>Number of Solvers: {}</li>",
+            track!(self.solvers())?.count()
+        )?;
+        track_writeln!(
+            w,
+            "<li>Number of Problems: {}</li>",
+            track!(self.problems())?.count()
+        )?;
+        track_writeln!(
+            w,
+            "<li>Metrics Precedence: <code>{}</code></li>",
+            escape(&self.metrics_precedence())
+        )?;
+        track_writeln!(w, "</ul>")?;
+
+        track!(self.report_overall_results_html(w))?;
+        track!(self.report_individual_results_html(w))?;
+        track!(self.report_value_distributions_html(w))?;
+        track!(self.report_problems_html(w))?;
+
+        track_writeln!(w, "</body>")?;
+        track_writeln!(w, "</html>")?;
+        Ok(())
+    }
+
+    fn report_overall_results_html<W: Write>(&self, w: &mut W) -> Result<()> {
+        let results = track!(self.overall_results())?;
+
+        track_writeln!(w, "<h2>Overall Results</h2>")?;
+        track_writeln!(w, "<table>")?;
+        track_writeln!(w, "<tr><th>Solver</th><th>Borda</th><th>Firsts</th></tr>")?;
+        for row in &results.rows {
+            track_writeln!(
+                w,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&row.solver.spec.name),
+                row.borda,
+                row.firsts
+            )?;
+        }
+        track_writeln!(w, "</table>")?;
+
+        let rankings = track!(self.budget_rankings())?;
+        track_writeln!(w, "<h3>Rankings by Budget</h3>")?;
+        track_writeln!(
+            w,
+            "<p>Borda scores of the best values found within the fractions of the budgets:</p>"
+        )?;
+        track_writeln!(w, "<table>")?;
+        track_write!(w, "<tr><th>Solver</th>")?;
+        for &f in &rankings.fractions {
+            track_write!(w, "<th>{}</th>", budget_label(f))?;
+        }
+        track_writeln!(w, "</tr>")?;
+        for row in &rankings.rows {
+            track_write!(w, "<tr><td>{}</td>", escape(&row.solver.spec.name))?;
+            for borda in &row.bordas {
+                track_write!(w, "<td class=\"num\">{}</td>", borda)?;
+            }
+            track_writeln!(w, "</tr>")?;
+        }
+        track_writeln!(w, "</table>")?;
+
+        if !results.excluded_problems.is_empty() {
+            track_writeln!(
+                w,
+                "<p>The following problems aren't considered in the above \
+                 result because some of the solvers don't participate in the problems:</p>"
+            )?;
+            track_writeln!(w, "<ul>")?;
+            for (problem_id, problem) in results.excluded_problems {
+                track_writeln!(
+                    w,
+                    "<li><a href=\"#id-{}\">{}</a></li>",
+                    problem_id,
+                    escape(&problem.spec.name)
+                )?;
+            }
+            track_writeln!(w, "</ul>")?;
+        }
+        Ok(())
+    }
+
+    fn report_individual_results_html<W: Write>(&self, w: &mut W) -> Result<()> {
+        track_writeln!(w, "<h2>Individual Results</h2>")?;
+
+        let results = track!(self.individual_results())?;
+        for (problem_no, result) in results.into_iter().enumerate() {
+            track_writeln!(
+                w,
+                "<h3>({}) Problem: <a href=\"#id-{}\">{}</a></h3>",
+                problem_no + 1,
+                result.problem_id,
+                escape(&result.contest.problem.spec.name)
+            )?;
+
+            track_writeln!(w, "<table>")?;
+            track_writeln!(
+                w,
+                "<tr><th>Ranking</th><th>Solver</th><th>Best ({0})</th>\
+                 <th>AUC ({0})</th><th>Elapsed (avg +- sd)</th></tr>",
+                self.summary_label()
+            )?;
+            for row in &result.rows {
+                track_writeln!(
+                    w,
+                    "<tr><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{}</td>\
+                     <td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    row.ranking,
+                    escape(&row.solver.spec.name),
+                    row.best_value,
+                    row.auc,
+                    row.elapsed_time
+                )?;
+            }
+            track_writeln!(w, "</table>")?;
+
+            if result.contest.problem.spec.values_domain.variables().len() != 1 {
+                // Convergence plots don't support multi-objective problems.
+                continue;
+            }
+
+            let mut data = Vec::new();
+            for row in &result.rows {
+                let competitor = &result.contest.competitors[&row.solver_id];
+                data.extend(
+                    convergence_curve(competitor)
+                        .into_iter()
+                        .map(|(budget, avg, sd)| {
+                            json!({
+                                "solver": row.solver.spec.name,
+                                "budget": budget,
+                                "value": avg,
+                                "lower": avg - sd,
+                                "upper": avg + sd,
+                            })
+                        }),
+                );
+            }
+            let ylabel = result.contest.problem.spec.values_domain.variables()[0].name();
+            let spec = json!({
+                "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+                "width": 720,
+                "height": 360,
+                "data": {"values": data},
+                "encoding": {
+                    "x": {"field": "budget", "type": "quantitative", "title": "Budget"},
+                    "color": {"field": "solver", "type": "nominal", "title": "Solver"},
+                },
+                "layer": [
+                    {
+                        "mark": {"type": "errorband", "opacity": 0.2},
+                        "encoding": {
+                            "y": {"field": "lower", "type": "quantitative", "title": ylabel},
+                            "y2": {"field": "upper"},
+                        },
+                    },
+                    {
+                        "mark": "line",
+                        "params": [{"name": "zoom", "select": "interval", "bind": "scales"}],
+                        "encoding": {
+                            "y": {"field": "value", "type": "quantitative", "title": ylabel},
+                            "tooltip": [
+                                {"field": "solver"},
+                                {"field": "budget", "format": ".3f"},
+                                {"field": "value", "title": "avg"},
+                            ],
+                        },
+                    },
+                ],
+            });
+            let spec = track!(serde_json::to_string(&spec).map_err(Error::from))?;
+
+            track_writeln!(w, "<div id=\"curve-{}\"></div>", result.problem_id)?;
+            track_writeln!(
+                w,
+                "<script>vegaEmbed(\"#curve-{}\", {});</script>",
+                result.problem_id,
+                spec.replace("</", "<\\/")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn report_value_distributions_html<W: Write>(&self, w: &mut W) -> Result<()> {
+        track_writeln!(w, "<h2>Value Distributions</h2>")?;
+        track_writeln!(
+            w,
+            "<p>Distributions of the last values of all the trials \
+             (multi-objective problems are omitted):</p>"
+        )?;
+
+        for d in track!(self.value_distributions())? {
+            track_writeln!(
+                w,
+                "<h3>Problem: <a href=\"#id-{}\">{}</a></h3>",
+                d.problem_id,
+                escape(&d.problem.spec.name)
+            )?;
+            track_writeln!(w, "<p>Observed values: {}</p>", d.count)?;
+
+            track_writeln!(w, "<table>")?;
+            track_write!(w, "<tr>")?;
+            for (q, _) in &d.percentiles {
+                track_write!(w, "<th>{}</th>", percentile_label(*q))?;
+            }
+            track_writeln!(w, "</tr>")?;
+            track_write!(w, "<tr>")?;
+            for (_, v) in &d.percentiles {
+                track_write!(w, "<td class=\"num\">{:.6}</td>", v)?;
+            }
+            track_writeln!(w, "</tr>")?;
+            track_writeln!(w, "</table>")?;
+
+            track_writeln!(w, "<table>")?;
+            track_write!(w, "<tr><th>Bin</th>")?;
+            for (_, solver) in &d.solvers {
+                track_write!(w, "<th>{}</th>", escape(&solver.spec.name))?;
+            }
+            track_writeln!(w, "</tr>")?;
+            for bin in &d.bins {
+                track_write!(w, "<tr><td>{}</td>", bin_label(bin))?;
+                for count in &bin.counts {
+                    track_write!(w, "<td class=\"num\">{}</td>", count)?;
+                }
+                track_writeln!(w, "</tr>")?;
+            }
+            track_writeln!(w, "</table>")?;
+        }
+        Ok(())
+    }
+
+    fn report_problems_html<W: Write>(&self, w: &mut W) -> Result<()> {
+        track_writeln!(w, "<h2>Problems</h2>")?;
+        for (id, problem) in track!(self.problems())? {
+            track_writeln!(
+                w,
+                "<h3 id=\"id-{}\">{}</h3>",
+                id,
+                escape(&problem.spec.name)
+            )?;
+            let json = track!(serde_json::to_string_pretty(&problem.recipe).map_err(Error::from))?;
+            track_writeln!(w, "<p>recipe:</p>")?;
+            track_writeln!(w, "<pre>{}</pre>", escape(&json))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the `(budget, avg, sd)` points of the best values of the given competitor.
+fn convergence_curve(competitor: &Competitor) -> Vec<(f64, f64, f64)> {
+    let study = competitor.studies[0];
+    let problem_steps = study.problem.spec.steps.last() as f64;
+    let study_steps = study.study_steps();
+    let interval = std::cmp::max(1, study_steps / MAX_CURVE_POINTS);
+
+    let best_values = competitor
+        .studies
+        .iter()
+        .map(|s| s.best_values())
+        .collect::<Vec<_>>();
+    let mut points = Vec::new();
+    for step in (interval..=study_steps).step_by(interval as usize) {
+        let values = best_values
+            .iter()
+            .filter_map(|x| x.range(..=step).last().map(|v| *v.1))
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            continue;
+        }
+
+        let avg = average(values.iter().copied());
+        let sd = stddev(values.into_iter());
+        points.push((step as f64 / problem_steps, avg, sd));
+    }
+    points
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
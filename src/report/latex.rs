@@ -0,0 +1,177 @@
+//! LaTeX output of the `kurobako report` command.
+use super::{budget_label, Reporter};
+use kurobako_core::Result;
+use std::io::Write;
+
+impl Reporter {
+    /// Prints the result tables as LaTeX `table` environments (requires the `booktabs` package).
+    ///
+    /// In the table of each problem, the best solvers are bolded.
+    /// If a best solver is significantly better than all the other solvers,
+    /// it is marked with `$^\dagger$`.
+    pub(super) fn report_all_latex(&self, mut writer: impl Write) -> Result<()> {
+        let w = &mut writer;
+        track!(self.report_overall_results_latex(w))?;
+        track!(self.report_budget_rankings_latex(w))?;
+        track!(self.report_individual_results_latex(w))?;
+        Ok(())
+    }
+
+    fn report_overall_results_latex<W: Write>(&self, w: &mut W) -> Result<()> {
+        let results = track!(self.overall_results())?;
+        let max_borda = results.rows.iter().map(|r| r.borda).max();
+        let max_firsts = results.rows.iter().map(|r| r.firsts).max();
+
+        track_writeln!(w, "\\begin{{table}}[htbp]")?;
+        track_writeln!(w, "\\centering")?;
+        track_writeln!(w, "\\caption{{Overall results}}")?;
+        track_writeln!(w, "\\begin{{tabular}}{{lrr}}")?;
+        track_writeln!(w, "\\toprule")?;
+        track_writeln!(w, "Solver & Borda & Firsts \\\\")?;
+        track_writeln!(w, "\\midrule")?;
+        for row in &results.rows {
+            track_writeln!(
+                w,
+                "{} & {} & {} \\\\",
+                escape(&row.solver.spec.name),
+                bold_if(&row.borda.to_string(), Some(row.borda) == max_borda),
+                bold_if(&row.firsts.to_string(), Some(row.firsts) == max_firsts)
+            )?;
+        }
+        track_writeln!(w, "\\bottomrule")?;
+        track_writeln!(w, "\\end{{tabular}}")?;
+        track_writeln!(w, "\\end{{table}}")?;
+        track_writeln!(w)?;
+
+        if !results.excluded_problems.is_empty() {
+            let names = results
+                .excluded_problems
+                .iter()
+                .map(|(_, p)| escape(&p.spec.name))
+                .collect::<Vec<_>>();
+            track_writeln!(
+                w,
+                "% The following problems aren't considered in the above result \
+                 because some of the solvers don't participate in the problems: {}",
+                names.join(", ")
+            )?;
+            track_writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    fn report_budget_rankings_latex<W: Write>(&self, w: &mut W) -> Result<()> {
+        let rankings = track!(self.budget_rankings())?;
+
+        track_writeln!(w, "\\begin{{table}}[htbp]")?;
+        track_writeln!(w, "\\centering")?;
+        track_writeln!(w, "\\caption{{Borda scores by budget}}")?;
+        track_writeln!(
+            w,
+            "\\begin{{tabular}}{{l{}}}",
+            "r".repeat(rankings.fractions.len())
+        )?;
+        track_writeln!(w, "\\toprule")?;
+        let header = rankings
+            .fractions
+            .iter()
+            .map(|&f| escape(&budget_label(f)))
+            .collect::<Vec<_>>();
+        track_writeln!(w, "Solver & {} \\\\", header.join(" & "))?;
+        track_writeln!(w, "\\midrule")?;
+        let max_bordas = (0..rankings.fractions.len())
+            .map(|i| rankings.rows.iter().map(|r| r.bordas[i]).max())
+            .collect::<Vec<_>>();
+        for row in &rankings.rows {
+            let bordas = row
+                .bordas
+                .iter()
+                .zip(max_bordas.iter())
+                .map(|(&b, &max)| bold_if(&b.to_string(), Some(b) == max))
+                .collect::<Vec<_>>();
+            track_writeln!(
+                w,
+                "{} & {} \\\\",
+                escape(&row.solver.spec.name),
+                bordas.join(" & ")
+            )?;
+        }
+        track_writeln!(w, "\\bottomrule")?;
+        track_writeln!(w, "\\end{{tabular}}")?;
+        track_writeln!(w, "\\end{{table}}")?;
+        track_writeln!(w)?;
+        Ok(())
+    }
+
+    fn report_individual_results_latex<W: Write>(&self, w: &mut W) -> Result<()> {
+        let results = track!(self.individual_results())?;
+        for result in results {
+            track_writeln!(w, "\\begin{{table}}[htbp]")?;
+            track_writeln!(w, "\\centering")?;
+            track_writeln!(
+                w,
+                "\\caption{{Problem: {}}}",
+                escape(&result.contest.problem.spec.name)
+            )?;
+            track_writeln!(w, "\\begin{{tabular}}{{rlrrr}}")?;
+            track_writeln!(w, "\\toprule")?;
+            let label = self
+                .summary_label()
+                .replace("+-", "$\\pm$")
+                .replace('%', "\\%");
+            track_writeln!(
+                w,
+                "Ranking & Solver & Best ({0}) & AUC ({0}) \
+                 & Elapsed (avg $\\pm$ sd) \\\\",
+                label
+            )?;
+            track_writeln!(w, "\\midrule")?;
+            for row in &result.rows {
+                let is_best = row.ranking == 1;
+                let mut solver = escape(&row.solver.spec.name);
+                if row.significantly_best {
+                    solver += "$^\\dagger$";
+                }
+                track_writeln!(
+                    w,
+                    "{} & {} & {} & {} & {} \\\\",
+                    row.ranking,
+                    bold_if(&solver, is_best),
+                    bold_if(&row.best_value.replace("+-", "$\\pm$"), is_best),
+                    bold_if(&row.auc.replace("+-", "$\\pm$"), is_best),
+                    row.elapsed_time.replace("+-", "$\\pm$")
+                )?;
+            }
+            track_writeln!(w, "\\bottomrule")?;
+            track_writeln!(w, "\\end{{tabular}}")?;
+            track_writeln!(w, "\\end{{table}}")?;
+            track_writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+fn bold_if(s: &str, bold: bool) -> String {
+    if bold {
+        format!("\\textbf{{{}}}", s)
+    } else {
+        s.to_owned()
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut t = String::new();
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                t.push('\\');
+                t.push(c);
+            }
+            '~' => t.push_str("\\textasciitilde{}"),
+            '^' => t.push_str("\\textasciicircum{}"),
+            '\\' => t.push_str("\\textbackslash{}"),
+            _ => t.push(c),
+        }
+    }
+    t
+}
@@ -1,30 +1,61 @@
 //! `kurobako run` command.
+use self::dashboard::Dashboard;
+use self::hook::Hooks;
+use self::pruner::StudyPruner;
+use self::remote::RemoteWorker;
+use self::snapshot::{SnapshotFile, StudySnapshot};
+use self::trial_log::TrialLog;
 use crate::problem::KurobakoProblemRecipe;
-use crate::record::{StudyRecord, StudyRecordBuilder, TrialRecordBuilder};
+use crate::record::{
+    self, FailedStudyRecord, ProblemRecord, StudyRecord, StudyRecordBuilder, TrialRecordBuilder,
+};
 use crate::solver::KurobakoSolverRecipe;
-use crate::study::{Scheduling, StudyRecipe};
+use crate::study::{Scheduling, StudyRecipe, StudySelector};
 use crate::time::ElapsedSeconds;
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use kurobako_core::compression::Compression;
+use kurobako_core::domain::ConstraintViolation;
+use kurobako_core::epi::OutputCapture;
+use kurobako_core::json;
 use kurobako_core::problem::ProblemRecipe as _;
 use kurobako_core::problem::{
-    BoxEvaluator, BoxProblem, Evaluator as _, Problem as _, ProblemFactory as _, ProblemSpec,
+    AbortHandle, BoxEvaluator, BoxProblem, Evaluator as _, Problem as _, ProblemFactory as _,
+    ProblemSpec,
 };
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
-use kurobako_core::solver::{BoxSolver, Solver as _, SolverFactory as _, SolverRecipe as _};
+use kurobako_core::solver::{
+    BoxSolver, Capability, Solver as _, SolverFactory as _, SolverRecipe as _,
+};
 use kurobako_core::trial::Values;
-use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, TrialId};
+use kurobako_core::trial::{EvaluatedTrial, IdGen, NextTrial, PriorTrial, TrialId};
 use kurobako_core::{Error, ErrorKind, Result};
 use rand::seq::SliceRandom;
+use serde::Serialize;
 use std::collections::{HashMap, VecDeque};
-use std::io::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
-use std::sync::atomic::{self, AtomicUsize};
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 use trackable::error::ErrorKindExt;
 
+pub(crate) use self::remote::Response;
+
+mod dashboard;
+mod hook;
+mod metrics;
+mod pruner;
+mod remote;
+mod snapshot;
+mod trial_log;
+
 /// Options of the `kurobako run` command.
 #[derive(Debug, Clone, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
@@ -33,10 +64,308 @@ pub struct RunnerOpt {
     #[structopt(long, short = "p", default_value = "1")]
     pub parallelism: NonZeroUsize,
 
-    /// Disables progress bar.
+    /// Disables progress output (progress bar or plain progress lines).
     #[structopt(long, short = "q")]
     pub quiet: bool,
+
+    /// Checkpoint file (JSON Lines) to record the results of completed studies.
+    ///
+    /// If the file already exists, the studies recorded in it are skipped
+    /// (their results are output as is) and only the remaining studies are run.
+    #[structopt(long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Compresses the output results (`gzip` or `zstd`).
+    ///
+    /// Each result is compressed separately (as a gzip member or a zstd frame),
+    /// so the output can be decompressed even if the benchmark is interrupted.
+    /// The commands that read results (e.g., `kurobako report`) decompress them transparently.
+    /// Note that the checkpoint file is compressed if its path ends with `.gz` or `.zst`.
+    #[structopt(long)]
+    pub compress: Option<Compression>,
+
+    /// Maximum number of retries of a study that failed due to an I/O error
+    /// (e.g., a crash of an external solver or problem process).
+    ///
+    /// A retried study is replayed from scratch with the same random seed.
+    #[structopt(long, default_value = "0")]
+    pub max_retries: usize,
+
+    /// Seconds to wait before the first retry (the wait time is doubled on each retry).
+    #[structopt(long, default_value = "1.0")]
+    pub retry_backoff: f64,
+
+    /// Wall-clock time limit (in seconds) of an evaluation.
+    ///
+    /// An evaluation exceeding the limit is aborted and the trial is recorded as failed
+    /// (i.e., it has no values) while consuming the requested steps.
+    /// The process of an external program problem is killed and then respawned on the abort.
+    #[structopt(long)]
+    pub trial_timeout: Option<f64>,
+
+    /// Execution order of the studies.
+    #[structopt(long, default_value = "spec", possible_values = Order::POSSIBLE_VALUES)]
+    pub order: Order,
+
+    /// Random seed used to shuffle the studies (`--order shuffle`).
+    ///
+    /// If omitted, a random seed is used, so the order differs between runs.
+    #[structopt(long)]
+    pub seed: Option<u64>,
+
+    /// Behavior on a failure of a study or a trial.
+    ///
+    /// - `abort`: aborts the whole benchmark.
+    /// - `skip-study`: skips the failed study, and outputs a record that has the recipe and the error of the study
+    ///   instead of its result.
+    /// - `skip-trial`: in addition to `skip-study`, records a trial whose evaluation failed as failed
+    ///   (i.e., it has no values) and continues the study.
+    ///
+    /// The records of the skipped studies are ignored by the commands that read results (e.g., `kurobako report`).
+    #[structopt(long, default_value = "abort", possible_values = ErrorPolicy::POSSIBLE_VALUES)]
+    pub on_error: ErrorPolicy,
+
+    /// Shows a dashboard (running studies, best values, failures and throughput)
+    /// instead of the progress bars.
+    #[structopt(long)]
+    pub dashboard: bool,
+
+    /// Address on which the metrics of the benchmark are exposed in the Prometheus format
+    /// (e.g., `0.0.0.0:9898`).
+    ///
+    /// The metrics include the numbers of the completed and failed studies, the number of evaluations
+    /// and the best value of each pair of a problem and a solver.
+    /// Note that the studies run on remote workers aren't reflected in the metrics.
+    #[structopt(long)]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Addresses of remote workers (see `kurobako worker`) to run the studies on.
+    ///
+    /// If specified, the studies are farmed out to the workers instead of being run locally,
+    /// and each worker is assigned up to `--parallelism` studies at the same time.
+    /// The studies assigned to a worker that became unavailable are reassigned to the other workers.
+    #[structopt(long)]
+    pub workers: Vec<String>,
+
+    /// Shared secret token presented to the remote workers (see `kurobako worker --token`).
+    ///
+    /// Required if `--workers` is specified.
+    #[structopt(long, env = "KUROBAKO_WORKER_TOKEN", hide_env_values = true)]
+    pub worker_token: Option<String>,
+
+    /// Runs only the studies that match any of the given selectors (`tag=VALUE` or `name=VALUE`).
+    #[structopt(long, number_of_values = 1)]
+    pub only: Vec<StudySelector>,
+
+    /// Skips the studies that match any of the given selectors (`tag=VALUE` or `name=VALUE`).
+    #[structopt(long, number_of_values = 1)]
+    pub skip: Vec<StudySelector>,
+
+    /// Runs only the `K`-th of `N` shards of the studies (`K/N`, where `1 <= K <= N`).
+    ///
+    /// The studies are assigned to the shards in a round-robin manner
+    /// (after applying `--repeat`, `--only` and `--skip`),
+    /// so the results of all the shards can be merged by `kurobako merge`.
+    #[structopt(long)]
+    pub shard: Option<Shard>,
+
+    /// File (JSON Lines) to which the ask/evaluate/tell events of all trials are appended with timestamps.
+    #[structopt(long)]
+    pub trial_log: Option<PathBuf>,
+
+    /// Captures the standard error outputs of external solvers and problems into the study records.
+    ///
+    /// If a study fails, the captured output is printed to the standard error instead.
+    #[structopt(long)]
+    pub capture_stderr: bool,
+
+    /// Maximum number of the bytes of the captured standard error output kept per study
+    /// (the oldest part is discarded).
+    #[structopt(long, default_value = "65536")]
+    pub capture_stderr_limit: usize,
+
+    /// Number of times each given study is repeated.
+    ///
+    /// If the seed of a study has been specified, the repeated studies use the seeds
+    /// `seed`, `seed + 1`, ..., `seed + N - 1`.
+    /// Otherwise, a distinct random seed is used for each of them.
+    /// In either case, the used seeds are recorded in the study records.
+    #[structopt(long, default_value = "1")]
+    pub repeat: NonZeroUsize,
+
+    /// Directory in which the evaluation results of problems are cached.
+    ///
+    /// The cache is keyed by the problem, the parameters and the evaluation step,
+    /// and shared by all the studies (and subsequent runs) using the same directory.
+    /// Note that this option should be used only for deterministic problems.
+    #[structopt(long)]
+    pub eval_cache: Option<PathBuf>,
+
+    /// Shell command invoked on each study start/end and trial completion.
+    ///
+    /// The event is given to the standard input of the command as a JSON object
+    /// that has the `event` field (`study_started`, `trial_completed`, `study_finished` or `study_failed`).
+    #[structopt(long, number_of_values = 1)]
+    pub hook: Vec<String>,
+
+    /// Directory in which the snapshots of the running studies are saved.
+    ///
+    /// If a study is interrupted, it is resumed from the last snapshot when run again.
+    /// Snapshots are taken only for the studies that have explicit seeds, concurrency of `1`
+    /// and no runner-side pruner, and whose solvers have the `CHECKPOINTABLE` capability.
+    /// The snapshot of a study is removed once the study completes.
+    #[structopt(long)]
+    pub solver_checkpoint_dir: Option<PathBuf>,
+
+    /// Number of the trials told to the solver between consecutive snapshots.
+    #[structopt(long, default_value = "10")]
+    pub solver_checkpoint_interval: NonZeroUsize,
+}
+impl Default for RunnerOpt {
+    // The same values as the defaults of the command line options.
+    fn default() -> Self {
+        let one = NonZeroUsize::new(1).unwrap_or_else(|| unreachable!());
+        Self {
+            parallelism: one,
+            quiet: false,
+            checkpoint: None,
+            compress: None,
+            max_retries: 0,
+            retry_backoff: 1.0,
+            trial_timeout: None,
+            order: Order::Spec,
+            seed: None,
+            on_error: ErrorPolicy::Abort,
+            dashboard: false,
+            metrics_addr: None,
+            workers: Vec::new(),
+            worker_token: None,
+            only: Vec::new(),
+            skip: Vec::new(),
+            shard: None,
+            trial_log: None,
+            capture_stderr: false,
+            capture_stderr_limit: 65536,
+            repeat: one,
+            eval_cache: None,
+            hook: Vec::new(),
+            solver_checkpoint_dir: None,
+            solver_checkpoint_interval: NonZeroUsize::new(10).unwrap_or_else(|| unreachable!()),
+        }
+    }
+}
+
+/// Execution order of studies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Order {
+    /// The order of the given study recipes.
+    Spec,
+
+    /// Random order (determined by the `--seed` option).
+    Shuffle,
+
+    /// Ascending order of the budgets.
+    ShortestFirst,
+
+    /// Round-robin order across solvers.
+    ///
+    /// The studies of each solver keep the order of the given study recipes,
+    /// so that the progress of the solvers is balanced at any point in time.
+    SolverRoundRobin,
+}
+impl Order {
+    const POSSIBLE_VALUES: &'static [&'static str] =
+        &["spec", "shuffle", "shortest-first", "solver-round-robin"];
 }
+impl Default for Order {
+    fn default() -> Self {
+        Self::Spec
+    }
+}
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "spec" => Ok(Self::Spec),
+            "shuffle" => Ok(Self::Shuffle),
+            "shortest-first" => Ok(Self::ShortestFirst),
+            "solver-round-robin" => Ok(Self::SolverRoundRobin),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown order: {:?}", s),
+        }
+    }
+}
+
+/// Behavior on a failure of a study or a trial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorPolicy {
+    /// Aborts the whole benchmark.
+    Abort,
+
+    /// Skips the failed study.
+    SkipStudy,
+
+    /// Skips the failed trial (and the failed study).
+    SkipTrial,
+}
+impl ErrorPolicy {
+    pub(crate) const POSSIBLE_VALUES: &'static [&'static str] =
+        &["abort", "skip-study", "skip-trial"];
+
+    fn skips_study(self) -> bool {
+        self != Self::Abort
+    }
+}
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        Self::Abort
+    }
+}
+impl FromStr for ErrorPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "abort" => Ok(Self::Abort),
+            "skip-study" => Ok(Self::SkipStudy),
+            "skip-trial" => Ok(Self::SkipTrial),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown error policy: {:?}", s),
+        }
+    }
+}
+
+/// Shard of studies (`K/N`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shard {
+    /// Index of the shard (1-origin).
+    pub index: usize,
+
+    /// Number of the shards.
+    pub count: usize,
+}
+impl Shard {
+    fn contains(self, study_index: usize) -> bool {
+        study_index % self.count == self.index - 1
+    }
+}
+impl FromStr for Shard {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut tokens = s.splitn(2, '/');
+        let index = tokens.next().unwrap_or_else(|| unreachable!());
+        let count = track_assert_some!(tokens.next(), ErrorKind::InvalidInput; s);
+        let index: usize =
+            track!(index.parse().map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))); s)?;
+        let count: usize =
+            track!(count.parse().map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))); s)?;
+        track_assert!(1 <= index && index <= count, ErrorKind::InvalidInput; s);
+        Ok(Self { index, count })
+    }
+}
+
+// Set when the benchmark is interrupted by Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Clone)]
 struct Cancel(Arc<Mutex<Option<Error>>>);
@@ -74,7 +403,7 @@ pub struct Runner {
 impl Runner {
     /// Makes a `Runner` instance.
     pub fn new(opt: RunnerOpt) -> Self {
-        let target = if opt.quiet {
+        let target = if opt.quiet || opt.dashboard {
             ProgressDrawTarget::hidden()
         } else {
             ProgressDrawTarget::stderr_with_hz(1)
@@ -89,11 +418,84 @@ impl Runner {
 
     /// Runs the benchmark.
     pub fn run(mut self) -> Result<()> {
-        let recipes = track!(self.read_study_recipes())?;
+        track_assert!(
+            self.opt.retry_backoff >= 0.0,
+            ErrorKind::InvalidInput; self.opt.retry_backoff
+        );
+        if let Some(timeout) = self.opt.trial_timeout {
+            track_assert!(timeout > 0.0, ErrorKind::InvalidInput; timeout);
+        }
+
+        let mut recipes = track!(self.read_study_recipes())?;
+        let repeat = self.opt.repeat.get();
+        if repeat > 1 {
+            recipes = repeat_recipes(recipes, repeat);
+        }
+        let only = &self.opt.only;
+        let skip = &self.opt.skip;
+        recipes.retain(|r| {
+            (only.is_empty() || only.iter().any(|s| s.is_match(r)))
+                && !skip.iter().any(|s| s.is_match(r))
+        });
+        if let Some(shard) = self.opt.shard {
+            recipes = recipes
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| shard.contains(*i))
+                .map(|(_, r)| r)
+                .collect();
+        }
+        let checkpoint = if let Some(path) = self.opt.checkpoint.clone() {
+            let checkpoint = track!(Checkpoint::open(path))?;
+            recipes = track!(checkpoint.restore(recipes, self.opt.compress))?;
+            Some(Arc::new(Mutex::new(checkpoint)))
+        } else {
+            None
+        };
+        match self.opt.order {
+            Order::Spec => {}
+            Order::Shuffle => {
+                let seed = self.opt.seed.unwrap_or_else(rand::random);
+                recipes.shuffle(&mut ArcRng::new(seed));
+            }
+            Order::ShortestFirst => recipes.sort_by_key(|r| r.budget),
+            Order::SolverRoundRobin => recipes = track!(interleave_by_solver(recipes))?,
+        }
+        let worker_token = if self.opt.workers.is_empty() {
+            None
+        } else {
+            Some(track_assert_some!(
+                self.opt.worker_token.clone(),
+                ErrorKind::InvalidInput,
+                "`--worker-token` is required to use `--workers`"
+            ))
+        };
+        track!(self.install_interrupt_handler())?;
         let pb = self.create_pb(&recipes);
+        let show_dashboard = self.opt.dashboard && !self.opt.quiet;
+        let dashboard = if show_dashboard || self.opt.metrics_addr.is_some() {
+            Some(Dashboard::new(recipes.len()))
+        } else {
+            None
+        };
+        if let (Some(addr), Some(dashboard)) = (self.opt.metrics_addr, &dashboard) {
+            track!(metrics::spawn_server(addr, dashboard.clone()))?;
+        }
+        let renderer = dashboard
+            .as_ref()
+            .filter(|_| show_dashboard)
+            .map(|d| d.spawn_renderer());
 
-        self.spawn_runners(recipes, pb);
+        if let Some(token) = worker_token {
+            self.spawn_remote_runners(recipes, pb, checkpoint, token);
+        } else {
+            self.spawn_runners(recipes, pb, checkpoint, dashboard.clone());
+        }
         track!(self.mpb.join().map_err(|e| ErrorKind::Other.cause(e)))?;
+        if let (Some(dashboard), Some(renderer)) = (dashboard, renderer) {
+            dashboard.close();
+            let _ = renderer.join();
+        }
         eprintln!();
 
         if let Some(e) = self.cancel.take() {
@@ -103,7 +505,30 @@ impl Runner {
         }
     }
 
-    fn spawn_runners(&self, recipes: Vec<StudyRecipe>, pb: ProgressBar) {
+    // On the first Ctrl-C, stops starting new studies and aborts the running ones,
+    // so that the process exits after the records (and the checkpoint) of the completed studies are fully written.
+    // The aborted studies aren't recorded in the checkpoint, so they are run again on resume.
+    fn install_interrupt_handler(&self) -> Result<()> {
+        let cancel = self.cancel.clone();
+        track!(ctrlc::set_handler(move || {
+            if INTERRUPTED.swap(true, atomic::Ordering::SeqCst) {
+                // The external programs don't receive the signal because they run in their own process groups.
+                kurobako_core::epi::kill_all_processes();
+                std::process::exit(130);
+            }
+            eprintln!("\nInterrupted (press Ctrl-C again to exit immediately)");
+            cancel.cancel(Error::from(ErrorKind::Other.cause("Interrupted")));
+        })
+        .map_err(|e| Error::from(ErrorKind::Other.cause(e))))
+    }
+
+    fn spawn_runners(
+        &self,
+        recipes: Vec<StudyRecipe>,
+        pb: ProgressBar,
+        checkpoint: Option<Arc<Mutex<Checkpoint>>>,
+        dashboard: Option<Dashboard>,
+    ) {
         pb.tick();
 
         let pb_len = recipes.len() as u64;
@@ -119,6 +544,8 @@ impl Runner {
             let cancel = self.cancel.clone();
             let opt = self.opt.clone();
             let mpb = Arc::clone(&self.mpb);
+            let checkpoint = checkpoint.clone();
+            let dashboard = dashboard.clone();
             thread::spawn(move || {
                 while !cancel.is_canceled() {
                     let i = next_index.fetch_add(1, atomic::Ordering::SeqCst);
@@ -130,17 +557,25 @@ impl Runner {
                         recipes[i].take().unwrap_or_else(|| unreachable!())
                     };
 
-                    let result = track!(StudyRunner::with_mpb(&recipe, &opt, &mpb))
-                        .and_then(|runner| track!(runner.run()));
-
-                    fn output(record: StudyRecord) -> Result<()> {
-                        let stdout = std::io::stdout();
-                        let mut stdout = stdout.lock();
-                        track!(serde_json::to_writer(&mut stdout, &record).map_err(Error::from))?;
-                        track!(writeln!(stdout).map_err(Error::from))?;
-                        Ok(())
-                    }
-                    let result = track!(result.and_then(output));
+                    let result = track!(run_study_with_retries(
+                        recipe.clone(),
+                        &opt,
+                        &mpb,
+                        dashboard.as_ref()
+                    ));
+                    let result = match result {
+                        Err(e) if opt.on_error.skips_study() && !is_interrupted() => {
+                            track!(skip_study(recipe, &e, opt.compress))
+                        }
+                        result => track!(result.and_then(|record| {
+                            if let Some(checkpoint) = &checkpoint {
+                                let mut checkpoint =
+                                    track!(checkpoint.lock().map_err(Error::from))?;
+                                track!(checkpoint.record(&record))?;
+                            }
+                            track!(output(&record, opt.compress))
+                        })),
+                    };
                     pb.inc(1);
 
                     if let Err(e) = result {
@@ -155,12 +590,116 @@ impl Runner {
         }
     }
 
+    fn spawn_remote_runners(
+        &self,
+        recipes: Vec<StudyRecipe>,
+        pb: ProgressBar,
+        checkpoint: Option<Arc<Mutex<Checkpoint>>>,
+        token: String,
+    ) {
+        pb.tick();
+
+        let pb_len = recipes.len() as u64;
+        let queue = Arc::new(Mutex::new(RemoteQueue {
+            recipes: recipes.into_iter().collect(),
+            in_flight: 0,
+        }));
+        let parallelism = self.opt.parallelism.get();
+        let alive = Arc::new(AtomicUsize::new(self.opt.workers.len() * parallelism));
+        for addr in &self.opt.workers {
+            for _ in 0..parallelism {
+                let addr = addr.clone();
+                let token = token.clone();
+                let pb = pb.clone();
+                let queue = Arc::clone(&queue);
+                let alive = Arc::clone(&alive);
+                let cancel = self.cancel.clone();
+                let checkpoint = checkpoint.clone();
+                let compress = self.opt.compress;
+                let on_error = self.opt.on_error;
+                thread::spawn(move || {
+                    let lock = || queue.lock().unwrap_or_else(|e| panic!("{}", e));
+                    match track!(RemoteWorker::connect(&addr, &token)) {
+                        Err(e) => eprintln!("Worker {:?} is unavailable: {}", addr, e),
+                        Ok(mut worker) => {
+                            while !cancel.is_canceled() {
+                                let mut recipe = {
+                                    let mut queue = lock();
+                                    if let Some(recipe) = queue.recipes.pop_front() {
+                                        queue.in_flight += 1;
+                                        recipe
+                                    } else if queue.in_flight == 0 {
+                                        break;
+                                    } else {
+                                        // Waits for the in-flight studies that may be reassigned.
+                                        std::mem::drop(queue);
+                                        thread::sleep(Duration::from_millis(100));
+                                        continue;
+                                    }
+                                };
+
+                                if recipe.seed.is_none() {
+                                    recipe.seed = Some(rand::random());
+                                }
+                                let response = match track!(worker.run_study(&recipe)) {
+                                    Err(e) => {
+                                        eprintln!(
+                                            "Worker {:?} became unavailable \
+                                             (the study is reassigned): {}",
+                                            addr, e
+                                        );
+                                        let mut queue = lock();
+                                        queue.recipes.push_back(recipe);
+                                        queue.in_flight -= 1;
+                                        break;
+                                    }
+                                    Ok(response) => response,
+                                };
+                                lock().in_flight -= 1;
+
+                                let result = match response
+                                    .map_err(|e| Error::from(ErrorKind::Other.cause(e)))
+                                {
+                                    Err(e) if on_error.skips_study() && !is_interrupted() => {
+                                        track!(skip_study(recipe, &e, compress))
+                                    }
+                                    response => response.and_then(|record| {
+                                        if let Some(checkpoint) = &checkpoint {
+                                            let mut checkpoint =
+                                                track!(checkpoint.lock().map_err(Error::from))?;
+                                            track!(checkpoint.record(&record))?;
+                                        }
+                                        track!(output(&record, compress))
+                                    }),
+                                };
+                                pb.inc(1);
+
+                                if let Err(e) = result {
+                                    if cancel.cancel(e) {
+                                        pb.finish_with_message("canceled");
+                                    }
+                                } else if pb.position() == pb_len {
+                                    pb.finish_with_message("done");
+                                }
+                            }
+                        }
+                    }
+
+                    let is_last = alive.fetch_sub(1, atomic::Ordering::SeqCst) == 1;
+                    if is_last && !lock().recipes.is_empty() {
+                        let e = ErrorKind::Other.cause("All workers are unavailable").into();
+                        if cancel.cancel(e) {
+                            pb.finish_with_message("canceled");
+                        }
+                    }
+                });
+            }
+        }
+    }
+
     fn read_study_recipes(&mut self) -> Result<Vec<StudyRecipe>> {
         let stdin = std::io::stdin();
-        serde_json::Deserializer::from_reader(stdin.lock())
-            .into_iter()
-            .map(|recipe| track!(recipe.map_err(Error::from)))
-            .collect()
+        track!(json::load_recipes(stdin.lock()))
     }
 
     fn create_pb(&self, recipes: &[StudyRecipe]) -> ProgressBar {
@@ -173,6 +712,238 @@ impl Runner {
     }
 }
 
+/// Loads the trials of the prior studies that have the same search space as the given problem.
+///
+/// The values of a trial are the ones of its last successful evaluation.
+fn load_prior_trials(path: &Path, problem: &ProblemSpec) -> Result<Vec<PriorTrial>> {
+    let file = track!(File::open(path).map_err(Error::from); path)?;
+    let studies = track!(record::load_studies(BufReader::new(file)); path)?;
+    let trials = studies
+        .iter()
+        .filter(|s| {
+            s.problem.spec.params_domain == problem.params_domain
+                && s.problem.spec.values_domain == problem.values_domain
+        })
+        .flat_map(|s| s.trials.iter())
+        .filter_map(|t| {
+            t.evaluations
+                .iter()
+                .rev()
+                .find(|e| !e.values.is_empty())
+                .map(|e| PriorTrial {
+                    params: t.params.clone(),
+                    values: e.values.clone(),
+                    step: e.end_step,
+                })
+        })
+        .collect();
+    Ok(trials)
+}
+
+fn repeat_recipes(recipes: Vec<StudyRecipe>, repeat: usize) -> Vec<StudyRecipe> {
+    recipes
+        .into_iter()
+        .flat_map(|recipe| {
+            (0..repeat).map(move |i| {
+                let mut recipe = recipe.clone();
+                recipe.seed = recipe.seed.map(|s| s.wrapping_add(i as u64));
+                recipe
+            })
+        })
+        .collect()
+}
+
+fn interleave_by_solver(recipes: Vec<StudyRecipe>) -> Result<Vec<StudyRecipe>> {
+    let mut groups: Vec<(serde_json::Value, VecDeque<StudyRecipe>)> = Vec::new();
+    for recipe in recipes {
+        let key = track!(serde_json::to_value(&recipe.solver).map_err(Error::from))?;
+        if let Some((_, group)) = groups.iter_mut().find(|(k, _)| *k == key) {
+            group.push_back(recipe);
+        } else {
+            groups.push((key, vec![recipe].into()));
+        }
+    }
+
+    let mut interleaved = Vec::new();
+    while !groups.is_empty() {
+        for (_, group) in &mut groups {
+            interleaved.extend(group.pop_front());
+        }
+        groups.retain(|(_, group)| !group.is_empty());
+    }
+    Ok(interleaved)
+}
+
+#[derive(Debug)]
+struct RemoteQueue {
+    recipes: VecDeque<StudyRecipe>,
+    in_flight: usize,
+}
+
+/// Runs a study without showing the progress.
+pub(crate) fn run_study(recipe: StudyRecipe, opt: &RunnerOpt) -> Result<StudyRecord> {
+    let mpb = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    track!(run_study_with_retries(recipe, opt, &mpb, None))
+}
+
+fn run_study_with_retries(
+    mut recipe: StudyRecipe,
+    opt: &RunnerOpt,
+    mpb: &MultiProgress,
+    dashboard: Option<&Dashboard>,
+) -> Result<StudyRecord> {
+    // Only the studies with explicit seeds can be identified on resume.
+    let snapshot_dir = opt
+        .solver_checkpoint_dir
+        .as_ref()
+        .filter(|_| recipe.seed.is_some());
+    if recipe.seed.is_none() {
+        recipe.seed = Some(rand::random());
+    }
+
+    let mut backoff = opt.retry_backoff;
+    let mut retries = 0;
+    loop {
+        let result = track!(StudyRunner::with_mpb(&recipe, opt, mpb)).and_then(|mut runner| {
+            runner.dashboard = dashboard.cloned();
+            if let Some(dir) = snapshot_dir {
+                track!(runner.enable_snapshots(dir, &recipe))?;
+            }
+            track!(runner.run())
+        });
+        match result {
+            Err(e)
+                if retries < opt.max_retries
+                    && is_retriable(&e)
+                    && !INTERRUPTED.load(atomic::Ordering::SeqCst) =>
+            {
+                eprintln!(
+                    "Study failed (retry {}/{} after {} seconds): {}",
+                    retries + 1,
+                    opt.max_retries,
+                    backoff,
+                    e
+                );
+                thread::sleep(Duration::from_secs_f64(backoff));
+                backoff *= 2.0;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_interrupted() -> bool {
+    INTERRUPTED.load(atomic::Ordering::SeqCst)
+}
+
+// The skipped study isn't recorded in the checkpoint, so it is run again on resume.
+fn skip_study(recipe: StudyRecipe, e: &Error, compress: Option<Compression>) -> Result<()> {
+    eprintln!("Study failed (skipped): {}", e);
+    track!(output(&FailedStudyRecord::new(recipe, e), compress))
+}
+
+// Only the errors caused by transient I/O failures (e.g., a crashed external program) are retried.
+fn is_retriable(e: &Error) -> bool {
+    matches!(e.kind(), ErrorKind::IoError | ErrorKind::UnexpectedEos)
+}
+
+// Emits the record as soon as the study finishes so that
+// the results can be monitored (and aren't lost on crash) during a long benchmark.
+fn output<T: Serialize>(record: &T, compress: Option<Compression>) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    track!(write_record(&mut stdout, record, compress))
+}
+
+fn write_record<W: Write, T: Serialize>(
+    mut writer: W,
+    record: &T,
+    compress: Option<Compression>,
+) -> Result<()> {
+    let mut line = track!(serde_json::to_vec(record).map_err(Error::from))?;
+    line.push(b'\n');
+    if let Some(compression) = compress {
+        line = track!(compression.compress(&line))?;
+    }
+    track!(writer.write_all(&line).map_err(Error::from))?;
+    track!(writer.flush().map_err(Error::from))?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Checkpoint {
+    file: File,
+    compression: Option<Compression>,
+    completed: Vec<StudyRecord>,
+}
+impl Checkpoint {
+    fn open(path: PathBuf) -> Result<Self> {
+        let completed = if path.exists() {
+            let file = track!(File::open(&path).map_err(Error::from); path)?;
+            track!(record::load_studies(BufReader::new(file)); path)?
+        } else {
+            Vec::new()
+        };
+        let file = track!(OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(Error::from); path)?;
+        Ok(Self {
+            file,
+            compression: Compression::from_path(&path),
+            completed,
+        })
+    }
+
+    /// Outputs the completed studies, then returns the recipes of the remaining ones.
+    ///
+    /// The seed of a recipe is compared only if it has been specified explicitly.
+    fn restore(
+        &self,
+        recipes: Vec<StudyRecipe>,
+        compress: Option<Compression>,
+    ) -> Result<Vec<StudyRecipe>> {
+        let mut completed = self
+            .completed
+            .iter()
+            .map(|record| track!(checkpoint_key(&record.recipe())).map(|key| Some((record, key))))
+            .collect::<Result<Vec<_>>>()?;
+        let mut remainings = Vec::new();
+        for recipe in recipes {
+            let key = track!(checkpoint_key(&recipe))?;
+            let position = completed.iter().position(|c| {
+                c.as_ref().map_or(false, |(record, k)| {
+                    recipe.seed.map_or(true, |seed| seed == record.seed) && *k == key
+                })
+            });
+
+            if let Some((record, _)) = position.and_then(|i| completed[i].take()) {
+                track!(output(record, compress))?;
+            } else {
+                remainings.push(recipe);
+            }
+        }
+        Ok(remainings)
+    }
+
+    fn record(&mut self, record: &StudyRecord) -> Result<()> {
+        track!(write_record(&mut self.file, record, self.compression))
+    }
+}
+
+// Returns the key to identify the completed studies of a recipe.
+//
+// The key consists of the whole recipe except for the seed (which is compared separately).
+fn checkpoint_key(recipe: &StudyRecipe) -> Result<serde_json::Value> {
+    let recipe = StudyRecipe {
+        seed: None,
+        ..recipe.clone()
+    };
+    track!(serde_json::to_value(recipe).map_err(Error::from))
+}
+
 #[derive(Debug)]
 pub(crate) struct StudyRunner {
     solver: BoxSolver,
@@ -183,15 +954,31 @@ pub(crate) struct StudyRunner {
     idg: IdGen,
     threads: EvaluationThreads,
     evaluators: HashMap<TrialId, EvaluatorState>,
+    running: RunningEvaluations,
+    batch_size: Option<NonZeroUsize>,
+    batch: VecDeque<(NextTrial, ElapsedSeconds)>,
+    pruner: Option<StudyPruner>,
+    trial_log: Option<TrialLog>,
+    hooks: Option<Hooks>,
+    snapshot: Option<SnapshotFile>,
+    unsaved_trials: usize,
+    solver_checkpointable: bool,
+    output_capture: Option<OutputCapture>,
     study_steps: u64,
+    time_budget: Option<Duration>,
     opt: RunnerOpt,
+    label: String,
+    start_time: Instant,
+    last_reported: Option<Instant>,
+    solver_name: String,
+    dashboard: Option<Dashboard>,
     _mpb: Option<MultiProgress>,
 }
 impl StudyRunner {
     pub fn new(study: &StudyRecipe) -> Result<Self> {
         let opt = RunnerOpt {
-            parallelism: unsafe { NonZeroUsize::new_unchecked(1) },
             quiet: true,
+            ..RunnerOpt::default()
         };
         let mpb = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
         let mut this = track!(Self::with_mpb(study, &opt, &mpb))?;
@@ -200,14 +987,30 @@ impl StudyRunner {
     }
 
     fn with_mpb(study: &StudyRecipe, opt: &RunnerOpt, mpb: &MultiProgress) -> Result<Self> {
-        let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
+        let mut registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
+        let output_capture = if opt.capture_stderr {
+            let capture = OutputCapture::new(Some(opt.capture_stderr_limit));
+            registry = registry.with_output_capture(capture.clone());
+            Some(capture)
+        } else {
+            None
+        };
 
         let random_seed = study.seed.unwrap_or_else(rand::random);
         let rng = ArcRng::new(random_seed);
 
         let problem_factory = track!(study.problem.create_factory(&registry))?;
         let problem_spec = track!(problem_factory.specification())?;
-        let problem = track!(problem_factory.create_problem(rng.clone()))?;
+        let mut problem = track!(problem_factory.create_problem(rng.clone()))?;
+        if let Some(dir) = &opt.eval_cache {
+            track!(std::fs::create_dir_all(dir).map_err(Error::from); dir)?;
+            let problem_record = ProblemRecord {
+                recipe: study.problem.clone(),
+                spec: problem_spec.clone(),
+            };
+            let path = dir.join(format!("{}.jsonl", track!(problem_record.id())?));
+            problem = track!(crate::problem::with_shared_cache(problem, &path))?;
+        }
 
         let solver_factory = track!(study.solver.create_factory(&registry))?;
         let solver_spec = track!(solver_factory.specification())?;
@@ -217,15 +1020,33 @@ impl StudyRunner {
             .incapables(&problem_spec.requirements())
             .collect::<Vec<_>>();
         track_assert!(incapables.is_empty(), ErrorKind::Incapable; incapables);
+        let solver_checkpointable = solver_spec
+            .capabilities
+            .is_capable(Capability::Checkpointable);
 
-        let solver = track!(solver_factory.create_solver(rng.clone(), &problem_spec))?;
+        let mut solver = track!(solver_factory.create_solver(rng.clone(), &problem_spec))?;
+        if let Some(path) = &study.prior {
+            if solver_spec
+                .capabilities
+                .is_capable(Capability::WarmStarting)
+            {
+                let trials = track!(load_prior_trials(path, &problem_spec))?;
+                track!(solver.tell_priors(trials))?;
+            }
+        }
 
         let study_steps = problem_spec.steps.last() * study.budget;
+        if let Some(time_budget) = study.time_budget {
+            track_assert!(time_budget > 0.0, ErrorKind::InvalidInput; time_budget);
+        }
+        let time_budget = study.time_budget.map(Duration::from_secs_f64);
+        let solver_name = solver_spec.name.clone();
+        let label = format!("{:?} {:?}", solver_spec.name, problem_spec.name);
         let pb = mpb.add(ProgressBar::new(study_steps));
         let pb_style = ProgressStyle::default_bar().template(&format!(
             "(STUDY) [{{elapsed_precise}}] [STEPS {{pos:>6}}/{{len}} \
-             {{percent:>3}}%] [ETA {{eta:>3}}] {:?} {:?}",
-            solver_spec.name, problem_spec.name
+             {{percent:>3}}%] [ETA {{eta:>3}}] {}",
+            label
         ));
         pb.set_style(pb_style);
 
@@ -233,6 +1054,29 @@ impl StudyRunner {
         recipe.seed = Some(random_seed);
         let study_record = StudyRecordBuilder::new(recipe, solver_spec, problem_spec.clone());
         let threads = EvaluationThreads::new(study, rng);
+        let pruner = study
+            .pruner
+            .map(|p| StudyPruner::new(p, &problem_spec.steps));
+        let trial_log = if let Some(path) = &opt.trial_log {
+            Some(track!(TrialLog::open(
+                path,
+                &solver_name,
+                &problem_spec.name,
+                random_seed
+            ))?)
+        } else {
+            None
+        };
+        let hooks = if opt.hook.is_empty() {
+            None
+        } else {
+            Some(Hooks::new(
+                &opt.hook,
+                &solver_name,
+                &problem_spec.name,
+                random_seed,
+            ))
+        };
         Ok(Self {
             solver,
             problem,
@@ -242,52 +1086,236 @@ impl StudyRunner {
             idg: IdGen::new(),
             threads,
             evaluators: HashMap::new(),
+            running: RunningEvaluations::new(),
+            batch_size: study.batch_size,
+            batch: VecDeque::new(),
+            pruner,
+            trial_log,
+            hooks,
+            snapshot: None,
+            unsaved_trials: 0,
+            solver_checkpointable,
+            output_capture,
             study_steps,
+            time_budget,
             opt: opt.clone(),
+            label,
+            start_time: Instant::now(),
+            last_reported: None,
+            solver_name,
+            dashboard: None,
             _mpb: None,
         })
     }
 
+    // Restores the study from the snapshot if it exists.
+    fn enable_snapshots(&mut self, dir: &Path, recipe: &StudyRecipe) -> Result<()> {
+        if recipe.concurrency.get() != 1 || self.pruner.is_some() || !self.solver_checkpointable {
+            return Ok(());
+        }
+
+        let file = track!(SnapshotFile::new(dir, recipe))?;
+        if let Some(snapshot) = track!(file.load())? {
+            track!(self.solver.load_state(snapshot.solver_state))?;
+            self.idg = IdGen::from_next_id(snapshot.next_trial_id);
+            self.pb.set_position(snapshot.elapsed_steps);
+            self.threads.threads[0].elapsed_steps = snapshot.elapsed_steps;
+            self.study_record.restore_trials(snapshot.trials);
+            self.study_record.restore_infeasible_trials(
+                snapshot.infeasible_trials,
+                snapshot.constraint_violation,
+            );
+        }
+        self.snapshot = Some(file);
+        Ok(())
+    }
+
+    // A snapshot is taken only when no trials are in progress,
+    // because the states of the evaluators can't be saved.
+    fn save_snapshot_if_needed(&mut self) -> Result<()> {
+        if self.unsaved_trials < self.opt.solver_checkpoint_interval.get()
+            || !self.threads.is_all_idle()
+            || !self.evaluators.is_empty()
+            || !self.batch.is_empty()
+        {
+            return Ok(());
+        }
+        if let Some(file) = &self.snapshot {
+            let snapshot = StudySnapshot {
+                solver_state: track!(self.solver.save_state())?,
+                next_trial_id: self.idg.peek_id().get(),
+                elapsed_steps: self.pb.position(),
+                trials: self
+                    .study_record
+                    .trials()
+                    .map(|(id, t)| (id, t.clone()))
+                    .collect(),
+                infeasible_trials: self.study_record.infeasible_trials(),
+                constraint_violation: self.study_record.constraint_violation(),
+            };
+            track!(file.save(&snapshot))?;
+            self.unsaved_trials = 0;
+        }
+        Ok(())
+    }
+
     pub fn run_init(&mut self) -> Result<()> {
         self.pb.reset_elapsed();
+        self.start_time = Instant::now();
         Ok(())
     }
 
     pub fn run_once(&mut self) -> Result<()> {
         track!(self.fill_waiting_queue())?;
+        if self.threads.scheduling == Scheduling::Async {
+            return track!(self.run_once_async());
+        }
 
         let start_step = self.pb.position();
         let thread = track!(self.threads.next())?;
         let thread_id = thread.thread_id;
-        let WaitingTrial {
-            asked_trial,
-            ask_elapsed,
-        } = track!(thread.next_trial())?;
-        let next_step = track_assert_some!(asked_trial.next_step, ErrorKind::Bug);
+        let waiting = track!(thread.next_trial())?;
+        let next_step = track_assert_some!(waiting.asked_trial.next_step, ErrorKind::Bug);
 
         let problem_spec = &self.problem_spec;
         let evaluators = &mut self.evaluators;
+        let timeout = self.opt.trial_timeout.map(Duration::from_secs_f64);
+        let on_error = self.opt.on_error;
         let ((elapsed_steps, evaluated_trial), evaluate_elapsed) =
             ElapsedSeconds::try_time(|| {
-                track!(thread.evaluate(asked_trial.id, next_step, problem_spec, evaluators))
+                track!(thread.evaluate(
+                    waiting.asked_trial.id,
+                    next_step,
+                    problem_spec,
+                    evaluators,
+                    timeout,
+                    on_error
+                ))
             })?;
+        track!(self.tell(
+            thread_id,
+            waiting,
+            start_step,
+            elapsed_steps,
+            evaluated_trial,
+            evaluate_elapsed
+        ))
+    }
+
+    // Starts the evaluations of the waiting trials, and handles the one that finishes first.
+    fn run_once_async(&mut self) -> Result<()> {
+        for thread in self.threads.threads.iter_mut() {
+            if thread.running || thread.waitings.is_empty() {
+                continue;
+            }
+
+            let waiting = track!(thread.next_trial())?;
+            let next_step = track_assert_some!(waiting.asked_trial.next_step, ErrorKind::Bug);
+            let next_step = track_assert_some!(
+                self.problem_spec.steps.iter().find(|&s| s >= next_step),
+                ErrorKind::Bug
+            );
+            let state = track_assert_some!(
+                self.evaluators.remove(&waiting.asked_trial.id),
+                ErrorKind::Bug
+            );
+            thread.running = true;
+            self.running
+                .start(thread.thread_id, waiting, state, next_step);
+        }
+
+        let timeout = self.opt.trial_timeout.map(Duration::from_secs_f64);
+        let finished = track!(self.running.wait(timeout, self.opt.on_error))?;
+        let thread = track_assert_some!(
+            self.threads
+                .threads
+                .iter_mut()
+                .find(|t| t.thread_id == finished.thread_id),
+            ErrorKind::Bug
+        );
+        thread.running = false;
+
+        let trial_id = finished.waiting.asked_trial.id;
+        let (elapsed_steps, evaluated_trial) =
+            if let Some((state, current_step, values)) = finished.result {
+                track!(complete_evaluation(
+                    trial_id,
+                    state,
+                    current_step,
+                    values,
+                    &self.problem_spec,
+                    &mut self.evaluators
+                ))?
+            } else {
+                timed_out_evaluation(trial_id, finished.prev_step, finished.next_step)
+            };
+        thread.elapsed_steps += elapsed_steps;
+
+        let start_step = self.pb.position();
+        track!(self.tell(
+            finished.thread_id,
+            finished.waiting,
+            start_step,
+            elapsed_steps,
+            evaluated_trial,
+            finished.elapsed
+        ))
+    }
+
+    fn tell(
+        &mut self,
+        thread_id: usize,
+        waiting: WaitingTrial,
+        start_step: u64,
+        elapsed_steps: u64,
+        evaluated_trial: EvaluatedTrial,
+        evaluate_elapsed: ElapsedSeconds,
+    ) -> Result<()> {
         self.pb.inc(elapsed_steps);
         let end_step = self.pb.position();
 
+        if let Some(log) = &mut self.trial_log {
+            track!(log.evaluate(&evaluated_trial, evaluate_elapsed))?;
+        }
+
         if end_step <= self.study_steps {
+            let mut rung = None;
+            if let Some(pruner) = &mut self.pruner {
+                let (pruned, r) = pruner.report(&evaluated_trial, self.problem_spec.steps.last());
+                if pruned {
+                    self.evaluators.remove(&evaluated_trial.id);
+                }
+                rung = r;
+            }
+
             let ((), tell_elapsed) =
                 ElapsedSeconds::try_time(|| track!(self.solver.tell(evaluated_trial.clone())))?;
+            if let Some(log) = &mut self.trial_log {
+                track!(log.tell(evaluated_trial.id, tell_elapsed))?;
+            }
 
+            if let Some(hooks) = &self.hooks {
+                hooks.trial_completed(
+                    evaluated_trial.id,
+                    waiting.asked_trial.params.get(),
+                    &evaluated_trial.values,
+                    start_step,
+                    end_step,
+                );
+            }
+
+            self.unsaved_trials += 1;
             self.study_record.add_trial(TrialRecordBuilder {
-                id: asked_trial.id,
+                id: waiting.asked_trial.id,
                 thread_id,
-                params: asked_trial.params,
+                params: waiting.asked_trial.params,
                 values: evaluated_trial.values,
                 start_step,
                 end_step,
-                ask_elapsed,
+                ask_elapsed: waiting.ask_elapsed,
                 tell_elapsed,
                 evaluate_elapsed,
+                rung,
             });
         }
 
@@ -296,20 +1324,57 @@ impl StudyRunner {
 
     fn fill_waiting_queue(&mut self) -> Result<()> {
         while self.threads.has_idle_thread() {
-            let (asked_trial, ask_elapsed) =
-                ElapsedSeconds::try_time(|| track!(self.solver.ask(&mut self.idg)))?;
+            let (asked_trial, ask_elapsed) = if let Some(batch_size) = self.batch_size {
+                if self.batch.is_empty() {
+                    if !self.threads.is_all_idle() {
+                        // Waits until all the trials of the current batch are evaluated.
+                        break;
+                    }
+                    track!(self.ask_batch(batch_size))?;
+                }
+                track_assert_some!(self.batch.pop_front(), ErrorKind::Bug)
+            } else {
+                let (trial, elapsed) =
+                    ElapsedSeconds::try_time(|| track!(self.solver.ask(&mut self.idg)))?;
+                if let Some(log) = &mut self.trial_log {
+                    track!(log.ask(&trial, elapsed))?;
+                }
+                (trial, elapsed)
+            };
 
-            if let Err(e) = track!(self.init_evaluator(&asked_trial)) {
-                if *e.kind() != ErrorKind::UnevaluableParams {
-                    return Err(e);
-                } else {
-                    let unevaluable = EvaluatedTrial {
+            if self
+                .pruner
+                .as_ref()
+                .map_or(false, |p| p.is_pruned(asked_trial.id))
+            {
+                if asked_trial.next_step.is_some() {
+                    let pruned = EvaluatedTrial {
                         id: asked_trial.id,
                         values: Values::new(vec![]),
                         current_step: 0,
                     };
-                    track!(self.solver.tell(unevaluable))?
+                    track!(self.solver.tell(pruned))?;
                 }
+            } else if let Err(e) = track!(self.init_evaluator(&asked_trial)) {
+                if *e.kind() != ErrorKind::UnevaluableParams {
+                    let result: Result<()> = Err(e);
+                    track!(skip_failed_evaluation(
+                        result,
+                        asked_trial.id,
+                        self.opt.on_error
+                    ))?;
+                } else {
+                    let violation = e
+                        .concrete_cause::<ConstraintViolation>()
+                        .map_or(0.0, |v| v.0);
+                    self.study_record.add_infeasible_trial(violation);
+                }
+                let unevaluable = EvaluatedTrial {
+                    id: asked_trial.id,
+                    values: Values::new(vec![]),
+                    current_step: 0,
+                };
+                track!(self.solver.tell(unevaluable))?
             } else if asked_trial.next_step.is_some() {
                 track!(self.threads.assign(&asked_trial, ask_elapsed))?;
             } else {
@@ -319,6 +1384,26 @@ impl StudyRunner {
         Ok(())
     }
 
+    // The elapsed time of the ask is attributed to the first trial of the batch.
+    fn ask_batch(&mut self, batch_size: NonZeroUsize) -> Result<()> {
+        let (trials, ask_elapsed) = ElapsedSeconds::try_time(|| {
+            track!(self.solver.ask_batch(&mut self.idg, batch_size.get()))
+        })?;
+        track_assert!(!trials.is_empty(), ErrorKind::InvalidInput);
+        for (i, trial) in trials.into_iter().enumerate() {
+            let elapsed = if i == 0 {
+                ask_elapsed
+            } else {
+                ElapsedSeconds::zero()
+            };
+            if let Some(log) = &mut self.trial_log {
+                track!(log.ask(&trial, elapsed))?;
+            }
+            self.batch.push_back((trial, elapsed));
+        }
+        Ok(())
+    }
+
     pub fn current_step(&self) -> u64 {
         self.pb.position()
     }
@@ -335,16 +1420,102 @@ impl StudyRunner {
 
     fn run(mut self) -> Result<StudyRecord> {
         track!(self.run_init())?;
+        if let Some(hooks) = &self.hooks {
+            hooks.study_started();
+        }
+
+        let dashboard_id = self
+            .dashboard
+            .as_ref()
+            .map(|d| d.start_study(&self.solver_name, &self.problem_spec.name, self.study_steps));
+        let result = track!(self.run_steps(dashboard_id));
+        if let (Some(dashboard), Some(id)) = (&self.dashboard, dashboard_id) {
+            dashboard.finish_study(id, result.is_ok());
+        }
+        let stderr = self.output_capture.as_ref().and_then(|c| c.take());
+        if let (Err(e), Some(stderr)) = (&result, &stderr) {
+            eprintln!(
+                "Study failed: {}\nCaptured standard error output ({}):\n{}",
+                e, self.label, stderr
+            );
+        }
+        if let (Err(e), Some(hooks)) = (&result, &self.hooks) {
+            hooks.study_finished(Err(e));
+        }
+        result?;
+
+        self.pb.finish_and_clear();
+        if let Some(file) = &self.snapshot {
+            track!(file.remove())?;
+        }
+        let mut record = self.study_record.finish();
+        record.stderr = stderr;
+        if let Some(hooks) = &self.hooks {
+            hooks.study_finished(Ok(&record));
+        }
+        Ok(record)
+    }
 
+    fn run_steps(&mut self, dashboard_id: Option<usize>) -> Result<()> {
         while self.pb.position() < self.study_steps {
-            if self.pb.is_hidden() && !self.opt.quiet {
-                eprintln!("DONE: {}/{}", self.pb.position(), self.study_steps);
+            track_assert!(
+                !INTERRUPTED.load(atomic::Ordering::SeqCst),
+                ErrorKind::Other,
+                "Interrupted"
+            );
+            if self
+                .time_budget
+                .map_or(false, |t| self.start_time.elapsed() >= t)
+            {
+                break;
+            }
+            if self.pb.is_hidden() && !self.opt.quiet && !self.opt.dashboard {
+                self.report_progress();
             }
             track!(self.run_once())?;
+            if self.snapshot.is_some() {
+                track!(self.save_snapshot_if_needed())?;
+            }
+
+            if let (Some(dashboard), Some(id)) = (&self.dashboard, dashboard_id) {
+                let best_value = self
+                    .best_values()
+                    .filter(|vs| vs.len() == 1)
+                    .map(|vs| vs[0]);
+                dashboard.update_study(id, self.pb.position(), best_value);
+            }
         }
+        Ok(())
+    }
 
-        self.pb.finish_and_clear();
-        Ok(self.study_record.finish())
+    // Prints a plain progress line (at most once per second) when the progress bar can't be drawn
+    // (e.g., the standard error isn't a terminal).
+    fn report_progress(&mut self) {
+        if self
+            .last_reported
+            .map_or(false, |t| t.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_reported = Some(Instant::now());
+
+        let position = self.pb.position();
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let eta = if position == 0 {
+            "-".to_owned()
+        } else {
+            let remainings = self.study_steps.saturating_sub(position) as f64;
+            format!("{:.0}s", elapsed / position as f64 * remainings)
+        };
+        let percentage = if self.study_steps == 0 {
+            100
+        } else {
+            position * 100 / self.study_steps
+        };
+        eprintln!(
+            "(STUDY) [{:.0}s] [STEPS {:>6}/{} {:>3}%] [ETA {}] {}",
+            elapsed, position, self.study_steps, percentage, eta, self.label
+        );
     }
 
     #[allow(clippy::map_entry)]
@@ -383,26 +1554,37 @@ impl EvaluationThreads {
         self.threads.iter().any(|t| t.is_idle())
     }
 
+    fn is_all_idle(&self) -> bool {
+        self.threads.iter().all(|t| t.is_idle())
+    }
+
     fn next(&mut self) -> Result<&mut EvaluationThread> {
         match self.scheduling {
             Scheduling::Fair => {
                 let thread = track_assert_some!(
-                    self.threads.iter_mut().min_by_key(|t| t.elapsed_steps),
+                    self.threads
+                        .iter_mut()
+                        .filter(|t| !t.waitings.is_empty())
+                        .min_by_key(|t| t.elapsed_steps),
                     ErrorKind::Bug
                 );
                 Ok(thread)
             }
             Scheduling::Random => {
-                let thread =
-                    track_assert_some!(self.threads.choose_mut(&mut self.rng), ErrorKind::Bug);
-                Ok(thread)
+                // Some threads may have no waiting trials when the batch mode is enabled.
+                let candidates = (0..self.threads.len())
+                    .filter(|&i| !self.threads[i].waitings.is_empty())
+                    .collect::<Vec<_>>();
+                let i = *track_assert_some!(candidates.choose(&mut self.rng), ErrorKind::Bug);
+                Ok(&mut self.threads[i])
             }
+            Scheduling::Async => track_panic!(ErrorKind::Bug),
         }
     }
 
     fn assign(&mut self, trial: &NextTrial, ask_elapsed: ElapsedSeconds) -> Result<()> {
         let thread = track_assert_some!(
-            self.threads.iter_mut().find(|t| t.waitings.is_empty()),
+            self.threads.iter_mut().find(|t| t.is_idle()),
             ErrorKind::Bug
         );
         thread.waitings.push_back(WaitingTrial {
@@ -418,6 +1600,7 @@ struct EvaluationThread {
     thread_id: usize,
     waitings: VecDeque<WaitingTrial>,
     elapsed_steps: u64,
+    running: bool,
 }
 impl EvaluationThread {
     fn new(thread_id: usize) -> Self {
@@ -425,11 +1608,12 @@ impl EvaluationThread {
             thread_id,
             waitings: VecDeque::new(),
             elapsed_steps: 0,
+            running: false,
         }
     }
 
     fn is_idle(&self) -> bool {
-        self.waitings.is_empty()
+        self.waitings.is_empty() && !self.running
     }
 
     fn next_trial(&mut self) -> Result<WaitingTrial> {
@@ -443,6 +1627,8 @@ impl EvaluationThread {
         next_step: u64,
         problem_spec: &ProblemSpec,
         evaluators: &mut HashMap<TrialId, EvaluatorState>,
+        timeout: Option<Duration>,
+        on_error: ErrorPolicy,
     ) -> Result<(u64, EvaluatedTrial)> {
         let mut state = track_assert_some!(evaluators.remove(&trial_id), ErrorKind::Bug);
 
@@ -450,22 +1636,86 @@ impl EvaluationThread {
             problem_spec.steps.iter().find(|&s| s >= next_step),
             ErrorKind::Bug
         );
-        let (current_step, values) = track!(state.evaluator.evaluate(next_step))?;
-        track_assert!(state.current_step <= current_step, ErrorKind::Bug);
-        let elapsed_steps = current_step - state.current_step;
+        let prev_step = state.current_step;
+        let result = if let Some(timeout) = timeout {
+            track!(state.evaluate_with_timeout(next_step, timeout))
+        } else {
+            track!(state.evaluator.evaluate(next_step))
+                .map(move |(current_step, values)| Some((state, current_step, values)))
+        };
+        let (state, current_step, values) =
+            match track!(skip_failed_evaluation(result, trial_id, on_error))?.flatten() {
+                Some(x) => x,
+                None => {
+                    let (elapsed_steps, evaluated) =
+                        timed_out_evaluation(trial_id, prev_step, next_step);
+                    self.elapsed_steps += elapsed_steps;
+                    return Ok((elapsed_steps, evaluated));
+                }
+            };
+        let (elapsed_steps, evaluated) = track!(complete_evaluation(
+            trial_id,
+            state,
+            current_step,
+            values,
+            problem_spec,
+            evaluators
+        ))?;
         self.elapsed_steps += elapsed_steps;
+        Ok((elapsed_steps, evaluated))
+    }
+}
 
-        state.current_step = current_step;
-        if state.current_step < problem_spec.steps.last() && !values.is_empty() {
-            evaluators.insert(trial_id, state);
-        }
+fn complete_evaluation(
+    trial_id: TrialId,
+    mut state: EvaluatorState,
+    current_step: u64,
+    values: Values,
+    problem_spec: &ProblemSpec,
+    evaluators: &mut HashMap<TrialId, EvaluatorState>,
+) -> Result<(u64, EvaluatedTrial)> {
+    track_assert!(state.current_step <= current_step, ErrorKind::Bug);
+    let elapsed_steps = current_step - state.current_step;
 
-        let evaluated = EvaluatedTrial {
-            id: trial_id,
-            values,
-            current_step,
-        };
-        Ok((elapsed_steps, evaluated))
+    state.current_step = current_step;
+    if state.current_step < problem_spec.steps.last() && !values.is_empty() {
+        evaluators.insert(trial_id, state);
+    }
+
+    let evaluated = EvaluatedTrial {
+        id: trial_id,
+        values,
+        current_step,
+    };
+    Ok((elapsed_steps, evaluated))
+}
+
+// A timed-out evaluation is regarded as the one that consumed the steps but failed.
+fn timed_out_evaluation(
+    trial_id: TrialId,
+    prev_step: u64,
+    next_step: u64,
+) -> (u64, EvaluatedTrial) {
+    let evaluated = EvaluatedTrial {
+        id: trial_id,
+        values: Values::new(Vec::new()),
+        current_step: next_step,
+    };
+    (next_step - prev_step, evaluated)
+}
+
+// With `--on-error skip-trial`, a failed evaluation is regarded as a timed-out one (i.e., `None` is returned).
+fn skip_failed_evaluation<T>(
+    result: Result<T>,
+    trial_id: TrialId,
+    on_error: ErrorPolicy,
+) -> Result<Option<T>> {
+    match result {
+        Err(e) if on_error == ErrorPolicy::SkipTrial && !is_interrupted() => {
+            eprintln!("Trial {} failed (skipped): {}", trial_id.get(), e);
+            Ok(None)
+        }
+        result => track!(result).map(Some),
     }
 }
 
@@ -488,4 +1738,241 @@ impl EvaluatorState {
             current_step: 0,
         })
     }
+
+    // Evaluates in a separate thread and returns `None` if the evaluation doesn't finish in time.
+    //
+    // The timed-out evaluation is aborted via `Evaluator::abort_handle`.
+    // If the evaluator can't be aborted, the thread (and the evaluator) is just abandoned.
+    fn evaluate_with_timeout(
+        mut self,
+        next_step: u64,
+        timeout: Duration,
+    ) -> Result<Option<(Self, u64, Values)>> {
+        let abort_handle = self.evaluator.abort_handle();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = self.evaluator.evaluate(next_step);
+            let _ = tx.send((self, result));
+        });
+        match rx.recv_timeout(timeout) {
+            Ok((state, result)) => {
+                let (current_step, values) = track!(result)?;
+                Ok(Some((state, current_step, values)))
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(handle) = abort_handle {
+                    handle.abort();
+                }
+                Ok(None)
+            }
+            Err(e) => track_panic!(ErrorKind::Other, "Evaluation thread failed: {}", e),
+        }
+    }
+}
+
+// Sequence number, evaluator state and result sent from a background evaluation thread.
+type EvaluationMessage = (u64, EvaluatorState, Result<(u64, Values)>);
+
+// Evaluations that are actually running in background threads (used by `Scheduling::Async`).
+#[derive(Debug)]
+struct RunningEvaluations {
+    next_seqno: u64,
+    evaluations: HashMap<u64, RunningEvaluation>,
+    tx: mpsc::Sender<EvaluationMessage>,
+    rx: mpsc::Receiver<EvaluationMessage>,
+}
+impl RunningEvaluations {
+    fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            next_seqno: 0,
+            evaluations: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    fn start(
+        &mut self,
+        thread_id: usize,
+        waiting: WaitingTrial,
+        mut state: EvaluatorState,
+        next_step: u64,
+    ) {
+        let seqno = self.next_seqno;
+        self.next_seqno += 1;
+        self.evaluations.insert(
+            seqno,
+            RunningEvaluation {
+                thread_id,
+                waiting,
+                prev_step: state.current_step,
+                next_step,
+                start_time: Instant::now(),
+                abort_handle: state.evaluator.abort_handle(),
+            },
+        );
+
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let result = state.evaluator.evaluate(next_step);
+            let _ = tx.send((seqno, state, result));
+        });
+    }
+
+    // Waits until one of the running evaluations finishes or times out.
+    //
+    // Like `EvaluatorState::evaluate_with_timeout`, a timed-out evaluation is aborted
+    // (or abandoned if it can't be aborted), and its result is discarded when it arrives later.
+    fn wait(
+        &mut self,
+        timeout: Option<Duration>,
+        on_error: ErrorPolicy,
+    ) -> Result<FinishedEvaluation> {
+        track_assert!(!self.evaluations.is_empty(), ErrorKind::Bug);
+        loop {
+            let earliest = self
+                .evaluations
+                .iter()
+                .min_by_key(|(_, e)| e.start_time)
+                .map(|(&seqno, e)| (seqno, e.start_time));
+            let received = match (timeout, earliest) {
+                (Some(timeout), Some((seqno, start_time))) => {
+                    let wait = (start_time + timeout).saturating_duration_since(Instant::now());
+                    match self.rx.recv_timeout(wait) {
+                        Ok(x) => x,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            let evaluation =
+                                track_assert_some!(self.evaluations.remove(&seqno), ErrorKind::Bug);
+                            if let Some(handle) = &evaluation.abort_handle {
+                                handle.abort();
+                            }
+                            return Ok(evaluation.finish(None));
+                        }
+                        Err(e) => {
+                            track_panic!(ErrorKind::Other, "Evaluation thread failed: {}", e)
+                        }
+                    }
+                }
+                _ => track!(self.rx.recv().map_err(|e| ErrorKind::Other.cause(e)))?,
+            };
+
+            let (seqno, state, result) = received;
+            if let Some(evaluation) = self.evaluations.remove(&seqno) {
+                let trial_id = evaluation.waiting.asked_trial.id;
+                let result = track!(skip_failed_evaluation(result, trial_id, on_error))?;
+                return Ok(evaluation
+                    .finish(result.map(|(current_step, values)| (state, current_step, values))));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RunningEvaluation {
+    thread_id: usize,
+    waiting: WaitingTrial,
+    prev_step: u64,
+    next_step: u64,
+    start_time: Instant,
+    abort_handle: Option<AbortHandle>,
+}
+impl RunningEvaluation {
+    fn finish(self, result: Option<(EvaluatorState, u64, Values)>) -> FinishedEvaluation {
+        FinishedEvaluation {
+            thread_id: self.thread_id,
+            waiting: self.waiting,
+            prev_step: self.prev_step,
+            next_step: self.next_step,
+            elapsed: ElapsedSeconds::from(self.start_time.elapsed()),
+            result,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FinishedEvaluation {
+    thread_id: usize,
+    waiting: WaitingTrial,
+    prev_step: u64,
+    next_step: u64,
+    elapsed: ElapsedSeconds,
+
+    // `None` means that the evaluation timed out or failed (see `skip_failed_evaluation`).
+    result: Option<(EvaluatorState, u64, Values)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kurobako_core::problem::Evaluator;
+    use kurobako_core::trial::Params;
+
+    // An evaluator that sleeps until it is aborted.
+    #[derive(Debug, Default)]
+    struct SleepEvaluator {
+        aborted: Arc<AtomicBool>,
+    }
+    impl Evaluator for SleepEvaluator {
+        fn evaluate(&mut self, _next_step: u64) -> Result<(u64, Values)> {
+            while !self.aborted.load(atomic::Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            track_panic!(ErrorKind::Other, "Aborted");
+        }
+
+        fn abort_handle(&self) -> Option<AbortHandle> {
+            let aborted = Arc::clone(&self.aborted);
+            Some(AbortHandle::new(move || {
+                aborted.store(true, atomic::Ordering::SeqCst)
+            }))
+        }
+    }
+
+    fn sleep_evaluator() -> (EvaluatorState, Arc<AtomicBool>) {
+        let evaluator = SleepEvaluator::default();
+        let aborted = Arc::clone(&evaluator.aborted);
+        let state = EvaluatorState {
+            evaluator: BoxEvaluator::new(evaluator),
+            current_step: 0,
+        };
+        (state, aborted)
+    }
+
+    #[test]
+    fn only_io_errors_are_retriable() {
+        assert!(is_retriable(&ErrorKind::IoError.into()));
+        assert!(is_retriable(&ErrorKind::UnexpectedEos.into()));
+        assert!(!is_retriable(&ErrorKind::Other.into()));
+        assert!(!is_retriable(&ErrorKind::InvalidInput.into()));
+    }
+
+    #[test]
+    fn sync_evaluation_timeout_aborts_evaluator() -> trackable::result::TopLevelResult {
+        let (state, aborted) = sleep_evaluator();
+        let result = track!(state.evaluate_with_timeout(1, Duration::from_millis(100)))?;
+        assert!(result.is_none());
+        assert!(aborted.load(atomic::Ordering::SeqCst));
+        Ok(())
+    }
+
+    #[test]
+    fn async_evaluation_timeout_aborts_evaluator() -> trackable::result::TopLevelResult {
+        let (state, aborted) = sleep_evaluator();
+        let waiting = WaitingTrial {
+            asked_trial: NextTrial {
+                id: TrialId::new(0),
+                params: Params::new(Vec::new()),
+                next_step: Some(1),
+            },
+            ask_elapsed: ElapsedSeconds::zero(),
+        };
+        let mut running = RunningEvaluations::new();
+        running.start(0, waiting, state, 1);
+
+        let finished = track!(running.wait(Some(Duration::from_millis(100)), ErrorPolicy::Abort))?;
+        assert!(finished.result.is_none());
+        assert!(aborted.load(atomic::Ordering::SeqCst));
+        Ok(())
+    }
 }
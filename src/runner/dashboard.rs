@@ -0,0 +1,245 @@
+//! Terminal dashboard of the `kurobako run --dashboard` option.
+//!
+//! The state of the dashboard is also exposed by the `kurobako run --metrics-addr` option.
+//!
+//! The dashboard is drawn as plain text with ANSI escape sequences rather than by a TUI library
+//! such as `ratatui`, because it is a read-only view that needs neither input handling nor layout widgets,
+//! and it is drawn to the standard error in place of the progress bars (the standard output carries the results).
+#![allow(clippy::format_push_string)]
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Dashboard that periodically redraws the progress of a benchmark on the standard error.
+#[derive(Debug, Clone)]
+pub(crate) struct Dashboard {
+    state: Arc<Mutex<State>>,
+    closed: Arc<AtomicBool>,
+}
+impl Dashboard {
+    pub fn new(total_studies: usize) -> Self {
+        let state = State {
+            start_time: Instant::now(),
+            total_studies,
+            finished_studies: 0,
+            failures: 0,
+            evaluations: 0,
+            next_id: 0,
+            running: BTreeMap::new(),
+            best_values: BTreeMap::new(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Spawns a thread that redraws the dashboard until `close` is called.
+    pub fn spawn_renderer(&self) -> JoinHandle<()> {
+        let this = self.clone();
+        thread::spawn(move || {
+            while !this.closed.load(atomic::Ordering::SeqCst) {
+                this.render();
+                thread::sleep(REFRESH_INTERVAL);
+            }
+            this.render();
+        })
+    }
+
+    pub fn close(&self) {
+        self.closed.store(true, atomic::Ordering::SeqCst);
+    }
+
+    pub fn start_study(&self, solver: &str, problem: &str, max_steps: u64) -> usize {
+        self.with_state(|s| {
+            let id = s.next_id;
+            s.next_id += 1;
+            s.running.insert(
+                id,
+                RunningStudy {
+                    solver: solver.to_owned(),
+                    problem: problem.to_owned(),
+                    steps: 0,
+                    max_steps,
+                },
+            );
+            id
+        })
+    }
+
+    pub fn update_study(&self, id: usize, steps: u64, best_value: Option<f64>) {
+        self.with_state(|s| {
+            s.evaluations += 1;
+            if let Some(study) = s.running.get_mut(&id) {
+                study.steps = steps;
+                if let Some(v) = best_value {
+                    let key = (study.problem.clone(), study.solver.clone());
+                    let best = s.best_values.entry(key).or_insert(v);
+                    if v < *best {
+                        *best = v;
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn finish_study(&self, id: usize, succeeded: bool) {
+        self.with_state(|s| {
+            s.running.remove(&id);
+            if succeeded {
+                s.finished_studies += 1;
+            } else {
+                s.failures += 1;
+            }
+        })
+    }
+
+    /// Returns the current state in the Prometheus text exposition format.
+    pub fn render_metrics(&self) -> String {
+        self.with_state(|s| s.render_metrics())
+    }
+
+    fn with_state<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut State) -> T,
+    {
+        let mut state = self.state.lock().unwrap_or_else(|e| panic!("{}", e));
+        f(&mut state)
+    }
+
+    fn render(&self) {
+        let text = self.with_state(|s| s.render());
+        let stderr = std::io::stderr();
+        let mut stderr = stderr.lock();
+        let _ = write!(stderr, "\x1b[2J\x1b[H{}", text);
+        let _ = stderr.flush();
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    start_time: Instant,
+    total_studies: usize,
+    finished_studies: usize,
+    failures: usize,
+    evaluations: u64,
+    next_id: usize,
+    running: BTreeMap<usize, RunningStudy>,
+    best_values: BTreeMap<(String, String), f64>,
+}
+impl State {
+    fn render(&self) -> String {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let mut s = format!(
+            "[{:.0}s] STUDIES: {}/{} (running: {}, failures: {}) \
+             EVALUATIONS: {} ({:.2}/s)\n\n",
+            elapsed,
+            self.finished_studies,
+            self.total_studies,
+            self.running.len(),
+            self.failures,
+            self.evaluations,
+            self.evaluations as f64 / elapsed.max(1e-3)
+        );
+
+        s += "RUNNING STUDIES:\n";
+        for study in self.running.values() {
+            s += &format!(
+                "  [{:>3}%] {:?} {:?}\n",
+                study.steps * 100 / study.max_steps.max(1),
+                study.solver,
+                study.problem
+            );
+        }
+        s += "\n";
+
+        s += "BEST VALUES:\n";
+        let mut last_problem = None;
+        for ((problem, solver), value) in &self.best_values {
+            if last_problem != Some(problem) {
+                s += &format!("  {}\n", problem);
+                last_problem = Some(problem);
+            }
+            s += &format!("    {:<30} {}\n", solver, value);
+        }
+        s
+    }
+
+    fn render_metrics(&self) -> String {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let mut s = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+            s += &format!(
+                "# HELP {0} {1}\n# TYPE {0} {2}\n{0} {3}\n",
+                name, help, kind, value
+            );
+        };
+        metric(
+            "kurobako_studies",
+            "gauge",
+            "Number of the studies to be run.",
+            self.total_studies as f64,
+        );
+        metric(
+            "kurobako_studies_completed_total",
+            "counter",
+            "Number of the completed studies.",
+            self.finished_studies as f64,
+        );
+        metric(
+            "kurobako_study_failures_total",
+            "counter",
+            "Number of the failed studies.",
+            self.failures as f64,
+        );
+        metric(
+            "kurobako_running_studies",
+            "gauge",
+            "Number of the running studies.",
+            self.running.len() as f64,
+        );
+        metric(
+            "kurobako_evaluations_total",
+            "counter",
+            "Number of the evaluations.",
+            self.evaluations as f64,
+        );
+        metric(
+            "kurobako_evaluations_per_second",
+            "gauge",
+            "Average number of the evaluations per second.",
+            self.evaluations as f64 / elapsed.max(1e-3),
+        );
+
+        s += "# HELP kurobako_best_value Best value found so far for each pair of a problem and a solver.\n";
+        s += "# TYPE kurobako_best_value gauge\n";
+        for ((problem, solver), value) in &self.best_values {
+            s += &format!(
+                "kurobako_best_value{{problem=\"{}\",solver=\"{}\"}} {}\n",
+                escape_label(problem),
+                escape_label(solver),
+                value
+            );
+        }
+        s
+    }
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Debug)]
+struct RunningStudy {
+    solver: String,
+    problem: String,
+    steps: u64,
+    max_steps: u64,
+}
@@ -0,0 +1,122 @@
+//! Hook commands of the `kurobako run --hook` option.
+use crate::record::StudyRecord;
+use crate::time::DateTime;
+use chrono::Local;
+use kurobako_core::trial::TrialId;
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::Serialize;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+/// Shell commands that are invoked on the events of a study.
+///
+/// Each event is given to the standard input of the commands as a JSON object.
+/// The failures of the commands are reported to the standard error, and don't affect the study.
+#[derive(Debug)]
+pub(crate) struct Hooks {
+    commands: Vec<String>,
+    solver: String,
+    problem: String,
+    seed: u64,
+}
+impl Hooks {
+    pub fn new(commands: &[String], solver: &str, problem: &str, seed: u64) -> Self {
+        Self {
+            commands: commands.to_owned(),
+            solver: solver.to_owned(),
+            problem: problem.to_owned(),
+            seed,
+        }
+    }
+
+    pub fn study_started(&self) {
+        self.invoke(Event::StudyStarted);
+    }
+
+    pub fn trial_completed(
+        &self,
+        trial_id: TrialId,
+        params: &[f64],
+        values: &[f64],
+        start_step: u64,
+        end_step: u64,
+    ) {
+        self.invoke(Event::TrialCompleted {
+            trial_id,
+            params,
+            values,
+            start_step,
+            end_step,
+        });
+    }
+
+    pub fn study_finished(&self, result: std::result::Result<&StudyRecord, &Error>) {
+        match result {
+            Ok(record) => self.invoke(Event::StudyFinished { record }),
+            Err(e) => self.invoke(Event::StudyFailed {
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    fn invoke(&self, event: Event) {
+        let payload = Payload {
+            timestamp: Local::now(),
+            solver: &self.solver,
+            problem: &self.problem,
+            seed: self.seed,
+            event,
+        };
+        for command in &self.commands {
+            if let Err(e) = track!(run_command(command, &payload)) {
+                eprintln!("Hook command {:?} failed: {}", command, e);
+            }
+        }
+    }
+}
+
+fn run_command(command: &str, payload: &Payload) -> Result<()> {
+    let mut child = track!(Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(Error::from))?;
+    {
+        let mut stdin = child.stdin.take().unwrap_or_else(|| unreachable!());
+        track!(serde_json::to_writer(&mut stdin, payload).map_err(Error::from))?;
+        track!(writeln!(stdin).map_err(Error::from))?;
+    }
+    let status = track!(child.wait().map_err(Error::from))?;
+    track_assert!(status.success(), ErrorKind::Other; status);
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    timestamp: DateTime,
+    solver: &'a str,
+    problem: &'a str,
+    seed: u64,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    StudyStarted,
+    TrialCompleted {
+        trial_id: TrialId,
+        params: &'a [f64],
+        values: &'a [f64],
+        start_step: u64,
+        end_step: u64,
+    },
+    StudyFinished {
+        record: &'a StudyRecord,
+    },
+    StudyFailed {
+        error: String,
+    },
+}
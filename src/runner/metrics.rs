@@ -0,0 +1,51 @@
+//! Prometheus metrics endpoint of the `kurobako run --metrics-addr` option.
+use super::dashboard::Dashboard;
+use kurobako_core::{Error, Result};
+use std::io::{Read as _, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+/// Starts a thread that serves the metrics of the given dashboard state over HTTP.
+///
+/// Every request is responded with the metrics regardless of its method and path.
+pub(crate) fn spawn_server(addr: SocketAddr, dashboard: Dashboard) -> Result<()> {
+    let listener = track!(TcpListener::bind(addr).map_err(Error::from); addr)?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(Error::from)
+                .and_then(|stream| track!(respond(stream, &dashboard)));
+            if let Err(e) = result {
+                eprintln!("Metrics endpoint error: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, dashboard: &Dashboard) -> Result<()> {
+    // The request is read (and discarded) only up to the end of its header.
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let size = track!(stream.read(&mut buf).map_err(Error::from))?;
+        if size == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..size]);
+    }
+
+    let body = dashboard.render_metrics();
+    track!(write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .map_err(Error::from))?;
+    track!(stream.flush().map_err(Error::from))?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Runner-side pruners (see the `--pruner` option of `kurobako studies`).
+use crate::record::RungRecord;
+use crate::study::Pruner;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::problem::EvaluableSteps;
+use kurobako_core::trial::{EvaluatedTrial, TrialId};
+use std::collections::{HashMap, HashSet};
+
+// Minimum number of the values reported at a step before the median pruner starts to prune trials at the step.
+const MIN_TRIALS: usize = 5;
+
+// Reduction factor of the successive halving.
+const ETA: u64 = 3;
+
+/// Pruner that is applied by the runner regardless of the solver.
+#[derive(Debug)]
+pub(crate) enum StudyPruner {
+    Median(MedianPruner),
+    Hyperband(HyperbandPruner),
+}
+impl StudyPruner {
+    pub fn new(pruner: Pruner, steps: &EvaluableSteps) -> Self {
+        match pruner {
+            Pruner::Median => Self::Median(MedianPruner::default()),
+            Pruner::Hyperband => Self::Hyperband(HyperbandPruner::new(steps)),
+        }
+    }
+
+    pub fn is_pruned(&self, trial_id: TrialId) -> bool {
+        match self {
+            Self::Median(p) => p.pruned.contains(&trial_id),
+            Self::Hyperband(p) => p.pruned.contains(&trial_id),
+        }
+    }
+
+    /// Reports the intermediate result of a trial.
+    ///
+    /// The first item of the result indicates whether the trial should be pruned.
+    pub fn report(&mut self, trial: &EvaluatedTrial, last_step: u64) -> (bool, Option<RungRecord>) {
+        if trial.current_step >= last_step || trial.values.len() != 1 {
+            return (false, None);
+        }
+
+        match self {
+            Self::Median(p) => (p.report(trial), None),
+            Self::Hyperband(p) => p.report(trial),
+        }
+    }
+}
+
+/// Pruner based on the median stopping rule.
+///
+/// A trial is pruned if its intermediate value is worse than the median of
+/// the values of the preceding trials at the same step.
+/// Only single-objective problems are supported (otherwise no trials are pruned).
+#[derive(Debug, Default)]
+pub(crate) struct MedianPruner {
+    history: HashMap<u64, Vec<OrderedFloat<f64>>>,
+    pruned: HashSet<TrialId>,
+}
+impl MedianPruner {
+    fn report(&mut self, trial: &EvaluatedTrial) -> bool {
+        let value = OrderedFloat(trial.values[0]);
+        let values = self.history.entry(trial.current_step).or_default();
+        let prune = values.len() >= MIN_TRIALS && value > median(values);
+        values.push(value);
+
+        if prune {
+            self.pruned.insert(trial.id);
+        }
+        prune
+    }
+}
+
+/// Pruner based on Hyperband.
+///
+/// Trials are assigned to the brackets in a round-robin manner,
+/// and each bracket runs the asynchronous successive halving with its own minimum step.
+/// At each rung, a trial is promoted to the next rung only if its value is in the top `1/ETA`
+/// of the values reported at the rung in the bracket.
+/// Only single-objective problems are supported (otherwise no trials are pruned).
+#[derive(Debug)]
+pub(crate) struct HyperbandPruner {
+    brackets: Vec<Vec<u64>>,
+    rungs: HashMap<(usize, usize), Vec<OrderedFloat<f64>>>,
+    next_rungs: HashMap<TrialId, usize>,
+    pruned: HashSet<TrialId>,
+}
+impl HyperbandPruner {
+    fn new(steps: &EvaluableSteps) -> Self {
+        let min_step = steps.iter().next().unwrap_or_else(|| unreachable!()).max(1);
+        let last_step = steps.last();
+
+        let mut brackets = Vec::new();
+        let mut bracket_min_step = min_step;
+        loop {
+            // The steps of the rungs of a bracket are `bracket_min_step * ETA^i` (rounded up to evaluable steps).
+            let mut rungs = Vec::new();
+            let mut step = bracket_min_step;
+            while step < last_step {
+                if let Some(s) = steps.iter().find(|&s| s >= step) {
+                    if s < last_step && rungs.last().map_or(true, |&r| r < s) {
+                        rungs.push(s);
+                    }
+                }
+                step *= ETA;
+            }
+            let is_last = rungs.is_empty();
+            brackets.push(rungs);
+            if is_last {
+                break;
+            }
+            bracket_min_step *= ETA;
+        }
+
+        Self {
+            brackets,
+            rungs: HashMap::new(),
+            next_rungs: HashMap::new(),
+            pruned: HashSet::new(),
+        }
+    }
+
+    fn report(&mut self, trial: &EvaluatedTrial) -> (bool, Option<RungRecord>) {
+        let bracket = trial.id.get() as usize % self.brackets.len();
+        let next_rung = self.next_rungs.get(&trial.id).copied().unwrap_or(0);
+        let rung = if let Some(rung) = self.brackets[bracket]
+            .iter()
+            .rposition(|&s| s <= trial.current_step)
+            .filter(|&r| r >= next_rung)
+        {
+            rung
+        } else {
+            return (false, None);
+        };
+        self.next_rungs.insert(trial.id, rung + 1);
+
+        let value = OrderedFloat(trial.values[0]);
+        let values = self.rungs.entry((bracket, rung)).or_default();
+        values.push(value);
+        values.sort();
+        let promotable_index = (values.len() / ETA as usize).max(1) - 1;
+        let promoted = value <= values[promotable_index];
+
+        if !promoted {
+            self.pruned.insert(trial.id);
+        }
+        let record = RungRecord {
+            bracket,
+            rung,
+            step: self.brackets[bracket][rung],
+            promoted,
+        };
+        (!promoted, Some(record))
+    }
+}
+
+fn median(values: &[OrderedFloat<f64>]) -> OrderedFloat<f64> {
+    let mut values = values.to_owned();
+    values.sort();
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        OrderedFloat((values[n / 2 - 1].0 + values[n / 2].0) / 2.0)
+    }
+}
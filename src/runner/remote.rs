@@ -0,0 +1,60 @@
+//! Client of remote workers (see `kurobako worker`).
+use crate::record::StudyRecord;
+use crate::study::StudyRecipe;
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::net::TcpStream;
+
+/// Response of a remote worker to a study recipe.
+pub(crate) type Response = std::result::Result<StudyRecord, String>;
+
+/// Connection to a remote worker.
+#[derive(Debug)]
+pub(crate) struct RemoteWorker {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+impl RemoteWorker {
+    /// Connects to a remote worker and authenticates the connection with the given token.
+    pub fn connect(addr: &str, token: &str) -> Result<Self> {
+        let writer = track!(TcpStream::connect(addr).map_err(Error::from); addr)?;
+        let reader = BufReader::new(track!(writer.try_clone().map_err(Error::from))?);
+        let mut this = Self { reader, writer };
+
+        track!(this.send(&token))?;
+        let reply: std::result::Result<(), String> = track!(this.recv())?;
+        if let Err(e) = reply {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                "Worker {:?} rejected the connection: {}",
+                addr,
+                e
+            );
+        }
+        Ok(this)
+    }
+
+    /// Runs a study on the remote worker.
+    ///
+    /// An error is returned only if the communication with the worker failed.
+    /// The failure of the study itself is reported as the `Err` of the response.
+    pub fn run_study(&mut self, recipe: &StudyRecipe) -> Result<Response> {
+        track!(self.send(recipe))?;
+        track!(self.recv())
+    }
+
+    fn send<T: Serialize>(&mut self, message: &T) -> Result<()> {
+        track!(serde_json::to_writer(&mut self.writer, message).map_err(Error::from))?;
+        track!(writeln!(self.writer).map_err(Error::from))?;
+        track!(self.writer.flush().map_err(Error::from))
+    }
+
+    fn recv<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let mut line = String::new();
+        let size = track!(self.reader.read_line(&mut line).map_err(Error::from))?;
+        track_assert_ne!(size, 0, ErrorKind::UnexpectedEos);
+        track!(serde_json::from_str(&line).map_err(Error::from))
+    }
+}
@@ -0,0 +1,91 @@
+//! Study snapshots of the `kurobako run --solver-checkpoint-dir` option.
+use crate::record::TrialRecord;
+use crate::study::StudyRecipe;
+use kurobako_core::trial::TrialId;
+use kurobako_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write as _};
+use std::path::{Path, PathBuf};
+
+/// Snapshot of a running study that is used to resume the study from the middle.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct StudySnapshot {
+    /// The internal state of the solver.
+    pub solver_state: serde_json::Value,
+
+    /// The next trial ID.
+    pub next_trial_id: u64,
+
+    /// The number of the consumed steps.
+    pub elapsed_steps: u64,
+
+    /// The trials recorded so far.
+    pub trials: Vec<(TrialId, TrialRecord)>,
+
+    /// The number of the trials rejected as unevaluable so far.
+    #[serde(default)]
+    pub infeasible_trials: u64,
+
+    /// The total amount of the constraint violations of the infeasible trials so far.
+    #[serde(default)]
+    pub constraint_violation: f64,
+}
+
+/// File to which the snapshots of a study are saved.
+#[derive(Debug)]
+pub(crate) struct SnapshotFile {
+    path: PathBuf,
+}
+impl SnapshotFile {
+    /// Makes a `SnapshotFile` instance for the given study in the directory `dir`.
+    ///
+    /// The file name is derived from the recipe (including the seed),
+    /// so the same file is used when the study is run again.
+    pub fn new(dir: &Path, recipe: &StudyRecipe) -> Result<Self> {
+        track!(fs::create_dir_all(dir).map_err(Error::from); dir)?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(&track!(serde_json::to_vec(recipe).map_err(Error::from))?);
+        let mut name = String::with_capacity(64 + 5);
+        for b in hasher.finalize().as_slice() {
+            track_write!(&mut name, "{:02x}", b)?;
+        }
+        name += ".json";
+        Ok(Self {
+            path: dir.join(name),
+        })
+    }
+
+    pub fn load(&self) -> Result<Option<StudySnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let file = track!(File::open(&self.path).map_err(Error::from); self.path)?;
+        let snapshot =
+            track!(serde_json::from_reader(BufReader::new(file)).map_err(Error::from); self.path)?;
+        Ok(Some(snapshot))
+    }
+
+    // Writes to a temporary file first so that a crash while saving doesn't corrupt the previous snapshot.
+    pub fn save(&self, snapshot: &StudySnapshot) -> Result<()> {
+        let temp_path = self.path.with_extension("json.tmp");
+        {
+            let file = track!(File::create(&temp_path).map_err(Error::from); temp_path)?;
+            let mut writer = BufWriter::new(file);
+            track!(serde_json::to_writer(&mut writer, snapshot).map_err(Error::from))?;
+            track!(writer.flush().map_err(Error::from))?;
+        }
+        track!(fs::rename(&temp_path, &self.path).map_err(Error::from); temp_path, self.path)?;
+        Ok(())
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            track!(fs::remove_file(&self.path).map_err(Error::from); self.path)?;
+        }
+        Ok(())
+    }
+}
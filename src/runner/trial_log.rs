@@ -0,0 +1,107 @@
+//! Trial event log of the `kurobako run --trial-log` option.
+use crate::time::{DateTime, ElapsedSeconds};
+use chrono::Local;
+use kurobako_core::trial::{EvaluatedTrial, NextTrial, TrialId};
+use kurobako_core::{Error, Result};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::Path;
+
+/// Writer of the ask/evaluate/tell events of the trials of a study (JSON Lines).
+///
+/// The log file is opened in the append mode, so multiple studies can share the same file.
+#[derive(Debug)]
+pub(crate) struct TrialLog {
+    file: File,
+    solver: String,
+    problem: String,
+    seed: u64,
+}
+impl TrialLog {
+    pub fn open<P: AsRef<Path>>(path: P, solver: &str, problem: &str, seed: u64) -> Result<Self> {
+        let file = track!(OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .map_err(Error::from); path.as_ref())?;
+        Ok(Self {
+            file,
+            solver: solver.to_owned(),
+            problem: problem.to_owned(),
+            seed,
+        })
+    }
+
+    pub fn ask(&mut self, trial: &NextTrial, elapsed: ElapsedSeconds) -> Result<()> {
+        track!(self.write(
+            trial.id,
+            Event::Ask {
+                params: trial.params.get(),
+                next_step: trial.next_step,
+                elapsed,
+            }
+        ))
+    }
+
+    pub fn evaluate(&mut self, trial: &EvaluatedTrial, elapsed: ElapsedSeconds) -> Result<()> {
+        track!(self.write(
+            trial.id,
+            Event::Evaluate {
+                values: &trial.values,
+                current_step: trial.current_step,
+                elapsed,
+            }
+        ))
+    }
+
+    pub fn tell(&mut self, trial_id: TrialId, elapsed: ElapsedSeconds) -> Result<()> {
+        track!(self.write(trial_id, Event::Tell { elapsed }))
+    }
+
+    fn write(&mut self, trial_id: TrialId, event: Event) -> Result<()> {
+        let line = Line {
+            timestamp: Local::now(),
+            solver: &self.solver,
+            problem: &self.problem,
+            seed: self.seed,
+            trial_id,
+            event,
+        };
+
+        // Writes a line at once so that the lines of concurrent studies aren't interleaved.
+        let mut buf = track!(serde_json::to_vec(&line).map_err(Error::from))?;
+        buf.push(b'\n');
+        track!(self.file.write_all(&buf).map_err(Error::from))?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    timestamp: DateTime,
+    solver: &'a str,
+    problem: &'a str,
+    seed: u64,
+    trial_id: TrialId,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Ask {
+        params: &'a [f64],
+        next_step: Option<u64>,
+        elapsed: ElapsedSeconds,
+    },
+    Evaluate {
+        values: &'a [f64],
+        current_step: u64,
+        elapsed: ElapsedSeconds,
+    },
+    Tell {
+        elapsed: ElapsedSeconds,
+    },
+}
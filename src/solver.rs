@@ -5,7 +5,10 @@ use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::rng::ArcRng;
 use kurobako_core::solver::{BoxSolver, BoxSolverFactory, SolverFactory, SolverRecipe, SolverSpec};
 use kurobako_core::Result;
-use kurobako_solvers::{asha, nsga2, optuna, random};
+use kurobako_solvers::{
+    annealing, asha, bohb, cmaes, gp, grid, hyperband, moead, nelder_mead, nsga2, optuna, random,
+    sobol, tpe,
+};
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
@@ -18,10 +21,26 @@ pub struct KurobakoSolverRecipe {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
+    /// Tags of the solver (used to select studies by `kurobako run --only/--skip`).
+    #[structopt(long = "tag", number_of_values = 1)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
     #[structopt(flatten)]
     #[serde(flatten)]
     inner: InnerRecipe,
 }
+impl KurobakoSolverRecipe {
+    /// Returns the name of this recipe if specified.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the tags of this recipe.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
 impl SolverRecipe for KurobakoSolverRecipe {
     type Factory = KurobakoSolverFactory;
 
@@ -40,8 +59,18 @@ impl SolverRecipe for KurobakoSolverRecipe {
 enum InnerRecipe {
     Command(epi::solver::ExternalProgramSolverRecipe),
     Random(random::RandomSolverRecipe),
+    Sobol(sobol::SobolSolverRecipe),
+    Grid(grid::GridSolverRecipe),
+    Cmaes(cmaes::CmaEsSolverRecipe),
+    Tpe(tpe::TpeSolverRecipe),
+    Gp(gp::GpSolverRecipe),
+    NelderMead(nelder_mead::NelderMeadSolverRecipe),
+    Annealing(annealing::AnnealingSolverRecipe),
     Asha(asha::AshaSolverRecipe),
+    Hyperband(hyperband::HyperbandSolverRecipe),
+    Bohb(bohb::BohbSolverRecipe),
     Nsga2(nsga2::Nsga2SolverRecipe),
+    Moead(moead::MoeadSolverRecipe),
     Optuna(optuna::OptunaSolverRecipe),
 }
 impl SolverRecipe for InnerRecipe {
@@ -50,9 +79,19 @@ impl SolverRecipe for InnerRecipe {
     fn create_factory(&self, registry: &FactoryRegistry) -> Result<Self::Factory> {
         match self {
             Self::Random(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Sobol(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Grid(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Cmaes(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Tpe(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Gp(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::NelderMead(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Annealing(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
             Self::Optuna(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
             Self::Asha(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Hyperband(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Bohb(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
             Self::Nsga2(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
+            Self::Moead(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
             Self::Command(r) => track!(r.create_factory(registry)).map(BoxSolverFactory::new),
         }
     }
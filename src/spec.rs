@@ -1,14 +1,21 @@
 //! `kurobako spec` command.
 use crate::problem::KurobakoProblemRecipe;
 use crate::solver::KurobakoSolverRecipe;
+use kurobako_core::domain::{Domain, Range};
 use kurobako_core::json;
 use kurobako_core::problem::{ProblemFactory as _, ProblemRecipe as _, ProblemSpec};
 use kurobako_core::registry::FactoryRegistry;
 use kurobako_core::solver::{SolverFactory as _, SolverRecipe as _, SolverSpec};
-use kurobako_core::Result;
+use kurobako_core::{Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::str::FromStr;
 use structopt::StructOpt;
 
+// Maximum number of the steps shown in a table.
+const MAX_SHOWN_STEPS: usize = 10;
+
 /// Options of the `kurobako spec` command.
 #[derive(Debug, Clone, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
@@ -18,6 +25,10 @@ pub enum SpecOpt {
         /// Problem recipe (JSON).
         #[structopt(parse(try_from_str = json::parse_json))]
         problem: KurobakoProblemRecipe,
+
+        /// Output format.
+        #[structopt(long, default_value = "json", possible_values = Format::POSSIBLE_VALUES)]
+        format: Format,
     },
 
     /// Show the specification of the given solver.
@@ -25,6 +36,10 @@ pub enum SpecOpt {
         /// Solver recipe (JSON).
         #[structopt(parse(try_from_str = json::parse_json))]
         solver: KurobakoSolverRecipe,
+
+        /// Output format.
+        #[structopt(long, default_value = "json", possible_values = Format::POSSIBLE_VALUES)]
+        format: Format,
     },
 }
 
@@ -33,18 +48,49 @@ impl SpecOpt {
     pub fn get_spec(&self) -> Result<Spec> {
         let registry = FactoryRegistry::new::<KurobakoProblemRecipe, KurobakoSolverRecipe>();
         match self {
-            Self::Problem { problem } => {
+            Self::Problem { problem, .. } => {
                 let problem_factory = track!(problem.create_factory(&registry))?;
                 let problem_spec = track!(problem_factory.specification())?;
                 Ok(Spec::Problem(problem_spec))
             }
-            Self::Solver { solver } => {
+            Self::Solver { solver, .. } => {
                 let solver_factory = track!(solver.create_factory(&registry))?;
                 let solver_spec = track!(solver_factory.specification())?;
                 Ok(Spec::Solver(solver_spec))
             }
         }
     }
+
+    /// Returns the output format.
+    pub fn format(&self) -> Format {
+        match self {
+            Self::Problem { format, .. } | Self::Solver { format, .. } => *format,
+        }
+    }
+}
+
+/// Output format of the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON.
+    Json,
+
+    /// Human-readable table.
+    Table,
+}
+impl Format {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["json", "table"];
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "table" => Ok(Format::Table),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format name: {:?}", s),
+        }
+    }
 }
 
 /// Specification.
@@ -57,3 +103,96 @@ pub enum Spec {
     /// Solver specification.
     Solver(SolverSpec),
 }
+impl Spec {
+    /// Writes this specification as a human-readable table.
+    pub fn write_table<W: Write>(&self, mut writer: W) -> Result<()> {
+        match self {
+            Self::Problem(spec) => {
+                track_writeln!(writer, "Name: {}", spec.name)?;
+                track!(write_attrs(&mut writer, &spec.attrs))?;
+
+                let steps = spec
+                    .steps
+                    .iter()
+                    .take(MAX_SHOWN_STEPS + 1)
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>();
+                if steps.len() > MAX_SHOWN_STEPS {
+                    track_writeln!(
+                        writer,
+                        "Steps: {}, ..., {}",
+                        steps[..MAX_SHOWN_STEPS].join(", "),
+                        spec.steps.last()
+                    )?;
+                } else {
+                    track_writeln!(writer, "Steps: {}", steps.join(", "))?;
+                }
+                if let Some(point) = &spec.reference_point {
+                    track_writeln!(writer, "Reference Point: {:?}", point.get())?;
+                }
+
+                track_writeln!(writer, "\nParameters:")?;
+                track!(write_domain(&mut writer, &spec.params_domain))?;
+                track_writeln!(writer, "\nValues:")?;
+                track!(write_domain(&mut writer, &spec.values_domain))?;
+            }
+            Self::Solver(spec) => {
+                track_writeln!(writer, "Name: {}", spec.name)?;
+                track!(write_attrs(&mut writer, &spec.attrs))?;
+                let capabilities = spec
+                    .capabilities
+                    .iter()
+                    .map(|c| format!("{:?}", c))
+                    .collect::<Vec<_>>();
+                track_writeln!(writer, "Capabilities: {}", capabilities.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_attrs<W: Write>(mut writer: W, attrs: &BTreeMap<String, String>) -> Result<()> {
+    if attrs.is_empty() {
+        return Ok(());
+    }
+
+    track_writeln!(writer, "Attributes:")?;
+    for (k, v) in attrs {
+        track_writeln!(writer, "  {}: {}", k, v)?;
+    }
+    Ok(())
+}
+
+fn write_domain<W: Write>(mut writer: W, domain: &Domain) -> Result<()> {
+    track_writeln!(
+        writer,
+        "| Name | Type | Range | Distribution | Constraint |"
+    )?;
+    track_writeln!(
+        writer,
+        "|:-----|:-----|:------|:-------------|:-----------|"
+    )?;
+    for var in domain.variables() {
+        let (ty, range) = match var.range() {
+            Range::Continuous { low, high } => ("continuous", format!("[{}, {})", low, high)),
+            Range::Discrete { low, high } => ("discrete", format!("[{}, {})", low, high)),
+            Range::Categorical { choices } => {
+                ("categorical", format!("{{{}}}", choices.join(", ")))
+            }
+        };
+        track_writeln!(
+            writer,
+            "| {} | {} | {} | {:?} | {} |",
+            var.name(),
+            ty,
+            range,
+            var.distribution(),
+            if var.constraint().is_some() {
+                "yes"
+            } else {
+                ""
+            }
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,546 @@
+//! `kurobako stats` command.
+use self::aggregate::AggregateOpt;
+use self::eaf::{AttainmentSurface, EafOpt};
+use self::elo::{EloOpt, Rating};
+use self::ert::{Ert, ErtOpt};
+use self::feasibility::Feasibility;
+use self::friedman::{FriedmanOpt, FriedmanResult};
+use self::igd::{Igd, IgdOpt};
+use self::importance::{Importance, ImportanceOpt};
+use self::overhead::{Overhead, OverheadOpt};
+use self::profile::{Profile, ProfileOpt};
+use self::summary::{Sample, Summary, SummaryOpt};
+use self::wilcoxon::{BayesianResult, WilcoxonOpt, WilcoxonResult};
+use self::win_tie_loss::{WinTieLoss, WinTieLossOpt};
+use crate::markdown as md;
+use crate::markdown::MarkdownWriter;
+use crate::record::{self, StudyRecord};
+use kurobako_core::compression;
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+pub mod aggregate;
+pub mod eaf;
+pub mod elo;
+pub mod ert;
+pub mod feasibility;
+pub mod friedman;
+pub mod igd;
+pub mod importance;
+pub mod overhead;
+pub mod profile;
+pub mod summary;
+pub mod wilcoxon;
+pub mod win_tie_loss;
+
+mod group;
+
+/// Options of the `kurobako stats` command.
+///
+/// The summaries of the best values of each pair of a solver and a problem are always computed,
+/// and the other sections are added by the corresponding flags (e.g., `--friedman`).
+/// The studies are grouped by solver name and problem ID.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct StatsOpt {
+    /// Output format.
+    #[structopt(
+        long,
+        default_value = "json",
+        possible_values = Format::POSSIBLE_VALUES
+    )]
+    pub format: Format,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub aggregate: AggregateOpt,
+
+    /// Truncates all the studies to the given budget before computing the statistics
+    /// (if the value is omitted, the minimum budget among the studies is used).
+    ///
+    /// It's an error if the given budget exceeds the budget of any study.
+    #[structopt(long)]
+    pub truncate_budget: Option<Option<u64>>,
+
+    /// Reads the studies as a stream and outputs the updated summaries periodically
+    /// (e.g., `tail -f result.json | kurobako stats --stream`).
+    ///
+    /// Only the summaries section is available in this mode.
+    #[structopt(long)]
+    pub stream: bool,
+
+    /// Number of the studies read between the outputs of `--stream`.
+    #[structopt(long, default_value = "1")]
+    pub stream_interval: NonZeroUsize,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub summary: SummaryOpt,
+
+    /// Adds the data profiles and the performance profiles of the solvers.
+    #[structopt(long)]
+    pub profile: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub profile_opt: ProfileOpt,
+
+    /// Adds the success rates and the expected running times to reach target values.
+    #[structopt(long)]
+    pub ert: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub ert_opt: ErtOpt,
+
+    /// Adds the ranks of the solvers by the Friedman test with post-hoc tests.
+    #[structopt(long)]
+    pub friedman: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub friedman_opt: FriedmanOpt,
+
+    /// Adds the p-value matrix of the Wilcoxon signed-rank tests of every pair of the solvers.
+    #[structopt(long)]
+    pub wilcoxon: bool,
+
+    /// Adds the posterior probabilities of the Bayesian signed-rank tests of every pair of the solvers.
+    #[structopt(long)]
+    pub bayesian_signed_rank: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub wilcoxon_opt: WilcoxonOpt,
+
+    /// Adds the win/tie/loss counts of every pair of the solvers.
+    #[structopt(long)]
+    pub win_tie_loss: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub win_tie_loss_opt: WinTieLossOpt,
+
+    /// Adds the Elo ratings of the solvers.
+    #[structopt(long)]
+    pub elo: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub elo_opt: EloOpt,
+
+    /// Adds the latency statistics of the ask/tell calls and the evaluations of the solvers.
+    #[structopt(long)]
+    pub overhead: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub overhead_opt: OverheadOpt,
+
+    /// Adds the feasibility statistics of the studies.
+    #[structopt(long)]
+    pub feasibility: bool,
+
+    /// Adds the IGD and IGD+ of the multi-objective studies.
+    #[structopt(long)]
+    pub igd: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub igd_opt: IgdOpt,
+
+    /// Adds the empirical attainment surfaces of the bi-objective studies.
+    #[structopt(long)]
+    pub eaf: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub eaf_opt: EafOpt,
+
+    /// Adds the parameter importances of the problems.
+    #[structopt(long)]
+    pub importance: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub importance_opt: ImportanceOpt,
+}
+impl StatsOpt {
+    /// Computes the statistics of the given studies.
+    pub fn stats(&self, studies: &[StudyRecord]) -> Result<StatsSummary> {
+        let mut truncated;
+        let studies = if let Some(budget) = self.truncate_budget {
+            truncated = studies.to_vec();
+            track!(record::truncate_studies(&mut truncated, budget))?;
+            &truncated[..]
+        } else {
+            studies
+        };
+
+        let aggregate = &self.aggregate;
+        Ok(StatsSummary {
+            summaries: track!(self.summary.summaries(studies, aggregate))?,
+            profiles: if self.profile {
+                Some(track!(self.profile_opt.profiles(studies))?)
+            } else {
+                None
+            },
+            erts: if self.ert {
+                Some(track!(self.ert_opt.erts(studies))?)
+            } else {
+                None
+            },
+            friedman: if self.friedman {
+                Some(track!(self.friedman_opt.friedmans(studies, aggregate))?)
+            } else {
+                None
+            },
+            wilcoxon: if self.wilcoxon {
+                Some(track!(self.wilcoxon_opt.wilcoxon(studies))?)
+            } else {
+                None
+            },
+            bayesian_signed_rank: if self.bayesian_signed_rank {
+                Some(track!(self.wilcoxon_opt.bayesian(studies))?)
+            } else {
+                None
+            },
+            win_tie_loss: if self.win_tie_loss {
+                Some(track!(self
+                    .win_tie_loss_opt
+                    .win_tie_loss(studies, aggregate))?)
+            } else {
+                None
+            },
+            elo: if self.elo {
+                Some(track!(self.elo_opt.ratings(studies))?)
+            } else {
+                None
+            },
+            overheads: if self.overhead {
+                Some(track!(self.overhead_opt.overheads(studies))?)
+            } else {
+                None
+            },
+            feasibilities: if self.feasibility {
+                Some(track!(feasibility::feasibilities(studies))?)
+            } else {
+                None
+            },
+            igds: if self.igd {
+                Some(track!(self.igd_opt.igds(studies))?)
+            } else {
+                None
+            },
+            attainment_surfaces: if self.eaf {
+                Some(track!(self.eaf_opt.attainment_surfaces(studies))?)
+            } else {
+                None
+            },
+            importances: if self.importance {
+                Some(track!(self.importance_opt.importances(studies))?)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Writes the statistics of the given studies in the format specified by the options.
+    pub fn write<W: Write>(&self, studies: &[StudyRecord], writer: W) -> Result<()> {
+        let stats = track!(self.stats(studies))?;
+        track!(self.write_stats(&stats, writer))
+    }
+
+    /// Reads study records from `reader` one by one, and writes the updated summaries to `writer`
+    /// every time `--stream-interval` studies are read (and when the stream ends).
+    ///
+    /// Only the summaries of the problems of the newly read studies are written.
+    pub fn stream<R: Read, W: Write>(&self, reader: R, mut writer: W) -> Result<()> {
+        let sections = [
+            self.profile,
+            self.ert,
+            self.friedman,
+            self.wilcoxon,
+            self.bayesian_signed_rank,
+            self.win_tie_loss,
+            self.elo,
+            self.overhead,
+            self.feasibility,
+            self.igd,
+            self.eaf,
+            self.importance,
+        ];
+        if sections.iter().any(|&enabled| enabled) {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                "Only the summaries are available with `--stream`"
+            );
+        }
+        let budget = match self.truncate_budget {
+            None => None,
+            Some(None) => track_panic!(
+                ErrorKind::InvalidInput,
+                "The budget of `--truncate-budget` must be specified with `--stream`"
+            ),
+            Some(budget) => budget,
+        };
+
+        let reader = track!(compression::decompress(reader))?;
+        let mut samples = Vec::new();
+        let mut updated = BTreeSet::new();
+        for json in serde_json::Deserializer::from_reader(reader).into_iter::<serde_json::Value>() {
+            let mut json = track!(json.map_err(Error::from))?;
+            if json.get("error").is_some() {
+                continue;
+            }
+            track!(record::migrate(&mut json))?;
+            let mut study: StudyRecord = track!(serde_json::from_value(json).map_err(Error::from))?;
+            if budget.is_some() {
+                track!(record::truncate_studies(
+                    std::slice::from_mut(&mut study),
+                    budget
+                ))?;
+            }
+            let sample = track!(Sample::new(&study))?;
+            updated.insert(sample.problem_id.clone());
+            samples.push(sample);
+
+            if samples.len() % self.stream_interval.get() == 0 {
+                track!(self.write_updates(&samples, &mut updated, &mut writer))?;
+            }
+        }
+        track!(self.write_updates(&samples, &mut updated, &mut writer))?;
+        Ok(())
+    }
+
+    fn write_updates<W: Write>(
+        &self,
+        samples: &[Sample],
+        updated: &mut BTreeSet<String>,
+        writer: W,
+    ) -> Result<()> {
+        if updated.is_empty() {
+            return Ok(());
+        }
+        let summaries = track!(self
+            .summary
+            .summarize(samples, &self.aggregate, |p| { updated.contains(p) }))?;
+        let stats = StatsSummary {
+            summaries,
+            ..StatsSummary::default()
+        };
+        track!(self.write_stats(&stats, writer))?;
+        updated.clear();
+        Ok(())
+    }
+
+    fn write_stats<W: Write>(&self, stats: &StatsSummary, mut writer: W) -> Result<()> {
+        match self.format {
+            Format::Json => {
+                track!(serde_json::to_writer(&mut writer, stats).map_err(Error::from))?;
+                track_writeln!(writer)?;
+            }
+            Format::Markdown => {
+                let mut writer = MarkdownWriter::new(&mut writer);
+                track!(stats.write_markdown(&mut writer))?;
+            }
+        }
+        track!(writer.flush().map_err(Error::from))?;
+        Ok(())
+    }
+}
+
+/// Output format of the `kurobako stats` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON.
+    Json,
+
+    /// Markdown.
+    ///
+    /// The sections that have no tabular form (e.g., the profiles) are written as JSON code blocks.
+    Markdown,
+}
+impl Format {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["json", "markdown"];
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "markdown" => Ok(Format::Markdown),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format name: {:?}", s),
+        }
+    }
+}
+
+/// Statistics of benchmark results.
+///
+/// The sections other than `summaries` are present only if the corresponding flags are specified.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StatsSummary {
+    /// Summaries of the best values of each pair of a solver and a problem.
+    pub summaries: Vec<Summary>,
+
+    /// Data profiles and performance profiles (`--profile`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<Vec<Profile>>,
+
+    /// Success rates and expected running times (`--ert`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub erts: Option<Vec<Ert>>,
+
+    /// Results of the Friedman test for all the problems and for each group of them (`--friedman`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friedman: Option<Vec<FriedmanResult>>,
+
+    /// Results of the pairwise Wilcoxon signed-rank tests (`--wilcoxon`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wilcoxon: Option<WilcoxonResult>,
+
+    /// Results of the pairwise Bayesian signed-rank tests (`--bayesian-signed-rank`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bayesian_signed_rank: Option<BayesianResult>,
+
+    /// Pairwise win/tie/loss counts (`--win-tie-loss`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub win_tie_loss: Option<WinTieLoss>,
+
+    /// Elo ratings in descending order (`--elo`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elo: Option<Vec<Rating>>,
+
+    /// Latency statistics (`--overhead`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overheads: Option<Vec<Overhead>>,
+
+    /// Feasibility statistics (`--feasibility`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasibilities: Option<Vec<Feasibility>>,
+
+    /// IGD and IGD+ (`--igd`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub igds: Option<Vec<Igd>>,
+
+    /// Empirical attainment surfaces (`--eaf`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attainment_surfaces: Option<Vec<AttainmentSurface>>,
+
+    /// Parameter importances (`--importance`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importances: Option<Vec<Importance>>,
+}
+impl StatsSummary {
+    fn write_markdown<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut writer = track!(writer.heading("Statistics"))?;
+
+        {
+            let mut writer = track!(writer.heading("Summaries"))?;
+            track!(write_summaries(&self.summaries, &mut writer))?;
+            track!(writer.newline())?;
+        }
+        track!(write_json_section(&mut writer, "Profiles", &self.profiles))?;
+        track!(write_json_section(
+            &mut writer,
+            "Expected Running Times",
+            &self.erts
+        ))?;
+        track!(write_json_section(
+            &mut writer,
+            "Friedman Tests",
+            &self.friedman
+        ))?;
+        if let Some(result) = &self.wilcoxon {
+            let mut writer = track!(writer.heading("Wilcoxon Signed-Rank Tests (p-values)"))?;
+            track!(result.write_markdown(&mut writer))?;
+            track!(writer.newline())?;
+        }
+        if let Some(result) = &self.bayesian_signed_rank {
+            let mut writer = track!(writer.heading("Bayesian Signed-Rank Tests"))?;
+            track!(result.write_markdown(&mut writer))?;
+            track!(writer.newline())?;
+        }
+        if let Some(result) = &self.win_tie_loss {
+            let mut writer = track!(writer.heading("Win/Tie/Loss"))?;
+            track!(result.write_markdown(&mut writer))?;
+            track!(writer.newline())?;
+        }
+        track!(write_json_section(&mut writer, "Elo Ratings", &self.elo))?;
+        track!(write_json_section(
+            &mut writer,
+            "Overheads",
+            &self.overheads
+        ))?;
+        track!(write_json_section(
+            &mut writer,
+            "Feasibilities",
+            &self.feasibilities
+        ))?;
+        track!(write_json_section(&mut writer, "IGDs", &self.igds))?;
+        track!(write_json_section(
+            &mut writer,
+            "Attainment Surfaces",
+            &self.attainment_surfaces
+        ))?;
+        track!(write_json_section(
+            &mut writer,
+            "Parameter Importances",
+            &self.importances
+        ))?;
+        Ok(())
+    }
+}
+
+fn write_summaries<W: Write>(summaries: &[Summary], writer: &mut MarkdownWriter<W>) -> Result<()> {
+    let mut table = md::Table::new(
+        vec![
+            md::ColumnHeader::new("Solver", md::Align::Left),
+            md::ColumnHeader::new("Problem", md::Align::Left),
+            md::ColumnHeader::new("Studies", md::Align::Right),
+            md::ColumnHeader::new("Aggregate", md::Align::Right),
+            md::ColumnHeader::new("Std", md::Align::Right),
+            md::ColumnHeader::new("Min", md::Align::Right),
+            md::ColumnHeader::new("Median", md::Align::Right),
+            md::ColumnHeader::new("Max", md::Align::Right),
+            md::ColumnHeader::new("IQR", md::Align::Right),
+        ]
+        .into_iter(),
+    );
+    let format = |v: Option<f64>| v.map_or_else(|| "-".to_owned(), |v| format!("{:.6}", v));
+    for s in summaries {
+        table
+            .row()
+            .item(&s.solver)
+            .item(&s.problem)
+            .item(s.studies.to_string())
+            .item(format(s.aggregate))
+            .item(format(s.std))
+            .item(format(s.min))
+            .item(format(s.median))
+            .item(format(s.max))
+            .item(format(s.iqr));
+    }
+    track!(writer.write_table(&table))?;
+    Ok(())
+}
+
+fn write_json_section<W: Write, T: Serialize>(
+    writer: &mut MarkdownWriter<W>,
+    title: &str,
+    section: &Option<T>,
+) -> Result<()> {
+    if let Some(section) = section {
+        let json = track!(serde_json::to_string_pretty(section).map_err(Error::from))?;
+        let mut writer = track!(writer.heading(title))?;
+        track!(writer.code_block("json", &json))?;
+        track!(writer.newline())?;
+    }
+    Ok(())
+}
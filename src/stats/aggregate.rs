@@ -0,0 +1,93 @@
+//! Aggregation of the values of multiple studies (e.g., the best values across seeds).
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{Error, ErrorKind, Result};
+use serde::Serialize;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Options to specify how to aggregate values.
+#[derive(Debug, Clone, StructOpt, Serialize)]
+#[structopt(rename_all = "kebab-case")]
+pub struct AggregateOpt {
+    /// Aggregation method of the best values across seeds.
+    #[structopt(
+        long,
+        default_value = "mean",
+        possible_values = Aggregate::POSSIBLE_VALUES
+    )]
+    pub aggregate: Aggregate,
+
+    /// Fraction of the values trimmed from each end by `--aggregate trimmed-mean`.
+    #[structopt(long, default_value = "0.1")]
+    pub trim_fraction: f64,
+}
+impl AggregateOpt {
+    /// Aggregates the given values.
+    ///
+    /// Returns `None` if `values` is empty.
+    pub fn aggregate(&self, values: &[f64]) -> Result<Option<f64>> {
+        track_assert!(
+            0.0 <= self.trim_fraction && self.trim_fraction < 0.5,
+            ErrorKind::InvalidInput;
+            self.trim_fraction
+        );
+        if values.is_empty() {
+            return Ok(None);
+        }
+
+        let mut values = values.to_vec();
+        values.sort_by_key(|&v| OrderedFloat(v));
+        let value = match self.aggregate {
+            Aggregate::Mean => mean(&values),
+            Aggregate::Median => {
+                let n = values.len();
+                if n % 2 == 1 {
+                    values[n / 2]
+                } else {
+                    (values[n / 2 - 1] + values[n / 2]) / 2.0
+                }
+            }
+            Aggregate::TrimmedMean => {
+                let k = (values.len() as f64 * self.trim_fraction).floor() as usize;
+                mean(&values[k..values.len() - k])
+            }
+        };
+        Ok(Some(value))
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Aggregation method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Aggregate {
+    /// Arithmetic mean.
+    Mean,
+
+    /// Median.
+    Median,
+
+    /// Mean of the values excluding the smallest and the largest ones.
+    TrimmedMean,
+}
+impl Aggregate {
+    const POSSIBLE_VALUES: &'static [&'static str] = &["mean", "median", "trimmed-mean"];
+}
+impl FromStr for Aggregate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mean" => Ok(Aggregate::Mean),
+            "median" => Ok(Aggregate::Median),
+            "trimmed-mean" => Ok(Aggregate::TrimmedMean),
+            _ => track_panic!(
+                ErrorKind::InvalidInput,
+                "Unknown aggregation method: {:?}",
+                s
+            ),
+        }
+    }
+}
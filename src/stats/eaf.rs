@@ -0,0 +1,127 @@
+//! Empirical attainment surfaces of bi-objective studies.
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+const DEFAULT_LEVELS: [f64; 3] = [0.0, 50.0, 100.0];
+
+/// Options of the empirical attainment surfaces of `kurobako stats` (`--eaf`).
+///
+/// The studies of each pair of a solver and a problem are regarded as the runs of the empirical attainment function.
+/// The `p%`-attainment surface is the boundary of the region dominated by at least `k` of the `n` runs,
+/// where `k = max(1, ceil(p * n / 100))` (i.e., `0%` is the best and `100%` is the worst surface).
+///
+/// Only bi-objective studies are supported (the other ones are ignored).
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct EafOpt {
+    /// Attainment levels of the surfaces in percent (the default is `0`, `50` and `100`).
+    #[structopt(long, number_of_values = 1)]
+    pub level: Vec<f64>,
+}
+impl EafOpt {
+    /// Computes the attainment surfaces of the solvers of the given studies.
+    pub fn attainment_surfaces(&self, studies: &[StudyRecord]) -> Result<Vec<AttainmentSurface>> {
+        let levels = if self.level.is_empty() {
+            &DEFAULT_LEVELS[..]
+        } else {
+            &self.level[..]
+        };
+        for &level in levels {
+            track_assert!((0.0..=100.0).contains(&level), ErrorKind::InvalidInput; level);
+        }
+
+        let studies = studies
+            .iter()
+            .filter(|study| study.problem.spec.values_domain.len() == 2);
+        let groups = group::by_solver(track!(group::group_studies(studies))?);
+
+        let mut surfaces = Vec::new();
+        for ((solver, _), studies) in groups {
+            let problem = &studies[0].problem.spec.name;
+            let runs = studies
+                .iter()
+                .map(|study| {
+                    let last_step = study.problem.spec.steps.last();
+                    study
+                        .trials
+                        .iter()
+                        .filter_map(|t| t.values(last_step))
+                        .filter(|vs| vs.len() == 2)
+                        .map(|vs| (vs[0], vs[1]))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for &level in levels {
+                let k = ((level * runs.len() as f64 / 100.0).ceil() as usize).max(1);
+                surfaces.push(AttainmentSurface {
+                    solver: solver.clone(),
+                    problem: problem.clone(),
+                    level,
+                    runs: runs.len(),
+                    points: attainment_surface(&runs, k),
+                });
+            }
+        }
+        Ok(surfaces)
+    }
+}
+
+// Returns the vertices of the boundary of the region weakly dominated by at least `k` runs
+// (in ascending order of the first objective).
+fn attainment_surface(runs: &[Vec<(f64, f64)>], k: usize) -> Vec<(f64, f64)> {
+    let mut xs = runs
+        .iter()
+        .flat_map(|r| r.iter().map(|p| OrderedFloat(p.0)))
+        .collect::<Vec<_>>();
+    xs.sort();
+    xs.dedup();
+
+    let mut surface = Vec::<(f64, f64)>::new();
+    for OrderedFloat(x) in xs {
+        // The best second objective of each run among the points whose first objective is at most `x`.
+        let mut ys = runs
+            .iter()
+            .filter_map(|r| {
+                r.iter()
+                    .filter(|p| p.0 <= x)
+                    .map(|p| OrderedFloat(p.1))
+                    .min()
+            })
+            .collect::<Vec<_>>();
+        if ys.len() < k {
+            continue;
+        }
+        ys.sort();
+        let y = ys[k - 1].0;
+        if surface.last().map_or(true, |last| y < last.1) {
+            surface.push((x, y));
+        }
+    }
+    surface
+}
+
+/// Empirical attainment surface of a solver on a problem.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttainmentSurface {
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Attainment level in percent.
+    pub level: f64,
+
+    /// Number of the runs (studies).
+    pub runs: usize,
+
+    /// Vertices (`[f1, f2]`) of the surface in ascending order of the first objective.
+    ///
+    /// The surface is the staircase that goes right from each vertex to the next one's first objective,
+    /// then goes down to it.
+    pub points: Vec<(f64, f64)>,
+}
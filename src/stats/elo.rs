@@ -0,0 +1,135 @@
+//! Elo ratings of solvers.
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+/// Options of the Elo ratings of `kurobako stats` (`--elo`).
+///
+/// Every pair of studies of different solvers that share a problem and a seed is regarded as a game,
+/// and the solver that found the smaller best value wins (equal best values are draws).
+/// The games are played in the order of problem IDs, seeds and solver names.
+///
+/// Because only matched studies are compared, a single leaderboard can be made
+/// even if the problems only partially overlap between solvers.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct EloOpt {
+    /// Initial Elo rating of the solvers.
+    #[structopt(long, default_value = "1500")]
+    pub initial_rating: f64,
+
+    /// K-factor of the Elo ratings (the maximum change of a rating by a game).
+    #[structopt(long, default_value = "32")]
+    pub k_factor: f64,
+
+    /// Number of the times to play all the games of the Elo ratings.
+    ///
+    /// Playing the games multiple times reduces the dependence on the order of the games.
+    #[structopt(long, default_value = "1")]
+    pub rounds: usize,
+}
+impl EloOpt {
+    /// Computes the Elo ratings of the solvers of the given studies.
+    ///
+    /// The returned ratings are sorted in descending order.
+    pub fn ratings(&self, studies: &[StudyRecord]) -> Result<Vec<Rating>> {
+        track_assert!(self.k_factor > 0.0, ErrorKind::InvalidInput; self.k_factor);
+        track_assert!(self.rounds > 0, ErrorKind::InvalidInput);
+
+        let mut matches = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for (problem_id, solvers) in track!(group::group_studies(studies))? {
+            for (solver, studies) in solvers {
+                for study in studies {
+                    if let Some(v) = study.best_value() {
+                        matches
+                            .entry((problem_id.clone(), study.seed))
+                            .or_default()
+                            .insert(solver.clone(), v);
+                    }
+                }
+            }
+        }
+
+        let mut ratings = BTreeMap::new();
+        for solver in matches.values().flat_map(|m| m.keys()) {
+            ratings.entry(solver).or_insert_with(|| Rating {
+                solver: solver.clone(),
+                rating: self.initial_rating,
+                games: 0,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+            });
+        }
+
+        for round in 0..self.rounds {
+            for players in matches.values() {
+                let players = players.iter().collect::<Vec<_>>();
+                for (i, &(a, x)) in players.iter().enumerate() {
+                    for &(b, y) in &players[i + 1..] {
+                        let outcome = OrderedFloat(*x).cmp(&OrderedFloat(*y));
+                        let score = match outcome {
+                            Ordering::Less => 1.0,
+                            Ordering::Equal => 0.5,
+                            Ordering::Greater => 0.0,
+                        };
+                        let expected = 1.0
+                            / (1.0 + 10f64.powf((ratings[b].rating - ratings[a].rating) / 400.0));
+                        let delta = self.k_factor * (score - expected);
+                        ratings.get_mut(a).expect("unreachable").rating += delta;
+                        ratings.get_mut(b).expect("unreachable").rating -= delta;
+
+                        if round == 0 {
+                            ratings.get_mut(a).expect("unreachable").record(outcome);
+                            ratings
+                                .get_mut(b)
+                                .expect("unreachable")
+                                .record(outcome.reverse());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut ratings = ratings.into_values().collect::<Vec<_>>();
+        ratings.sort_by_key(|r| OrderedFloat(-r.rating));
+        Ok(ratings)
+    }
+}
+
+/// Elo rating of a solver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Rating {
+    /// Solver name.
+    pub solver: String,
+
+    /// Rating.
+    pub rating: f64,
+
+    /// Number of the games played.
+    pub games: usize,
+
+    /// Number of the wins.
+    pub wins: usize,
+
+    /// Number of the draws.
+    pub draws: usize,
+
+    /// Number of the losses.
+    pub losses: usize,
+}
+impl Rating {
+    fn record(&mut self, outcome: Ordering) {
+        self.games += 1;
+        match outcome {
+            Ordering::Less => self.wins += 1,
+            Ordering::Equal => self.draws += 1,
+            Ordering::Greater => self.losses += 1,
+        }
+    }
+}
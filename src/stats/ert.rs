@@ -0,0 +1,121 @@
+//! Fixed-target analysis (success rates and expected running times).
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+/// Options of the fixed-target analysis of `kurobako stats` (`--ert`).
+///
+/// A study reaches a target when it finds a value less than or equal to the target.
+/// The expected running time (ERT) is the total number of the steps consumed by the studies
+/// (until reaching the target, or until the end for the unsuccessful ones)
+/// divided by the number of the successful studies.
+///
+/// Multi-objective studies are ignored.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ErtOpt {
+    /// Target values of the fixed-target analysis.
+    #[structopt(long, number_of_values = 1)]
+    pub target: Vec<f64>,
+
+    /// Regards the targets of the fixed-target analysis as the simple regrets from the known optima of the problems.
+    ///
+    /// The studies of the problems whose optima are unknown are ignored.
+    #[structopt(long)]
+    pub relative: bool,
+}
+impl ErtOpt {
+    /// Computes the success rates and the ERTs of the solvers of the given studies.
+    pub fn erts(&self, studies: &[StudyRecord]) -> Result<Vec<Ert>> {
+        track_assert!(
+            !self.target.is_empty(),
+            ErrorKind::InvalidInput,
+            "No target is specified by `--target`"
+        );
+        for &target in &self.target {
+            track_assert!(target.is_finite(), ErrorKind::InvalidInput; target);
+        }
+
+        let studies = studies.iter().filter(|study| {
+            study.problem.spec.values_domain.len() == 1
+                && (!self.relative || study.optimum().is_some())
+        });
+        let groups = group::by_solver(track!(group::group_studies(studies))?);
+
+        let mut erts = Vec::new();
+        for ((solver, _), studies) in groups {
+            for &target in &self.target {
+                let mut successes = 0;
+                let mut total_steps = 0;
+                for study in &studies {
+                    let threshold = if self.relative {
+                        study.optimum().expect("unreachable") + target
+                    } else {
+                        target
+                    };
+                    let hit = study
+                        .best_values()
+                        .into_iter()
+                        .find(|&(_, v)| v <= threshold)
+                        .map(|(step, _)| step);
+                    if let Some(step) = hit {
+                        successes += 1;
+                        total_steps += step;
+                    } else {
+                        total_steps += consumed_steps(study);
+                    }
+                }
+                erts.push(Ert {
+                    solver: solver.clone(),
+                    problem: studies[0].problem.spec.name.clone(),
+                    target,
+                    studies: studies.len(),
+                    successes,
+                    success_rate: successes as f64 / studies.len() as f64,
+                    ert: if successes > 0 {
+                        Some(total_steps as f64 / successes as f64)
+                    } else {
+                        None
+                    },
+                });
+            }
+        }
+        Ok(erts)
+    }
+}
+
+fn consumed_steps(study: &StudyRecord) -> u64 {
+    study
+        .trials
+        .iter()
+        .filter_map(|t| t.end_step())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Fixed-target result of a solver on a problem.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ert {
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Target (absolute value or simple regret).
+    pub target: f64,
+
+    /// Number of the studies.
+    pub studies: usize,
+
+    /// Number of the studies that reached the target.
+    pub successes: usize,
+
+    /// Fraction of the studies that reached the target.
+    pub success_rate: f64,
+
+    /// Expected running time in steps (`null` if no study reached the target).
+    pub ert: Option<f64>,
+}
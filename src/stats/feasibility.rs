@@ -0,0 +1,65 @@
+//! Feasibility statistics of studies.
+use crate::record::StudyRecord;
+use kurobako_core::Result;
+use serde::{Deserialize, Serialize};
+
+/// Computes the feasibility statistics of the given studies (`--feasibility` of `kurobako stats`).
+///
+/// The infeasible trials are the ones whose parameters were rejected as unevaluable by the problem
+/// (e.g., the ones that violate the constraints attached by `kurobako problem constrain`).
+/// The amounts of the violations are available only for the constraints of `kurobako problem constrain`.
+pub fn feasibilities(studies: &[StudyRecord]) -> Result<Vec<Feasibility>> {
+    studies
+        .iter()
+        .map(|study| {
+            let feasible = study.trials.len() as u64;
+            let total = feasible + study.infeasible_trials;
+            Ok(Feasibility {
+                study: track!(study.id())?,
+                solver: study.solver.spec.name.clone(),
+                problem: study.problem.spec.name.clone(),
+                seed: study.seed,
+                feasible_trials: feasible,
+                infeasible_trials: study.infeasible_trials,
+                constraint_violation: study.constraint_violation,
+                feasibility_rate: if total > 0 {
+                    Some(feasible as f64 / total as f64)
+                } else {
+                    None
+                },
+                best_feasible_value: study.best_value(),
+            })
+        })
+        .collect()
+}
+
+/// Feasibility statistics of a study.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Feasibility {
+    /// Study ID.
+    pub study: String,
+
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Random seed of the study.
+    pub seed: u64,
+
+    /// Number of the evaluated trials.
+    pub feasible_trials: u64,
+
+    /// Number of the trials rejected as unevaluable.
+    pub infeasible_trials: u64,
+
+    /// Total amount of the constraint violations of the infeasible trials.
+    pub constraint_violation: f64,
+
+    /// Fraction of the feasible trials among all the asked trials (`null` if no trial was asked).
+    pub feasibility_rate: Option<f64>,
+
+    /// Best value among the feasible trials (`null` for multi-objective studies).
+    pub best_feasible_value: Option<f64>,
+}
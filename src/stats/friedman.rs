@@ -0,0 +1,381 @@
+//! Friedman test with post-hoc tests.
+use super::aggregate::AggregateOpt;
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::special::{chi_square_sf, normal_sf};
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use structopt::StructOpt;
+
+// Critical values of the Nemenyi test (the studentized range statistic divided by `sqrt(2)`)
+// for 2 to 10 solvers (Demšar, "Statistical Comparisons of Classifiers over Multiple Data Sets", 2006).
+const NEMENYI_Q_005: [f64; 9] = [
+    1.960, 2.343, 2.569, 2.728, 2.850, 2.949, 3.031, 3.102, 3.164,
+];
+const NEMENYI_Q_010: [f64; 9] = [
+    1.645, 2.052, 2.291, 2.459, 2.589, 2.693, 2.780, 2.855, 2.920,
+];
+
+/// Options of the Friedman test of `kurobako stats` (`--friedman`).
+///
+/// The solvers are ranked on each problem by the aggregate (the mean by default) of the best values of their studies,
+/// and the ranks are compared by the Friedman test.
+/// Only the problems on which all the solvers have been run are used.
+///
+/// If `--difficulty-baseline` is specified, the average ranks weighted by the difficulties of the problems
+/// are also computed. The difficulty of a problem is the normalized regret of the baseline solver
+/// (e.g., random search), that is, `(b - f_best) / (f_worst - f_best)`, where `b` is the aggregate
+/// of the best values of the baseline, `f_best` is the optimum of the problem (or the best value found
+/// by all the studies if it's unknown) and `f_worst` is the worst of the best values of all the studies.
+/// Thus, the problems on which the baseline easily finds the optimum hardly affect the weighted ranks.
+///
+/// If `--group-by` is specified, the test is also conducted for each group of the problems
+/// that have the same value of the given attribute (e.g., `family`).
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct FriedmanOpt {
+    /// Significance level of the Friedman test
+    /// (the critical difference is available only for `0.05` and `0.1`).
+    #[structopt(long, default_value = "0.05")]
+    pub alpha: f64,
+
+    /// Problem attribute used to group the problems in the Friedman test.
+    #[structopt(long)]
+    pub group_by: Option<String>,
+
+    /// Name of the baseline solver used to estimate the difficulties of the problems in the Friedman test.
+    #[structopt(long)]
+    pub difficulty_baseline: Option<String>,
+}
+impl FriedmanOpt {
+    /// Conducts the Friedman test for all the given studies, and for each group of them if `--group-by` is specified.
+    ///
+    /// The first element of the result is the one for all the studies.
+    /// The problems that don't have the attribute are excluded from the groups.
+    pub fn friedmans(
+        &self,
+        studies: &[StudyRecord],
+        aggregate: &AggregateOpt,
+    ) -> Result<Vec<FriedmanResult>> {
+        let mut results = vec![track!(self.friedman(studies, aggregate))?];
+        if let Some(key) = &self.group_by {
+            let mut groups = BTreeMap::<_, Vec<_>>::new();
+            for study in studies {
+                if let Some(value) = study.problem.spec.attrs.get(key) {
+                    groups.entry(value).or_default().push(study);
+                }
+            }
+            for (value, studies) in groups {
+                let mut result = track!(self.test(&studies, aggregate); key, value)?;
+                result.group = Some(format!("{}={}", key, value));
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Computes the Friedman ranks of the solvers of the given studies, and tests their differences.
+    pub fn friedman(
+        &self,
+        studies: &[StudyRecord],
+        aggregate: &AggregateOpt,
+    ) -> Result<FriedmanResult> {
+        track!(self.test(&studies.iter().collect::<Vec<_>>(), aggregate))
+    }
+
+    fn test(&self, studies: &[&StudyRecord], aggregate: &AggregateOpt) -> Result<FriedmanResult> {
+        track_assert!(0.0 < self.alpha && self.alpha < 1.0, ErrorKind::InvalidInput; self.alpha);
+
+        let mut problems = BTreeMap::<_, BTreeMap<_, _>>::new();
+        let mut optima = BTreeMap::new();
+        for (problem_id, solvers) in track!(group::group_studies(studies.iter().copied()))? {
+            for (solver, studies) in solvers {
+                let values = studies
+                    .iter()
+                    .filter_map(|s| s.best_value())
+                    .collect::<Vec<_>>();
+                if values.is_empty() {
+                    continue;
+                }
+                if let Some(optimum) = studies.iter().find_map(|s| s.optimum()) {
+                    optima.insert(problem_id.clone(), optimum);
+                }
+                problems
+                    .entry(problem_id.clone())
+                    .or_default()
+                    .insert(solver, values);
+            }
+        }
+        let solvers = problems
+            .values()
+            .flat_map(|p| p.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        problems.retain(|_, p| p.len() == solvers.len());
+
+        let k = solvers.len();
+        let n = problems.len();
+        track_assert!(k >= 2, ErrorKind::InvalidInput, "Too few solvers: {}", k);
+        track_assert!(
+            n >= 1,
+            ErrorKind::InvalidInput,
+            "No problem is shared by all solvers"
+        );
+
+        if let Some(baseline) = &self.difficulty_baseline {
+            track_assert!(
+                solvers.contains(baseline),
+                ErrorKind::InvalidInput,
+                "Unknown baseline solver: {:?}",
+                baseline
+            );
+        }
+
+        let mut rank_sums = vec![0.0; k];
+        let mut weighted_rank_sums = vec![0.0; k];
+        let mut weight_sum = 0.0;
+        for (problem_id, p) in &problems {
+            let scores = p
+                .values()
+                .map(|vs| track!(aggregate.aggregate(vs)))
+                .collect::<Result<Vec<_>>>()?;
+            let problem_ranks = ranks(scores.into_iter().flatten());
+            for (sum, rank) in rank_sums.iter_mut().zip(problem_ranks.iter()) {
+                *sum += rank;
+            }
+
+            if let Some(baseline) = &self.difficulty_baseline {
+                let weight = track!(Self::difficulty(
+                    p,
+                    baseline,
+                    optima.get(problem_id),
+                    aggregate
+                ))?;
+                for (sum, rank) in weighted_rank_sums.iter_mut().zip(problem_ranks.iter()) {
+                    *sum += weight * rank;
+                }
+                weight_sum += weight;
+            }
+        }
+        let average_ranks = rank_sums.iter().map(|s| s / n as f64).collect::<Vec<_>>();
+        let weighted_ranks = if weight_sum > 0.0 {
+            Some(
+                solvers
+                    .iter()
+                    .zip(weighted_rank_sums.iter())
+                    .map(|(s, r)| (s.clone(), r / weight_sum))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let (k_f, n_f) = (k as f64, n as f64);
+        let statistic = 12.0 * n_f / (k_f * (k_f + 1.0))
+            * average_ranks
+                .iter()
+                .map(|r| (r - (k_f + 1.0) / 2.0).powi(2))
+                .sum::<f64>();
+        let p_value = chi_square_sf(statistic, k_f - 1.0);
+
+        let se = (k_f * (k_f + 1.0) / (6.0 * n_f)).sqrt();
+        let q = if (self.alpha - 0.05).abs() < 1e-9 {
+            NEMENYI_Q_005.get(k - 2)
+        } else if (self.alpha - 0.1).abs() < 1e-9 {
+            NEMENYI_Q_010.get(k - 2)
+        } else {
+            None
+        };
+        let critical_difference = q.map(|q| q * se);
+
+        let solvers = solvers.into_iter().collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+        for i in 0..k {
+            for j in i + 1..k {
+                let z = (average_ranks[i] - average_ranks[j]).abs() / se;
+                pairs.push(PairwiseTest {
+                    solvers: (solvers[i].clone(), solvers[j].clone()),
+                    p_value: 2.0 * normal_sf(z),
+                    adjusted_p_value: 0.0,
+                    significant: false,
+                });
+            }
+        }
+        holm(&mut pairs, self.alpha);
+
+        Ok(FriedmanResult {
+            group: None,
+            problems: n,
+            weighted_ranks,
+            ranks: solvers
+                .iter()
+                .zip(average_ranks)
+                .map(|(s, r)| (s.clone(), r))
+                .collect(),
+            statistic,
+            p_value,
+            critical_difference,
+            pairs,
+        })
+    }
+
+    // Normalized regret of the baseline solver on the problem (in `[0, 1]`).
+    fn difficulty(
+        problem: &BTreeMap<String, Vec<f64>>,
+        baseline: &str,
+        optimum: Option<&f64>,
+        aggregate: &AggregateOpt,
+    ) -> Result<f64> {
+        let values = problem.values().flat_map(|vs| vs.iter().copied());
+        let best = optimum.copied().unwrap_or_else(|| {
+            values
+                .clone()
+                .min_by_key(|&v| OrderedFloat(v))
+                .expect("unreachable")
+        });
+        let worst = values
+            .max_by_key(|&v| OrderedFloat(v))
+            .expect("unreachable");
+        let b = track_assert_some!(
+            track!(aggregate.aggregate(&problem[baseline]))?,
+            ErrorKind::Bug
+        );
+        if worst <= best {
+            Ok(0.0)
+        } else {
+            Ok(((b - best) / (worst - best)).clamp(0.0, 1.0))
+        }
+    }
+}
+
+/// Result of the Friedman test.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FriedmanResult {
+    /// Group of the problems (`${ATTR}=${VALUE}`) or `null` for all the problems.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// Number of the problems used in the test.
+    pub problems: usize,
+
+    /// Average ranks of the solvers (the best one is `1`).
+    pub ranks: BTreeMap<String, f64>,
+
+    /// Average ranks weighted by the difficulties of the problems (only if `--difficulty-baseline` is specified).
+    ///
+    /// This is `null` if the baseline finds the optima of all the problems.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighted_ranks: Option<BTreeMap<String, f64>>,
+
+    /// Friedman statistic (chi-squared with `solvers - 1` degrees of freedom).
+    pub statistic: f64,
+
+    /// P-value of the Friedman test.
+    pub p_value: f64,
+
+    /// Critical difference of the average ranks by the Nemenyi test
+    /// (used to draw a critical difference diagram).
+    ///
+    /// This is available only if the significance level is `0.05` or `0.1` and there are at most 10 solvers.
+    pub critical_difference: Option<f64>,
+
+    /// Pairwise post-hoc tests.
+    pub pairs: Vec<PairwiseTest>,
+}
+
+/// Post-hoc test of a pair of solvers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PairwiseTest {
+    /// Solver names.
+    pub solvers: (String, String),
+
+    /// Unadjusted p-value of the difference of the average ranks.
+    pub p_value: f64,
+
+    /// P-value adjusted by the Holm method.
+    pub adjusted_p_value: f64,
+
+    /// Whether the difference is significant.
+    pub significant: bool,
+}
+
+// Ranks the scores in ascending order (ties get the average of their ranks).
+pub(crate) fn ranks<I>(scores: I) -> Vec<f64>
+where
+    I: Iterator<Item = f64>,
+{
+    let mut indexed = scores.enumerate().collect::<Vec<_>>();
+    indexed.sort_by_key(|&(_, s)| OrderedFloat(s));
+
+    let mut ranks = vec![0.0; indexed.len()];
+    let mut start = 0;
+    while start < indexed.len() {
+        let mut end = start + 1;
+        while end < indexed.len() && indexed[end].1 == indexed[start].1 {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &(i, _) in &indexed[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+fn holm(pairs: &mut [PairwiseTest], alpha: f64) {
+    let mut order = (0..pairs.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| OrderedFloat(pairs[i].p_value));
+
+    let m = pairs.len();
+    let mut max_adjusted: f64 = 0.0;
+    let mut rejecting = true;
+    for (rank, i) in order.into_iter().enumerate() {
+        let factor = (m - rank) as f64;
+        max_adjusted = max_adjusted.max((pairs[i].p_value * factor).min(1.0));
+        rejecting = rejecting && pairs[i].p_value <= alpha / factor;
+        pairs[i].adjusted_p_value = max_adjusted;
+        pairs[i].significant = rejecting;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_float_close(v0: f64, v1: f64) -> bool {
+        let rtol: f64 = 1e-5;
+        let atol: f64 = 1e-8;
+        (v0 - v1).abs() <= atol + rtol * v1.abs()
+    }
+
+    fn pair(p_value: f64) -> PairwiseTest {
+        PairwiseTest {
+            solvers: (String::new(), String::new()),
+            p_value,
+            adjusted_p_value: 0.0,
+            significant: false,
+        }
+    }
+
+    #[test]
+    fn ranks_works() {
+        let ranks = ranks(vec![2.0, 1.0, 2.0, 1.5].into_iter());
+        assert_eq!(ranks, vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn holm_works() {
+        let mut pairs = vec![pair(0.01), pair(0.04), pair(0.03)];
+        holm(&mut pairs, 0.05);
+
+        let adjusted = pairs.iter().map(|p| p.adjusted_p_value).collect::<Vec<_>>();
+        assert!(is_float_close(adjusted[0], 0.03));
+        assert!(is_float_close(adjusted[1], 0.06));
+        assert!(is_float_close(adjusted[2], 0.06));
+
+        let significants = pairs.iter().map(|p| p.significant).collect::<Vec<_>>();
+        assert_eq!(significants, vec![true, false, false]);
+    }
+}
@@ -0,0 +1,83 @@
+//! Grouping of studies by problem and solver.
+use crate::record::StudyRecord;
+use kurobako_core::Result;
+use std::collections::BTreeMap;
+
+/// Items grouped by problem ID, then by solver name.
+pub(crate) type Groups<T> = BTreeMap<String, BTreeMap<String, Vec<T>>>;
+
+/// Groups the items by the problem IDs and the solver names returned by `key`.
+pub(crate) fn group_by<T, I, F>(items: I, mut key: F) -> Result<Groups<T>>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> Result<(String, String)>,
+{
+    let mut groups = Groups::new();
+    for item in items {
+        let (problem, solver) = track!(key(&item))?;
+        groups
+            .entry(problem)
+            .or_default()
+            .entry(solver)
+            .or_default()
+            .push(item);
+    }
+    Ok(groups)
+}
+
+/// Groups the studies by problem ID and solver name, and sorts each group by seed.
+pub(crate) fn group_studies<'a, I>(studies: I) -> Result<Groups<&'a StudyRecord>>
+where
+    I: IntoIterator<Item = &'a StudyRecord>,
+{
+    let mut groups = track!(group_by(studies, |study| {
+        let problem = track!(study.problem.id())?;
+        Ok((problem, study.solver.spec.name.clone()))
+    }))?;
+    for solvers in groups.values_mut() {
+        for studies in solvers.values_mut() {
+            studies.sort_by_key(|s| s.seed);
+        }
+    }
+    Ok(groups)
+}
+
+/// Rearranges the groups so that they are keyed by pairs of a solver name and a problem ID.
+pub(crate) fn by_solver<T>(groups: Groups<T>) -> BTreeMap<(String, String), Vec<T>> {
+    groups
+        .into_iter()
+        .flat_map(|(problem, solvers)| {
+            solvers
+                .into_iter()
+                .map(move |(solver, items)| ((solver, problem.clone()), items))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_by_works() -> trackable::result::TopLevelResult {
+        let items = vec![
+            ("p1", "s2", 1),
+            ("p0", "s1", 2),
+            ("p1", "s2", 3),
+            ("p1", "s1", 4),
+        ];
+        let key = |&(p, s, _): &(&str, &str, usize)| Ok((p.to_owned(), s.to_owned()));
+        let groups = track!(group_by(items, key))?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["p1"]["s2"].len(), 2);
+
+        let groups = by_solver(groups);
+        let keys = groups.keys().cloned().collect::<Vec<_>>();
+        let pair = |s: &str, p: &str| (s.to_owned(), p.to_owned());
+        assert_eq!(
+            keys,
+            vec![pair("s1", "p0"), pair("s1", "p1"), pair("s2", "p1")]
+        );
+        Ok(())
+    }
+}
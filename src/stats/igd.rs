@@ -0,0 +1,113 @@
+//! IGD and IGD+ of multi-objective studies.
+use crate::best::dominates;
+use crate::record::StudyRecord;
+use kurobako_core::{igd, Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Options of the IGD and IGD+ of `kurobako stats` (`--igd`).
+///
+/// The IGD and IGD+ of a study are computed from the non-dominated trials evaluated at the last step of the problem.
+///
+/// The reference front is taken from the `pareto_front` attribute of the problem
+/// (a space separated list of comma separated points) if the `--reference-front` option isn't specified.
+/// The studies that have no reference front are skipped.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct IgdOpt {
+    /// Reference front file of the IGD and IGD+ (a JSON array of points, e.g., `[[0.0, 1.0], [1.0, 0.0]]`).
+    #[structopt(long)]
+    pub reference_front: Option<PathBuf>,
+}
+impl IgdOpt {
+    /// Computes the IGD and IGD+ of the given multi-objective studies.
+    pub fn igds(&self, studies: &[StudyRecord]) -> Result<Vec<Igd>> {
+        let reference_front: Option<Vec<Vec<f64>>> = if let Some(path) = &self.reference_front {
+            let file = track!(File::open(path).map_err(Error::from); path)?;
+            let front =
+                track!(serde_json::from_reader(BufReader::new(file)).map_err(Error::from); path)?;
+            Some(front)
+        } else {
+            None
+        };
+
+        let mut igds = Vec::new();
+        for study in studies {
+            let objectives = study.problem.spec.values_domain.len();
+            if objectives < 2 {
+                continue;
+            }
+
+            let front = if let Some(front) = &reference_front {
+                front.clone()
+            } else if let Some(front) = study.problem.spec.attrs.get("pareto_front") {
+                track!(parse_front(front))?
+            } else {
+                continue;
+            };
+            track_assert!(!front.is_empty(), ErrorKind::InvalidInput);
+            for point in &front {
+                track_assert_eq!(point.len(), objectives, ErrorKind::InvalidInput; point);
+            }
+
+            let last_step = study.problem.spec.steps.last();
+            let points = study
+                .trials
+                .iter()
+                .filter_map(|t| t.values(last_step))
+                .filter(|vs| !vs.is_empty())
+                .collect::<Vec<_>>();
+            let non_dominated = points
+                .iter()
+                .filter(|a| !points.iter().any(|b| dominates(b, a)))
+                .map(|vs| vs.to_vec())
+                .collect::<Vec<_>>();
+
+            igds.push(Igd {
+                study: track!(study.id())?,
+                solver: study.solver.spec.name.clone(),
+                problem: study.problem.spec.name.clone(),
+                seed: study.seed,
+                igd: igd::compute(&non_dominated, &front),
+                igd_plus: igd::compute_plus(&non_dominated, &front),
+            });
+        }
+        Ok(igds)
+    }
+}
+
+fn parse_front(s: &str) -> Result<Vec<Vec<f64>>> {
+    s.split_whitespace()
+        .map(|point| {
+            point
+                .split(',')
+                .map(|v| track!(v.parse().map_err(Error::from); point))
+                .collect()
+        })
+        .collect()
+}
+
+/// IGD and IGD+ of a study.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Igd {
+    /// Study ID.
+    pub study: String,
+
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Random seed of the study.
+    pub seed: u64,
+
+    /// Inverted generational distance (`null` if the study has no evaluated trials).
+    pub igd: f64,
+
+    /// IGD+.
+    pub igd_plus: f64,
+}
@@ -0,0 +1,150 @@
+//! Parameter importances of problems.
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::domain::Range;
+use kurobako_core::{ErrorKind, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use randomforest::criterion::Mse;
+use randomforest::table::{ColumnType, TableBuilder};
+use randomforest::{RandomForestRegressor, RandomForestRegressorOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use structopt::StructOpt;
+use trackable::error::ErrorKindExt;
+
+/// Options of the parameter importances of `kurobako stats` (`--importance`).
+///
+/// For each problem, a random forest regressor is fitted to the parameters and the values
+/// of the trials of all the studies, and the importance of each parameter is measured by
+/// the increase of the mean squared error of the forest when the parameter is permuted
+/// (normalized so that the importances sum to `1`).
+///
+/// The trials that have inactive parameters and the multi-objective studies are ignored.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ImportanceOpt {
+    /// Number of trees in a random forest used to compute the parameter importances.
+    #[structopt(long, default_value = "64")]
+    pub trees: NonZeroUsize,
+
+    /// Number of the permutations per parameter used to compute the parameter importances.
+    #[structopt(long, default_value = "5")]
+    pub permutations: NonZeroUsize,
+}
+impl ImportanceOpt {
+    /// Computes the parameter importances of the problems of the given studies.
+    pub fn importances(&self, studies: &[StudyRecord]) -> Result<Vec<Importance>> {
+        let studies = studies
+            .iter()
+            .filter(|study| study.problem.spec.values_domain.len() == 1);
+        let mut importances = Vec::new();
+        for solvers in track!(group::group_studies(studies))?.into_values() {
+            let studies = solvers.into_values().flatten().collect::<Vec<_>>();
+            let study = studies[0];
+            let last_step = study.problem.spec.steps.last();
+            let rows = studies
+                .iter()
+                .flat_map(|s| s.trials.iter())
+                .filter(|t| !t.params.get().iter().any(|p| p.is_nan()))
+                .filter_map(|t| {
+                    let value = t.value(last_step).filter(|v| v.is_finite())?;
+                    Some((t.params.get().to_vec(), value))
+                })
+                .collect::<Vec<_>>();
+            if rows.len() < 2 {
+                continue;
+            }
+
+            let vars = study.problem.spec.params_domain.variables();
+            let column_types = vars
+                .iter()
+                .map(|v| match v.range() {
+                    Range::Categorical { .. } => ColumnType::Categorical,
+                    _ => ColumnType::Numerical,
+                })
+                .collect::<Vec<_>>();
+            let regressor = track!(self.fit(&column_types, &rows))?;
+
+            let mut rng = StdRng::seed_from_u64(0);
+            let baseline = mse(&regressor, &rows);
+            let mut increases = Vec::new();
+            for i in 0..vars.len() {
+                let mut increase = 0.0;
+                for _ in 0..self.permutations.get() {
+                    let mut column = rows.iter().map(|r| r.0[i]).collect::<Vec<_>>();
+                    column.shuffle(&mut rng);
+                    let permuted = rows
+                        .iter()
+                        .zip(column)
+                        .map(|((params, value), p)| {
+                            let mut params = params.clone();
+                            params[i] = p;
+                            (params, *value)
+                        })
+                        .collect::<Vec<_>>();
+                    increase += (mse(&regressor, &permuted) - baseline).max(0.0);
+                }
+                increases.push(increase / self.permutations.get() as f64);
+            }
+
+            let total = increases.iter().sum::<f64>();
+            importances.push(Importance {
+                problem: study.problem.spec.name.clone(),
+                samples: rows.len(),
+                importances: vars
+                    .iter()
+                    .zip(increases)
+                    .map(|(v, x)| {
+                        let x = if total > 0.0 { x / total } else { 0.0 };
+                        (v.name().to_owned(), x)
+                    })
+                    .collect(),
+            });
+        }
+        Ok(importances)
+    }
+
+    fn fit(
+        &self,
+        column_types: &[ColumnType],
+        rows: &[(Vec<f64>, f64)],
+    ) -> Result<RandomForestRegressor> {
+        let mut table = TableBuilder::new();
+        table
+            .set_feature_column_types(column_types)
+            .expect("unreachable");
+        for (params, value) in rows {
+            track!(table
+                .add_row(params, *value)
+                .map_err(|e| ErrorKind::InvalidInput.cause(e)))?;
+        }
+        let table = track!(table.build().map_err(|e| ErrorKind::InvalidInput.cause(e)))?;
+        Ok(RandomForestRegressorOptions::new()
+            .parallel()
+            .trees(self.trees)
+            .fit(Mse, table))
+    }
+}
+
+fn mse(regressor: &RandomForestRegressor, rows: &[(Vec<f64>, f64)]) -> f64 {
+    rows.iter()
+        .map(|(params, value)| (regressor.predict(params) - value).powi(2))
+        .sum::<f64>()
+        / rows.len() as f64
+}
+
+/// Parameter importances of a problem.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Importance {
+    /// Problem name.
+    pub problem: String,
+
+    /// Number of the trials used to fit the model.
+    pub samples: usize,
+
+    /// Importances of the parameters.
+    pub importances: BTreeMap<String, f64>,
+}
@@ -0,0 +1,129 @@
+//! Latency statistics of the ask/tell calls and the evaluations.
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+/// Options of the latency statistics of `kurobako stats` (`--overhead`).
+///
+/// The latencies are collected from all the evaluations (i.e., ask-evaluate-tell cycles) of the studies of each solver.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct OverheadOpt {
+    /// Groups the latency statistics by problem as well as by solver.
+    #[structopt(long)]
+    pub by_problem: bool,
+}
+impl OverheadOpt {
+    /// Computes the latency statistics of the solvers of the given studies.
+    pub fn overheads(&self, studies: &[StudyRecord]) -> Result<Vec<Overhead>> {
+        let mut groups = BTreeMap::<_, (Option<String>, Vec<_>, Vec<_>, Vec<_>)>::new();
+        for ((solver, problem_id), studies) in
+            group::by_solver(track!(group::group_studies(studies))?)
+        {
+            let key = (solver, Some(problem_id).filter(|_| self.by_problem));
+            let (problem, asks, tells, evaluates) = groups.entry(key).or_default();
+            if self.by_problem {
+                *problem = Some(studies[0].problem.spec.name.clone());
+            }
+            for study in studies {
+                for e in study.trials.iter().flat_map(|t| t.evaluations.iter()) {
+                    asks.push(e.ask_elapsed.get());
+                    tells.push(e.tell_elapsed.get());
+                    evaluates.push(e.evaluate_elapsed.get());
+                }
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|((solver, _), (problem, asks, tells, evaluates))| {
+                let solver_total = asks.iter().sum::<f64>() + tells.iter().sum::<f64>();
+                let evaluate_total = evaluates.iter().sum::<f64>();
+                Overhead {
+                    solver,
+                    problem,
+                    evaluations: asks.len(),
+                    ask: LatencyStats::new(asks),
+                    tell: LatencyStats::new(tells),
+                    evaluate: LatencyStats::new(evaluates),
+                    overhead_ratio: if evaluate_total > 0.0 {
+                        Some(solver_total / evaluate_total)
+                    } else {
+                        None
+                    },
+                }
+            })
+            .collect())
+    }
+}
+
+/// Latency statistics of a solver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Overhead {
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name (only if `--by-problem` is specified).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+
+    /// Number of the evaluations.
+    pub evaluations: usize,
+
+    /// Latencies of the ask calls.
+    pub ask: LatencyStats,
+
+    /// Latencies of the tell calls.
+    pub tell: LatencyStats,
+
+    /// Latencies of the evaluations of the problems.
+    pub evaluate: LatencyStats,
+
+    /// Total time of the ask and tell calls divided by the total time of the evaluations
+    /// (`null` if the evaluations took no time).
+    pub overhead_ratio: Option<f64>,
+}
+
+/// Statistics of latencies (in seconds).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Mean.
+    pub mean: f64,
+
+    /// Median.
+    pub p50: f64,
+
+    /// 90th percentile.
+    pub p90: f64,
+
+    /// 99th percentile.
+    pub p99: f64,
+
+    /// Maximum.
+    pub max: f64,
+}
+impl LatencyStats {
+    fn new(mut latencies: Vec<f64>) -> Self {
+        if latencies.is_empty() {
+            return Self::default();
+        }
+        latencies.sort_by_key(|&x| OrderedFloat(x));
+
+        // Nearest-rank method.
+        let percentile = |p: f64| {
+            let rank = (p * latencies.len() as f64).ceil() as usize;
+            latencies[rank.max(1) - 1]
+        };
+        Self {
+            mean: latencies.iter().sum::<f64>() / latencies.len() as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: latencies[latencies.len() - 1],
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Data profiles and performance profiles of solvers.
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use structopt::StructOpt;
+
+const DEFAULT_TAUS: [f64; 3] = [1e-1, 1e-3, 1e-5];
+
+/// Options of the data and performance profiles of `kurobako stats` (`--profile`).
+///
+/// A study is regarded as having solved its problem at the precision level `tau`
+/// when it finds a value `f` that satisfies `f <= f_best + tau * (f_init - f_best)`,
+/// where `f_best` is the best value found by all the studies of the problem and
+/// `f_init` is the average of the first values of the studies.
+/// The cost to solve a problem is measured by the number of the consumed steps
+/// divided by the last step of the problem (i.e., the number of full evaluations).
+///
+/// Multi-objective studies are ignored.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct ProfileOpt {
+    /// Precision levels of the profiles (the default is `0.1`, `0.001` and `0.00001`).
+    #[structopt(long, number_of_values = 1)]
+    pub tau: Vec<f64>,
+}
+impl ProfileOpt {
+    /// Computes the data profiles and the performance profiles of the solvers of the given studies.
+    ///
+    /// The data profile of a solver is the empirical cumulative distribution of the costs of its studies.
+    ///
+    /// The performance profile (Dolan and Moré) of a solver is the empirical cumulative distribution
+    /// of the ratios of its costs to the minimum costs among the solvers.
+    /// For the ratios, the `i`-th study (in ascending order of seeds) of each solver on a problem
+    /// is regarded as a run on the same instance, and only the instances that all the solvers have run are used.
+    pub fn profiles(&self, studies: &[StudyRecord]) -> Result<Vec<Profile>> {
+        let taus = if self.tau.is_empty() {
+            &DEFAULT_TAUS[..]
+        } else {
+            &self.tau[..]
+        };
+        for &tau in taus {
+            track_assert!((0.0..=1.0).contains(&tau), ErrorKind::InvalidInput; tau);
+        }
+
+        let studies = studies
+            .iter()
+            .filter(|study| study.problem.spec.values_domain.len() == 1);
+        let problems = track!(group::group_studies(studies))?;
+        let solvers = problems
+            .values()
+            .flat_map(|p| p.keys())
+            .collect::<BTreeSet<_>>();
+
+        let mut profiles = Vec::new();
+        for &tau in taus {
+            let mut costs = BTreeMap::<_, Vec<_>>::new();
+            let mut ratios = BTreeMap::<_, Vec<_>>::new();
+            for problem in problems.values() {
+                let threshold = target_value(problem, tau);
+                let problem_costs = problem
+                    .iter()
+                    .map(|(solver, studies)| {
+                        let cs = studies
+                            .iter()
+                            .map(|s| solving_cost(s, threshold))
+                            .collect::<Vec<_>>();
+                        (solver, cs)
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                for (solver, cs) in &problem_costs {
+                    costs.entry(*solver).or_default().extend(cs.iter().copied());
+                }
+
+                if problem_costs.len() != solvers.len() {
+                    continue;
+                }
+                let instances = problem_costs.values().map(|cs| cs.len()).min().unwrap_or(0);
+                for i in 0..instances {
+                    let min_cost = problem_costs
+                        .values()
+                        .filter_map(|cs| cs[i])
+                        .min_by_key(|&c| OrderedFloat(c));
+                    for (solver, cs) in &problem_costs {
+                        let ratio = match (cs[i], min_cost) {
+                            (Some(c), Some(m)) if m > 0.0 => Some(c / m),
+                            (Some(_), Some(_)) => Some(1.0),
+                            _ => None,
+                        };
+                        ratios.entry(*solver).or_default().push(ratio);
+                    }
+                }
+            }
+
+            for (solver, cs) in costs {
+                profiles.push(Profile {
+                    kind: ProfileKind::Data,
+                    tau,
+                    solver: solver.clone(),
+                    points: ecdf(&cs),
+                });
+            }
+            for (solver, rs) in ratios {
+                profiles.push(Profile {
+                    kind: ProfileKind::Performance,
+                    tau,
+                    solver: solver.clone(),
+                    points: ecdf(&rs),
+                });
+            }
+        }
+        Ok(profiles)
+    }
+}
+
+fn target_value(problem: &BTreeMap<String, Vec<&StudyRecord>>, tau: f64) -> Option<f64> {
+    let studies = problem.values().flat_map(|s| s.iter());
+    let best = studies
+        .clone()
+        .filter_map(|s| s.best_value())
+        .min_by_key(|&v| OrderedFloat(v))?;
+    let inits = studies
+        .filter_map(|s| s.best_values().values().next().copied())
+        .collect::<Vec<_>>();
+    let init = inits.iter().sum::<f64>() / inits.len() as f64;
+    Some(best + tau * (init - best).max(0.0))
+}
+
+fn solving_cost(study: &StudyRecord, threshold: Option<f64>) -> Option<f64> {
+    let threshold = threshold?;
+    let (&step, _) = study.best_values().iter().find(|&(_, &v)| v <= threshold)?;
+    Some(step as f64 / study.problem.spec.steps.last() as f64)
+}
+
+// The unsolved runs (`None`) are counted in the denominator but never reach the numerator.
+fn ecdf(xs: &[Option<f64>]) -> Vec<(f64, f64)> {
+    let mut solved = xs.iter().filter_map(|&x| x).collect::<Vec<_>>();
+    solved.sort_by_key(|&x| OrderedFloat(x));
+
+    let mut points = Vec::<(f64, f64)>::new();
+    for (i, x) in solved.into_iter().enumerate() {
+        let fraction = (i + 1) as f64 / xs.len() as f64;
+        match points.last_mut() {
+            Some(last) if last.0 == x => last.1 = fraction,
+            _ => points.push((x, fraction)),
+        }
+    }
+    points
+}
+
+/// Data profile or performance profile of a solver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profile {
+    /// Kind of the profile.
+    pub kind: ProfileKind,
+
+    /// Precision level.
+    pub tau: f64,
+
+    /// Solver name.
+    pub solver: String,
+
+    /// Points (`[x, fraction]`) of the empirical cumulative distribution.
+    ///
+    /// `x` is a cost for data profiles, and a ratio of costs for performance profiles.
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Kind of profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    /// Data profile.
+    Data,
+
+    /// Performance profile.
+    Performance,
+}
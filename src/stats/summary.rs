@@ -0,0 +1,241 @@
+//! Summaries of the best values of the studies of each pair of a solver and a problem.
+use super::aggregate::AggregateOpt;
+use super::group;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::{ErrorKind, Result};
+use rustats::fundamental::{average, stddev};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+/// Options of the summaries of `kurobako stats`.
+///
+/// The best values of the studies are summarized for each pair of a solver and a problem,
+/// so that the dispersion across seeds can be inspected.
+///
+/// If `--epsilon` is specified, the first steps at which the studies reach within `epsilon`
+/// of the optimum of the problem (or of the best value found by all the studies of the problem
+/// if the optimum is unknown) are also summarized.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct SummaryOpt {
+    /// Tolerance to the optimum (or the best value) used to measure the convergence speeds.
+    #[structopt(long)]
+    pub epsilon: Option<f64>,
+}
+impl SummaryOpt {
+    /// Summarizes the best values of the given studies.
+    pub fn summaries(
+        &self,
+        studies: &[StudyRecord],
+        aggregate: &AggregateOpt,
+    ) -> Result<Vec<Summary>> {
+        let samples = studies
+            .iter()
+            .map(Sample::new)
+            .collect::<Result<Vec<_>>>()?;
+        track!(self.summarize(&samples, aggregate, |_| true))
+    }
+
+    // Summarizes the samples of the problems for which `is_target_problem` returns `true`.
+    pub(crate) fn summarize<F>(
+        &self,
+        samples: &[Sample],
+        aggregate: &AggregateOpt,
+        is_target_problem: F,
+    ) -> Result<Vec<Summary>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        if let Some(epsilon) = self.epsilon {
+            track_assert!(epsilon >= 0.0, ErrorKind::InvalidInput; epsilon);
+        }
+
+        let mut problem_bests = BTreeMap::<_, f64>::new();
+        for sample in samples {
+            if let Some(v) = sample.best_value {
+                let best = problem_bests.entry(sample.problem_id.as_str()).or_insert(v);
+                *best = best.min(v);
+            }
+        }
+
+        let targets = samples.iter().filter(|s| is_target_problem(&s.problem_id));
+        let groups = track!(group::group_by(targets, |s| Ok((
+            s.problem_id.clone(),
+            s.solver.clone()
+        ))))?;
+        group::by_solver(groups)
+            .into_iter()
+            .map(|((solver, problem_id), samples)| {
+                let values = samples
+                    .iter()
+                    .filter_map(|s| s.best_value)
+                    .collect::<Vec<_>>();
+                let aggregate = track!(aggregate.aggregate(&values))?;
+                let problem = samples[0].problem.clone();
+                let mut summary = Summary::new(solver, problem, values, aggregate);
+                if let Some(epsilon) = self.epsilon {
+                    let steps = samples
+                        .iter()
+                        .filter_map(|s| {
+                            let reference = s
+                                .optimum
+                                .or_else(|| problem_bests.get(problem_id.as_str()).copied())?;
+                            Some(
+                                s.best_values
+                                    .iter()
+                                    .find(|&&(_, v)| v <= reference + epsilon)
+                                    .map(|&(step, _)| step),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    summary.steps_to_target = Some(StepsToTarget::new(&steps));
+                }
+                Ok(summary)
+            })
+            .collect()
+    }
+}
+
+// Data of a study needed to summarize it (so that the trials of the studies don't need to be kept in streaming).
+#[derive(Debug)]
+pub(crate) struct Sample {
+    pub solver: String,
+    pub problem: String,
+    pub problem_id: String,
+    best_value: Option<f64>,
+    optimum: Option<f64>,
+    best_values: Vec<(u64, f64)>,
+}
+impl Sample {
+    pub fn new(study: &StudyRecord) -> Result<Self> {
+        Ok(Self {
+            solver: study.solver.spec.name.clone(),
+            problem: study.problem.spec.name.clone(),
+            problem_id: track!(study.problem.id())?,
+            best_value: study.best_value(),
+            optimum: study.optimum(),
+            best_values: study.best_values().into_iter().collect(),
+        })
+    }
+}
+
+/// Summary of the best values of the studies of a solver on a problem.
+///
+/// The statistics are `null` if no study has evaluated trials.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Summary {
+    /// Solver name.
+    pub solver: String,
+
+    /// Problem name.
+    pub problem: String,
+
+    /// Number of the studies that have evaluated trials.
+    pub studies: usize,
+
+    /// Aggregate specified by the `--aggregate` option.
+    pub aggregate: Option<f64>,
+
+    /// Mean.
+    pub mean: Option<f64>,
+
+    /// Standard deviation.
+    pub std: Option<f64>,
+
+    /// Minimum.
+    pub min: Option<f64>,
+
+    /// First quartile.
+    pub q1: Option<f64>,
+
+    /// Median.
+    pub median: Option<f64>,
+
+    /// Third quartile.
+    pub q3: Option<f64>,
+
+    /// Maximum.
+    pub max: Option<f64>,
+
+    /// Interquartile range (`q3 - q1`).
+    pub iqr: Option<f64>,
+
+    /// Summary of the steps to reach the target (only if `--epsilon` is specified).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps_to_target: Option<StepsToTarget>,
+}
+impl Summary {
+    fn new(solver: String, problem: String, mut values: Vec<f64>, aggregate: Option<f64>) -> Self {
+        values.sort_by_key(|&v| OrderedFloat(v));
+        let nonempty = !values.is_empty();
+        let q1 = quantile(&values, 0.25);
+        let q3 = quantile(&values, 0.75);
+        Self {
+            solver,
+            problem,
+            studies: values.len(),
+            aggregate,
+            mean: Some(average(values.iter().copied())).filter(|_| nonempty),
+            std: Some(stddev(values.iter().copied())).filter(|_| nonempty),
+            min: values.first().copied(),
+            q1,
+            median: quantile(&values, 0.5),
+            q3,
+            max: values.last().copied(),
+            iqr: q1.and_then(|q1| q3.map(|q3| q3 - q1)),
+            steps_to_target: None,
+        }
+    }
+}
+
+/// Summary of the first steps at which the studies reach the target.
+///
+/// The statistics are computed from the studies that reached the target (`null` if no study did).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StepsToTarget {
+    /// Number of the studies that reached the target.
+    pub reached: usize,
+
+    /// Number of the studies that have the target.
+    pub studies: usize,
+
+    /// Mean.
+    pub mean: Option<f64>,
+
+    /// Median.
+    pub median: Option<f64>,
+
+    /// Minimum.
+    pub min: Option<u64>,
+
+    /// Maximum.
+    pub max: Option<u64>,
+}
+impl StepsToTarget {
+    fn new(steps: &[Option<u64>]) -> Self {
+        let mut reached = steps.iter().filter_map(|&s| s).collect::<Vec<_>>();
+        reached.sort_unstable();
+        let values = reached.iter().map(|&s| s as f64).collect::<Vec<_>>();
+        Self {
+            reached: reached.len(),
+            studies: steps.len(),
+            mean: Some(average(values.iter().copied())).filter(|_| !values.is_empty()),
+            median: quantile(&values, 0.5),
+            min: reached.first().copied(),
+            max: reached.last().copied(),
+        }
+    }
+}
+
+// Linear interpolation between the closest ranks (the values must be sorted).
+pub(crate) fn quantile(values: &[f64], q: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let pos = q * (values.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    Some(values[lower] + (values[upper] - values[lower]) * (pos - lower as f64))
+}
@@ -0,0 +1,358 @@
+//! Pairwise Wilcoxon signed-rank tests (and their Bayesian counterparts).
+use super::friedman::ranks;
+use super::group;
+use crate::markdown as md;
+use crate::markdown::MarkdownWriter;
+use crate::record::StudyRecord;
+use kurobako_core::num::OrderedFloat;
+use kurobako_core::rng::gamma;
+use kurobako_core::special::normal_sf;
+use kurobako_core::{ErrorKind, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use structopt::StructOpt;
+
+/// Options of the pairwise signed-rank tests of `kurobako stats` (`--wilcoxon` and `--bayesian-signed-rank`).
+///
+/// The best values of each pair of solvers are matched by the problem and the seed of their studies,
+/// and compared by the two-sided Wilcoxon signed-rank test (normal approximation with the tie correction).
+///
+/// The Bayesian signed-rank test
+/// (Benavoli et al., "Time for a Change: a Tutorial for Comparing Multiple Classifiers Through Bayesian Analysis", 2017)
+/// outputs the posterior probabilities that a solver is practically better than,
+/// equivalent to or worse than another one instead.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct WilcoxonOpt {
+    /// Half width of the region of practical equivalence of the differences of best values
+    /// (used by the Bayesian signed-rank test).
+    #[structopt(long, default_value = "0.0")]
+    pub rope: f64,
+
+    /// Strength of the Dirichlet process prior, that is, the weight of the pseudo-observation at zero
+    /// (used by the Bayesian signed-rank test).
+    #[structopt(long, default_value = "0.5")]
+    pub prior: f64,
+
+    /// Number of the Monte Carlo samples drawn from the posterior (used by the Bayesian signed-rank test).
+    #[structopt(long, default_value = "10000")]
+    pub posterior_samples: usize,
+}
+impl WilcoxonOpt {
+    /// Tests every pair of the solvers of the given studies.
+    pub fn wilcoxon(&self, studies: &[StudyRecord]) -> Result<WilcoxonResult> {
+        let best_values = track!(best_values(studies))?;
+        let solvers = best_values.keys().cloned().collect::<Vec<_>>();
+        let mut p_values = vec![vec![None; solvers.len()]; solvers.len()];
+        let mut samples = vec![vec![0; solvers.len()]; solvers.len()];
+        for (i, a) in solvers.iter().enumerate() {
+            for (j, b) in solvers.iter().enumerate().skip(i + 1) {
+                let diffs = matched_diffs(&best_values, a, b);
+                let p = signed_rank_test(&diffs);
+                p_values[i][j] = p;
+                p_values[j][i] = p;
+                samples[i][j] = diffs.len();
+                samples[j][i] = diffs.len();
+            }
+        }
+        Ok(WilcoxonResult {
+            solvers,
+            p_values,
+            samples,
+        })
+    }
+
+    /// Tests every pair of the solvers of the given studies by the Bayesian signed-rank test.
+    pub fn bayesian(&self, studies: &[StudyRecord]) -> Result<BayesianResult> {
+        track_assert!(self.rope >= 0.0, ErrorKind::InvalidInput; self.rope);
+        track_assert!(self.prior > 0.0, ErrorKind::InvalidInput; self.prior);
+        track_assert!(self.posterior_samples > 0, ErrorKind::InvalidInput);
+
+        let best_values = track!(best_values(studies))?;
+        let solvers = best_values.keys().cloned().collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut pairs = Vec::new();
+        for (i, a) in solvers.iter().enumerate() {
+            for b in solvers.iter().skip(i + 1) {
+                let diffs = matched_diffs(&best_values, a, b);
+                let (better, equivalent, worse) = bayesian_signed_rank_test(
+                    &diffs,
+                    self.rope,
+                    self.prior,
+                    self.posterior_samples,
+                    &mut rng,
+                );
+                pairs.push(BayesianPair {
+                    solvers: (a.clone(), b.clone()),
+                    samples: diffs.len(),
+                    better,
+                    equivalent,
+                    worse,
+                });
+            }
+        }
+        Ok(BayesianResult { pairs })
+    }
+}
+
+/// Results of the pairwise Wilcoxon signed-rank tests.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WilcoxonResult {
+    /// Solver names.
+    pub solvers: Vec<String>,
+
+    /// Matrix of the p-values (`p_values[i][j]` is the one of `solvers[i]` and `solvers[j]`).
+    ///
+    /// A p-value is `null` if the pair has no non-zero difference (or it is a diagonal element).
+    pub p_values: Vec<Vec<Option<f64>>>,
+
+    /// Matrix of the numbers of the matched pairs of studies.
+    pub samples: Vec<Vec<usize>>,
+}
+impl WilcoxonResult {
+    pub(crate) fn write_markdown<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut table = md::Table::new(
+            std::iter::once(md::ColumnHeader::new("Solver", md::Align::Left)).chain(
+                self.solvers
+                    .iter()
+                    .map(|s| md::ColumnHeader::new(s, md::Align::Right)),
+            ),
+        );
+        for (solver, p_values) in self.solvers.iter().zip(self.p_values.iter()) {
+            let row = table.row();
+            row.item(solver);
+            for p in p_values {
+                row.item(p.map_or_else(|| "-".to_owned(), |p| format!("{:.3}", p)));
+            }
+        }
+        track!(writer.write_table(&table))?;
+        Ok(())
+    }
+}
+
+/// Results of the pairwise Bayesian signed-rank tests.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BayesianResult {
+    /// Tests of the pairs of the solvers.
+    pub pairs: Vec<BayesianPair>,
+}
+impl BayesianResult {
+    pub(crate) fn write_markdown<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut table = md::Table::new(
+            vec![
+                md::ColumnHeader::new("Solver A", md::Align::Left),
+                md::ColumnHeader::new("Solver B", md::Align::Left),
+                md::ColumnHeader::new("P(A < B)", md::Align::Right),
+                md::ColumnHeader::new("P(A = B)", md::Align::Right),
+                md::ColumnHeader::new("P(A > B)", md::Align::Right),
+            ]
+            .into_iter(),
+        );
+        for pair in &self.pairs {
+            table
+                .row()
+                .item(&pair.solvers.0)
+                .item(&pair.solvers.1)
+                .item(format!("{:.3}", pair.better))
+                .item(format!("{:.3}", pair.equivalent))
+                .item(format!("{:.3}", pair.worse));
+        }
+        track!(writer.write_table(&table))?;
+        Ok(())
+    }
+}
+
+/// Bayesian signed-rank test of a pair of solvers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BayesianPair {
+    /// Solver names.
+    pub solvers: (String, String),
+
+    /// Number of the matched pairs of studies.
+    pub samples: usize,
+
+    /// Posterior probability that the first solver is practically better (i.e., finds smaller values).
+    pub better: f64,
+
+    /// Posterior probability that the solvers are practically equivalent.
+    pub equivalent: f64,
+
+    /// Posterior probability that the first solver is practically worse.
+    pub worse: f64,
+}
+
+// Best values of the studies keyed by solver name, then by problem ID and seed.
+type BestValues = BTreeMap<String, BTreeMap<(String, u64), f64>>;
+
+fn best_values(studies: &[StudyRecord]) -> Result<BestValues> {
+    let mut best_values = BestValues::new();
+    for (problem_id, solvers) in track!(group::group_studies(studies))? {
+        for (solver, studies) in solvers {
+            for study in studies {
+                if let Some(v) = study.best_value() {
+                    best_values
+                        .entry(solver.clone())
+                        .or_default()
+                        .insert((problem_id.clone(), study.seed), v);
+                }
+            }
+        }
+    }
+    Ok(best_values)
+}
+
+fn matched_diffs(best_values: &BestValues, a: &str, b: &str) -> Vec<f64> {
+    best_values[a]
+        .iter()
+        .filter_map(|(key, x)| best_values[b].get(key).map(|y| x - y))
+        .collect()
+}
+
+// Returns the probabilities that the differences are practically negative, equivalent and positive
+// (i.e., that the first solver is better, equivalent and worse).
+//
+// The weights of the pseudo-observation at zero and the differences are sampled from
+// the Dirichlet distribution `Dir(prior, 1, ..., 1)`, and the winner of each sample is counted.
+fn bayesian_signed_rank_test<R: Rng>(
+    diffs: &[f64],
+    rope: f64,
+    prior: f64,
+    samples: usize,
+    rng: &mut R,
+) -> (f64, f64, f64) {
+    let mut zs = vec![0.0];
+    zs.extend_from_slice(diffs);
+
+    let mut counts = [0usize; 3];
+    let mut weights = vec![0.0; zs.len()];
+    for _ in 0..samples {
+        for (i, w) in weights.iter_mut().enumerate() {
+            *w = gamma(if i == 0 { prior } else { 1.0 }, rng);
+        }
+
+        // The weights don't need to be normalized because only the winner matters.
+        let mut thetas = [0.0; 3];
+        for i in 0..zs.len() {
+            for j in i..zs.len() {
+                let w = if i == j { 1.0 } else { 2.0 } * weights[i] * weights[j];
+                let s = zs[i] + zs[j];
+                if s < -2.0 * rope {
+                    thetas[0] += w;
+                } else if s > 2.0 * rope {
+                    thetas[2] += w;
+                } else {
+                    thetas[1] += w;
+                }
+            }
+        }
+
+        let winner = (0..3)
+            .max_by_key(|&k| OrderedFloat(thetas[k]))
+            .expect("unreachable");
+        counts[winner] += 1;
+    }
+
+    let n = samples as f64;
+    (
+        counts[0] as f64 / n,
+        counts[1] as f64 / n,
+        counts[2] as f64 / n,
+    )
+}
+
+// Returns the two-sided p-value of the Wilcoxon signed-rank test (zero differences are discarded).
+fn signed_rank_test(diffs: &[f64]) -> Option<f64> {
+    let diffs = diffs
+        .iter()
+        .copied()
+        .filter(|&d| d != 0.0)
+        .collect::<Vec<_>>();
+    if diffs.is_empty() {
+        return None;
+    }
+
+    let abs_ranks = ranks(diffs.iter().map(|d| d.abs()));
+    let w_plus = diffs
+        .iter()
+        .zip(abs_ranks.iter())
+        .filter(|&(&d, _)| d > 0.0)
+        .map(|(_, &r)| r)
+        .sum::<f64>();
+
+    let mut ties = BTreeMap::<_, usize>::new();
+    for &r in &abs_ranks {
+        *ties.entry(r.to_bits()).or_default() += 1;
+    }
+    let tie_correction = ties.values().map(|&t| (t * t * t - t) as f64).sum::<f64>() / 48.0;
+
+    let n = diffs.len() as f64;
+    let mean = n * (n + 1.0) / 4.0;
+    let var = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction;
+    if var <= 0.0 {
+        return Some(1.0);
+    }
+    let z = (((w_plus - mean).abs() - 0.5).max(0.0)) / var.sqrt();
+    Some((2.0 * normal_sf(z)).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_float_close(v0: f64, v1: f64) -> bool {
+        let rtol: f64 = 1e-5;
+        let atol: f64 = 1e-8;
+        (v0 - v1).abs() <= atol + rtol * v1.abs()
+    }
+
+    #[test]
+    fn signed_rank_test_works() {
+        // The normal approximation with the continuity correction
+        // (i.e., `scipy.stats.wilcoxon(diffs, correction=True, mode="approx")`).
+        let diffs = (1..=10).map(f64::from).collect::<Vec<_>>();
+        let p = signed_rank_test(&diffs).unwrap();
+        assert!(is_float_close(p, 0.005921537024148713));
+
+        let diffs = [
+            6.0, 8.0, 14.0, 16.0, 23.0, 24.0, 28.0, 29.0, 41.0, -48.0, 49.0, 56.0, 60.0, -67.0,
+            75.0,
+        ];
+        let p = signed_rank_test(&diffs).unwrap();
+        assert!(is_float_close(p, 0.043772323763041195));
+    }
+
+    #[test]
+    fn signed_rank_test_with_ties_and_zeros_works() {
+        let diffs = [0.5, -1.0, 2.0, -2.0, 3.0, 0.0, 4.0, 1.0];
+        let p = signed_rank_test(&diffs).unwrap();
+        assert!(is_float_close(p, 0.2701810957102335));
+
+        assert_eq!(signed_rank_test(&[0.0, 0.0]), None);
+        assert_eq!(signed_rank_test(&[1.0, -1.0]), Some(1.0));
+    }
+
+    #[test]
+    fn bayesian_signed_rank_test_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // All the pairwise sums are inside the ROPE.
+        let diffs = [0.01, -0.02, 0.03];
+        let probs = bayesian_signed_rank_test(&diffs, 0.1, 1.0, 100, &mut rng);
+        assert_eq!(probs, (0.0, 1.0, 0.0));
+
+        let diffs = [-1.0; 10];
+        let (better, equivalent, worse) =
+            bayesian_signed_rank_test(&diffs, 0.1, 1.0, 1000, &mut rng);
+        assert!(better > 0.95, "{}", better);
+        assert!(is_float_close(better + equivalent + worse, 1.0));
+
+        let diffs = [1.0; 10];
+        let (better, equivalent, worse) =
+            bayesian_signed_rank_test(&diffs, 0.1, 1.0, 1000, &mut rng);
+        assert!(worse > 0.95, "{}", worse);
+        assert!(is_float_close(better + equivalent + worse, 1.0));
+    }
+}
@@ -0,0 +1,132 @@
+//! Pairwise win/tie/loss counts of solvers.
+use super::aggregate::AggregateOpt;
+use super::group;
+use crate::markdown as md;
+use crate::markdown::MarkdownWriter;
+use crate::record::StudyRecord;
+use kurobako_core::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Write;
+use structopt::StructOpt;
+
+/// Options of the win/tie/loss counts of `kurobako stats` (`--win-tie-loss`).
+///
+/// For each problem, the solvers are compared by the aggregates (the means by default)
+/// of the best values of their studies across seeds.
+/// Two solvers are tied on a problem if the difference of their aggregates is within the tolerance.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct WinTieLossOpt {
+    /// Tolerance of the differences regarded as ties in the win/tie/loss counts.
+    #[structopt(long, default_value = "0.0")]
+    pub tolerance: f64,
+}
+impl WinTieLossOpt {
+    /// Counts the wins, ties and losses of every pair of the solvers of the given studies.
+    pub fn win_tie_loss(
+        &self,
+        studies: &[StudyRecord],
+        aggregate: &AggregateOpt,
+    ) -> Result<WinTieLoss> {
+        track_assert!(self.tolerance >= 0.0, ErrorKind::InvalidInput; self.tolerance);
+
+        let mut problems = track!(group::group_studies(studies))?;
+        for solvers in problems.values_mut() {
+            solvers.retain(|_, studies| studies.iter().any(|s| s.best_value().is_some()));
+        }
+
+        let solvers = problems
+            .values()
+            .flat_map(|p| p.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let n = solvers.len();
+        let mut wins = vec![vec![0; n]; n];
+        let mut ties = vec![vec![0; n]; n];
+        let mut losses = vec![vec![0; n]; n];
+        for problem in problems.values() {
+            let aggregates = solvers
+                .iter()
+                .map(|s| match problem.get(s) {
+                    Some(studies) => {
+                        let values = studies
+                            .iter()
+                            .filter_map(|s| s.best_value())
+                            .collect::<Vec<_>>();
+                        track!(aggregate.aggregate(&values))
+                    }
+                    None => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for i in 0..n {
+                for j in 0..n {
+                    let (a, b) = match (aggregates[i], aggregates[j]) {
+                        (Some(a), Some(b)) if i != j => (a, b),
+                        _ => continue,
+                    };
+                    if (a - b).abs() <= self.tolerance {
+                        ties[i][j] += 1;
+                    } else if a < b {
+                        wins[i][j] += 1;
+                    } else {
+                        losses[i][j] += 1;
+                    }
+                }
+            }
+        }
+        Ok(WinTieLoss {
+            solvers,
+            wins,
+            ties,
+            losses,
+        })
+    }
+}
+
+/// Pairwise win/tie/loss counts of solvers.
+///
+/// `wins[i][j]` is the number of the problems on which `solvers[i]` beats `solvers[j]`
+/// (`ties` and `losses` are the same).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WinTieLoss {
+    /// Solver names.
+    pub solvers: Vec<String>,
+
+    /// Matrix of the numbers of the wins.
+    pub wins: Vec<Vec<usize>>,
+
+    /// Matrix of the numbers of the ties.
+    pub ties: Vec<Vec<usize>>,
+
+    /// Matrix of the numbers of the losses.
+    pub losses: Vec<Vec<usize>>,
+}
+impl WinTieLoss {
+    pub(crate) fn write_markdown<W: Write>(&self, writer: &mut MarkdownWriter<W>) -> Result<()> {
+        let mut table = md::Table::new(
+            std::iter::once(md::ColumnHeader::new("Solver (W/T/L)", md::Align::Left)).chain(
+                self.solvers
+                    .iter()
+                    .map(|s| md::ColumnHeader::new(s, md::Align::Right)),
+            ),
+        );
+        for (i, solver) in self.solvers.iter().enumerate() {
+            let row = table.row();
+            row.item(solver);
+            for j in 0..self.solvers.len() {
+                if i == j {
+                    row.item("-");
+                } else {
+                    row.item(format!(
+                        "{}/{}/{}",
+                        self.wins[i][j], self.ties[i][j], self.losses[i][j]
+                    ));
+                }
+            }
+        }
+        track!(writer.write_table(&table))?;
+        Ok(())
+    }
+}
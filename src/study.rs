@@ -6,6 +6,7 @@ use kurobako_core::{Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
 
@@ -32,9 +33,33 @@ pub struct StudyRecipe {
     /// Random seed.
     #[structopt(long)]
     pub seed: Option<u64>,
+
+    /// Wall-clock time budget (in seconds).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_budget: Option<f64>,
+
+    /// Number of trials asked to the solver at once.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<NonZeroUsize>,
+
+    /// Runner-side pruner.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruner: Option<Pruner>,
+
+    /// Results file (JSON Lines) of prior studies used for warm starting.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior: Option<PathBuf>,
 }
 
 /// Logical threads scheduling policy for executing a study.
+///
+/// `random` and `fair` simulate concurrent evaluations by interleaving them on a single thread.
+/// `async` actually runs the evaluations of the logical threads simultaneously,
+/// and tells the solver the results in the order of their completion.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, StructOpt, Serialize, Deserialize)]
 #[structopt(rename_all = "kebab-case")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -42,6 +67,7 @@ pub struct StudyRecipe {
 pub enum Scheduling {
     Random,
     Fair,
+    Async,
 }
 impl Default for Scheduling {
     fn default() -> Self {
@@ -55,6 +81,7 @@ impl FromStr for Scheduling {
         match s {
             "random" => Ok(Self::Random),
             "fair" => Ok(Self::Fair),
+            "async" => Ok(Self::Async),
             _ => track_panic!(ErrorKind::InvalidInput, "Unknown scheduling type: {:?}", s),
         }
     }
@@ -64,6 +91,37 @@ impl fmt::Display for Scheduling {
         match self {
             Self::Random => write!(f, "random"),
             Self::Fair => write!(f, "fair"),
+            Self::Async => write!(f, "async"),
+        }
+    }
+}
+
+/// Pruner that is applied by the runner regardless of the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Pruner {
+    /// Prunes a trial if its intermediate value is worse than the median of the ones of the preceding trials.
+    Median,
+
+    /// Allocates the steps to trials based on the successive halving brackets of Hyperband.
+    Hyperband,
+}
+impl FromStr for Pruner {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "median" => Ok(Self::Median),
+            "hyperband" => Ok(Self::Hyperband),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown pruner type: {:?}", s),
+        }
+    }
+}
+impl fmt::Display for Pruner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Median => write!(f, "median"),
+            Self::Hyperband => write!(f, "hyperband"),
         }
     }
 }
@@ -92,7 +150,7 @@ pub struct StudiesRecipe {
     #[structopt(long, default_value = "1")]
     pub concurrency: NonZeroUsize,
 
-    /// Scheduling policy of logical threads.
+    /// Scheduling policy of logical threads (`random`, `fair` or `async`).
     ///
     /// This option is ignored when `concurrency` is less then `2`.
     #[structopt(long, default_value = "random")]
@@ -101,15 +159,76 @@ pub struct StudiesRecipe {
     /// Random seed.
     #[structopt(long)]
     pub seed: Option<u64>,
+
+    /// Wall-clock time budget (in seconds) of a study execution.
+    ///
+    /// If this is specified, the solver isn't asked for new trials once the time budget is exhausted.
+    /// Note that `budget` is still the upper limit of the evaluation steps,
+    /// so give a large `budget` to make the time budget the only limit.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_budget: Option<f64>,
+
+    /// Number of trials asked to the solver at once.
+    ///
+    /// If this is specified, the runner asks the solver for a batch of trials,
+    /// and asks the next batch after all the trials in the batch have been evaluated.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<NonZeroUsize>,
+
+    /// Runner-side pruner (`median` or `hyperband`).
+    ///
+    /// If this is specified, the runner prunes trials based on their intermediate values
+    /// in addition to the pruning done by the solver.
+    /// The pruned trials are told to the solver as failed ones when the solver asks to resume them.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruner: Option<Pruner>,
+
+    /// Random seeds pinned to the studies.
+    ///
+    /// If this is specified, a study is executed once for each seed
+    /// (`repeats` and `seed` are ignored).
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seeds: Vec<u64>,
+
+    /// Results file (JSON Lines) of prior studies used for warm starting.
+    ///
+    /// The trials of the prior studies that have the same search space as the problem of a study
+    /// are told to the solver before the first ask.
+    /// They are told only to the solvers that have the `WARM_STARTING` capability.
+    #[structopt(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior: Option<PathBuf>,
+
+    /// Combinations of solvers and problems to be excluded (`solver=PATTERN,problem=PATTERN`).
+    ///
+    /// A pattern is matched against the name and the tags of a recipe,
+    /// and may contain `*` wildcards (an omitted pattern is regarded as `*`).
+    #[structopt(long, number_of_values = 1)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<ExcludeRule>,
 }
 impl StudiesRecipe {
     /// Returns a iterator that iterates over the study recipes specified by this recipe.
     pub fn studies(&self) -> impl Iterator<Item = StudyRecipe> {
+        let seeds = if self.seeds.is_empty() {
+            (0..self.repeats)
+                .map(|i| self.seed.map(|s| s + i as u64))
+                .collect::<Vec<_>>()
+        } else {
+            self.seeds.iter().copied().map(Some).collect()
+        };
+
         let mut studies = Vec::new();
         for problem in &self.problems {
-            for i in 0..self.repeats {
+            for &seed in &seeds {
                 for solver in &self.solvers {
-                    let seed = self.seed.map(|s| s + i as u64);
+                    if self.exclude.iter().any(|r| r.is_match(solver, problem)) {
+                        continue;
+                    }
                     let study = StudyRecipe {
                         solver: solver.clone(),
                         problem: problem.clone(),
@@ -117,6 +236,10 @@ impl StudiesRecipe {
                         concurrency: self.concurrency,
                         scheduling: self.scheduling,
                         seed,
+                        time_budget: self.time_budget,
+                        batch_size: self.batch_size,
+                        pruner: self.pruner,
+                        prior: self.prior.clone(),
                     };
                     studies.push(study);
                 }
@@ -125,3 +248,120 @@ impl StudiesRecipe {
         studies.into_iter()
     }
 }
+
+/// Rule to exclude combinations of solvers and problems from studies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludeRule {
+    /// Pattern of solvers.
+    #[serde(default = "wildcard")]
+    pub solver: String,
+
+    /// Pattern of problems.
+    #[serde(default = "wildcard")]
+    pub problem: String,
+}
+impl ExcludeRule {
+    fn is_match(&self, solver: &KurobakoSolverRecipe, problem: &KurobakoProblemRecipe) -> bool {
+        let matches = |pattern: &str, name: Option<&str>, tags: &[String]| {
+            pattern == "*"
+                || name.map_or(false, |n| wildcard_match(pattern, n))
+                || tags.iter().any(|t| wildcard_match(pattern, t))
+        };
+        matches(&self.solver, solver.name(), solver.tags())
+            && matches(&self.problem, problem.name(), problem.tags())
+    }
+}
+impl FromStr for ExcludeRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut rule = Self {
+            solver: wildcard(),
+            problem: wildcard(),
+        };
+        for item in s.split(',') {
+            let mut tokens = item.splitn(2, '=');
+            let key = tokens.next().unwrap_or_else(|| unreachable!());
+            let value = track_assert_some!(tokens.next(), ErrorKind::InvalidInput; s).to_owned();
+            match key {
+                "solver" => rule.solver = value,
+                "problem" => rule.problem = value,
+                _ => track_panic!(ErrorKind::InvalidInput, "Unknown exclusion key: {:?}", key),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+fn wildcard() -> String {
+    "*".to_owned()
+}
+
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_else(|| unreachable!());
+    if !s.starts_with(first) {
+        return false;
+    }
+
+    let mut rest = &s[first.len()..];
+    let parts = parts.collect::<Vec<_>>();
+    if let Some((last, middles)) = parts.split_last() {
+        for part in middles {
+            if let Some(i) = rest.find(part) {
+                rest = &rest[i + part.len()..];
+            } else {
+                return false;
+            }
+        }
+        rest.ends_with(last)
+    } else {
+        rest.is_empty()
+    }
+}
+
+/// Selector of study recipes (`tag=VALUE` or `name=VALUE`).
+///
+/// A selector matches a study recipe if the solver or the problem of the study has the tag or the name.
+#[derive(Debug, Clone)]
+pub struct StudySelector {
+    key: SelectorKey,
+    value: String,
+}
+impl StudySelector {
+    /// Returns `true` if the given study recipe matches this selector.
+    pub fn is_match(&self, study: &StudyRecipe) -> bool {
+        match self.key {
+            SelectorKey::Tag => study
+                .solver
+                .tags()
+                .iter()
+                .chain(study.problem.tags())
+                .any(|t| *t == self.value),
+            SelectorKey::Name => {
+                study.solver.name() == Some(self.value.as_str())
+                    || study.problem.name() == Some(self.value.as_str())
+            }
+        }
+    }
+}
+impl FromStr for StudySelector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut tokens = s.splitn(2, '=');
+        let key = match tokens.next() {
+            Some("tag") => SelectorKey::Tag,
+            Some("name") => SelectorKey::Name,
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown selector: {:?}", s),
+        };
+        let value = track_assert_some!(tokens.next(), ErrorKind::InvalidInput; s).to_owned();
+        Ok(Self { key, value })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SelectorKey {
+    Tag,
+    Name,
+}
@@ -0,0 +1,178 @@
+//! `kurobako worker` command.
+use crate::runner::{self, ErrorPolicy, RunnerOpt};
+use crate::study::StudyRecipe;
+use kurobako_core::{Error, ErrorKind, Result};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+use structopt::StructOpt;
+
+// Time limit of the handshake (i.e., the authentication of a connection).
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Options of the `kurobako worker` command.
+///
+/// A worker receives study recipes (JSON lines) from `kurobako run --workers` over TCP,
+/// and replies the study records (JSON lines) in the same order.
+/// The studies sent through different connections are run concurrently.
+///
+/// Note that a worker runs any study it receives, including `command` solvers and problems
+/// that spawn arbitrary programs on the worker host.
+/// Each connection must start with the shared token given by `--token`
+/// (and `kurobako run --worker-token`), and the connections without it are rejected.
+/// The traffic isn't encrypted, so expose a worker only on a trusted network
+/// (or through an SSH tunnel).
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct WorkerOpt {
+    /// Address to listen on.
+    ///
+    /// Only local connections are accepted by default.
+    /// Specify a non-loopback address (e.g., `0.0.0.0:7878`) to accept remote ones.
+    #[structopt(long, default_value = "127.0.0.1:7878")]
+    pub listen: SocketAddr,
+
+    /// Shared secret token that the clients must present on connection.
+    #[structopt(long, env = "KUROBAKO_WORKER_TOKEN", hide_env_values = true)]
+    pub token: String,
+
+    /// Maximum number of retries of a study that failed due to an I/O error.
+    #[structopt(long, default_value = "0")]
+    pub max_retries: usize,
+
+    /// Seconds to wait before the first retry (the wait time is doubled on each retry).
+    #[structopt(long, default_value = "1.0")]
+    pub retry_backoff: f64,
+
+    /// Wall-clock time limit (in seconds) of an evaluation.
+    #[structopt(long)]
+    pub trial_timeout: Option<f64>,
+
+    /// Behavior on a failure of a trial.
+    ///
+    /// A failed study is always replied as an error, which is handled by `kurobako run --on-error`
+    /// (so `skip-study` is the same as `abort` here).
+    #[structopt(long, default_value = "abort", possible_values = ErrorPolicy::POSSIBLE_VALUES)]
+    pub on_error: ErrorPolicy,
+}
+impl WorkerOpt {
+    /// Runs the worker.
+    pub fn run(&self) -> Result<()> {
+        track_assert!(!self.token.is_empty(), ErrorKind::InvalidInput; "Empty token");
+        let listener = track!(TcpListener::bind(self.listen).map_err(Error::from); self.listen)?;
+        eprintln!("Listening on {}", self.listen);
+
+        let opt = self.runner_opt();
+        for stream in listener.incoming() {
+            let stream = track!(stream.map_err(Error::from))?;
+            let opt = opt.clone();
+            let token = self.token.clone();
+            thread::spawn(move || {
+                if let Err(e) = track!(handle_connection(stream, &token, &opt)) {
+                    eprintln!("Connection error: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn runner_opt(&self) -> RunnerOpt {
+        RunnerOpt {
+            quiet: true,
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            trial_timeout: self.trial_timeout,
+            on_error: self.on_error,
+            ..RunnerOpt::default()
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, token: &str, opt: &RunnerOpt) -> Result<()> {
+    let mut reader = BufReader::new(track!(stream.try_clone().map_err(Error::from))?);
+    let mut writer = stream;
+
+    // The first line is the token (a JSON string), to which `Ok` or `Err` is replied.
+    track!(writer
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(Error::from))?;
+    let mut line = String::new();
+    track!(reader.read_line(&mut line).map_err(Error::from))?;
+    let authenticated = serde_json::from_str::<String>(&line)
+        .map(|t| constant_time_eq(t.as_bytes(), token.as_bytes()))
+        .unwrap_or(false);
+    let reply: std::result::Result<(), String> = if authenticated {
+        Ok(())
+    } else {
+        Err("Invalid token".to_owned())
+    };
+    track!(serde_json::to_writer(&mut writer, &reply).map_err(Error::from))?;
+    track!(writeln!(writer).map_err(Error::from))?;
+    track!(writer.flush().map_err(Error::from))?;
+    track_assert!(
+        authenticated,
+        ErrorKind::InvalidInput,
+        "Rejected an unauthenticated connection from {:?}",
+        writer.peer_addr().ok()
+    );
+    track!(writer.set_read_timeout(None).map_err(Error::from))?;
+
+    for recipe in serde_json::Deserializer::from_reader(reader).into_iter::<StudyRecipe>() {
+        let recipe = track!(recipe.map_err(Error::from))?;
+        let response: runner::Response =
+            track!(runner::run_study(recipe, opt)).map_err(|e| e.to_string());
+        track!(serde_json::to_writer(&mut writer, &response).map_err(Error::from))?;
+        track!(writeln!(writer).map_err(Error::from))?;
+        track!(writer.flush().map_err(Error::from))?;
+    }
+    Ok(())
+}
+
+// Compares the byte strings in the time independent of the position of the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_works() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret!"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn connections_without_the_token_are_rejected() -> trackable::result::TopLevelResult {
+        let listener = track!(TcpListener::bind("127.0.0.1:0").map_err(Error::from))?;
+        let addr = track!(listener.local_addr().map_err(Error::from))?;
+        let server = thread::spawn(move || {
+            listener
+                .incoming()
+                .take(2)
+                .map(|stream| {
+                    let stream = stream.unwrap_or_else(|e| panic!("{}", e));
+                    handle_connection(stream, "secret", &RunnerOpt::default()).is_ok()
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for token in &["\"wrong\"", "\"secret\""] {
+            let mut stream = track!(TcpStream::connect(addr).map_err(Error::from))?;
+            track!(writeln!(stream, "{}", token).map_err(Error::from))?;
+            let mut reply = String::new();
+            track!(BufReader::new(stream)
+                .read_line(&mut reply)
+                .map_err(Error::from))?;
+            let reply: std::result::Result<(), String> =
+                track!(serde_json::from_str(&reply).map_err(Error::from))?;
+            assert_eq!(reply.is_ok(), *token == "\"secret\"");
+        }
+        assert_eq!(server.join().ok(), Some(vec![false, true]));
+        Ok(())
+    }
+}